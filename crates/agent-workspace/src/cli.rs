@@ -13,18 +13,25 @@ pub const CLI_VERSION: &str = match option_env!("AWL_RELEASE_VERSION") {
 #[command(
     name = "agent-workspace-launcher",
     version,
-    about = "Workspace lifecycle CLI (container + host runtimes)",
+    about = "Workspace lifecycle CLI (container, podman, host + ssh runtimes)",
     disable_help_subcommand = true
 )]
 pub struct Cli {
     #[arg(
         long,
         global = true,
-        value_name = "container|host",
+        value_name = "container|host|podman|ssh://host",
         help = "Select runtime backend (default: container)"
     )]
     pub runtime: Option<String>,
 
+    #[arg(
+        long,
+        value_name = "name",
+        help = "Use a named profile from the config file"
+    )]
+    pub profile: Option<String>,
+
     #[command(subcommand)]
     pub command: CliCommand,
 }
@@ -36,17 +43,43 @@ pub enum CliCommand {
     #[command(disable_help_flag = true)]
     Create(PassthroughArgs),
     #[command(disable_help_flag = true)]
+    Clone(PassthroughArgs),
+    #[command(disable_help_flag = true)]
     Rsync(PassthroughArgs),
     #[command(disable_help_flag = true)]
+    Cp(PassthroughArgs),
+    #[command(disable_help_flag = true)]
     Ls(PassthroughArgs),
     #[command(disable_help_flag = true)]
     Rm(PassthroughArgs),
     #[command(disable_help_flag = true)]
+    Rename(PassthroughArgs),
+    #[command(disable_help_flag = true)]
+    Snapshot(PassthroughArgs),
+    #[command(disable_help_flag = true)]
+    Restore(PassthroughArgs),
+    #[command(disable_help_flag = true)]
     Exec(PassthroughArgs),
     #[command(disable_help_flag = true)]
+    Inspect(PassthroughArgs),
+    #[command(disable_help_flag = true)]
+    Start(PassthroughArgs),
+    #[command(disable_help_flag = true)]
+    Stop(PassthroughArgs),
+    #[command(disable_help_flag = true)]
+    Upgrade(PassthroughArgs),
+    #[command(disable_help_flag = true)]
+    Gc(PassthroughArgs),
+    #[command(disable_help_flag = true)]
+    Doctor(PassthroughArgs),
+    #[command(disable_help_flag = true)]
     Reset(PassthroughArgs),
     #[command(disable_help_flag = true)]
     Tunnel(PassthroughArgs),
+    #[command(disable_help_flag = true)]
+    Forward(PassthroughArgs),
+    #[command(disable_help_flag = true)]
+    Config(PassthroughArgs),
     #[command(name = "__complete", hide = true, disable_help_flag = true)]
     Complete(PassthroughArgs),
 }
@@ -65,49 +98,38 @@ pub struct PassthroughArgs {
 
 pub struct ForwardRequest {
     pub subcommand: &'static str,
+    pub profile: Option<String>,
     pub args: Vec<OsString>,
 }
 
 impl CliCommand {
-    pub fn into_forward_request(self) -> ForwardRequest {
-        match self {
-            Self::Auth(args) => ForwardRequest {
-                subcommand: "auth",
-                args: args.args,
-            },
-            Self::Create(args) => ForwardRequest {
-                subcommand: "create",
-                args: args.args,
-            },
-            Self::Rsync(args) => ForwardRequest {
-                subcommand: "rsync",
-                args: args.args,
-            },
-            Self::Ls(args) => ForwardRequest {
-                subcommand: "ls",
-                args: args.args,
-            },
-            Self::Rm(args) => ForwardRequest {
-                subcommand: "rm",
-                args: args.args,
-            },
-            Self::Exec(args) => ForwardRequest {
-                subcommand: "exec",
-                args: args.args,
-            },
-            Self::Reset(args) => ForwardRequest {
-                subcommand: "reset",
-                args: args.args,
-            },
-            Self::Tunnel(args) => ForwardRequest {
-                subcommand: "tunnel",
-                args: args.args,
-            },
-            Self::Complete(args) => ForwardRequest {
-                subcommand: "__complete",
-                args: args.args,
-            },
-        }
+    /// The launcher subcommand name and its passthrough args.
+    fn into_parts(self) -> (&'static str, Vec<OsString>) {
+        let (subcommand, args) = match self {
+            Self::Auth(args) => ("auth", args),
+            Self::Create(args) => ("create", args),
+            Self::Clone(args) => ("clone", args),
+            Self::Rsync(args) => ("rsync", args),
+            Self::Cp(args) => ("cp", args),
+            Self::Ls(args) => ("ls", args),
+            Self::Rm(args) => ("rm", args),
+            Self::Rename(args) => ("rename", args),
+            Self::Snapshot(args) => ("snapshot", args),
+            Self::Restore(args) => ("restore", args),
+            Self::Exec(args) => ("exec", args),
+            Self::Inspect(args) => ("inspect", args),
+            Self::Start(args) => ("start", args),
+            Self::Stop(args) => ("stop", args),
+            Self::Upgrade(args) => ("upgrade", args),
+            Self::Gc(args) => ("gc", args),
+            Self::Doctor(args) => ("doctor", args),
+            Self::Reset(args) => ("reset", args),
+            Self::Tunnel(args) => ("tunnel", args),
+            Self::Forward(args) => ("forward", args),
+            Self::Config(args) => ("config", args),
+            Self::Complete(args) => ("__complete", args),
+        };
+        (subcommand, args.args)
     }
 }
 
@@ -140,15 +162,17 @@ impl Cli {
     }
 
     pub fn into_forward_request(self) -> ForwardRequest {
-        let mut request = self.command.into_forward_request();
-        if request.subcommand != "__complete"
+        let (subcommand, mut args) = self.command.into_parts();
+        if subcommand != "__complete"
             && let Some(runtime) = self.runtime
         {
-            request
-                .args
-                .insert(0, OsString::from(format!("--runtime={runtime}")));
+            args.insert(0, OsString::from(format!("--runtime={runtime}")));
+        }
+        ForwardRequest {
+            subcommand,
+            profile: self.profile,
+            args,
         }
-        request
     }
 }
 
@@ -166,7 +190,9 @@ mod tests {
 
         let help = String::from_utf8(out).expect("utf8");
         for subcommand in [
-            "auth", "create", "rsync", "ls", "rm", "exec", "reset", "tunnel",
+            "auth", "create", "clone", "rsync", "cp", "ls", "rm", "rename", "snapshot", "restore",
+            "exec", "inspect", "start", "stop", "upgrade", "gc", "doctor", "reset", "tunnel",
+            "forward", "config",
         ] {
             assert!(
                 help.contains(subcommand),
@@ -253,6 +279,29 @@ mod tests {
         assert_eq!(collected, vec!["--runtime=host", "--output", "json"]);
     }
 
+    #[test]
+    fn parse_profile_is_top_level_and_leaves_subcommand_args() {
+        let cli = Cli::try_parse_from([
+            PRIMARY_BIN_NAME,
+            "--profile",
+            "work",
+            "auth",
+            "--profile",
+            "codex-team",
+            "codex",
+        ])
+        .expect("parse profile");
+
+        let request = cli.into_forward_request();
+        assert_eq!(request.profile.as_deref(), Some("work"));
+        let collected: Vec<String> = request
+            .args
+            .iter()
+            .map(|item| item.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(collected, vec!["--profile", "codex-team", "codex"]);
+    }
+
     #[test]
     fn parse_hidden_complete_subcommand() {
         let cli = Cli::try_parse_from([
@@ -23,7 +23,10 @@ impl Candidate {
 }
 
 pub(crate) const GLOBAL_OPTIONS: &[(&str, &str)] = &[
-    ("--runtime", "Select runtime backend (container or host)"),
+    (
+        "--runtime",
+        "Select runtime backend (container, host, podman or ssh://host)",
+    ),
     ("--help", "Show help for command"),
     ("--version", "Show CLI version"),
     ("-h", "Show help for command"),
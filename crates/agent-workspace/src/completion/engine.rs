@@ -23,22 +23,41 @@ pub(crate) fn complete<P: WorkspaceProvider>(
     let current = request.current_word();
     let words_before = request.words_before_cursor();
 
-    let mut workspace_ctx = WorkspaceContext::new(provider, request.runtime);
+    let mut workspace_ctx = WorkspaceContext::new(provider, request.runtime.clone());
 
     let candidates = if let Some(runtime_candidates) = complete_runtime_value(words_before, current)
     {
         runtime_candidates
+    } else if let Some(profile_candidates) = detect_subcommand(words_before)
+        .is_none()
+        .then(|| complete_profile_value(words_before, current))
+        .flatten()
+    {
+        profile_candidates
     } else if let Some((subcommand_idx, subcommand)) = detect_subcommand(words_before) {
         let args_before = strip_runtime_tokens(&words_before[(subcommand_idx + 1)..]);
         match subcommand {
             "auth" => complete_auth(current, &args_before, &mut workspace_ctx),
             "create" => complete_create(current, &args_before),
+            "clone" => complete_clone(&args_before, &mut workspace_ctx),
             "rsync" => complete_rsync(current, &args_before, &mut workspace_ctx),
+            "cp" => complete_cp(&args_before, &mut workspace_ctx),
             "ls" => complete_ls(current, &args_before),
             "rm" => complete_rm(&args_before, &mut workspace_ctx),
+            "rename" => complete_rename(&args_before, &mut workspace_ctx),
+            "snapshot" => complete_snapshot(current, &args_before, &mut workspace_ctx),
+            "restore" => complete_restore(current, &args_before),
             "exec" => complete_exec(current, &args_before, &mut workspace_ctx),
+            "inspect" => complete_inspect(current, &args_before, &mut workspace_ctx),
+            "start" => complete_start(&args_before, &mut workspace_ctx),
+            "stop" => complete_stop(&args_before, &mut workspace_ctx),
+            "upgrade" => complete_upgrade(current, &args_before, &mut workspace_ctx),
+            "gc" => complete_gc(current, &args_before),
+            "doctor" => complete_doctor(current, &args_before),
             "reset" => complete_reset(current, &args_before, &mut workspace_ctx),
             "tunnel" => complete_tunnel(current, &args_before, &mut workspace_ctx),
+            "forward" => complete_forward(current, &args_before, &mut workspace_ctx),
+            "config" => complete_config(&args_before),
             _ => Vec::new(),
         }
     } else {
@@ -58,14 +77,28 @@ fn complete_top_level(_current: &str) -> Vec<Candidate> {
         &[
             ("auth", "Update auth material in workspace"),
             ("create", "Create a new workspace"),
+            ("clone", "Copy a workspace into a new one"),
             ("rsync", "Sync files between host and container"),
+            ("cp", "Copy files into or out of a workspace"),
             ("ls", "List workspaces"),
             ("rm", "Remove workspace(s)"),
+            ("rename", "Rename a workspace"),
+            ("snapshot", "Archive a workspace"),
+            ("restore", "Restore a workspace from a snapshot"),
             ("exec", "Run command in workspace"),
+            ("inspect", "Show workspace status"),
+            ("start", "Start a stopped workspace"),
+            ("stop", "Stop workspace(s)"),
+            ("upgrade", "Recreate workspace(s) on a newer image"),
+            ("gc", "Remove stale workspaces and orphaned volumes"),
+            ("doctor", "Check the runtime, tools, and credentials"),
             ("reset", "Reset repos in workspace"),
             ("tunnel", "Start VS Code tunnel"),
+            ("forward", "Forward host ports into a workspace"),
+            ("config", "Show or edit launcher config"),
         ],
     );
+    push_described_values(&mut out, &[("--profile", "Use a named config profile")]);
     push_global_options(&mut out);
     out
 }
@@ -123,7 +156,8 @@ fn complete_rsync<P: WorkspaceProvider>(
         match token {
             "--user" | "-u" => j += 2,
             _ if token.starts_with("--user=") => j += 1,
-            "--root" | "--delete" | "--dry-run" | "-n" | "--help" | "-h" => j += 1,
+            "--root" | "--delete" | "--dry-run" | "-n" | "--watch" | "--gitignore" | "--help"
+            | "-h" => j += 1,
             _ if token.starts_with('-') => j += 1,
             _ => {
                 positional_seen += 1;
@@ -140,6 +174,18 @@ fn complete_rsync<P: WorkspaceProvider>(
             ("--delete", "Delete files not present at source"),
             ("--dry-run", "Preview changes without writing"),
             ("-n", "Alias of --dry-run"),
+        ],
+    );
+    if direction == Some("push") {
+        push_described_values(
+            &mut out,
+            &[("--watch", "Keep pushing changes until interrupted")],
+        );
+    }
+    push_described_values(
+        &mut out,
+        &[
+            ("--gitignore", "Skip .gitignore'd paths and .git/"),
             ("--help", "Show help for rsync"),
             ("-h", "Show help for rsync"),
         ],
@@ -153,13 +199,77 @@ fn complete_rsync<P: WorkspaceProvider>(
     out
 }
 
+fn complete_cp<P: WorkspaceProvider>(
+    args_before: &[String],
+    workspace_ctx: &mut WorkspaceContext<'_, P>,
+) -> Vec<Candidate> {
+    let mut out: Vec<Candidate> = Vec::new();
+    push_described_values(
+        &mut out,
+        &[("--help", "Show help for cp"), ("-h", "Show help for cp")],
+    );
+    push_global_options(&mut out);
+
+    let positional_seen = args_before
+        .iter()
+        .filter(|token| !token.starts_with('-'))
+        .count();
+    if positional_seen < 2 {
+        out.extend(
+            workspace_ctx
+                .workspace_names()
+                .into_iter()
+                .map(|name| Candidate::value(format!("{name}:"))),
+        );
+    }
+    out
+}
+
 fn complete_create(current: &str, args_before: &[String]) -> Vec<Candidate> {
     if let Some((option, inline)) = value_option(
         args_before,
         current,
-        &["--name", "--image", "--ref", "--private-repo"],
+        &[
+            "--name",
+            "--image",
+            "--ref",
+            "--private-repo",
+            "--idle-timeout",
+            "--jobs",
+            "--depth",
+            "--filter",
+            "--sparse",
+            "--env",
+            "--env-file",
+            "--mount",
+            "--publish",
+            "--cpus",
+            "--memory",
+        ],
     ) {
         return match option.as_str() {
+            "--cpus" => value_suggestions(&option, inline, &["2", "4", "8"]),
+            "--jobs" => value_suggestions(&option, inline, &["1", "4", "8"]),
+            "--depth" => value_suggestions(&option, inline, &["1", "10", "50"]),
+            "--filter" => value_suggestions_described(
+                &option,
+                inline,
+                &[
+                    ("blob:none", "Fetch file contents on demand"),
+                    ("tree:0", "Fetch trees and file contents on demand"),
+                    ("blob:limit=1m", "Skip files over 1 MiB until needed"),
+                ],
+            ),
+            "--memory" => value_suggestions(&option, inline, &["4g", "8g", "16g"]),
+            "--idle-timeout" => value_suggestions_described(
+                &option,
+                inline,
+                &[
+                    ("30m", "Stop after 30 idle minutes"),
+                    ("2h", "Stop after 2 idle hours"),
+                    ("8h", "Stop after 8 idle hours"),
+                ],
+            ),
             "--ref" => value_suggestions_described(
                 &option,
                 inline,
@@ -183,6 +293,24 @@ fn complete_create(current: &str, args_before: &[String]) -> Vec<Candidate> {
             ("--no-work-repos", "Skip cloning work repositories"),
             ("--no-extras", "Skip optional setup extras"),
             ("--no-pull", "Do not pull image before create"),
+            ("--worktree", "Share one git object store per repo (host)"),
+            ("--no-manifest", "Ignore the repo's awl.toml manifest"),
+            (
+                "--trust-manifest",
+                "Apply manifest env and post_create outside a container",
+            ),
+            ("--idle-timeout", "Allow stop --idle after this idle time"),
+            ("--jobs", "Clone up to N repos at once"),
+            ("--depth", "Shallow-clone repos to N commits"),
+            ("--filter", "Partial-clone repos with a git filter"),
+            ("--single-branch", "Clone only the checked-out branch"),
+            ("--sparse", "Check out only these repo paths"),
+            ("--env", "Set a workspace env variable (NAME=value)"),
+            ("--env-file", "Read workspace env variables from a file"),
+            ("--mount", "Bind a host path into the container"),
+            ("--publish", "Publish a container port on the host"),
+            ("--cpus", "Limit container CPUs"),
+            ("--memory", "Limit container memory"),
             ("--help", "Show help for create"),
             ("-h", "Show help for create"),
         ],
@@ -191,15 +319,79 @@ fn complete_create(current: &str, args_before: &[String]) -> Vec<Candidate> {
     out
 }
 
+fn complete_config(args_before: &[String]) -> Vec<Candidate> {
+    let positionals: Vec<&str> = args_before
+        .iter()
+        .map(String::as_str)
+        .filter(|token| !token.starts_with('-'))
+        .collect();
+
+    let mut out: Vec<Candidate> = Vec::new();
+    match positionals.as_slice() {
+        [] => push_described_values(
+            &mut out,
+            &[
+                ("get", "Print effective value"),
+                ("set", "Write value to config file"),
+                ("list", "List effective values and sources"),
+                ("path", "Print config file path"),
+            ],
+        ),
+        ["get" | "set"] => {
+            for setting in crate::config::SETTINGS {
+                out.push(Candidate::value(setting.key));
+            }
+        }
+        _ => {}
+    }
+    if positionals.first() == Some(&"get") {
+        push_described_values(&mut out, &[("--show-source", "Print value source")]);
+    }
+    push_described_values(
+        &mut out,
+        &[
+            ("--help", "Show help for config"),
+            ("-h", "Show help for config"),
+        ],
+    );
+    push_global_options(&mut out);
+    out
+}
+
 fn complete_ls(current: &str, args_before: &[String]) -> Vec<Candidate> {
-    if let Some((option, inline)) = value_option(args_before, current, &["--output"]) {
-        return value_suggestions_described(&option, inline, &[("json", "JSON output format")]);
+    if let Some((option, inline)) = value_option(
+        args_before,
+        current,
+        &["--output", "--sort", "--filter", "--format"],
+    ) {
+        let values: &[(&str, &str)] = match option.as_str() {
+            "--sort" => &[
+                ("name", "Sort by workspace name"),
+                ("created", "Sort by creation time, oldest first"),
+            ],
+            "--filter" => &[
+                ("name=", "Match workspace name"),
+                ("repo=", "Match primary repo (OWNER/REPO)"),
+                ("state=running", "Only running workspaces"),
+                ("state=stopped", "Only stopped workspaces"),
+                ("image=", "Match container image"),
+            ],
+            // Free-form template: nothing useful to suggest.
+            "--format" => &[],
+            _ => &[("json", "JSON output format")],
+        };
+        return value_suggestions_described(&option, inline, values);
     }
 
     let mut out: Vec<Candidate> = Vec::new();
     push_described_values(
         &mut out,
         &[
+            ("--long", "Show repo, runtime, state, age, image and size"),
+            ("-l", "Show repo, runtime, state, age, image and size"),
+            ("--filter", "Filter by key=value (name, repo, state, image)"),
+            ("--sort", "Sort by name or created"),
+            ("--format", "Print each workspace with a template"),
             ("--json", "Shortcut for --output json"),
             ("--output", "Set output format"),
             ("--help", "Show help for ls"),
@@ -210,6 +402,311 @@ fn complete_ls(current: &str, args_before: &[String]) -> Vec<Candidate> {
     out
 }
 
+fn complete_inspect<P: WorkspaceProvider>(
+    current: &str,
+    args_before: &[String],
+    workspace_ctx: &mut WorkspaceContext<'_, P>,
+) -> Vec<Candidate> {
+    if let Some((option, inline)) = value_option(args_before, current, &["--output"]) {
+        return value_suggestions_described(&option, inline, &[("json", "JSON output format")]);
+    }
+
+    let mut out: Vec<Candidate> = Vec::new();
+    push_described_values(
+        &mut out,
+        &[
+            ("--json", "Shortcut for --output json"),
+            ("--output", "Set output format"),
+            ("--help", "Show help for inspect"),
+            ("-h", "Show help for inspect"),
+        ],
+    );
+    push_global_options(&mut out);
+
+    let mut workspace_seen = false;
+    let mut idx = 0usize;
+    while idx < args_before.len() {
+        let token = args_before[idx].as_str();
+        if token == "--output" {
+            idx += 2;
+            continue;
+        }
+        if !token.starts_with('-') {
+            workspace_seen = true;
+            break;
+        }
+        idx += 1;
+    }
+    if !workspace_seen {
+        out.extend(workspace_ctx.workspace_candidates(None));
+    }
+
+    out
+}
+
+fn complete_clone<P: WorkspaceProvider>(
+    args_before: &[String],
+    workspace_ctx: &mut WorkspaceContext<'_, P>,
+) -> Vec<Candidate> {
+    let mut out: Vec<Candidate> = Vec::new();
+    push_described_values(
+        &mut out,
+        &[
+            ("--help", "Show help for clone"),
+            ("-h", "Show help for clone"),
+        ],
+    );
+    push_global_options(&mut out);
+
+    if !args_before.iter().any(|token| !token.starts_with('-')) {
+        out.extend(workspace_ctx.workspace_candidates(None));
+    }
+
+    out
+}
+
+fn complete_rename<P: WorkspaceProvider>(
+    args_before: &[String],
+    workspace_ctx: &mut WorkspaceContext<'_, P>,
+) -> Vec<Candidate> {
+    let mut out: Vec<Candidate> = Vec::new();
+    push_described_values(
+        &mut out,
+        &[
+            ("--help", "Show help for rename"),
+            ("-h", "Show help for rename"),
+        ],
+    );
+    push_global_options(&mut out);
+
+    // Only the workspace being renamed exists yet; the new name is free text.
+    if !args_before.iter().any(|token| !token.starts_with('-')) {
+        out.extend(workspace_ctx.workspace_candidates(None));
+    }
+
+    out
+}
+
+fn complete_snapshot<P: WorkspaceProvider>(
+    current: &str,
+    args_before: &[String],
+    workspace_ctx: &mut WorkspaceContext<'_, P>,
+) -> Vec<Candidate> {
+    if let Some((option, inline)) = value_option(args_before, current, &["--output", "-o"]) {
+        return value_suggestions(&option, inline, &[]);
+    }
+
+    let mut out: Vec<Candidate> = Vec::new();
+    push_described_values(
+        &mut out,
+        &[
+            ("--output", "Archive path (.tar, .tar.gz, .tar.zst)"),
+            ("-o", "Archive path (.tar, .tar.gz, .tar.zst)"),
+            ("--include-secrets", "Keep auth material in the archive"),
+            ("--help", "Show help for snapshot"),
+            ("-h", "Show help for snapshot"),
+        ],
+    );
+    push_global_options(&mut out);
+
+    let mut workspace_seen = false;
+    let mut skip_value = false;
+    for token in args_before {
+        if skip_value {
+            skip_value = false;
+        } else if token == "--output" || token == "-o" {
+            skip_value = true;
+        } else if !token.starts_with('-') {
+            workspace_seen = true;
+        }
+    }
+    if !workspace_seen {
+        out.extend(workspace_ctx.workspace_candidates(None));
+    }
+
+    out
+}
+
+fn complete_gc(current: &str, args_before: &[String]) -> Vec<Candidate> {
+    if let Some((option, inline)) = value_option(args_before, current, &["--older-than"]) {
+        return value_suggestions_described(
+            &option,
+            inline,
+            &[
+                ("7d", "Created over a week ago"),
+                ("14d", "Created over two weeks ago"),
+                ("30d", "Created over a month ago"),
+            ],
+        );
+    }
+
+    let mut out: Vec<Candidate> = Vec::new();
+    push_described_values(
+        &mut out,
+        &[
+            (
+                "--older-than",
+                "Also remove workspaces created before this age",
+            ),
+            ("--dry-run", "List what would be removed"),
+            ("-n", "List what would be removed"),
+            ("--yes", "Skip confirmation prompt"),
+            ("-y", "Skip confirmation prompt"),
+            ("--help", "Show help for gc"),
+            ("-h", "Show help for gc"),
+        ],
+    );
+    push_global_options(&mut out);
+    out
+}
+
+fn complete_doctor(current: &str, args_before: &[String]) -> Vec<Candidate> {
+    if let Some((option, inline)) = value_option(args_before, current, &["--output"]) {
+        return value_suggestions_described(
+            &option,
+            inline,
+            &[
+                ("text", "Human-readable report"),
+                ("json", "JSON output format"),
+            ],
+        );
+    }
+
+    let mut out: Vec<Candidate> = Vec::new();
+    push_described_values(
+        &mut out,
+        &[
+            ("--json", "Shortcut for --output json"),
+            ("--output", "Set output format"),
+            ("--help", "Show help for doctor"),
+            ("-h", "Show help for doctor"),
+        ],
+    );
+    push_global_options(&mut out);
+    out
+}
+
+fn complete_restore(current: &str, args_before: &[String]) -> Vec<Candidate> {
+    if let Some((option, inline)) = value_option(
+        args_before,
+        current,
+        &["--name", "--env", "--mount", "--publish"],
+    ) {
+        return value_suggestions(&option, inline, &[]);
+    }
+
+    let mut out: Vec<Candidate> = Vec::new();
+    push_described_values(
+        &mut out,
+        &[
+            ("--name", "Name for the restored workspace"),
+            ("--env", "Set a workspace env variable (NAME=value)"),
+            ("--mount", "Bind a host path into the container"),
+            ("--publish", "Publish a container port on the host"),
+            ("--help", "Show help for restore"),
+            ("-h", "Show help for restore"),
+        ],
+    );
+    push_global_options(&mut out);
+    out
+}
+
+fn complete_start<P: WorkspaceProvider>(
+    args_before: &[String],
+    workspace_ctx: &mut WorkspaceContext<'_, P>,
+) -> Vec<Candidate> {
+    let mut out: Vec<Candidate> = Vec::new();
+    push_described_values(
+        &mut out,
+        &[
+            ("--help", "Show help for start"),
+            ("-h", "Show help for start"),
+        ],
+    );
+    push_global_options(&mut out);
+
+    if !args_before.iter().any(|token| !token.starts_with('-')) {
+        out.extend(workspace_ctx.workspace_candidates(None));
+    }
+
+    out
+}
+
+fn complete_stop<P: WorkspaceProvider>(
+    args_before: &[String],
+    workspace_ctx: &mut WorkspaceContext<'_, P>,
+) -> Vec<Candidate> {
+    let sweep = args_before
+        .iter()
+        .any(|token| token == "--all" || token == "--idle");
+    let workspace_seen = args_before.iter().any(|token| !token.starts_with('-'));
+
+    let mut out: Vec<Candidate> = Vec::new();
+    push_described_values(
+        &mut out,
+        &[
+            ("--all", "Stop all running workspaces"),
+            ("--idle", "Stop workspaces idle past their --idle-timeout"),
+            ("--help", "Show help for stop"),
+            ("-h", "Show help for stop"),
+        ],
+    );
+    push_global_options(&mut out);
+
+    if !sweep && !workspace_seen {
+        out.extend(workspace_ctx.workspace_candidates(None));
+    }
+
+    out
+}
+
+fn complete_upgrade<P: WorkspaceProvider>(
+    current: &str,
+    args_before: &[String],
+    workspace_ctx: &mut WorkspaceContext<'_, P>,
+) -> Vec<Candidate> {
+    if let Some((option, inline)) = value_option(args_before, current, &["--image"]) {
+        return value_suggestions(&option, inline, &[]);
+    }
+
+    let mut all = false;
+    let mut workspace_seen = false;
+    let mut idx = 0usize;
+    while idx < args_before.len() {
+        let token = args_before[idx].as_str();
+        match token {
+            "--image" => idx += 1,
+            "--all" => all = true,
+            _ if token.starts_with('-') => {}
+            _ => workspace_seen = true,
+        }
+        idx += 1;
+    }
+
+    let mut out: Vec<Candidate> = Vec::new();
+    push_described_values(
+        &mut out,
+        &[
+            ("--all", "Upgrade every workspace"),
+            ("--image", "Move to this image instead"),
+            ("--pull", "Pull the image first"),
+            (
+                "--sync-baseline",
+                "Re-sync zsh-kit, agent-kit, and nils-cli",
+            ),
+            ("--help", "Show help for upgrade"),
+            ("-h", "Show help for upgrade"),
+        ],
+    );
+    push_global_options(&mut out);
+
+    if !all && !workspace_seen {
+        out.extend(workspace_ctx.workspace_candidates(None));
+    }
+
+    out
+}
+
 fn complete_rm<P: WorkspaceProvider>(
     args_before: &[String],
     workspace_ctx: &mut WorkspaceContext<'_, P>,
@@ -337,6 +834,11 @@ fn complete_tunnel<P: WorkspaceProvider>(
         &[
             ("--name", "Set tunnel display name"),
             ("--detach", "Start tunnel in background"),
+            ("--status", "Show the detached tunnel's state"),
+            ("--logs", "Print the detached tunnel's log"),
+            ("--follow", "Keep following the log with --logs"),
+            ("-f", "Keep following the log with --logs"),
+            ("--stop", "Stop the detached tunnel"),
             ("--output", "Set output format"),
             ("--help", "Show help for tunnel"),
             ("-h", "Show help for tunnel"),
@@ -351,6 +853,57 @@ fn complete_tunnel<P: WorkspaceProvider>(
     out
 }
 
+fn complete_forward<P: WorkspaceProvider>(
+    current: &str,
+    args_before: &[String],
+    workspace_ctx: &mut WorkspaceContext<'_, P>,
+) -> Vec<Candidate> {
+    if let Some((option, inline)) = value_option(args_before, current, &["--address"]) {
+        return value_suggestions_described(
+            &option,
+            inline,
+            &[
+                ("127.0.0.1", "Loopback only (default)"),
+                ("0.0.0.0", "All host interfaces"),
+            ],
+        );
+    }
+
+    let mut workspace_seen = false;
+    let mut idx = 0usize;
+    while idx < args_before.len() {
+        let token = args_before[idx].as_str();
+        match token {
+            "--address" => idx += 2,
+            _ if token.starts_with('-') => idx += 1,
+            _ => {
+                workspace_seen = true;
+                break;
+            }
+        }
+    }
+
+    let mut out: Vec<Candidate> = Vec::new();
+    push_described_values(
+        &mut out,
+        &[
+            ("--address", "Host address to listen on"),
+            ("--list", "List active forwards"),
+            ("--stop", "Stop a workspace's forwards"),
+            ("--all", "With --stop, stop every forward"),
+            ("--help", "Show help for forward"),
+            ("-h", "Show help for forward"),
+        ],
+    );
+    push_global_options(&mut out);
+
+    if !workspace_seen {
+        out.extend(workspace_ctx.workspace_candidates(None));
+    }
+
+    out
+}
+
 fn complete_auth<P: WorkspaceProvider>(
     current: &str,
     args_before: &[String],
@@ -768,6 +1321,8 @@ fn complete_runtime_value(words_before: &[String], current: &str) -> Option<Vec<
                 &[
                     ("container", "Use container runtime"),
                     ("host", "Use host runtime"),
+                    ("podman", "Use podman runtime"),
+                    ("ssh://", "Use ssh runtime (ssh://[user@]host[:port])"),
                 ],
                 runtime_prefix,
             ),
@@ -778,12 +1333,36 @@ fn complete_runtime_value(words_before: &[String], current: &str) -> Option<Vec<
         return Some(vec![
             Candidate::described("container", "Use container runtime"),
             Candidate::described("host", "Use host runtime"),
+            Candidate::described("podman", "Use podman runtime"),
+            Candidate::described("ssh://", "Use ssh runtime (ssh://[user@]host[:port])"),
         ]);
     }
 
     None
 }
 
+fn complete_profile_value(words_before: &[String], current: &str) -> Option<Vec<Candidate>> {
+    let profiles = || crate::config::profile_names();
+    if let Some(profile_prefix) = current.strip_prefix("--profile=") {
+        let names = profiles();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        return Some(
+            with_prefix("--profile=", &names)
+                .into_iter()
+                .filter(|candidate| {
+                    candidate.value["--profile=".len()..].starts_with(profile_prefix)
+                })
+                .collect(),
+        );
+    }
+
+    if words_before.last().is_some_and(|last| last == "--profile") {
+        return Some(profiles().into_iter().map(Candidate::value).collect());
+    }
+
+    None
+}
+
 fn detect_subcommand(words_before: &[String]) -> Option<(usize, &str)> {
     if words_before.len() <= 1 {
         return None;
@@ -792,12 +1371,12 @@ fn detect_subcommand(words_before: &[String]) -> Option<(usize, &str)> {
     let mut idx = 1usize;
     while idx < words_before.len() {
         let token = words_before[idx].as_str();
-        if token == "--runtime" {
+        if token == "--runtime" || token == "--profile" {
             idx += 2;
             continue;
         }
 
-        if token.starts_with("--runtime=") {
+        if token.starts_with("--runtime=") || token.starts_with("--profile=") {
             idx += 1;
             continue;
         }
@@ -924,7 +1503,7 @@ impl<'a, P: WorkspaceProvider> WorkspaceContext<'a, P> {
 
     fn workspace_names(&mut self) -> Vec<String> {
         if self.cache.is_none() {
-            self.cache = Some(self.provider.list_workspaces(self.runtime));
+            self.cache = Some(self.provider.list_workspaces(self.runtime.clone()));
         }
 
         match self.cache.as_ref() {
@@ -197,8 +197,8 @@ mod engine_tests {
     impl WorkspaceProvider for StubWorkspaceProvider {
         fn list_workspaces(&self, runtime: Runtime) -> Result<Vec<String>, String> {
             Ok(match runtime {
-                Runtime::Host => self.host.clone(),
-                Runtime::Container => self.container.clone(),
+                Runtime::Host | Runtime::Ssh(_) => self.host.clone(),
+                Runtime::Container | Runtime::Podman => self.container.clone(),
             })
         }
     }
@@ -253,7 +253,11 @@ mod engine_tests {
 
         assert!(rendered.contains("auth\tUpdate auth material in workspace"));
         assert!(rendered.contains("create\tCreate a new workspace"));
-        assert!(rendered.contains("--runtime\tSelect runtime backend (container or host)"));
+        assert!(
+            rendered.contains(
+                "--runtime\tSelect runtime backend (container, host, podman or ssh://host)"
+            )
+        );
     }
 
     #[test]
@@ -268,7 +272,7 @@ mod engine_tests {
             .map(|candidate| candidate.value)
             .collect();
 
-        assert_eq!(values, vec!["container", "host"]);
+        assert_eq!(values, vec!["container", "host", "podman", "ssh://"]);
     }
 
     #[test]
@@ -285,6 +289,7 @@ mod engine_tests {
 
         assert!(rendered.contains("container\tUse container runtime"));
         assert!(rendered.contains("host\tUse host runtime"));
+        assert!(rendered.contains("podman\tUse podman runtime"));
     }
 
     #[test]
@@ -451,6 +456,7 @@ mod workspace_provider_tests {
         let merged_path = format!("{}:{}", bin_dir.display(), existing_path);
         unsafe {
             std::env::set_var("PATH", merged_path);
+            std::env::set_var("AGENT_WORKSPACE_DOCKER_API", "off");
         }
 
         let provider = RuntimeWorkspaceProvider;
@@ -461,8 +467,65 @@ mod workspace_provider_tests {
 
         unsafe {
             std::env::set_var("PATH", existing_path);
+            std::env::remove_var("AGENT_WORKSPACE_DOCKER_API");
         }
     }
+
+    #[test]
+    fn container_workspace_provider_prefers_engine_api_socket() {
+        use std::io::{BufRead, BufReader};
+        use std::os::unix::net::UnixListener;
+
+        let _guard = crate::env_lock()
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner());
+        let temp = tempfile::tempdir().expect("tempdir");
+        let socket_path = temp.path().join("docker.sock");
+        let listener = UnixListener::bind(&socket_path).expect("bind fake docker socket");
+
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("accept");
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).expect("read request");
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).expect("read header");
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+            }
+            let body = r#"[{"Names":["/ws-b"]},{"Names":["/ws-a"]}]"#;
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            )
+            .expect("write response");
+            request_line
+        });
+
+        unsafe {
+            std::env::set_var("DOCKER_HOST", format!("unix://{}", socket_path.display()));
+            std::env::remove_var("AGENT_WORKSPACE_DOCKER_API");
+        }
+
+        let provider = RuntimeWorkspaceProvider;
+        let workspaces = provider.list_workspaces(Runtime::Container);
+
+        unsafe {
+            std::env::remove_var("DOCKER_HOST");
+        }
+
+        assert_eq!(
+            workspaces.expect("list container workspaces"),
+            vec!["ws-a", "ws-b"]
+        );
+        let request_line = server.join().expect("join fake server");
+        assert!(request_line.starts_with("GET /containers/json?all=1&filters="));
+        assert!(request_line.contains("agent-kit.workspace%3D1"));
+    }
 }
 
 #[cfg(test)]
@@ -552,8 +615,8 @@ mod matrix_tests {
     impl WorkspaceProvider for StubWorkspaceProvider {
         fn list_workspaces(&self, runtime: Runtime) -> Result<Vec<String>, String> {
             Ok(match runtime {
-                Runtime::Host => vec!["host-ws".to_string()],
-                Runtime::Container => vec!["container-ws".to_string()],
+                Runtime::Host | Runtime::Ssh(_) => vec!["host-ws".to_string()],
+                Runtime::Container | Runtime::Podman => vec!["container-ws".to_string()],
             })
         }
     }
@@ -118,6 +118,7 @@ impl CompletionRequest {
 
         let shell = shell.ok_or_else(|| String::from("missing --shell"))?;
         let mode = parse_mode_from_env()?;
+        activate_profile_from_words(&words, cword);
         let runtime = resolve_runtime_from_words(&words, cword);
 
         Ok(Self {
@@ -197,6 +198,28 @@ pub(crate) fn parse_mode_value(raw: &str) -> Result<CompletionMode, String> {
     }
 }
 
+/// Select the `--profile` typed so far so config-backed settings (runtime, home) follow it.
+fn activate_profile_from_words(words: &[String], cword: usize) {
+    let mut profile: Option<&str> = None;
+    let mut idx = 1usize;
+    while idx < cword {
+        let token = words[idx].trim();
+        if token == "--profile" && idx + 1 < cword {
+            profile = Some(words[idx + 1].trim());
+            idx += 2;
+            continue;
+        }
+        if let Some(value) = token.strip_prefix("--profile=") {
+            profile = Some(value.trim());
+        }
+        idx += 1;
+    }
+
+    if let Some(profile) = profile.filter(|value| !value.is_empty()) {
+        let _ = crate::config::activate(Some(profile));
+    }
+}
+
 fn resolve_runtime_from_words(words: &[String], cword: usize) -> Runtime {
     let mut runtime_from_flag: Option<Runtime> = None;
     let mut idx = 1usize;
@@ -229,12 +252,10 @@ fn resolve_runtime_from_words(words: &[String], cword: usize) -> Runtime {
         return runtime;
     }
 
-    for key in ["AGENT_WORKSPACE_RUNTIME", "AWL_RUNTIME"] {
-        if let Ok(value) = std::env::var(key)
-            && let Ok(runtime) = parse_runtime_value(value.trim())
-        {
-            return runtime;
-        }
+    if let Some(value) = crate::config::value("runtime")
+        && let Ok(runtime) = parse_runtime_value(value.trim())
+    {
+        return runtime;
     }
 
     Runtime::Container
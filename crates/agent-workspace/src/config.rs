@@ -0,0 +1,612 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const CONFIG_DIR_NAME: &str = "agent-workspace-launcher";
+const CONFIG_FILE_NAME: &str = "config.toml";
+const DEFAULT_SECTION: &str = "default";
+const PROFILE_SECTION_PREFIX: &str = "profiles.";
+
+/// A setting resolved with precedence env > active profile > `[default]` > built-in default.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Setting {
+    pub(crate) key: &'static str,
+    /// Env vars checked in order; the first non-empty one wins.
+    pub(crate) env: &'static [&'static str],
+    pub(crate) default: Option<&'static str>,
+    /// Expand a leading `~/` in config file values.
+    expand_home: bool,
+}
+
+const fn setting(
+    key: &'static str,
+    env: &'static [&'static str],
+    default: Option<&'static str>,
+) -> Setting {
+    Setting {
+        key,
+        env,
+        default,
+        expand_home: false,
+    }
+}
+
+const fn path_setting(key: &'static str, env: &'static [&'static str]) -> Setting {
+    Setting {
+        key,
+        env,
+        default: None,
+        expand_home: true,
+    }
+}
+
+pub(crate) const SETTINGS: &[Setting] = &[
+    setting(
+        "runtime",
+        &["AGENT_WORKSPACE_RUNTIME", "AWL_RUNTIME"],
+        Some("container"),
+    ),
+    path_setting("home", &["AGENT_WORKSPACE_HOME"]),
+    setting(
+        "prefix",
+        &["AGENT_WORKSPACE_PREFIX", "CODEX_WORKSPACE_PREFIX"],
+        Some("agent-ws"),
+    ),
+    setting(
+        "auth",
+        &["AGENT_WORKSPACE_AUTH", "CODEX_WORKSPACE_AUTH"],
+        Some("auto"),
+    ),
+    setting(
+        "image",
+        &["AGENT_ENV_IMAGE", "CODEX_ENV_IMAGE"],
+        Some("graysurf/agent-env:latest"),
+    ),
+    setting(
+        "gpg_key",
+        &["AGENT_WORKSPACE_GPG_KEY", "CODEX_WORKSPACE_GPG_KEY"],
+        None,
+    ),
+    setting("github_host", &["GITHUB_HOST"], Some("github.com")),
+    setting(
+        "codex_profile",
+        &[
+            "AGENT_WORKSPACE_CODEX_PROFILE",
+            "CODEX_WORKSPACE_CODEX_PROFILE",
+        ],
+        None,
+    ),
+    path_setting("codex_auth_file", &["CODEX_AUTH_FILE"]),
+    path_setting("codex_secret_dir", &["CODEX_SECRET_DIR"]),
+    setting(
+        "zsh_kit_repo",
+        &["AGENT_WORKSPACE_ZSH_KIT_REPO"],
+        Some("https://github.com/graysurf/zsh-kit.git"),
+    ),
+    setting(
+        "agent_kit_repo",
+        &["AGENT_WORKSPACE_AGENT_KIT_REPO"],
+        Some("https://github.com/graysurf/agent-kit.git"),
+    ),
+    setting(
+        "nils_cli_formula",
+        &["AGENT_WORKSPACE_NILS_CLI_FORMULA"],
+        Some("graysurf/tap/nils-cli"),
+    ),
+    setting("podman_userns", &["AGENT_WORKSPACE_PODMAN_USERNS"], None),
+    setting("docker_api", &["AGENT_WORKSPACE_DOCKER_API"], Some("auto")),
+    setting("ssh_command", &["AGENT_WORKSPACE_SSH"], Some("ssh")),
+    setting("ssh_home", &["AGENT_WORKSPACE_SSH_HOME"], None),
+    setting("hooks", &["AGENT_WORKSPACE_HOOKS"], Some("on")),
+    setting("repo_hooks", &["AGENT_WORKSPACE_REPO_HOOKS"], Some("auto")),
+    setting(
+        "hook_post_create",
+        &["AGENT_WORKSPACE_HOOK_POST_CREATE"],
+        None,
+    ),
+    setting("hook_pre_rm", &["AGENT_WORKSPACE_HOOK_PRE_RM"], None),
+    setting(
+        "hook_post_reset",
+        &["AGENT_WORKSPACE_HOOK_POST_RESET"],
+        None,
+    ),
+    setting("hook_pre_exec", &["AGENT_WORKSPACE_HOOK_PRE_EXEC"], None),
+];
+
+/// Where an effective value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Source {
+    Flag(&'static str),
+    Env(&'static str),
+    Profile(String),
+    ConfigDefault,
+    BuiltIn,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Flag(flag) => write!(f, "flag {flag}"),
+            Self::Env(name) => write!(f, "env {name}"),
+            Self::Profile(name) => write!(f, "profile {name}"),
+            Self::ConfigDefault => write!(f, "config [{DEFAULT_SECTION}]"),
+            Self::BuiltIn => write!(f, "built-in default"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ConfigFile {
+    sections: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl ConfigFile {
+    /// Parse the TOML subset the launcher writes: `[default]` and `[profiles.<name>]` tables
+    /// holding `key = value` pairs, where values are strings or bare scalars. Kept separate from
+    /// `crate::toml` (manifests) so bare values and `config set`'s line edits keep working.
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut file = Self::default();
+        let mut current = String::from(DEFAULT_SECTION);
+
+        for (idx, raw_line) in text.lines().enumerate() {
+            let line_no = idx + 1;
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                current =
+                    parse_section_header(line).map_err(|err| format!("line {line_no}: {err}"))?;
+                file.section_mut(&current);
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {line_no}: expected key = value"));
+            };
+            let key = key.trim();
+            if !is_valid_key(key) {
+                return Err(format!("line {line_no}: invalid key: {key}"));
+            }
+            let value =
+                parse_value(value.trim()).map_err(|err| format!("line {line_no}: {err}"))?;
+
+            let entries = file.section_mut(&current);
+            entries.retain(|(existing, _)| existing != key);
+            entries.push((key.to_string(), value));
+        }
+
+        Ok(file)
+    }
+
+    fn section_mut(&mut self, name: &str) -> &mut Vec<(String, String)> {
+        let idx = match self.sections.iter().position(|(known, _)| known == name) {
+            Some(idx) => idx,
+            None => {
+                self.sections.push((name.to_string(), Vec::new()));
+                self.sections.len() - 1
+            }
+        };
+        &mut self.sections[idx].1
+    }
+
+    fn has_section(&self, name: &str) -> bool {
+        self.sections.iter().any(|(known, _)| known == name)
+    }
+
+    fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections
+            .iter()
+            .find(|(known, _)| known == section)?
+            .1
+            .iter()
+            .find(|(known, _)| known == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn profile_names(&self) -> Vec<String> {
+        self.sections
+            .iter()
+            .filter_map(|(name, _)| name.strip_prefix(PROFILE_SECTION_PREFIX))
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+struct Active {
+    file: ConfigFile,
+    profile: Option<String>,
+}
+
+static ACTIVE: Mutex<Option<Active>> = Mutex::new(None);
+
+pub(crate) fn config_path() -> PathBuf {
+    if let Some(path) = env_nonempty("AGENT_WORKSPACE_CONFIG") {
+        return PathBuf::from(path);
+    }
+
+    if let Some(dir) = env_nonempty("XDG_CONFIG_HOME") {
+        return PathBuf::from(dir)
+            .join(CONFIG_DIR_NAME)
+            .join(CONFIG_FILE_NAME);
+    }
+
+    if let Some(home) = env_nonempty("HOME") {
+        return PathBuf::from(home)
+            .join(".config")
+            .join(CONFIG_DIR_NAME)
+            .join(CONFIG_FILE_NAME);
+    }
+
+    std::env::temp_dir()
+        .join(CONFIG_DIR_NAME)
+        .join(CONFIG_FILE_NAME)
+}
+
+/// Profile chosen by `--profile`, else `AGENT_WORKSPACE_PROFILE`.
+pub(crate) fn select_profile(flag: Option<&str>) -> Result<Option<String>, String> {
+    match flag {
+        Some(flag) => parse_profile_name(flag).map(Some),
+        None => env_nonempty("AGENT_WORKSPACE_PROFILE")
+            .map(|value| parse_profile_name(&value))
+            .transpose(),
+    }
+}
+
+fn parse_profile_name(raw: &str) -> Result<String, String> {
+    let name = raw.trim();
+    if is_valid_key(name) {
+        Ok(name.to_string())
+    } else {
+        Err(format!(
+            "invalid profile name: {raw} (expected letters, digits, '-' or '_')"
+        ))
+    }
+}
+
+/// Load the config file and select `profile`, which must exist in the file.
+pub(crate) fn activate(profile: Option<&str>) -> Result<(), String> {
+    let path = config_path();
+    let file = load_file(&path)?;
+    if let Some(profile) = profile
+        && !file.has_section(&section_name(Some(profile)))
+    {
+        return Err(format!(
+            "profile not found: {profile} (in {})",
+            path.display()
+        ));
+    }
+
+    *ACTIVE.lock().unwrap_or_else(|poison| poison.into_inner()) = Some(Active {
+        file,
+        profile: profile.map(str::to_string),
+    });
+    Ok(())
+}
+
+fn with_active<T>(f: impl FnOnce(&Active) -> T) -> T {
+    let mut guard = ACTIVE.lock().unwrap_or_else(|poison| poison.into_inner());
+    let active = guard.get_or_insert_with(|| Active {
+        file: load_file(&config_path()).unwrap_or_default(),
+        profile: env_nonempty("AGENT_WORKSPACE_PROFILE"),
+    });
+    f(active)
+}
+
+fn load_file(path: &Path) -> Result<ConfigFile, String> {
+    if !path.is_file() {
+        return Ok(ConfigFile::default());
+    }
+
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("failed to read config {}: {err}", path.display()))?;
+    ConfigFile::parse(&text).map_err(|err| format!("invalid config {}: {err}", path.display()))
+}
+
+pub(crate) fn find_setting(key: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|setting| setting.key == key)
+}
+
+/// Effective value and its source, or `None` when the setting has no value anywhere.
+pub(crate) fn lookup(key: &str) -> Option<(String, Source)> {
+    let setting = find_setting(key)?;
+
+    for name in setting.env {
+        if let Some(value) = env_nonempty(name) {
+            return Some((value, Source::Env(name)));
+        }
+    }
+
+    let from_file = with_active(|active| {
+        if let Some(profile) = active.profile.as_deref()
+            && let Some(value) = active.file.get(&section_name(Some(profile)), key)
+        {
+            return Some((value.to_string(), Source::Profile(profile.to_string())));
+        }
+        active
+            .file
+            .get(DEFAULT_SECTION, key)
+            .map(|value| (value.to_string(), Source::ConfigDefault))
+    });
+    if let Some((value, source)) = from_file {
+        let value = if setting.expand_home {
+            expand_home(&value)
+        } else {
+            value
+        };
+        return Some((value, source));
+    }
+
+    setting
+        .default
+        .map(|value| (value.to_string(), Source::BuiltIn))
+}
+
+pub(crate) fn value(key: &str) -> Option<String> {
+    lookup(key).map(|(value, _)| value)
+}
+
+pub(crate) fn active_profile() -> Option<String> {
+    with_active(|active| active.profile.clone())
+}
+
+pub(crate) fn profile_names() -> Vec<String> {
+    with_active(|active| active.file.profile_names())
+}
+
+/// Write `key = value` into `[profiles.<profile>]` (or `[default]`), keeping the rest of the file.
+pub(crate) fn set_value(profile: Option<&str>, key: &str, value: &str) -> Result<PathBuf, String> {
+    let path = config_path();
+    let text = if path.is_file() {
+        fs::read_to_string(&path)
+            .map_err(|err| format!("failed to read config {}: {err}", path.display()))?
+    } else {
+        String::new()
+    };
+    ConfigFile::parse(&text).map_err(|err| format!("invalid config {}: {err}", path.display()))?;
+
+    let updated = upsert_entry(&text, &section_name(profile), key, value);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("failed to create config dir {}: {err}", parent.display()))?;
+    }
+    fs::write(&path, updated)
+        .map_err(|err| format!("failed to write config {}: {err}", path.display()))?;
+    Ok(path)
+}
+
+pub(crate) fn section_name(profile: Option<&str>) -> String {
+    match profile {
+        Some(profile) => format!("{PROFILE_SECTION_PREFIX}{profile}"),
+        None => String::from(DEFAULT_SECTION),
+    }
+}
+
+fn upsert_entry(text: &str, section: &str, key: &str, value: &str) -> String {
+    let entry = format!("{key} = {}", quote_value(value));
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+
+    let mut current = String::from(DEFAULT_SECTION);
+    let mut section_seen = false;
+    let mut insert_at: Option<usize> = None;
+    for (idx, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            if let Ok(name) = parse_section_header(trimmed) {
+                current = name;
+            }
+            if current == section {
+                section_seen = true;
+                insert_at = Some(idx + 1);
+            }
+            continue;
+        }
+        if current != section || trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if trimmed
+            .split_once('=')
+            .is_some_and(|(existing, _)| existing.trim() == key)
+        {
+            lines[idx] = entry;
+            return join_lines(&lines);
+        }
+        if section_seen {
+            insert_at = Some(idx + 1);
+        }
+    }
+
+    match insert_at {
+        Some(idx) => lines.insert(idx, entry),
+        None => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(format!("[{section}]"));
+            lines.push(entry);
+        }
+    }
+    join_lines(&lines)
+}
+
+fn join_lines(lines: &[String]) -> String {
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+fn parse_section_header(line: &str) -> Result<String, String> {
+    let without_comment = strip_comment(line);
+    let Some(inner) = without_comment
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+    else {
+        return Err(format!("invalid section header: {line}"));
+    };
+    if inner.starts_with('[') {
+        return Err(format!("array tables are not supported: {line}"));
+    }
+
+    let name = inner
+        .split('.')
+        .map(|part| part.trim().trim_matches('"'))
+        .collect::<Vec<&str>>()
+        .join(".");
+    if name == DEFAULT_SECTION {
+        return Ok(name);
+    }
+    if let Some(profile) = name.strip_prefix(PROFILE_SECTION_PREFIX)
+        && is_valid_key(profile)
+    {
+        return Ok(name);
+    }
+
+    Err(format!(
+        "unsupported section [{name}] (expected [{DEFAULT_SECTION}] or [{PROFILE_SECTION_PREFIX}<name>])"
+    ))
+}
+
+fn parse_value(raw: &str) -> Result<String, String> {
+    if let Some(rest) = raw.strip_prefix('"') {
+        let mut out = String::new();
+        let mut chars = rest.chars();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '"' => return expect_line_end(chars.as_str()).map(|()| out),
+                '\\' => match chars.next() {
+                    Some('"') => out.push('"'),
+                    Some('\\') => out.push('\\'),
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some(other) => return Err(format!("unsupported escape: \\{other}")),
+                    None => break,
+                },
+                _ => out.push(ch),
+            }
+        }
+        return Err(String::from("unterminated string"));
+    }
+
+    if let Some(rest) = raw.strip_prefix('\'') {
+        let Some((literal, tail)) = rest.split_once('\'') else {
+            return Err(String::from("unterminated string"));
+        };
+        expect_line_end(tail)?;
+        return Ok(literal.to_string());
+    }
+
+    let bare = strip_comment(raw).trim();
+    if bare.is_empty() {
+        return Err(String::from("missing value"));
+    }
+    Ok(bare.to_string())
+}
+
+fn expect_line_end(tail: &str) -> Result<(), String> {
+    let tail = tail.trim();
+    if tail.is_empty() || tail.starts_with('#') {
+        Ok(())
+    } else {
+        Err(format!("unexpected trailing text: {tail}"))
+    }
+}
+
+fn strip_comment(raw: &str) -> &str {
+    raw.split_once('#').map_or(raw, |(head, _)| head).trim()
+}
+
+fn quote_value(value: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-')
+}
+
+fn expand_home(value: &str) -> String {
+    if let Some(rest) = value.strip_prefix("~/")
+        && let Some(home) = env_nonempty("HOME")
+    {
+        return format!("{}/{rest}", home.trim_end_matches('/'));
+    }
+    value.to_string()
+}
+
+fn env_nonempty(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigFile, parse_value, upsert_entry};
+
+    #[test]
+    fn parses_default_and_profile_sections() {
+        let file = ConfigFile::parse(
+            "runtime = \"host\"\n\n[default]\nprefix = 'team' # inline\n\n[profiles.work]\ngithub_host = \"ghe.example.com\"\n",
+        )
+        .expect("parse config");
+        assert_eq!(file.get("default", "runtime"), Some("host"));
+        assert_eq!(file.get("default", "prefix"), Some("team"));
+        assert_eq!(
+            file.get("profiles.work", "github_host"),
+            Some("ghe.example.com")
+        );
+        assert_eq!(file.profile_names(), vec![String::from("work")]);
+    }
+
+    #[test]
+    fn rejects_unknown_sections_with_line_number() {
+        let err = ConfigFile::parse("[default]\nimage = \"x\"\n[servers]\n").expect_err("reject");
+        assert!(err.starts_with("line 3:"), "{err}");
+    }
+
+    #[test]
+    fn parses_escaped_strings() {
+        assert_eq!(
+            parse_value(r#""a \"b\" \\c""#).expect("parse"),
+            r#"a "b" \c"#
+        );
+        assert!(parse_value("\"open").is_err());
+    }
+
+    #[test]
+    fn upsert_replaces_or_appends_entries() {
+        let text = "[default]\nruntime = \"host\"\n\n[profiles.work]\nimage = \"a\"\n";
+        let replaced = upsert_entry(text, "profiles.work", "image", "b");
+        assert!(replaced.contains("image = \"b\"\n"));
+        assert!(!replaced.contains("image = \"a\""));
+
+        let inserted = upsert_entry(text, "default", "prefix", "team");
+        assert_eq!(
+            inserted,
+            "[default]\nruntime = \"host\"\nprefix = \"team\"\n\n[profiles.work]\nimage = \"a\"\n"
+        );
+
+        let appended = upsert_entry(text, "profiles.home", "runtime", "podman");
+        assert!(appended.ends_with("\n\n[profiles.home]\nruntime = \"podman\"\n"));
+        assert!(ConfigFile::parse(&appended).is_ok());
+    }
+}
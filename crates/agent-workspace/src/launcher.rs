@@ -1,11 +1,35 @@
+mod archive;
 mod auth;
+mod backend;
+mod clone;
+mod config;
 mod container;
+mod cp;
 mod create;
+mod doctor;
+mod engine_api;
 mod exec;
+mod forward;
+mod gc;
+mod hooks;
+mod host;
+mod inspect;
+mod json;
+mod local_sync;
 mod ls;
+mod manifest;
+mod rename;
 mod reset;
+mod restore;
 mod rm;
+mod rsync;
+mod snapshot;
+mod ssh;
+mod start;
+mod stop;
 mod tunnel;
+mod upgrade;
+mod watch;
 
 use std::ffi::OsString;
 use std::fs;
@@ -15,68 +39,150 @@ use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::EXIT_RUNTIME;
+use crate::config::Source;
 use crate::runtime::{Runtime, resolve_runtime};
 
+use backend::WorkspaceBackend;
+use container::{ContainerBackend, ContainerEngine};
+use host::HostBackend;
+use ssh::SshBackend;
+
 const PRIMARY_COMMAND_NAME: &str = "agent-workspace-launcher";
 const WORKSPACE_META_FILE: &str = ".workspace-meta";
+/// `KEY=VALUE` lines exported by host and ssh `exec`; containers get them at creation.
+const WORKSPACE_ENV_FILE: &str = ".workspace-env";
+
+#[cfg(test)]
+fn dispatch(subcommand: &str, args: &[OsString]) -> i32 {
+    dispatch_with_profile(subcommand, None, args)
+}
+
+/// Dispatch with the top-level `--profile` value (falls back to `AGENT_WORKSPACE_PROFILE`).
+pub fn dispatch_with_profile(subcommand: &str, profile: Option<&str>, args: &[OsString]) -> i32 {
+    let profile = match crate::config::select_profile(profile) {
+        Ok(profile) => profile,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if subcommand == "config" {
+        return config::run(profile.as_deref(), args);
+    }
+
+    if let Err(err) = crate::config::activate(profile.as_deref()) {
+        eprintln!("error: {err}");
+        eprintln!("hint: run '{PRIMARY_COMMAND_NAME} config list' to inspect the config file");
+        return EXIT_RUNTIME;
+    }
 
-pub fn dispatch(subcommand: &str, args: &[OsString]) -> i32 {
     let (runtime, filtered_args) = match resolve_runtime(args) {
         Ok(value) => value,
         Err(err) => {
             eprintln!("error: {err}");
-            eprintln!("hint: use --runtime container or --runtime host");
+            eprintln!(
+                "hint: use --runtime container, --runtime podman, --runtime host, or --runtime ssh://[user@]host"
+            );
             return EXIT_RUNTIME;
         }
     };
 
-    let status = match runtime {
-        Runtime::Host => dispatch_host(subcommand, &filtered_args),
-        Runtime::Container => dispatch_container(subcommand, &filtered_args),
+    let runtime_pinned = filtered_args.len() != args.len()
+        || crate::config::lookup("runtime").is_some_and(|(_, source)| source != Source::BuiltIn);
+
+    let backend = backend_for(runtime);
+    let backend = backend.as_ref();
+    let status = match subcommand {
+        "auth" => auth::run(backend, &filtered_args),
+        "create" => create::run(backend, &filtered_args, runtime_pinned),
+        "clone" => clone::run(backend, &filtered_args),
+        "rsync" => rsync::run(backend, &filtered_args),
+        "cp" => cp::run(backend, &filtered_args),
+        "ls" => ls::run(backend, &filtered_args),
+        "rm" => rm::run(backend, &filtered_args),
+        "rename" => rename::run(backend, &filtered_args),
+        "snapshot" => snapshot::run(backend, &filtered_args),
+        "restore" => restore::run(backend, &filtered_args),
+        "exec" => exec::run(backend, &filtered_args),
+        "inspect" => inspect::run(backend, &filtered_args),
+        "start" => start::run(backend, &filtered_args),
+        "stop" => stop::run(backend, &filtered_args),
+        "upgrade" => upgrade::run(backend, &filtered_args),
+        "gc" => gc::run(backend, &filtered_args),
+        "doctor" => doctor::run(backend, &filtered_args),
+        "reset" => reset::run(backend, &filtered_args),
+        "tunnel" => tunnel::run(backend, &filtered_args),
+        "forward" => forward::run(backend, &filtered_args),
+        _ => {
+            eprintln!("error: unknown subcommand: {subcommand}");
+            EXIT_RUNTIME
+        }
     };
 
-    if status != 0 && runtime == Runtime::Container && !command_exists("docker") {
-        eprintln!(
-            "hint: install/start Docker or retry with '--runtime host' (or AGENT_WORKSPACE_RUNTIME=host)"
-        );
+    // `doctor` already reports the runtime's availability with its own hints.
+    if status != 0
+        && subcommand != "doctor"
+        && let Some(hint) = backend.unavailable_hint()
+    {
+        eprintln!("hint: {hint}");
     }
 
     status
 }
 
 pub(crate) fn completion_workspace_names(runtime: Runtime) -> Result<Vec<String>, String> {
+    Ok(backend_for(runtime)
+        .list()?
+        .into_iter()
+        .map(|workspace| workspace.name)
+        .collect())
+}
+
+fn backend_for(runtime: Runtime) -> Box<dyn WorkspaceBackend> {
     match runtime {
-        Runtime::Host => Ok(list_workspaces_on_disk()?
-            .into_iter()
-            .map(|workspace| workspace.name)
-            .collect()),
-        Runtime::Container => container::completion_workspace_names(),
+        Runtime::Host => Box::new(HostBackend),
+        Runtime::Container => Box::new(ContainerBackend {
+            engine: ContainerEngine::Docker,
+        }),
+        Runtime::Podman => Box::new(ContainerBackend {
+            engine: ContainerEngine::Podman,
+        }),
+        Runtime::Ssh(target) => Box::new(SshBackend::new(&target)),
     }
 }
 
-fn dispatch_host(subcommand: &str, args: &[OsString]) -> i32 {
-    match subcommand {
-        "auth" => auth::run(args),
-        "create" => create::run(args),
-        "rsync" => {
-            eprintln!("error: rsync is only available in container runtime");
-            eprintln!("hint: retry with '--runtime container'");
-            EXIT_RUNTIME
+/// Resolve a workspace by name, or pick the only one when no name is given, then make it
+/// ready for exec-style operations.
+fn resolve_ready_workspace(
+    backend: &dyn WorkspaceBackend,
+    name: Option<&str>,
+) -> Result<String, String> {
+    let workspace = if let Some(name) = name.and_then(trimmed_nonempty) {
+        match backend.resolve(&name)? {
+            Some(workspace) => workspace,
+            None => return Err(format!("workspace not found: {name}")),
         }
-        "ls" => ls::run(args),
-        "rm" => rm::run(args),
-        "exec" => exec::run(args),
-        "reset" => reset::run(args),
-        "tunnel" => tunnel::run(args),
-        _ => {
-            eprintln!("error: unknown subcommand: {subcommand}");
-            EXIT_RUNTIME
+    } else {
+        let workspaces: Vec<String> = backend
+            .list()?
+            .into_iter()
+            .map(|workspace| workspace.name)
+            .collect();
+        match workspaces.as_slice() {
+            [] => return Err(String::from("no workspaces found")),
+            [single] => single.clone(),
+            _ => {
+                return Err(format!(
+                    "multiple workspaces found; specify one: {}",
+                    workspaces.join(", ")
+                ));
+            }
         }
-    }
-}
+    };
 
-fn dispatch_container(subcommand: &str, args: &[OsString]) -> i32 {
-    container::dispatch(subcommand, args)
+    backend.ensure_ready(&workspace)?;
+    Ok(workspace)
 }
 
 #[derive(Debug, Clone)]
@@ -165,6 +271,9 @@ fn list_workspaces_on_disk() -> Result<Vec<Workspace>, String> {
         let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
             continue;
         };
+        if name.starts_with('.') {
+            continue;
+        }
 
         workspaces.push(Workspace { name, path });
     }
@@ -182,7 +291,11 @@ fn resolve_workspace(name: &str) -> Result<Option<Workspace>, String> {
     let root = ensure_workspace_root()?;
     let prefixes = workspace_prefixes();
 
+    // Dot-dirs under the root (`.mirrors`, `.forwards`) hold launcher state, not workspaces.
     for candidate in workspace_resolution_candidates(&workspace_name, &prefixes) {
+        if candidate.starts_with('.') {
+            continue;
+        }
         let path = root.join(&candidate);
         if path.is_dir() {
             return Ok(Some(Workspace {
@@ -202,8 +315,21 @@ fn ensure_workspace_root() -> Result<PathBuf, String> {
     Ok(root)
 }
 
+/// Fresh private directory under the system temp dir, e.g. `agent-workspace-manifest-<pid>-<nanos>`.
+fn scratch_dir(label: &str) -> Result<PathBuf, String> {
+    let pid = std::process::id();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+    let path = std::env::temp_dir().join(format!("agent-workspace-{label}-{pid}-{now}"));
+    fs::create_dir_all(&path)
+        .map_err(|err| format!("failed to create {}: {err}", path.display()))?;
+    Ok(path)
+}
+
 fn workspace_storage_root() -> PathBuf {
-    if let Ok(value) = std::env::var("AGENT_WORKSPACE_HOME")
+    if let Some(value) = crate::config::value("home")
         && let Some(cleaned) = trimmed_nonempty(&value)
     {
         return PathBuf::from(cleaned);
@@ -235,13 +361,7 @@ fn workspace_storage_root() -> PathBuf {
 fn workspace_prefixes() -> Vec<String> {
     let mut prefixes: Vec<String> = Vec::new();
 
-    if let Ok(value) = std::env::var("AGENT_WORKSPACE_PREFIX")
-        && let Some(cleaned) = trimmed_nonempty(&value)
-    {
-        push_unique(&mut prefixes, cleaned);
-    }
-
-    if let Ok(value) = std::env::var("CODEX_WORKSPACE_PREFIX")
+    if let Some(value) = crate::config::value("prefix")
         && let Some(cleaned) = trimmed_nonempty(&value)
     {
         push_unique(&mut prefixes, cleaned);
@@ -362,7 +482,7 @@ fn command_exists(command: &str) -> bool {
 }
 
 fn resolve_codex_auth_file() -> String {
-    if let Ok(value) = std::env::var("CODEX_AUTH_FILE")
+    if let Some(value) = crate::config::value("codex_auth_file")
         && !value.trim().is_empty()
     {
         return value;
@@ -384,7 +504,7 @@ fn resolve_codex_profile_auth_files(profile: &str) -> Vec<String> {
     };
 
     let mut dirs: Vec<String> = Vec::new();
-    if let Ok(value) = std::env::var("CODEX_SECRET_DIR")
+    if let Some(value) = crate::config::value("codex_secret_dir")
         && !value.trim().is_empty()
     {
         dirs.push(value);
@@ -427,13 +547,7 @@ fn resolve_codex_profile_auth_files(profile: &str) -> Vec<String> {
 }
 
 fn default_gpg_signing_key() -> Option<String> {
-    if let Ok(value) = std::env::var("AGENT_WORKSPACE_GPG_KEY")
-        && let Some(cleaned) = trimmed_nonempty(&value)
-    {
-        return Some(cleaned);
-    }
-
-    if let Ok(value) = std::env::var("CODEX_WORKSPACE_GPG_KEY")
+    if let Some(value) = crate::config::value("gpg_key")
         && let Some(cleaned) = trimmed_nonempty(&value)
     {
         return Some(cleaned);
@@ -484,6 +598,67 @@ fn confirm_or_abort(prompt: &str) -> bool {
     matches!(input.trim().to_ascii_lowercase().as_str(), "y" | "yes")
 }
 
+/// Parse a duration such as `90s`, `30m`, `2h`, `1d` or `1h30m` into seconds; bare numbers are
+/// seconds.
+fn parse_duration(input: &str) -> Result<u64, String> {
+    let cleaned = input.trim();
+    let invalid = || format!("invalid duration (expected e.g. 30m, 2h, 1d): {input}");
+    if cleaned.is_empty() {
+        return Err(invalid());
+    }
+    if let Ok(secs) = cleaned.parse::<u64>() {
+        return Ok(secs);
+    }
+
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for ch in cleaned.chars() {
+        if ch.is_ascii_digit() {
+            digits.push(ch);
+            continue;
+        }
+        let unit = match ch {
+            's' => 1,
+            'm' => 60,
+            'h' => 3_600,
+            'd' => 86_400,
+            'w' => 604_800,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = digits.parse().map_err(|_| invalid())?;
+        total = value
+            .checked_mul(unit)
+            .and_then(|secs| total.checked_add(secs))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+    if !digits.is_empty() {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+/// Render seconds as the two largest units, e.g. `2h5m` or `45s`.
+fn format_duration(secs: u64) -> String {
+    let units = [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)];
+    let mut out = String::new();
+    let mut remaining = secs;
+    let mut parts = 0;
+    for (suffix, size) in units {
+        if remaining >= size && parts < 2 {
+            out.push_str(&format!("{}{suffix}", remaining / size));
+            remaining %= size;
+            parts += 1;
+        } else if parts > 0 {
+            break;
+        }
+    }
+    if out.is_empty() {
+        out.push_str("0s");
+    }
+    out
+}
+
 fn trimmed_nonempty(input: &str) -> Option<String> {
     let trimmed = input.trim();
     if trimmed.is_empty() {
@@ -541,15 +716,33 @@ mod tests {
     use tempfile::TempDir;
 
     use super::{
-        Workspace,
-        auth::{codex_auth_targets, resolve_workspace_for_auth},
-        create::parse_create_args,
-        dispatch,
+        RepoSpec, Workspace,
+        auth::resolve_workspace_for_auth,
+        backend::{
+            COPY_IN_SCRIPT, COPY_OUT_SCRIPT, CheckStatus, CloneOptions, CopyRequest, ExecRequest,
+            RemoveOptions, RepoSlot, RepoStatus, RsyncDirection, RsyncTransfer, TunnelAction,
+            TunnelStatus, WorkspaceBackend, stream_copy_in, stream_copy_out,
+        },
+        container::ContainerEngine,
+        cp::parse_cp_args,
+        create::{CloneJob, clone_repos, parse_create_args},
+        dispatch, dispatch_with_profile,
+        doctor::parse_doctor_args,
+        engine_api::{EngineApi, ExecError},
         exec::parse_exec_args,
-        normalize_workspace_name_for_create, parse_repo_spec,
+        format_duration,
+        forward::{PortSpec, live_forwards, parse_forward_args},
+        gc::stale_workspaces,
+        host::{HostBackend, codex_auth_targets},
+        local_sync::{SyncOptions, sync_tree},
+        ls::{LsSort, parse_ls_args},
+        manifest::fetch_manifest,
+        normalize_workspace_name_for_create, parse_duration, parse_repo_spec,
         reset::parse_reset_work_repos_args,
         resolve_codex_auth_file, resolve_codex_profile_auth_files,
         tunnel::parse_tunnel_args,
+        upgrade::parse_upgrade_args,
+        watch::SourceWatcher,
         workspace_name_variants, workspace_prefixes, workspace_storage_root,
     };
 
@@ -562,15 +755,19 @@ mod tests {
         unsafe {
             std::env::set_var("AGENT_WORKSPACE_HOME", temp.path());
             std::env::set_var("AGENT_WORKSPACE_RUNTIME", "host");
+            std::env::set_var("AGENT_WORKSPACE_CONFIG", temp.path().join("config.toml"));
             std::env::remove_var("AGENT_WORKSPACE_PREFIX");
             std::env::remove_var("CODEX_WORKSPACE_PREFIX");
+            std::env::remove_var("AGENT_WORKSPACE_PROFILE");
         }
+        crate::config::activate(None).expect("reset config");
 
         let result = f(&temp);
 
         unsafe {
             std::env::remove_var("AGENT_WORKSPACE_HOME");
             std::env::remove_var("AGENT_WORKSPACE_RUNTIME");
+            std::env::remove_var("AGENT_WORKSPACE_CONFIG");
             std::env::remove_var("AGENT_WORKSPACE_PREFIX");
             std::env::remove_var("CODEX_WORKSPACE_PREFIX");
             std::env::remove_var("CODEX_AUTH_FILE");
@@ -582,6 +779,47 @@ mod tests {
         result
     }
 
+    /// Run the container runtime against a `docker` shell script that logs its args to
+    /// `docker.log`; `DOCKER_HOST` names a missing socket so engine API calls fall back to it.
+    fn with_fake_docker<T>(temp: &TempDir, script: &str, f: impl FnOnce() -> T) -> T {
+        use std::os::unix::fs::PermissionsExt;
+
+        let bin = temp.path().join("bin");
+        std::fs::create_dir_all(&bin).expect("create bin");
+        let docker = bin.join("docker");
+        std::fs::write(
+            &docker,
+            format!(
+                "#!/bin/sh\necho \"$*\" >> '{}'\n{script}",
+                temp.path().join("docker.log").display()
+            ),
+        )
+        .expect("write fake docker");
+        std::fs::set_permissions(&docker, std::fs::Permissions::from_mode(0o755))
+            .expect("chmod fake docker");
+
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        unsafe {
+            std::env::set_var("AGENT_WORKSPACE_RUNTIME", "container");
+            std::env::set_var(
+                "DOCKER_HOST",
+                format!("unix://{}", temp.path().join("missing.sock").display()),
+            );
+            std::env::set_var(
+                "PATH",
+                format!("{}:{}", bin.display(), path.to_string_lossy()),
+            );
+        }
+
+        let result = f();
+
+        unsafe {
+            std::env::set_var("PATH", path);
+            std::env::remove_var("DOCKER_HOST");
+        }
+        result
+    }
+
     #[test]
     fn parse_repo_spec_accepts_owner_repo() {
         let parsed = parse_repo_spec("octo/demo", "github.com").expect("parse owner/repo");
@@ -599,6 +837,171 @@ mod tests {
         assert_eq!(parsed.clone_url, "https://example.com/octo/demo.git");
     }
 
+    fn spawn_fake_engine(
+        socket: &std::path::Path,
+        connections: usize,
+        respond: fn(&str, &str) -> (u16, Vec<u8>, bool),
+    ) -> std::thread::JoinHandle<Vec<String>> {
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::os::unix::net::UnixListener;
+
+        let listener = UnixListener::bind(socket).expect("bind fake engine socket");
+        std::thread::spawn(move || {
+            let mut seen: Vec<String> = Vec::new();
+            for _ in 0..connections {
+                let (stream, _) = listener.accept().expect("accept");
+                let mut reader = BufReader::new(stream);
+                let mut request_line = String::new();
+                reader
+                    .read_line(&mut request_line)
+                    .expect("read request line");
+                let mut content_length = 0usize;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).expect("read header");
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+                let mut body = vec![0u8; content_length];
+                reader.read_exact(&mut body).expect("read body");
+
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+                let (status, payload, chunked) = respond(&method, &path);
+
+                let mut stream = reader.into_inner();
+                let mut response = format!("HTTP/1.1 {status} X\r\n").into_bytes();
+                if chunked {
+                    response.extend_from_slice(b"Transfer-Encoding: chunked\r\n\r\n");
+                    for chunk in payload.chunks(5) {
+                        response.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+                        response.extend_from_slice(chunk);
+                        response.extend_from_slice(b"\r\n");
+                    }
+                    response.extend_from_slice(b"0\r\n\r\n");
+                } else {
+                    response.extend_from_slice(
+                        format!("Content-Length: {}\r\n\r\n", payload.len()).as_bytes(),
+                    );
+                    response.extend_from_slice(&payload);
+                }
+                stream.write_all(&response).expect("write response");
+                seen.push(format!("{method} {path}"));
+            }
+            seen
+        })
+    }
+
+    #[test]
+    fn engine_api_reports_missing_container_and_collects_exec_output() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let socket = temp.path().join("docker.sock");
+        let server = spawn_fake_engine(&socket, 4, |method, path| match (method, path) {
+            ("GET", "/containers/ws-missing/json") => {
+                (404, br#"{"message":"No such container"}"#.to_vec(), false)
+            }
+            ("POST", "/containers/ws-demo/exec") => (201, br#"{"Id":"abc123"}"#.to_vec(), false),
+            ("POST", "/exec/abc123/start") => {
+                let mut frames: Vec<u8> = Vec::new();
+                for (stream, payload) in [(1u8, &b"out\n"[..]), (2u8, &b"err\n"[..])] {
+                    frames.extend_from_slice(&[stream, 0, 0, 0]);
+                    frames.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+                    frames.extend_from_slice(payload);
+                }
+                (200, frames, false)
+            }
+            ("GET", "/exec/abc123/json") => {
+                (200, br#"{"Running":false,"ExitCode":3}"#.to_vec(), true)
+            }
+            _ => (500, br#"{"message":"unexpected request"}"#.to_vec(), false),
+        });
+
+        let api = EngineApi::new(&socket);
+        assert_eq!(api.container_exists("ws-missing"), Ok(false));
+        let output = api
+            .exec(
+                "ws-demo",
+                &["bash", "-lc", "echo out; echo err >&2; exit 3"],
+            )
+            .expect("exec through fake engine");
+        assert_eq!(output.exit_code, 3);
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "err\n");
+
+        let seen = server.join().expect("join fake engine");
+        assert_eq!(
+            seen,
+            vec![
+                "GET /containers/ws-missing/json",
+                "POST /containers/ws-demo/exec",
+                "POST /exec/abc123/start",
+                "GET /exec/abc123/json",
+            ]
+        );
+    }
+
+    #[test]
+    fn engine_api_exec_errors_say_whether_the_command_started() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let socket = temp.path().join("docker.sock");
+        let server = spawn_fake_engine(&socket, 4, |method, path| match (method, path) {
+            ("POST", "/containers/ws-gone/exec") => {
+                (404, br#"{"message":"No such container"}"#.to_vec(), false)
+            }
+            ("POST", "/containers/ws-demo/exec") => (201, br#"{"Id":"abc123"}"#.to_vec(), false),
+            ("POST", "/exec/abc123/start") => (200, Vec::new(), false),
+            _ => (500, br#"{"message":"daemon went away"}"#.to_vec(), false),
+        });
+
+        let api = EngineApi::new(&socket);
+        assert!(matches!(
+            api.exec("ws-gone", &["true"]),
+            Err(ExecError::NotStarted(_))
+        ));
+        assert_eq!(
+            api.exec("ws-demo", &["true"]),
+            Err(ExecError::Started(String::from(
+                "docker exec ws-demo failed: daemon went away"
+            )))
+        );
+
+        let seen = server.join().expect("join fake engine");
+        assert_eq!(
+            seen,
+            vec![
+                "POST /containers/ws-gone/exec",
+                "POST /containers/ws-demo/exec",
+                "POST /exec/abc123/start",
+                "GET /exec/abc123/json",
+            ]
+        );
+    }
+
+    #[test]
+    fn podman_qualifies_short_image_names() {
+        assert_eq!(
+            ContainerEngine::Podman.qualify_image("graysurf/agent-env:latest"),
+            "docker.io/graysurf/agent-env:latest"
+        );
+        assert_eq!(
+            ContainerEngine::Podman.qualify_image("ubuntu"),
+            "docker.io/library/ubuntu"
+        );
+        assert_eq!(
+            ContainerEngine::Podman.qualify_image("ghcr.io/octo/env:1"),
+            "ghcr.io/octo/env:1"
+        );
+        assert_eq!(
+            ContainerEngine::Docker.qualify_image("graysurf/agent-env:latest"),
+            "graysurf/agent-env:latest"
+        );
+    }
+
     #[test]
     fn workspace_variants_strip_prefixes() {
         let prefixes = workspace_prefixes();
@@ -624,6 +1027,90 @@ mod tests {
         assert!(err.contains("--no-work-repos"));
     }
 
+    #[test]
+    fn parse_create_validates_container_options() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let source = temp.path().display().to_string();
+        parse_create_args(&[
+            OsString::from("--env=A=1"),
+            OsString::from("--mount"),
+            OsString::from(format!("{source}:/data:ro")),
+            OsString::from("--publish=127.0.0.1:3000:3000/tcp"),
+            OsString::from("--cpus=1.5"),
+            OsString::from("--memory=8g"),
+        ])
+        .expect("parse container options");
+
+        for (args, expected) in [
+            (["--mount", "/definitely/missing:/data"], "source not found"),
+            (["--mount", &format!("{source}:/work")], "workspace volume"),
+            (
+                ["--mount", &format!("{source}:/data:rx")],
+                "invalid --mount",
+            ),
+            (["--publish", "3000:web"], "invalid --publish"),
+            (["--cpus", "0"], "invalid --cpus"),
+            (["--memory", "8gb"], "invalid --memory"),
+            (["--env", "BAD-NAME=1"], "invalid variable name"),
+        ] {
+            let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+            let err = parse_create_args(&args).expect_err("reject invalid option");
+            assert!(err.contains(expected), "{err}");
+        }
+    }
+
+    #[test]
+    fn host_create_applies_env_file_then_env_flags() {
+        with_workspace_env(|temp| {
+            let env_file = temp.path().join("dev.env");
+            std::fs::write(&env_file, "# comment\nAPP_MODE=dev\nAPP_PORT=3000\n\n")
+                .expect("write env file");
+            assert_eq!(
+                dispatch(
+                    "create",
+                    &[
+                        OsString::from("--no-work-repos"),
+                        OsString::from("--name"),
+                        OsString::from("ws-env"),
+                        OsString::from("--env"),
+                        OsString::from("APP_MODE=prod"),
+                        OsString::from("--env-file"),
+                        env_file.into_os_string(),
+                    ],
+                ),
+                0
+            );
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = std::fs::metadata(temp.path().join("env").join(".workspace-env"))
+                    .expect("env file metadata")
+                    .permissions()
+                    .mode();
+                assert_eq!(mode & 0o777, 0o600);
+            }
+
+            let command: Vec<OsString> = [
+                "bash",
+                "-c",
+                "test \"$APP_MODE\" = prod && test \"$APP_PORT\" = 3000",
+            ]
+            .into_iter()
+            .map(OsString::from)
+            .collect();
+            let code = HostBackend
+                .exec(
+                    "env",
+                    &ExecRequest {
+                        user: None,
+                        command: &command,
+                    },
+                )
+                .expect("exec");
+            assert_eq!(code, 0);
+        });
+    }
+
     #[test]
     fn parse_exec_supports_user_and_command() {
         let parsed = parse_exec_args(&[
@@ -687,6 +1174,105 @@ mod tests {
         assert!(parsed.output_json);
     }
 
+    #[test]
+    fn parse_upgrade_takes_workspace_or_all() {
+        let parsed = parse_upgrade_args(&[
+            OsString::from("ws-test"),
+            OsString::from("--image=example/dev:2"),
+            OsString::from("--pull"),
+            OsString::from("--sync-baseline"),
+        ])
+        .expect("parse upgrade args");
+        assert_eq!(parsed.workspace.as_deref(), Some("ws-test"));
+        assert_eq!(parsed.image.as_deref(), Some("example/dev:2"));
+        assert!(parsed.pull && parsed.sync_baseline && !parsed.all);
+
+        let err = parse_upgrade_args(&[OsString::from("ws-test"), OsString::from("--all")])
+            .expect_err("reject workspace with --all");
+        assert!(err.contains("not both"));
+        let err = parse_upgrade_args(&[]).expect_err("reject missing target");
+        assert!(err.contains("<workspace> or --all"));
+    }
+
+    #[test]
+    fn host_upgrade_is_rejected() {
+        with_workspace_env(|_| {
+            assert_eq!(
+                dispatch("upgrade", &[OsString::from("--all")]),
+                crate::EXIT_RUNTIME
+            );
+        });
+    }
+
+    #[test]
+    fn parse_tunnel_control_flags() {
+        let parsed = parse_tunnel_args(&[
+            OsString::from("ws-test"),
+            OsString::from("-f"),
+            OsString::from("--logs"),
+        ])
+        .expect("parse tunnel logs");
+        assert_eq!(parsed.control, Some(TunnelAction::Logs { follow: true }));
+
+        let err = parse_tunnel_args(&[OsString::from("--status"), OsString::from("--stop")])
+            .expect_err("reject two actions");
+        assert!(err.contains("mutually exclusive"));
+        let err = parse_tunnel_args(&[OsString::from("--stop"), OsString::from("--detach")])
+            .expect_err("reject stop with detach");
+        assert!(err.contains("cannot be combined"));
+        let err = parse_tunnel_args(&[OsString::from("--follow")]).expect_err("reject follow");
+        assert!(err.contains("requires --logs"));
+    }
+
+    #[test]
+    fn host_tunnel_control_reports_and_stops_recorded_tunnel() {
+        with_workspace_env(|temp| {
+            assert_eq!(
+                dispatch(
+                    "create",
+                    &[
+                        OsString::from("--no-work-repos"),
+                        OsString::from("--name"),
+                        OsString::from("ws-tun"),
+                    ],
+                ),
+                0
+            );
+            let workspace = temp.path().join("tun");
+            std::fs::write(
+                workspace.join("code-tunnel.log"),
+                "log into https://github.com/login/device and use code AB12-CD34\n",
+            )
+            .expect("write log");
+
+            let backend = HostBackend;
+            let status =
+                TunnelStatus::parse(&backend.tunnel_control("tun", TunnelAction::Status).unwrap());
+            assert!(!status.recorded);
+            assert_eq!(status.login_code.as_deref(), Some("AB12-CD34"));
+
+            let mut child = std::process::Command::new("bash")
+                .args(["-c", "exec -a code-tunnel sleep 30"])
+                .spawn()
+                .expect("spawn fake tunnel");
+            std::fs::write(
+                workspace.join("code-tunnel.state"),
+                format!("pid={}\nname=demo\nstarted_unix=1\n", child.id()),
+            )
+            .expect("write state");
+            let status =
+                TunnelStatus::parse(&backend.tunnel_control("tun", TunnelAction::Status).unwrap());
+            assert!(status.recorded && status.running);
+            assert_eq!(status.pid, Some(child.id()));
+            assert_eq!(status.name.as_deref(), Some("demo"));
+
+            let stopped = backend.tunnel_control("tun", TunnelAction::Stop).unwrap();
+            assert_eq!(stopped.trim(), "stopped");
+            assert!(!child.wait().expect("wait fake tunnel").success());
+            assert!(!workspace.join("code-tunnel.state").exists());
+        });
+    }
+
     #[test]
     fn workspace_storage_root_uses_explicit_env() {
         with_workspace_env(|temp| {
@@ -695,6 +1281,61 @@ mod tests {
         });
     }
 
+    #[test]
+    fn config_profile_applies_below_env_and_above_default() {
+        use crate::config::{Source, lookup};
+
+        with_workspace_env(|temp| {
+            let set = |profile: Option<&str>, args: &[&str]| {
+                let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+                dispatch_with_profile("config", profile, &args)
+            };
+            assert_eq!(set(None, &["set", "github_host", "ghe.default.example"]), 0);
+            assert_eq!(
+                set(Some("work"), &["set", "github_host", "ghe.work.example"]),
+                0
+            );
+            assert_eq!(set(Some("work"), &["set", "prefix", "team-ws"]), 0);
+            assert_ne!(set(None, &["set", "runtime", "vm"]), 0);
+            assert_ne!(set(None, &["set", "no_such_key", "x"]), 0);
+
+            let text = std::fs::read_to_string(temp.path().join("config.toml")).expect("read");
+            assert!(text.contains(
+                "[profiles.work]\ngithub_host = \"ghe.work.example\"\nprefix = \"team-ws\"\n"
+            ));
+
+            crate::config::activate(None).expect("activate default");
+            assert_eq!(
+                lookup("github_host"),
+                Some((String::from("ghe.default.example"), Source::ConfigDefault))
+            );
+
+            crate::config::activate(Some("work")).expect("activate work");
+            assert_eq!(
+                lookup("github_host"),
+                Some((
+                    String::from("ghe.work.example"),
+                    Source::Profile(String::from("work"))
+                ))
+            );
+            assert_eq!(
+                lookup("auth"),
+                Some((String::from("auto"), Source::BuiltIn))
+            );
+            assert!(workspace_prefixes().contains(&String::from("team-ws")));
+            assert_eq!(
+                lookup("home"),
+                Some((
+                    temp.path().display().to_string(),
+                    Source::Env("AGENT_WORKSPACE_HOME")
+                ))
+            );
+
+            assert!(crate::config::activate(Some("missing")).is_err());
+            assert_ne!(dispatch_with_profile("ls", Some("missing"), &[]), 0);
+        });
+    }
+
     #[test]
     fn create_ls_rm_lifecycle_works_without_repos() {
         with_workspace_env(|temp| {
@@ -715,38 +1356,1671 @@ mod tests {
         });
     }
 
+    #[cfg(unix)]
     #[test]
-    fn resolve_workspace_for_auth_uses_single_workspace_when_unspecified() {
+    fn ssh_runtime_runs_host_layout_through_ssh_command() {
+        use std::os::unix::fs::PermissionsExt;
+
         with_workspace_env(|temp| {
-            std::fs::create_dir_all(temp.path().join("ws-only")).expect("create workspace");
+            // Stand-in for `ssh`: log the argv, drop the options/destination, and run the remote
+            // command locally.
+            let stub = temp.path().join("ssh-stub");
+            let argv_log = temp.path().join("ssh-argv.log");
+            std::fs::write(
+                &stub,
+                format!(
+                    "#!/usr/bin/env bash\nprintf '%s\\n' \"$@\" >>'{}'\nfor last; do :; done\nexec bash -c \"$last\"\n",
+                    argv_log.display()
+                ),
+            )
+            .expect("write ssh stub");
+            std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755))
+                .expect("chmod ssh stub");
+            let remote_root = temp.path().join("remote");
+            unsafe {
+                std::env::set_var("AGENT_WORKSPACE_SSH", &stub);
+                std::env::set_var("AGENT_WORKSPACE_SSH_HOME", &remote_root);
+            }
+
+            let runtime = OsString::from("--runtime=ssh://dev@build-box:2222");
+            let code = dispatch(
+                "create",
+                &[
+                    runtime.clone(),
+                    OsString::from("--no-work-repos"),
+                    OsString::from("--env"),
+                    OsString::from("API_TOKEN=hunter2"),
+                    OsString::from("--name"),
+                    OsString::from("ws-remote"),
+                ],
+            );
+            assert_eq!(code, 0);
+            let workspace = remote_root.join("remote");
+            assert!(workspace.join("work").is_dir());
+            assert!(workspace.join(".workspace-meta").is_file());
+            let env_file = workspace.join(".workspace-env");
+            assert_eq!(
+                std::fs::read_to_string(&env_file).expect("read env file"),
+                "API_TOKEN=hunter2\n"
+            );
+            assert_eq!(
+                std::fs::metadata(&env_file)
+                    .expect("env file metadata")
+                    .permissions()
+                    .mode()
+                    & 0o777,
+                0o600
+            );
+            let argv = std::fs::read_to_string(&argv_log).expect("read ssh argv");
+            assert!(!argv.contains("hunter2"), "{argv}");
 
-            let workspace = resolve_workspace_for_auth(None).expect("resolve default workspace");
-            assert_eq!(workspace.name, "ws-only");
+            let names = super::completion_workspace_names(crate::runtime::Runtime::Ssh(
+                String::from("dev@build-box"),
+            ))
+            .expect("list ssh workspaces");
+            assert_eq!(names, vec!["remote"]);
+
+            let remove_code = dispatch(
+                "rm",
+                &[
+                    runtime,
+                    OsString::from("--yes"),
+                    OsString::from("ws-remote"),
+                ],
+            );
+            assert_eq!(remove_code, 0);
+            assert!(!workspace.exists());
+
+            unsafe {
+                std::env::remove_var("AGENT_WORKSPACE_SSH");
+                std::env::remove_var("AGENT_WORKSPACE_SSH_HOME");
+            }
         });
     }
 
     #[test]
-    fn codex_auth_targets_include_compat_path() {
+    fn host_create_accepts_container_only_flags() {
         with_workspace_env(|temp| {
-            unsafe {
-                std::env::set_var("CODEX_AUTH_FILE", "/home/agent/.codex/auth.json");
-            }
-            let workspace = Workspace {
-                name: String::from("ws-test"),
-                path: temp.path().join("ws-test"),
+            let code = dispatch(
+                "create",
+                &[
+                    OsString::from("--no-work-repos"),
+                    OsString::from("--image"),
+                    OsString::from("example/image:latest"),
+                    OsString::from("--no-pull"),
+                    OsString::from("--name"),
+                    OsString::from("ws-flags"),
+                ],
+            );
+            assert_eq!(code, 0);
+            assert!(temp.path().join("flags").is_dir());
+        });
+    }
+
+    fn git(dir: &std::path::Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .expect("run git");
+        assert!(
+            output.status.success(),
+            "git {args:?}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn host_worktree_workspaces_share_one_mirror() {
+        with_workspace_env(|temp| {
+            let upstream = tempfile::tempdir().expect("upstream tempdir");
+            let origin = upstream.path().join("demo");
+            std::fs::create_dir_all(&origin).expect("create origin");
+            git(&origin, &["init", "--quiet", "--initial-branch=main"]);
+            git(
+                &origin,
+                &["commit", "--quiet", "--allow-empty", "-m", "init"],
+            );
+            let spec = RepoSpec {
+                owner: String::from("octo"),
+                repo: String::from("demo"),
+                owner_repo: String::from("octo/demo"),
+                clone_url: origin.display().to_string(),
+            };
+            let options = CloneOptions {
+                refspec: None,
+                worktree: true,
+                quiet: false,
+                ..Default::default()
             };
 
-            let targets = codex_auth_targets(&workspace);
-            assert!(
-                targets
-                    .iter()
-                    .any(|path| path.ends_with(".codex/auth.json"))
+            for name in ["ws-a", "ws-b"] {
+                let code = dispatch(
+                    "create",
+                    &[
+                        OsString::from("--no-work-repos"),
+                        OsString::from("--name"),
+                        OsString::from(name),
+                    ],
+                );
+                assert_eq!(code, 0);
+            }
+
+            let checkout_a = HostBackend
+                .clone_repo("a", &spec, RepoSlot::Work, &options)
+                .expect("worktree for a");
+            HostBackend
+                .clone_repo("b", &spec, RepoSlot::Work, &options)
+                .expect("worktree for b");
+
+            let mirror = temp.path().join(".mirrors").join("octo").join("demo.git");
+            assert!(mirror.join("HEAD").is_file());
+            let checkout_a = std::path::PathBuf::from(checkout_a);
+            assert!(checkout_a.join(".git").is_file());
+            assert_eq!(git(&checkout_a, &["symbolic-ref", "--short", "HEAD"]), "a");
+
+            let names: Vec<String> = HostBackend
+                .list()
+                .expect("list")
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect();
+            assert!(!names.iter().any(|name| name.starts_with('.')));
+
+            git(
+                &origin,
+                &["commit", "--quiet", "--allow-empty", "-m", "next"],
             );
-            assert!(
-                targets
-                    .iter()
-                    .any(|path| path.ends_with("home/agent/.codex/auth.json"))
+            HostBackend
+                .reset_repo("a", &checkout_a.display().to_string(), "origin/main")
+                .expect("reset worktree");
+            assert_eq!(git(&checkout_a, &["symbolic-ref", "--short", "HEAD"]), "a");
+            assert_eq!(
+                git(&checkout_a, &["rev-parse", "HEAD"]),
+                git(&origin, &["rev-parse", "HEAD"])
+            );
+
+            HostBackend
+                .remove("a", &RemoveOptions::default())
+                .expect("remove a");
+            let worktrees = git(&mirror, &["worktree", "list", "--porcelain"]);
+            assert!(!worktrees.contains("/a/work/"));
+            assert!(worktrees.contains("/b/work/"));
+            assert!(git(&mirror, &["branch", "--list", "a"]).is_empty());
+        });
+    }
+
+    #[test]
+    fn create_clones_repos_in_parallel_and_keeps_job_order() {
+        for value in ["2", "--jobs=8"] {
+            let args: Vec<OsString> = if value.starts_with("--") {
+                vec![OsString::from(value)]
+            } else {
+                vec![OsString::from("--jobs"), OsString::from(value)]
+            };
+            parse_create_args(&args).expect("parse --jobs");
+        }
+        for value in ["--jobs=0", "--jobs=many"] {
+            let err = parse_create_args(&[OsString::from(value)]).expect_err("reject --jobs");
+            assert!(err.contains("invalid --jobs"), "{err}");
+        }
+
+        with_workspace_env(|temp| {
+            let upstream = tempfile::tempdir().expect("upstream tempdir");
+            let spec = |repo: &str| RepoSpec {
+                owner: String::from("octo"),
+                repo: repo.to_string(),
+                owner_repo: format!("octo/{repo}"),
+                clone_url: upstream.path().join(repo).display().to_string(),
+            };
+            for repo in ["one", "two"] {
+                let origin = upstream.path().join(repo);
+                std::fs::create_dir_all(&origin).expect("create origin");
+                git(&origin, &["init", "--quiet", "--initial-branch=main"]);
+                git(
+                    &origin,
+                    &["commit", "--quiet", "--allow-empty", "-m", "init"],
+                );
+            }
+            let code = dispatch(
+                "create",
+                &[
+                    OsString::from("--no-work-repos"),
+                    OsString::from("--name"),
+                    OsString::from("ws-jobs"),
+                ],
+            );
+            assert_eq!(code, 0);
+
+            let jobs: Vec<CloneJob<'_>> = ["one", "missing", "two"]
+                .into_iter()
+                .map(|repo| CloneJob {
+                    spec: spec(repo),
+                    slot: RepoSlot::Work,
+                    refspec: None,
+                    primary: repo == "one",
+                })
+                .collect();
+            let options = CloneOptions {
+                refspec: None,
+                worktree: false,
+                quiet: true,
+                ..Default::default()
+            };
+            let results = clone_repos(&HostBackend, "jobs", &jobs, &options, 2);
+
+            assert_eq!(results.len(), 3);
+            let work = temp.path().join("jobs").join("work").join("octo");
+            assert_eq!(
+                results[0].outcome.as_deref(),
+                Ok(work.join("one").display().to_string().as_str())
+            );
+            assert!(results[1].outcome.is_err());
+            assert!(results[2].outcome.is_ok());
+            assert!(work.join("two").join(".git").is_dir());
+        });
+    }
+
+    #[test]
+    fn host_shallow_sparse_clone_is_recorded_and_kept_by_reset() {
+        for (args, expected) in [
+            (vec!["--depth", "0"], "invalid --depth"),
+            (vec!["--filter", "none"], "invalid --filter"),
+            (vec!["--sparse", "../src"], "invalid --sparse"),
+            (vec!["--worktree", "--single-branch"], "--worktree cannot"),
+        ] {
+            let args: Vec<OsString> = args.into_iter().map(OsString::from).collect();
+            let err = parse_create_args(&args).expect_err("reject clone mode");
+            assert!(err.contains(expected), "{err}");
+        }
+
+        with_workspace_env(|temp| {
+            let upstream = tempfile::tempdir().expect("upstream tempdir");
+            let origin = upstream.path().join("mono");
+            std::fs::create_dir_all(origin.join("src")).expect("create origin");
+            git(&origin, &["init", "--quiet", "--initial-branch=main"]);
+            for commit in ["one", "two"] {
+                std::fs::write(origin.join("src").join(commit), commit).expect("write src");
+                std::fs::write(origin.join(commit), commit).expect("write root");
+                git(&origin, &["add", "."]);
+                git(&origin, &["commit", "--quiet", "-m", commit]);
+            }
+            git(&origin, &["branch", "dev"]);
+            let spec = RepoSpec {
+                owner: String::from("octo"),
+                repo: String::from("mono"),
+                owner_repo: String::from("octo/mono"),
+                clone_url: format!("file://{}", origin.display()),
+            };
+            let code = dispatch(
+                "create",
+                &[
+                    OsString::from("--no-work-repos"),
+                    OsString::from("--name"),
+                    OsString::from("ws-mono"),
+                ],
+            );
+            assert_eq!(code, 0);
+
+            let sparse = vec![String::from("src")];
+            let options = CloneOptions {
+                quiet: true,
+                depth: Some(1),
+                single_branch: true,
+                sparse: &sparse,
+                ..Default::default()
+            };
+            let checkout = HostBackend
+                .clone_repo("mono", &spec, RepoSlot::Work, &options)
+                .expect("shallow clone");
+            let checkout = std::path::PathBuf::from(checkout);
+            assert_eq!(checkout, temp.path().join("mono/work/octo/mono"));
+            assert_eq!(git(&checkout, &["rev-list", "--count", "HEAD"]), "1");
+            assert_eq!(git(&checkout, &["config", "agent-workspace.depth"]), "1");
+            assert_eq!(
+                git(&checkout, &["config", "agent-workspace.single-branch"]),
+                "true"
+            );
+            assert!(checkout.join("src").join("two").is_file());
+
+            git(
+                &origin,
+                &["commit", "--quiet", "--allow-empty", "-m", "three"],
+            );
+            let path = checkout.display().to_string();
+            for refspec in ["origin/main", "origin/dev"] {
+                HostBackend
+                    .reset_repo("mono", &path, refspec)
+                    .expect("reset shallow clone");
+                assert_eq!(git(&checkout, &["rev-list", "--count", "HEAD"]), "1");
+            }
+            assert_eq!(
+                git(&checkout, &["config", "--get-all", "remote.origin.fetch"]),
+                "+refs/heads/main:refs/remotes/origin/main"
+            );
+            assert_eq!(
+                git(&checkout, &["rev-parse", "--is-shallow-repository"]),
+                "true"
+            );
+        });
+    }
+
+    #[test]
+    fn host_clone_copies_workspace_with_fresh_meta_and_worktrees() {
+        with_workspace_env(|temp| {
+            let upstream = tempfile::tempdir().expect("upstream tempdir");
+            let origin = upstream.path().join("demo");
+            std::fs::create_dir_all(&origin).expect("create origin");
+            git(&origin, &["init", "--quiet", "--initial-branch=main"]);
+            git(
+                &origin,
+                &["commit", "--quiet", "--allow-empty", "-m", "init"],
+            );
+            let spec = RepoSpec {
+                owner: String::from("octo"),
+                repo: String::from("demo"),
+                owner_repo: String::from("octo/demo"),
+                clone_url: origin.display().to_string(),
+            };
+
+            let code = dispatch(
+                "create",
+                &[
+                    OsString::from("--no-work-repos"),
+                    OsString::from("--name"),
+                    OsString::from("ws-src"),
+                ],
+            );
+            assert_eq!(code, 0);
+            let source_checkout = std::path::PathBuf::from(
+                HostBackend
+                    .clone_repo(
+                        "src",
+                        &spec,
+                        RepoSlot::Work,
+                        &CloneOptions {
+                            refspec: None,
+                            worktree: true,
+                            quiet: false,
+                            ..Default::default()
+                        },
+                    )
+                    .expect("worktree for src"),
+            );
+            std::fs::write(source_checkout.join("notes.txt"), "wip").expect("write notes");
+
+            let code = dispatch("clone", &[OsString::from("src"), OsString::from("src")]);
+            assert_ne!(code, 0);
+
+            let code = dispatch("clone", &[OsString::from("src"), OsString::from("ws-copy")]);
+            assert_eq!(code, 0);
+            let copy = temp.path().join("copy");
+            let meta = std::fs::read_to_string(copy.join(".workspace-meta")).expect("meta");
+            assert!(meta.lines().any(|line| line == "name=copy"));
+            assert!(meta.lines().any(|line| line == "primary_repo=none"));
+
+            let checkout = copy.join("work").join("octo").join("demo");
+            assert_eq!(
+                std::fs::read_to_string(checkout.join("notes.txt")).expect("copied notes"),
+                "wip"
+            );
+            assert_eq!(git(&checkout, &["symbolic-ref", "--short", "HEAD"]), "copy");
+            assert_eq!(
+                git(&source_checkout, &["symbolic-ref", "--short", "HEAD"]),
+                "src"
+            );
+            assert_eq!(git(&checkout, &["status", "--porcelain"]), "?? notes.txt");
+            let mirror = temp.path().join(".mirrors").join("octo").join("demo.git");
+            let worktrees = git(&mirror, &["worktree", "list", "--porcelain"]);
+            assert!(worktrees.contains("/src/work/"));
+            assert!(worktrees.contains("/copy/work/"));
+        });
+    }
+
+    #[test]
+    fn host_snapshot_round_trips_and_skips_secrets_by_default() {
+        with_workspace_env(|temp| {
+            let code = dispatch(
+                "create",
+                &[
+                    OsString::from("--no-work-repos"),
+                    OsString::from("--name"),
+                    OsString::from("ws-orig"),
+                ],
+            );
+            assert_eq!(code, 0);
+            let original = temp.path().join("orig");
+            std::fs::write(original.join("work").join("notes.txt"), "wip").expect("write notes");
+            std::fs::write(original.join("auth").join("github.env"), "GH_TOKEN=x")
+                .expect("write token");
+            std::fs::write(original.join(".workspace-env"), "API_KEY=x\n").expect("write env");
+
+            let archives = tempfile::tempdir().expect("archive tempdir");
+            let plain = archives.path().join("orig.tar.gz");
+            let secret = archives.path().join("orig-secrets.tar");
+            for (archive, extra) in [(&plain, None), (&secret, Some("--include-secrets"))] {
+                let mut args = vec![
+                    OsString::from("orig"),
+                    OsString::from("-o"),
+                    archive.clone().into_os_string(),
+                ];
+                args.extend(extra.map(OsString::from));
+                assert_eq!(dispatch("snapshot", &args), 0);
+                assert!(archive.is_file());
+            }
+
+            let code = dispatch("restore", &[plain.clone().into_os_string()]);
+            assert_ne!(code, 0, "restoring over an existing workspace must fail");
+
+            let code = dispatch(
+                "restore",
+                &[
+                    plain.into_os_string(),
+                    OsString::from("--name"),
+                    OsString::from("ws-copy"),
+                ],
+            );
+            assert_eq!(code, 0);
+            let copy = temp.path().join("copy");
+            assert_eq!(
+                std::fs::read_to_string(copy.join("work").join("notes.txt")).expect("notes"),
+                "wip"
+            );
+            assert!(copy.join("auth").is_dir());
+            assert!(!copy.join("auth").join("github.env").exists());
+            assert!(!copy.join(".workspace-env").exists());
+            let meta = std::fs::read_to_string(copy.join(".workspace-meta")).expect("meta");
+            assert!(meta.lines().any(|line| line == "name=copy"));
+
+            let code = dispatch(
+                "restore",
+                &[secret.into_os_string(), OsString::from("--name=with-auth")],
+            );
+            assert_eq!(code, 0);
+            assert!(
+                temp.path()
+                    .join("with-auth")
+                    .join("auth")
+                    .join("github.env")
+                    .is_file()
+            );
+            assert!(
+                temp.path()
+                    .join("with-auth")
+                    .join(".workspace-env")
+                    .is_file()
+            );
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn snapshot_with_secrets_is_written_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        with_workspace_env(|_temp| {
+            let code = dispatch(
+                "create",
+                &[
+                    OsString::from("--no-work-repos"),
+                    OsString::from("--name"),
+                    OsString::from("ws-secret"),
+                ],
+            );
+            assert_eq!(code, 0);
+
+            let archives = tempfile::tempdir().expect("archive tempdir");
+            let fresh = archives.path().join("fresh.tar.gz");
+            // An existing world-readable file is tightened too, not just written into.
+            let existing = archives.path().join("existing.tar");
+            std::fs::write(&existing, "old").expect("write old archive");
+            std::fs::set_permissions(&existing, std::fs::Permissions::from_mode(0o644))
+                .expect("chmod old archive");
+
+            for archive in [&fresh, &existing] {
+                let code = dispatch(
+                    "snapshot",
+                    &[
+                        OsString::from("secret"),
+                        OsString::from("--include-secrets"),
+                        OsString::from("-o"),
+                        archive.clone().into_os_string(),
+                    ],
+                );
+                assert_eq!(code, 0);
+                let mode = std::fs::metadata(archive)
+                    .expect("archive metadata")
+                    .permissions()
+                    .mode();
+                assert_eq!(mode & 0o777, 0o600, "{}", archive.display());
+            }
+        });
+    }
+
+    #[test]
+    fn host_gc_removes_only_stale_workspaces_and_reports_unmanaged_dirs() {
+        with_workspace_env(|temp| {
+            for name in ["ws-old", "ws-new"] {
+                let code = dispatch(
+                    "create",
+                    &[
+                        OsString::from("--no-work-repos"),
+                        OsString::from("--name"),
+                        OsString::from(name),
+                    ],
+                );
+                assert_eq!(code, 0);
+            }
+            std::fs::write(
+                temp.path().join("old").join(".workspace-meta"),
+                "name=old\ncreated_unix=1000\nprimary_repo=none\n",
+            )
+            .expect("age old workspace");
+            std::fs::create_dir_all(temp.path().join("stray")).expect("create stray dir");
+
+            let entries = vec![
+                (String::from("a"), Some(100)),
+                (String::from("b"), Some(950)),
+                (String::from("c"), None),
+            ];
+            assert_eq!(
+                stale_workspaces(&entries, 100, 1000),
+                vec![(String::from("a"), 900)]
+            );
+
+            let older_than = [OsString::from("--older-than"), OsString::from("14d")];
+            let mut dry_run = older_than.to_vec();
+            dry_run.push(OsString::from("--dry-run"));
+            assert_eq!(dispatch("gc", &dry_run), 0);
+            assert!(temp.path().join("old").is_dir());
+
+            let mut confirmed = older_than.to_vec();
+            confirmed.push(OsString::from("--yes"));
+            assert_eq!(dispatch("gc", &confirmed), 0);
+            assert!(!temp.path().join("old").exists());
+            assert!(temp.path().join("new").is_dir());
+            assert!(temp.path().join("stray").is_dir());
+        });
+    }
+
+    #[test]
+    fn host_rsync_maps_workspace_paths_and_builtin_copy_mirrors_rsync() {
+        with_workspace_env(|temp| {
+            let code = dispatch(
+                "create",
+                &[
+                    OsString::from("--no-work-repos"),
+                    OsString::from("--name"),
+                    OsString::from("ws-sync"),
+                ],
+            );
+            assert_eq!(code, 0);
+
+            let src = temp.path().join("src");
+            std::fs::create_dir_all(src.join("sub")).expect("create src");
+            std::fs::write(src.join("a.txt"), "a").expect("write a");
+            std::fs::write(src.join("sub").join("b.txt"), "b").expect("write b");
+
+            let code = dispatch(
+                "rsync",
+                &[
+                    OsString::from("push"),
+                    OsString::from("sync"),
+                    OsString::from(format!("{}/", src.display())),
+                    OsString::from("/work/dst"),
+                ],
+            );
+            assert_eq!(code, 0);
+            let dst = temp.path().join("sync").join("work").join("dst");
+            assert_eq!(std::fs::read_to_string(dst.join("a.txt")).expect("a"), "a");
+            assert_eq!(
+                std::fs::read_to_string(dst.join("sub").join("b.txt")).expect("b"),
+                "b"
+            );
+
+            let out = temp.path().join("out");
+            let dst_arg = dst.display().to_string();
+            let out_arg = out.display().to_string();
+            sync_tree(&dst_arg, &out_arg, SyncOptions::default()).expect("copy dir");
+            assert!(out.join("dst").join("sub").join("b.txt").is_file());
+
+            std::fs::remove_file(dst.join("a.txt")).expect("remove a");
+            let out_dst = format!("{out_arg}/dst");
+            let delete = SyncOptions {
+                delete: true,
+                dry_run: true,
+            };
+            sync_tree(&format!("{dst_arg}/"), &out_dst, delete).expect("dry run");
+            assert!(out.join("dst").join("a.txt").is_file());
+            let delete = SyncOptions {
+                delete: true,
+                dry_run: false,
+            };
+            sync_tree(&format!("{dst_arg}/"), &out_dst, delete).expect("delete");
+            assert!(!out.join("dst").join("a.txt").exists());
+            assert!(out.join("dst").join("sub").join("b.txt").is_file());
+        });
+    }
+
+    #[test]
+    fn cp_parses_workspace_side_and_contents_operand() {
+        let parsed = parse_cp_args(&[OsString::from("ws-a:/work/src/."), OsString::from("out")])
+            .expect("parse");
+        assert_eq!(parsed.workspace, "ws-a");
+        assert!(!parsed.into_workspace);
+        assert_eq!(
+            parsed.request,
+            Some(CopyRequest {
+                src: String::from("/work/src"),
+                dest: String::from("out"),
+                contents_only: true,
+            })
+        );
+
+        let parsed =
+            parse_cp_args(&[OsString::from("./a:b"), OsString::from(":notes")]).expect("parse");
+        assert_eq!(parsed.workspace, "");
+        assert!(parsed.into_workspace);
+        assert!(parse_cp_args(&[OsString::from("a"), OsString::from("b")]).is_err());
+        assert!(parse_cp_args(&[OsString::from("ws:a"), OsString::from("ws:b")]).is_err());
+    }
+
+    #[test]
+    fn forward_parses_port_specs_and_prunes_dead_registrations() {
+        let parsed = parse_forward_args(&[
+            OsString::from("ws-a"),
+            OsString::from("3000"),
+            OsString::from("8081:8080"),
+        ])
+        .expect("parse");
+        assert_eq!(parsed.workspace.as_deref(), Some("ws-a"));
+        assert_eq!(
+            parsed.ports,
+            vec![
+                PortSpec {
+                    local: 3000,
+                    remote: 3000
+                },
+                PortSpec {
+                    local: 8081,
+                    remote: 8080
+                },
+            ]
+        );
+        let parsed = parse_forward_args(&[OsString::from("5173")]).expect("parse lone port");
+        assert_eq!(parsed.workspace, None);
+        assert!(parse_forward_args(&[OsString::from("ws-a")]).is_err());
+        assert!(parse_forward_args(&[OsString::from("ws-a"), OsString::from("0")]).is_err());
+        assert!(parse_forward_args(&[OsString::from("--stop")]).is_err());
+        assert!(parse_forward_args(&[OsString::from("--stop"), OsString::from("--all")]).is_ok());
+
+        let temp = tempfile::tempdir().expect("tempdir");
+        // The test binary is not a `forward` process, so its own pid counts as stale too.
+        let stale = temp.path().join(format!("{}.forward", std::process::id()));
+        std::fs::write(
+            &stale,
+            format!(
+                "pid={}\nruntime=container\nworkspace=ws-a\naddress=127.0.0.1\nports=3000:3000\n",
+                std::process::id()
+            ),
+        )
+        .expect("write registration");
+        assert!(live_forwards(temp.path(), None).is_empty());
+        assert!(!stale.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn forward_list_and_stop_match_the_resolved_workspace() {
+        with_workspace_env(|temp| {
+            // A stand-in relay: `forward_is_running` only looks for a `forward` argv entry.
+            let mut relay = std::process::Command::new("bash")
+                .args(["-c", "exec -a forward sleep 30"])
+                .spawn()
+                .expect("spawn fake forward");
+            let registry = temp.path().join(".forwards");
+            std::fs::create_dir_all(&registry).expect("create registry");
+            std::fs::write(
+                registry.join(format!("{}.forward", relay.id())),
+                format!(
+                    "pid={}\nruntime=container\nworkspace=agent-ws-foo\naddress=127.0.0.1\nports=3000:3000\n",
+                    relay.id()
+                ),
+            )
+            .expect("write registration");
+            std::thread::sleep(std::time::Duration::from_millis(200));
+
+            let (list, stop, stop_again) = with_fake_docker(
+                temp,
+                "if [ \"$1 $2 $3\" = \"container inspect agent-ws-foo\" ]; then echo '[{}]'; exit 0; fi\nexit 1\n",
+                || {
+                    let forward = |args: &[&str]| {
+                        let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+                        dispatch("forward", &args)
+                    };
+                    (
+                        forward(&["--list", "foo"]),
+                        forward(&["--stop", "foo"]),
+                        forward(&["--stop", "foo"]),
+                    )
+                },
+            );
+            let _ = relay.kill();
+            let _ = relay.wait();
+
+            assert_eq!(list, 0);
+            assert_eq!(stop, 0);
+            // Nothing is left to stop, and an explicit workspace with no forwards is an error.
+            assert_eq!(stop_again, crate::EXIT_RUNTIME);
+        });
+    }
+
+    #[test]
+    fn host_cp_follows_docker_cp_destination_rules() {
+        with_workspace_env(|temp| {
+            let code = dispatch(
+                "create",
+                &[
+                    OsString::from("--no-work-repos"),
+                    OsString::from("--name"),
+                    OsString::from("ws-cp"),
+                ],
+            );
+            assert_eq!(code, 0);
+
+            let src = temp.path().join("src");
+            std::fs::create_dir_all(src.join("sub")).expect("create src");
+            std::fs::write(src.join("a.txt"), "a").expect("write a");
+            std::fs::write(src.join("sub").join("b.txt"), "b").expect("write b");
+            let src_arg = src.display().to_string();
+            let ws = temp.path().join("cp");
+
+            // Missing destination: the directory becomes it; existing: it lands inside.
+            for dest in ["cp:/copy", "cp:/copy"] {
+                let code = dispatch("cp", &[OsString::from(&src_arg), OsString::from(dest)]);
+                assert_eq!(code, 0);
+            }
+            assert!(ws.join("copy").join("sub").join("b.txt").is_file());
+            assert!(ws.join("copy").join("src").join("a.txt").is_file());
+
+            let code = dispatch(
+                "cp",
+                &[
+                    OsString::from("cp:/copy/a.txt"),
+                    OsString::from(format!("{}/", temp.path().join("out").display())),
+                ],
+            );
+            assert_ne!(code, 0, "a file is not copied into a missing dest/");
+            let out = temp.path().join("out");
+            std::fs::create_dir_all(&out).expect("create out");
+            let code = dispatch(
+                "cp",
+                &[
+                    OsString::from("cp:/copy/."),
+                    OsString::from(out.display().to_string()),
+                ],
+            );
+            assert_eq!(code, 0);
+            assert!(out.join("a.txt").is_file());
+            assert!(out.join("src").join("sub").join("b.txt").is_file());
+
+            let code = dispatch(
+                "cp",
+                &[
+                    OsString::from("cp:copy/a.txt"),
+                    OsString::from(out.join("renamed.txt").display().to_string()),
+                ],
+            );
+            assert_eq!(code, 0);
+            assert_eq!(
+                std::fs::read_to_string(out.join("renamed.txt")).expect("renamed"),
+                "a"
+            );
+            let code = dispatch(
+                "cp",
+                &[
+                    OsString::from(src_arg.as_str()),
+                    OsString::from("cp:/copy/a.txt"),
+                ],
+            );
+            assert_ne!(code, 0, "a directory never replaces a file");
+        });
+    }
+
+    #[test]
+    fn streamed_cp_scripts_round_trip_through_tar() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let src = temp.path().join("src");
+        let remote = temp.path().join("remote");
+        std::fs::create_dir_all(src.join("sub")).expect("create src");
+        std::fs::create_dir_all(&remote).expect("create remote");
+        std::fs::write(src.join("sub").join("b.txt"), "b").expect("write b");
+        let run_script = |script: &str, args: &[&str], stdin, stdout| {
+            let output = std::process::Command::new("bash")
+                .arg("-c")
+                .arg(script)
+                .arg("--")
+                .args(args)
+                .stdin(stdin)
+                .stdout(stdout)
+                .output()
+                .expect("run script");
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        };
+
+        let request = CopyRequest {
+            src: src.display().to_string(),
+            dest: remote.display().to_string(),
+            contents_only: false,
+        };
+        let target = stream_copy_in(&request, "", |args, archive| {
+            run_script(COPY_IN_SCRIPT, args, archive, std::process::Stdio::piped())
+        })
+        .expect("copy in");
+        assert_eq!(target, remote.join("src").display().to_string());
+        assert!(remote.join("src").join("sub").join("b.txt").is_file());
+
+        let out = temp.path().join("out");
+        let request = CopyRequest {
+            src: remote.join("src").join("sub").display().to_string(),
+            dest: out.display().to_string(),
+            contents_only: false,
+        };
+        let target = stream_copy_out(&request, |args, archive| {
+            run_script(COPY_OUT_SCRIPT, args, std::process::Stdio::null(), archive)
+        })
+        .expect("copy out");
+        assert_eq!(target, out.display().to_string());
+        assert_eq!(std::fs::read_to_string(out.join("b.txt")).expect("b"), "b");
+    }
+
+    #[test]
+    fn rsync_watch_batches_changed_paths_and_drops_gitignored() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let src = temp.path().join("src");
+        std::fs::create_dir_all(&src).expect("create src");
+        git(&src, &["init", "--quiet"]);
+        std::fs::write(src.join(".gitignore"), "*.log\n").expect("write gitignore");
+
+        let mut watcher = SourceWatcher::start(&src, true).expect("start watcher");
+        // Let inotifywait set up its watches (or the poller take its first scan).
+        std::thread::sleep(std::time::Duration::from_millis(1500));
+        std::fs::write(src.join("kept.txt"), "kept").expect("write kept");
+        std::fs::write(src.join("build.log"), "noise").expect("write ignored");
+
+        let batch = watcher.next_batch().expect("change batch");
+        assert!(batch.contains(&String::from("kept.txt")), "{batch:?}");
+        assert!(
+            !batch.iter().any(|path| path.ends_with(".log")),
+            "{batch:?}"
+        );
+        assert!(
+            !batch.iter().any(|path| path.starts_with(".git")),
+            "{batch:?}"
+        );
+
+        let transfer = RsyncTransfer {
+            direction: RsyncDirection::Push,
+            src: format!("{}/", src.display()),
+            dest: String::from("/work/src"),
+            user: String::from("agent"),
+            delete: true,
+            dry_run: false,
+            gitignore: true,
+            rsync_args: Vec::new(),
+            paths: batch,
+        };
+        let flags = transfer.rsync_flags();
+        for flag in [
+            "--files-from=-",
+            "--delete-missing-args",
+            "--filter=:- .gitignore",
+        ] {
+            assert!(flags.iter().any(|arg| arg == flag), "{flags:?}");
+        }
+    }
+
+    #[test]
+    fn host_doctor_reports_workspace_root_writability() {
+        with_workspace_env(|temp| {
+            let checks = HostBackend.doctor_checks();
+            assert_eq!(checks.len(), 1);
+            assert_eq!(checks[0].name, "workspace-root");
+            assert_eq!(checks[0].status, CheckStatus::Ok);
+            assert!(
+                std::fs::read_dir(temp.path())
+                    .expect("read root")
+                    .all(|entry| {
+                        !entry
+                            .expect("dir entry")
+                            .file_name()
+                            .to_string_lossy()
+                            .starts_with(".awl-doctor")
+                    })
+            );
+
+            let blocker = temp.path().join("not-a-dir");
+            std::fs::write(&blocker, "").expect("write blocker file");
+            unsafe {
+                std::env::set_var("AGENT_WORKSPACE_HOME", &blocker);
+            }
+            crate::config::activate(None).expect("reload config");
+            let checks = HostBackend.doctor_checks();
+            assert_eq!(checks[0].status, CheckStatus::Fail);
+            assert!(checks[0].hint.is_some());
+
+            let json = parse_doctor_args(&[OsString::from("--output"), OsString::from("json")])
+                .expect("parse --output json");
+            assert!(json.json);
+            assert!(parse_doctor_args(&[OsString::from("--output=yaml")]).is_err());
+        });
+    }
+
+    #[test]
+    fn host_rename_moves_workspace_and_relinks_worktrees() {
+        with_workspace_env(|temp| {
+            let upstream = tempfile::tempdir().expect("upstream tempdir");
+            let origin = upstream.path().join("demo");
+            std::fs::create_dir_all(&origin).expect("create origin");
+            git(&origin, &["init", "--quiet", "--initial-branch=main"]);
+            git(
+                &origin,
+                &["commit", "--quiet", "--allow-empty", "-m", "init"],
+            );
+            let spec = RepoSpec {
+                owner: String::from("octo"),
+                repo: String::from("demo"),
+                owner_repo: String::from("octo/demo"),
+                clone_url: origin.display().to_string(),
+            };
+
+            for name in ["ws-old", "ws-taken"] {
+                let code = dispatch(
+                    "create",
+                    &[
+                        OsString::from("--no-work-repos"),
+                        OsString::from("--name"),
+                        OsString::from(name),
+                    ],
+                );
+                assert_eq!(code, 0);
+            }
+            HostBackend
+                .clone_repo(
+                    "old",
+                    &spec,
+                    RepoSlot::Work,
+                    &CloneOptions {
+                        refspec: None,
+                        worktree: true,
+                        quiet: false,
+                        ..Default::default()
+                    },
+                )
+                .expect("worktree for old");
+
+            let code = dispatch("rename", &[OsString::from("old"), OsString::from("taken")]);
+            assert_ne!(code, 0);
+            assert!(temp.path().join("old").is_dir());
+
+            let code = dispatch(
+                "rename",
+                &[OsString::from("old"), OsString::from("ws-New Name")],
+            );
+            assert_eq!(code, 0);
+            assert!(!temp.path().join("old").exists());
+            let renamed = temp.path().join("new-name");
+            let meta = std::fs::read_to_string(renamed.join(".workspace-meta")).expect("meta");
+            assert!(meta.lines().any(|line| line == "name=new-name"));
+            assert!(meta.lines().any(|line| line.starts_with("created_unix=")));
+
+            let checkout = renamed.join("work").join("octo").join("demo");
+            assert_eq!(
+                git(&checkout, &["symbolic-ref", "--short", "HEAD"]),
+                "new-name"
+            );
+            let mirror = temp.path().join(".mirrors").join("octo").join("demo.git");
+            let worktrees = git(&mirror, &["worktree", "list", "--porcelain"]);
+            assert!(worktrees.contains("/new-name/work/"));
+            assert!(git(&mirror, &["branch", "--list", "old"]).is_empty());
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hooks_run_on_host_and_in_workspace_and_abort_on_failure() {
+        use std::os::unix::fs::PermissionsExt;
+
+        with_workspace_env(|temp| {
+            let code = dispatch(
+                "create",
+                &[
+                    OsString::from("--no-work-repos"),
+                    OsString::from("--name"),
+                    OsString::from("ws-hooks"),
+                ],
+            );
+            assert_eq!(code, 0);
+            let workspace = temp.path().join("hooks");
+            let repo = workspace.join("work").join("octo").join("app");
+            std::fs::create_dir_all(repo.join(".awl").join("hooks")).expect("create repo");
+            git(&repo, &["init", "--quiet"]);
+            let script = repo.join(".awl").join("hooks").join("pre-exec");
+            std::fs::write(
+                &script,
+                "#!/usr/bin/env bash\necho \"$AWL_HOOK $AWL_WORKSPACE\" > repo-hook.txt\n",
+            )
+            .expect("write hook");
+            std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))
+                .expect("chmod hook");
+            unsafe {
+                std::env::set_var(
+                    "AGENT_WORKSPACE_HOOK_PRE_EXEC",
+                    "printf '%s' \"$AWL_REPOS\" > host-hook.txt",
+                );
+                std::env::set_var("AGENT_WORKSPACE_HOOK_PRE_RM", "exit 3");
+            }
+
+            let code = dispatch(
+                "exec",
+                &[OsString::from("ws-hooks"), OsString::from("true")],
+            );
+            assert_eq!(code, 0);
+            assert_eq!(
+                std::fs::read_to_string(workspace.join("host-hook.txt")).expect("host hook"),
+                repo.display().to_string()
+            );
+            assert!(
+                !repo.join("repo-hook.txt").exists(),
+                "repo hooks must be opted into outside a container"
+            );
+
+            unsafe {
+                std::env::set_var("AGENT_WORKSPACE_REPO_HOOKS", "on");
+            }
+            let code = dispatch(
+                "exec",
+                &[OsString::from("ws-hooks"), OsString::from("true")],
+            );
+            assert_eq!(code, 0);
+            assert_eq!(
+                std::fs::read_to_string(repo.join("repo-hook.txt")).expect("repo hook"),
+                "pre-exec hooks\n"
+            );
+
+            let code = dispatch("rm", &[OsString::from("--yes"), OsString::from("ws-hooks")]);
+            assert_ne!(code, 0);
+            assert!(workspace.is_dir());
+
+            unsafe {
+                std::env::set_var("AGENT_WORKSPACE_HOOKS", "off");
+            }
+            let code = dispatch("rm", &[OsString::from("--yes"), OsString::from("ws-hooks")]);
+            assert_eq!(code, 0);
+            assert!(!workspace.exists());
+
+            unsafe {
+                std::env::remove_var("AGENT_WORKSPACE_HOOK_PRE_EXEC");
+                std::env::remove_var("AGENT_WORKSPACE_HOOK_PRE_RM");
+                std::env::remove_var("AGENT_WORKSPACE_HOOKS");
+                std::env::remove_var("AGENT_WORKSPACE_REPO_HOOKS");
+            }
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn container_rm_of_stopped_workspace_skips_hooks_without_starting_it() {
+        with_workspace_env(|temp| {
+            unsafe {
+                std::env::set_var("AGENT_WORKSPACE_HOOK_PRE_RM", "exit 3");
+            }
+            let code = with_fake_docker(
+                temp,
+                "if [ \"$1 $3\" = \"container -f\" ]; then echo false; fi\n",
+                || {
+                    dispatch(
+                        "rm",
+                        &[OsString::from("--yes"), OsString::from("ws-stopped")],
+                    )
+                },
+            );
+            unsafe {
+                std::env::remove_var("AGENT_WORKSPACE_HOOK_PRE_RM");
+            }
+
+            assert_eq!(code, 0);
+            let calls =
+                std::fs::read_to_string(temp.path().join("docker.log")).expect("read docker log");
+            assert!(calls.contains("rm -f ws-stopped"), "{calls}");
+            assert!(
+                !calls.lines().any(|line| line.starts_with("start")),
+                "{calls}"
+            );
+            assert!(
+                !calls.lines().any(|line| line.starts_with("exec")),
+                "{calls}"
+            );
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn container_tunnel_control_reports_stopped_workspace_without_starting_it() {
+        with_workspace_env(|temp| {
+            let codes = with_fake_docker(
+                temp,
+                "if [ \"$1 $3\" = \"container -f\" ]; then echo false; fi\n",
+                || {
+                    ["--status", "--stop", "--logs"].map(|action| {
+                        dispatch(
+                            "tunnel",
+                            &[OsString::from("ws-stopped"), OsString::from(action)],
+                        )
+                    })
+                },
+            );
+
+            assert_eq!(codes, [0, 0, crate::EXIT_RUNTIME]);
+            let calls =
+                std::fs::read_to_string(temp.path().join("docker.log")).expect("read docker log");
+            assert!(
+                !calls
+                    .lines()
+                    .any(|line| line.starts_with("start") || line.starts_with("exec")),
+                "{calls}"
+            );
+        });
+    }
+
+    #[test]
+    fn container_snapshot_keeps_env_names_and_restore_drops_saved_mounts() {
+        with_workspace_env(|temp| {
+            let info = temp.path().join("info.json");
+            std::fs::write(
+                &info,
+                r#"[{"Image":"sha256:abc","Config":{"Image":"agent-env:1","Labels":{"agent-kit.workspace":"1","agent-kit.env":"API_TOKEN,REGION","agent-kit.mounts":"/srv:/data","agent-kit.publish":"3000:3000","agent-kit.cpus":"2"},"Env":["PATH=/usr/bin","HOME=/home/agent","API_TOKEN=hunter2","REGION=eu"]},"HostConfig":{}}]"#,
+            )
+            .expect("write inspect output");
+            let script = format!(
+                r#"case "$1 $2 $3" in
+  "container inspect ws-src") cat '{}' ;;
+  "container inspect "*) exit 1 ;;
+  "volume inspect "*) exit 1 ;;
+  "image inspect "*) echo '[{{"Config":{{"Env":["PATH=/usr/bin"]}}}}]' ;;
+  run*"tar -C /from"*) tar -cf - -T /dev/null ;;
+  run*) cat >/dev/null ;;
+esac
+"#,
+                info.display()
+            );
+            let archive = temp.path().join("ws.tar");
+
+            let (snapshot, restore) = with_fake_docker(temp, &script, || {
+                let snapshot = dispatch(
+                    "snapshot",
+                    &[
+                        OsString::from("ws-src"),
+                        OsString::from("-o"),
+                        archive.clone().into_os_string(),
+                    ],
+                );
+                let restore = dispatch(
+                    "restore",
+                    &[
+                        OsString::from("--name"),
+                        OsString::from("ws-copy"),
+                        OsString::from("--env"),
+                        OsString::from("REGION=us"),
+                        archive.clone().into_os_string(),
+                    ],
+                );
+                (snapshot, restore)
+            });
+            assert_eq!(snapshot, 0);
+            assert_eq!(restore, 0);
+
+            let meta = std::process::Command::new("tar")
+                .arg("-xOf")
+                .arg(&archive)
+                .arg("awl-snapshot.meta")
+                .output()
+                .expect("read snapshot manifest");
+            let meta = String::from_utf8_lossy(&meta.stdout);
+            assert!(meta.contains("env=API_TOKEN\n"), "{meta}");
+            assert!(meta.contains("env=HOME=/home/agent\n"), "{meta}");
+            assert!(!meta.contains("hunter2"), "{meta}");
+
+            let calls =
+                std::fs::read_to_string(temp.path().join("docker.log")).expect("read docker log");
+            let run = calls
+                .lines()
+                .find(|line| line.starts_with("run -d --name agent-ws-copy"))
+                .unwrap_or_else(|| panic!("restore runs no container: {calls}"));
+            assert!(run.contains("-e REGION=us"), "{run}");
+            assert!(run.contains("--label agent-kit.env=REGION"), "{run}");
+            assert!(run.contains("--cpus=2"), "{run}");
+            assert!(!run.contains("API_TOKEN"), "{run}");
+            assert!(!run.contains("/srv:/data"), "{run}");
+            assert!(!run.contains("3000:3000"), "{run}");
+        });
+    }
+
+    #[test]
+    fn container_rename_warns_when_the_old_container_cannot_be_removed() {
+        with_workspace_env(|temp| {
+            let info = temp.path().join("info.json");
+            std::fs::write(
+                &info,
+                r#"[{"Image":"sha256:abc","State":{"Running":false},"Config":{"Image":"agent-env:1","Labels":{"agent-kit.workspace":"1"},"Env":[]},"HostConfig":{}}]"#,
+            )
+            .expect("write inspect output");
+            let script = format!(
+                r#"case "$1 $2 $3" in
+  "container inspect ws-old") cat '{}' ;;
+  "container inspect "*) exit 1 ;;
+  "volume inspect "*) exit 1 ;;
+  "rm -f ws-old") echo 'removal of container ws-old is already in progress' >&2; exit 1 ;;
+esac
+"#,
+                info.display()
+            );
+
+            let code = with_fake_docker(temp, &script, || {
+                dispatch(
+                    "rename",
+                    &[OsString::from("ws-old"), OsString::from("ws-new")],
+                )
+            });
+
+            assert_eq!(code, 0);
+            let calls =
+                std::fs::read_to_string(temp.path().join("docker.log")).expect("read docker log");
+            assert!(
+                calls
+                    .lines()
+                    .any(|line| line.starts_with("run -d --name agent-ws-new")),
+                "{calls}"
+            );
+            assert!(calls.contains("rm -f ws-old"), "{calls}");
+        });
+    }
+
+    #[test]
+    fn durations_parse_and_format() {
+        assert_eq!(parse_duration("90"), Ok(90));
+        assert_eq!(parse_duration("30m"), Ok(1_800));
+        assert_eq!(parse_duration("1h30m"), Ok(5_400));
+        assert_eq!(parse_duration("2d"), Ok(172_800));
+        assert!(parse_duration("2x").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("5m3").is_err());
+
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(7_500), "2h5m");
+        assert_eq!(format_duration(7_205), "2h");
+        assert_eq!(format_duration(90_061), "1d1h");
+    }
+
+    #[test]
+    fn start_and_stop_are_rejected_by_host_runtime() {
+        with_workspace_env(|_temp| {
+            let code = dispatch(
+                "create",
+                &[
+                    OsString::from("--no-work-repos"),
+                    OsString::from("--idle-timeout"),
+                    OsString::from("2h"),
+                    OsString::from("--name"),
+                    OsString::from("ws-idle"),
+                ],
+            );
+            assert_eq!(code, 0);
+            assert_ne!(dispatch("start", &[OsString::from("ws-idle")]), 0);
+            assert_ne!(dispatch("stop", &[OsString::from("--idle")]), 0);
+            assert_ne!(
+                dispatch(
+                    "stop",
+                    &[OsString::from("--all"), OsString::from("ws-idle")]
+                ),
+                0
+            );
+        });
+    }
+
+    #[test]
+    fn ls_parses_long_filters_sort_and_format() {
+        let args: Vec<OsString> = [
+            "-l",
+            "--filter",
+            "repo=octo/demo",
+            "--filter=state=running",
+            "--sort=created",
+            "--format",
+            "{{.Name}} {{ .Repo }}",
+        ]
+        .into_iter()
+        .map(OsString::from)
+        .collect();
+        let parsed = parse_ls_args(&args).expect("parse ls");
+        assert!(parsed.long);
+        assert_eq!(
+            parsed.filters,
+            vec![
+                (String::from("repo"), String::from("octo/demo")),
+                (String::from("state"), String::from("running")),
+            ]
+        );
+        assert_eq!(parsed.sort, LsSort::Created);
+        assert_eq!(parsed.format.as_deref(), Some("{{.Name}} {{ .Repo }}"));
+
+        for (bad, expected) in [
+            ("--filter=owner=x", "unsupported --filter key: owner"),
+            ("--sort=size", "unsupported --sort value: size"),
+            ("--format={{.Nope}}", "unknown --format field: .Nope"),
+        ] {
+            let err = parse_ls_args(&[OsString::from(bad)]).expect_err(bad);
+            assert!(err.starts_with(expected), "{bad}: {err}");
+        }
+    }
+
+    #[test]
+    fn host_ls_entries_carry_workspace_meta() {
+        with_workspace_env(|_temp| {
+            let code = dispatch(
+                "create",
+                &[
+                    OsString::from("--no-work-repos"),
+                    OsString::from("--name"),
+                    OsString::from("ws-listed"),
+                ],
+            );
+            assert_eq!(code, 0);
+
+            let entries = HostBackend.list().expect("list");
+            let entry = entries
+                .iter()
+                .find(|entry| entry.name == "listed")
+                .expect("listed workspace");
+            assert!(entry.created_unix.is_some());
+            assert_eq!(entry.repo, None);
+            assert_eq!(entry.state, None);
+            assert!(
+                HostBackend
+                    .disk_usage("listed")
+                    .expect("disk usage")
+                    .is_some()
+            );
+        });
+    }
+
+    #[test]
+    fn host_inspect_reports_meta_auth_and_repo_status() {
+        with_workspace_env(|temp| {
+            let code = dispatch(
+                "create",
+                &[
+                    OsString::from("--no-work-repos"),
+                    OsString::from("--name"),
+                    OsString::from("ws-inspect"),
+                ],
+            );
+            assert_eq!(code, 0);
+            let workspace = temp.path().join("inspect");
+
+            let upstream = tempfile::tempdir().expect("upstream tempdir");
+            git(
+                upstream.path(),
+                &["init", "--quiet", "--initial-branch=main"],
+            );
+            git(
+                upstream.path(),
+                &["commit", "--quiet", "--allow-empty", "-m", "init"],
+            );
+            let repo = workspace.join("work").join("octo").join("app");
+            git(
+                temp.path(),
+                &[
+                    "clone",
+                    "--quiet",
+                    upstream.path().to_str().expect("utf8 path"),
+                    repo.to_str().expect("utf8 path"),
+                ],
+            );
+            git(
+                &repo,
+                &["commit", "--quiet", "--allow-empty", "-m", "local"],
+            );
+            std::fs::write(repo.join("scratch.txt"), "wip\n").expect("write scratch");
+            std::fs::write(workspace.join("auth").join("github.env"), "token=x\n")
+                .expect("write auth");
+
+            let details = HostBackend.inspect("inspect").expect("inspect");
+            assert_eq!(details.name, "inspect");
+            assert!(details.created_unix.is_some());
+            assert_eq!(details.primary_repo, None);
+            assert_eq!(details.state, None);
+            assert_eq!(
+                details.auth_files,
+                vec![
+                    workspace
+                        .join("auth")
+                        .join("github.env")
+                        .display()
+                        .to_string()
+                ]
+            );
+            assert_eq!(
+                details.repos,
+                vec![RepoStatus {
+                    path: repo.display().to_string(),
+                    branch: Some(String::from("main")),
+                    dirty: true,
+                    upstream: Some(String::from("origin/main")),
+                    ahead: Some(1),
+                    behind: Some(0),
+                }]
+            );
+        });
+    }
+
+    #[test]
+    fn repo_manifest_is_fetched_and_env_reaches_host_exec() {
+        with_workspace_env(|temp| {
+            let upstream = tempfile::tempdir().expect("upstream tempdir");
+            let origin = upstream.path().join("app");
+            std::fs::create_dir_all(origin.join(".awl")).expect("create origin");
+            git(&origin, &["init", "--quiet", "--initial-branch=main"]);
+            std::fs::write(
+                origin.join(".awl").join("workspace.toml"),
+                "image = \"example/dev:1\"\nrepos = [\"octo/tools\"]\n\n[env]\nAPP_MODE = \"dev\"\n",
+            )
+            .expect("write manifest");
+            git(&origin, &["add", "."]);
+            git(&origin, &["commit", "--quiet", "-m", "manifest"]);
+            let spec = RepoSpec {
+                owner: String::from("octo"),
+                repo: String::from("app"),
+                owner_repo: String::from("octo/app"),
+                clone_url: origin.display().to_string(),
+            };
+
+            for refspec in [None, Some("origin/main"), Some("origin/missing")] {
+                let (path, manifest) = fetch_manifest(&spec, refspec)
+                    .expect("fetch manifest")
+                    .expect("manifest present");
+                assert_eq!(path, ".awl/workspace.toml");
+                assert_eq!(manifest.image.as_deref(), Some("example/dev:1"));
+                assert_eq!(manifest.repos, vec![(String::from("octo/tools"), None)]);
+            }
+
+            let upstream_app = upstream.path().join("octo").join("app.git");
+            std::fs::create_dir_all(upstream.path().join("octo")).expect("create upstream owner");
+            std::fs::rename(&origin, &upstream_app).expect("move origin under owner");
+            unsafe {
+                std::env::set_var("GIT_CONFIG_COUNT", "1");
+                std::env::set_var(
+                    "GIT_CONFIG_KEY_0",
+                    format!("url.{}/.insteadOf", upstream.path().display()),
+                );
+                std::env::set_var("GIT_CONFIG_VALUE_0", "https://github.com/");
+            }
+            let create = |trust: bool| {
+                let mut args = vec![OsString::from("octo/app"), OsString::from("--no-extras")];
+                if trust {
+                    args.push(OsString::from("--trust-manifest"));
+                }
+                args.extend([OsString::from("--name"), OsString::from("ws-manifest")]);
+                dispatch("create", &args)
+            };
+            let refused = create(false);
+            let workspace_after_refusal = temp.path().join("manifest").exists();
+            let trusted = create(true);
+            unsafe {
+                std::env::remove_var("GIT_CONFIG_COUNT");
+                std::env::remove_var("GIT_CONFIG_KEY_0");
+                std::env::remove_var("GIT_CONFIG_VALUE_0");
+            }
+            assert_eq!(refused, crate::EXIT_RUNTIME);
+            assert!(!workspace_after_refusal);
+            assert_eq!(trusted, 0);
+
+            let code = dispatch(
+                "exec",
+                &[
+                    OsString::from("ws-manifest"),
+                    OsString::from("bash"),
+                    OsString::from("-c"),
+                    OsString::from("test \"$APP_MODE\" = dev"),
+                ],
+            );
+            assert_eq!(code, 0);
+        });
+    }
+
+    #[test]
+    fn host_create_requires_trust_for_manifest_post_create() {
+        with_workspace_env(|temp| {
+            let upstream = tempfile::tempdir().expect("upstream tempdir");
+            let origin = upstream.path().join("octo").join("app.git");
+            std::fs::create_dir_all(&origin).expect("create origin");
+            git(&origin, &["init", "--quiet", "--initial-branch=main"]);
+            std::fs::write(origin.join("awl.toml"), "post_create = \"touch trusted\"\n")
+                .expect("write manifest");
+            git(&origin, &["add", "."]);
+            git(&origin, &["commit", "--quiet", "-m", "manifest"]);
+            unsafe {
+                std::env::set_var("GIT_CONFIG_COUNT", "1");
+                std::env::set_var(
+                    "GIT_CONFIG_KEY_0",
+                    format!("url.{}/.insteadOf", upstream.path().display()),
+                );
+                std::env::set_var("GIT_CONFIG_VALUE_0", "https://github.com/");
+            }
+
+            let refused = dispatch(
+                "create",
+                &[
+                    OsString::from("octo/app"),
+                    OsString::from("--name"),
+                    OsString::from("ws-trust"),
+                ],
+            );
+            let workspace_after_refusal = temp.path().join("trust").exists();
+            let trusted = dispatch(
+                "create",
+                &[
+                    OsString::from("octo/app"),
+                    OsString::from("--trust-manifest"),
+                    OsString::from("--name"),
+                    OsString::from("ws-trust"),
+                ],
+            );
+
+            unsafe {
+                std::env::remove_var("GIT_CONFIG_COUNT");
+                std::env::remove_var("GIT_CONFIG_KEY_0");
+                std::env::remove_var("GIT_CONFIG_VALUE_0");
+            }
+            assert_eq!(refused, crate::EXIT_RUNTIME);
+            assert!(!workspace_after_refusal);
+            assert_eq!(trusted, 0);
+            assert!(temp.path().join("trust/work/octo/app/trusted").is_file());
+        });
+    }
+
+    #[test]
+    fn host_rm_refuses_launcher_state_dirs() {
+        with_workspace_env(|temp| {
+            let mirrors = temp.path().join(".mirrors");
+            std::fs::create_dir_all(mirrors.join("octo").join("app.git")).expect("create mirror");
+
+            let code = dispatch("rm", &[OsString::from("--yes"), OsString::from(".mirrors")]);
+
+            assert_ne!(code, 0);
+            assert!(mirrors.join("octo").join("app.git").is_dir());
+        });
+    }
+
+    #[test]
+    fn resolve_workspace_for_auth_uses_single_workspace_when_unspecified() {
+        with_workspace_env(|temp| {
+            std::fs::create_dir_all(temp.path().join("ws-only")).expect("create workspace");
+
+            let workspace =
+                resolve_workspace_for_auth(&HostBackend, None).expect("resolve default workspace");
+            assert_eq!(workspace, "ws-only");
+        });
+    }
+
+    #[test]
+    fn codex_auth_targets_include_compat_path() {
+        with_workspace_env(|temp| {
+            unsafe {
+                std::env::set_var("CODEX_AUTH_FILE", "/home/agent/.codex/auth.json");
+            }
+            let workspace = Workspace {
+                name: String::from("ws-test"),
+                path: temp.path().join("ws-test"),
+            };
+
+            let targets = codex_auth_targets(&workspace);
+            assert!(
+                targets
+                    .iter()
+                    .any(|path| path.ends_with(".codex/auth.json"))
+            );
+            assert!(
+                targets
+                    .iter()
+                    .any(|path| path.ends_with("home/agent/.codex/auth.json"))
+            );
+
+            unsafe {
+                std::env::remove_var("CODEX_AUTH_FILE");
+            }
+            std::fs::write(
+                temp.path().join("config.toml"),
+                "[profiles.work]\ncodex_auth_file = \"/home/agent/.config/codex/auth.json\"\n",
+            )
+            .expect("write config");
+            crate::config::activate(Some("work")).expect("activate work");
+            let targets = codex_auth_targets(&workspace);
+            assert!(
+                targets
+                    .iter()
+                    .any(|path| path.ends_with("home/agent/.config/codex/auth.json"))
             );
+            crate::config::activate(None).expect("reset config");
         });
     }
 
@@ -0,0 +1,240 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+
+use crate::EXIT_RUNTIME;
+
+use super::set_owner_only_permissions;
+
+/// Manifest at the root of a `snapshot` archive; every other member is one `<part>.tar`.
+pub(super) const SNAPSHOT_MANIFEST_FILE: &str = "awl-snapshot.meta";
+
+const SNAPSHOT_FORMAT: &str = "1";
+
+/// Which workspace layout a snapshot holds: `host` (host and ssh) or `container` (docker and podman).
+pub(super) const HOST_LAYOUT: &str = "host";
+pub(super) const CONTAINER_LAYOUT: &str = "container";
+
+/// The single part of a host-layout snapshot: the workspace directory tree.
+pub(super) const HOST_WORKSPACE_PART: &str = "workspace";
+
+/// What `restore` needs to rebuild a workspace besides the part tarballs.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(super) struct SnapshotManifest {
+    pub(super) layout: String,
+    pub(super) name: String,
+    pub(super) image: Option<String>,
+    /// `key=value` container labels, including `agent-kit.created-at` and `agent-kit.repo`.
+    pub(super) labels: Vec<String>,
+    /// `NAME=value` container env that does not come from the image.
+    pub(super) env: Vec<String>,
+    pub(super) parts: Vec<String>,
+    pub(super) secrets: bool,
+}
+
+impl SnapshotManifest {
+    pub(super) fn render(&self) -> String {
+        let mut out = format!(
+            "format={SNAPSHOT_FORMAT}\nlayout={}\nname={}\nsecrets={}\n",
+            self.layout,
+            self.name,
+            if self.secrets { "1" } else { "0" }
+        );
+        if let Some(image) = self.image.as_deref() {
+            out.push_str(&format!("image={image}\n"));
+        }
+        for (key, values) in [
+            ("part", &self.parts),
+            ("label", &self.labels),
+            ("env", &self.env),
+        ] {
+            for value in values.iter().filter(|value| !value.contains('\n')) {
+                out.push_str(&format!("{key}={value}\n"));
+            }
+        }
+        out
+    }
+
+    pub(super) fn parse(text: &str) -> Result<Self, String> {
+        let mut manifest = Self::default();
+        let mut format = None;
+
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key {
+                "format" => format = Some(value.to_string()),
+                "layout" => manifest.layout = value.to_string(),
+                "name" => manifest.name = value.to_string(),
+                "secrets" => manifest.secrets = value == "1",
+                "image" => manifest.image = Some(value.to_string()),
+                "part" => manifest.parts.push(value.to_string()),
+                "label" => manifest.labels.push(value.to_string()),
+                "env" => manifest.env.push(value.to_string()),
+                _ => {}
+            }
+        }
+
+        if format.as_deref() != Some(SNAPSHOT_FORMAT) {
+            return Err(format!(
+                "unsupported snapshot format: {}",
+                format.as_deref().unwrap_or("(missing)")
+            ));
+        }
+        if manifest.name.is_empty() || manifest.parts.is_empty() {
+            return Err(String::from("snapshot manifest is missing name or parts"));
+        }
+        Ok(manifest)
+    }
+
+    pub(super) fn expect_layout(&self, layout: &str, runtime: &str) -> Result<(), String> {
+        if self.layout == layout {
+            return Ok(());
+        }
+        Err(format!(
+            "snapshot holds a {} workspace and cannot be restored by the {runtime} runtime",
+            self.layout
+        ))
+    }
+}
+
+/// Path of the tarball for `part` inside an unpacked snapshot.
+pub(super) fn part_path(staging: &Path, part: &str) -> PathBuf {
+    staging.join(format!("{part}.tar"))
+}
+
+/// Write the manifest and bundle it with the part tarballs; `tar -a` compresses by extension.
+pub(super) fn pack_snapshot(
+    output: &Path,
+    staging: &Path,
+    manifest: &SnapshotManifest,
+) -> Result<(), String> {
+    fs::write(staging.join(SNAPSHOT_MANIFEST_FILE), manifest.render())
+        .map_err(|err| format!("failed to write snapshot manifest: {err}"))?;
+    if manifest.secrets {
+        create_owner_only(output)?;
+    }
+
+    let mut cmd = Command::new("tar");
+    cmd.arg("-caf").arg(output).arg("-C").arg(staging);
+    cmd.arg(SNAPSHOT_MANIFEST_FILE);
+    for part in &manifest.parts {
+        cmd.arg(format!("{part}.tar"));
+    }
+    run_tar(&mut cmd)
+}
+
+/// Create (or truncate) `path` as 0600 before `tar` writes into it; `tar` keeps an existing
+/// file's mode, so archives holding auth files and env values never start out world-readable.
+fn create_owner_only(path: &Path) -> Result<(), String> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(path)
+        .map_err(|err| format!("failed to create {}: {err}", path.display()))?;
+    set_owner_only_permissions(path);
+    Ok(())
+}
+
+/// Extract `archive` into `staging` and read its manifest; compression is detected by `tar`.
+pub(super) fn unpack_snapshot(archive: &Path, staging: &Path) -> Result<SnapshotManifest, String> {
+    let mut cmd = Command::new("tar");
+    cmd.arg("-xf").arg(archive).arg("-C").arg(staging);
+    run_tar(&mut cmd)?;
+
+    let text = fs::read_to_string(staging.join(SNAPSHOT_MANIFEST_FILE))
+        .map_err(|err| format!("{} is not a workspace snapshot: {err}", archive.display()))?;
+    let manifest = SnapshotManifest::parse(&text)?;
+    for part in &manifest.parts {
+        if !part_path(staging, part).is_file() {
+            return Err(format!("snapshot is missing part: {part}"));
+        }
+    }
+    Ok(manifest)
+}
+
+/// Archive the contents of `dir` (as `./...` members) into `tarball`, skipping `excludes` patterns.
+pub(super) fn tar_directory(dir: &Path, tarball: &Path, excludes: &[&str]) -> Result<(), String> {
+    let mut cmd = Command::new("tar");
+    cmd.arg("-C").arg(dir).arg("-cf").arg(tarball);
+    for pattern in excludes {
+        cmd.arg(format!("--exclude={pattern}"));
+    }
+    cmd.arg(".");
+    run_tar(&mut cmd)
+}
+
+pub(super) fn untar_directory(tarball: &Path, dir: &Path) -> Result<(), String> {
+    let mut cmd = Command::new("tar");
+    cmd.arg("-C").arg(dir).arg("-xf").arg(tarball);
+    run_tar(&mut cmd)
+}
+
+/// Spawn `tar -cf -` of `src` as a single member named after its basename; `cp` pipes the
+/// child's stdout into a workspace. Returns the child and the member name.
+pub(super) fn spawn_tar_stream(src: &Path) -> Result<(Child, String), String> {
+    let name = src
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("cannot copy {}: path has no file name", src.display()))?;
+    let parent = src
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let child = Command::new("tar")
+        .arg("-C")
+        .arg(parent)
+        .args(["-cf", "-", "--"])
+        .arg(&name)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("failed to run tar: {err}"))?;
+    Ok((child, name))
+}
+
+/// Spawn `tar -xf -` into `dir`, reading the archive from the child's stdin.
+pub(super) fn spawn_untar_stream(dir: &Path) -> Result<Child, String> {
+    Command::new("tar")
+        .arg("-C")
+        .arg(dir)
+        .args(["-x", "--no-same-owner", "-f", "-"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("failed to run tar: {err}"))
+}
+
+/// Wait for a child from `spawn_tar_stream` / `spawn_untar_stream` once its pipe is consumed.
+pub(super) fn finish_tar_stream(child: Child) -> Result<(), String> {
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("failed to wait for tar: {err}"))?;
+    tar_result(&output)
+}
+
+fn run_tar(cmd: &mut Command) -> Result<(), String> {
+    let output = cmd
+        .output()
+        .map_err(|err| format!("failed to run tar: {err}"))?;
+    tar_result(&output)
+}
+
+fn tar_result(output: &Output) -> Result<(), String> {
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if stderr.is_empty() {
+        Err(format!(
+            "tar failed (exit {})",
+            output.status.code().unwrap_or(EXIT_RUNTIME)
+        ))
+    } else {
+        Err(stderr)
+    }
+}
@@ -1,15 +1,15 @@
 use std::ffi::OsString;
 use std::fs;
 use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::process::Command;
 
 use crate::EXIT_RUNTIME;
 
+use super::backend::WorkspaceBackend;
 use super::{
-    PRIMARY_COMMAND_NAME, Workspace, command_exists, default_gpg_signing_key,
-    list_workspaces_on_disk, map_workspace_internal_path, push_unique_path,
-    resolve_codex_auth_file, resolve_codex_profile_auth_files, resolve_workspace, trimmed_nonempty,
-    write_file_secure,
+    PRIMARY_COMMAND_NAME, command_exists, default_gpg_signing_key, push_unique_path,
+    resolve_codex_auth_file, resolve_codex_profile_auth_files, resolve_ready_workspace,
+    trimmed_nonempty,
 };
 
 #[derive(Debug, Default, Clone)]
@@ -108,7 +108,7 @@ fn parse_auth_args(args: &[OsString]) -> Result<ParsedAuth, String> {
     Ok(parsed)
 }
 
-pub(super) fn run(args: &[OsString]) -> i32 {
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
     let parsed = match parse_auth_args(args) {
         Ok(parsed) => parsed,
         Err(err) => {
@@ -123,7 +123,12 @@ pub(super) fn run(args: &[OsString]) -> i32 {
         return 0;
     }
 
-    let workspace = match resolve_workspace_for_auth(parsed.workspace.as_deref()) {
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    let workspace = match resolve_workspace_for_auth(backend, parsed.workspace.as_deref()) {
         Ok(workspace) => workspace,
         Err(err) => {
             eprintln!("error: {err}");
@@ -138,9 +143,9 @@ pub(super) fn run(args: &[OsString]) -> i32 {
         .to_ascii_lowercase();
 
     match provider.as_str() {
-        "github" => run_auth_github(&workspace, parsed.host.as_deref()),
-        "codex" => run_auth_codex(&workspace, parsed.profile.as_deref()),
-        "gpg" => run_auth_gpg(&workspace, parsed.key.as_deref()),
+        "github" => run_auth_github(backend, &workspace, parsed.host.as_deref()),
+        "codex" => run_auth_codex(backend, &workspace, parsed.profile.as_deref()),
+        "gpg" => run_auth_gpg(backend, &workspace, parsed.key.as_deref()),
         _ => {
             eprintln!("error: unknown auth provider: {provider}");
             eprintln!("hint: expected: codex|github|gpg");
@@ -149,18 +154,14 @@ pub(super) fn run(args: &[OsString]) -> i32 {
     }
 }
 
-fn run_auth_github(workspace: &Workspace, host: Option<&str>) -> i32 {
+fn run_auth_github(backend: &dyn WorkspaceBackend, workspace: &str, host: Option<&str>) -> i32 {
     let gh_host = host
         .and_then(trimmed_nonempty)
-        .or_else(|| std::env::var("GITHUB_HOST").ok())
+        .or_else(|| crate::config::value("github_host"))
         .filter(|value| !value.trim().is_empty())
         .unwrap_or_else(|| String::from("github.com"));
 
-    let auth_mode = std::env::var("AGENT_WORKSPACE_AUTH")
-        .ok()
-        .filter(|value| !value.trim().is_empty())
-        .or_else(|| std::env::var("CODEX_WORKSPACE_AUTH").ok())
-        .unwrap_or_else(|| String::from("auto"));
+    let auth_mode = crate::config::value("auth").unwrap_or_else(|| String::from("auto"));
 
     let env_token = std::env::var("GH_TOKEN")
         .ok()
@@ -213,36 +214,23 @@ fn run_auth_github(workspace: &Workspace, host: Option<&str>) -> i32 {
         return EXIT_RUNTIME;
     };
 
-    let content = format!("host={gh_host}\ntoken={token}\n");
-    let target = workspace.path.join("auth").join("github.env");
-    if let Err(err) = write_file_secure(&target, content.as_bytes()) {
-        eprintln!(
-            "error: failed to write GitHub auth file {}: {err}",
-            target.display()
-        );
+    if let Err(err) = backend.store_github_token(workspace, &gh_host, &token) {
+        eprintln!("error: {err}");
         return EXIT_RUNTIME;
     }
 
-    println!(
-        "auth: github -> {} ({gh_host}; source={chosen_source})",
-        workspace.name
-    );
+    println!("auth: github -> {workspace} ({gh_host}; source={chosen_source})");
     0
 }
 
-fn run_auth_codex(workspace: &Workspace, profile_arg: Option<&str>) -> i32 {
-    let profile = profile_arg
-        .and_then(trimmed_nonempty)
-        .or_else(|| {
-            std::env::var("AGENT_WORKSPACE_CODEX_PROFILE")
-                .ok()
-                .and_then(|value| trimmed_nonempty(&value))
-        })
-        .or_else(|| {
-            std::env::var("CODEX_WORKSPACE_CODEX_PROFILE")
-                .ok()
-                .and_then(|value| trimmed_nonempty(&value))
-        });
+fn run_auth_codex(
+    backend: &dyn WorkspaceBackend,
+    workspace: &str,
+    profile_arg: Option<&str>,
+) -> i32 {
+    let profile = profile_arg.and_then(trimmed_nonempty).or_else(|| {
+        crate::config::value("codex_profile").and_then(|value| trimmed_nonempty(&value))
+    });
 
     let mut candidate_files: Vec<PathBuf> = Vec::new();
     if let Some(profile) = profile.as_deref() {
@@ -279,7 +267,7 @@ fn run_auth_codex(workspace: &Workspace, profile_arg: Option<&str>) -> i32 {
             }
         };
 
-        if let Err(err) = sync_codex_auth_into_workspace(workspace, &auth_data) {
+        if let Err(err) = backend.store_codex_auth(workspace, &auth_data) {
             eprintln!(
                 "warn: failed to sync codex auth from {}: {err}",
                 candidate.display()
@@ -289,14 +277,12 @@ fn run_auth_codex(workspace: &Workspace, profile_arg: Option<&str>) -> i32 {
 
         if let Some(profile) = profile.as_deref() {
             println!(
-                "auth: codex -> {} (profile={profile}; source={})",
-                workspace.name,
+                "auth: codex -> {workspace} (profile={profile}; source={})",
                 candidate.display()
             );
         } else {
             println!(
-                "auth: codex -> {} (source={})",
-                workspace.name,
+                "auth: codex -> {workspace} (source={})",
                 candidate.display()
             );
         }
@@ -308,15 +294,7 @@ fn run_auth_codex(workspace: &Workspace, profile_arg: Option<&str>) -> i32 {
     EXIT_RUNTIME
 }
 
-fn sync_codex_auth_into_workspace(workspace: &Workspace, auth_data: &[u8]) -> Result<(), String> {
-    let targets = codex_auth_targets(workspace);
-    for target in targets {
-        write_file_secure(&target, auth_data)?;
-    }
-    Ok(())
-}
-
-fn run_auth_gpg(workspace: &Workspace, key_arg: Option<&str>) -> i32 {
+fn run_auth_gpg(backend: &dyn WorkspaceBackend, workspace: &str, key_arg: Option<&str>) -> i32 {
     let key = key_arg
         .and_then(trimmed_nonempty)
         .or_else(default_gpg_signing_key);
@@ -330,55 +308,21 @@ fn run_auth_gpg(workspace: &Workspace, key_arg: Option<&str>) -> i32 {
         return EXIT_RUNTIME;
     };
 
-    if command_exists("gpg") {
-        let status = Command::new("gpg")
-            .args(["--batch", "--list-secret-keys", &key])
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status();
-
-        match status {
-            Ok(result) if result.success() => {}
-            Ok(_) => {
-                eprintln!("error: gpg key not found in host keyring: {key}");
-                return EXIT_RUNTIME;
-            }
-            Err(err) => {
-                eprintln!("error: failed to run gpg for key lookup: {err}");
-                return EXIT_RUNTIME;
-            }
-        }
-    } else {
-        eprintln!("warn: gpg not found in PATH; writing key id only");
+    if let Err(err) = backend.verify_gpg_key(workspace, &key) {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
     }
 
-    let target = workspace.path.join("auth").join("gpg-key.txt");
-    if let Err(err) = write_file_secure(&target, format!("{key}\n").as_bytes()) {
-        eprintln!(
-            "error: failed to write gpg auth file {}: {err}",
-            target.display()
-        );
+    if let Err(err) = backend.store_gpg_key(workspace, &key) {
+        eprintln!("error: {err}");
         return EXIT_RUNTIME;
     }
 
-    println!("auth: gpg -> {} (key={key})", workspace.name);
+    println!("auth: gpg -> {workspace} (key={key})");
     0
 }
 
-pub(super) fn codex_auth_targets(workspace: &Workspace) -> Vec<PathBuf> {
-    let mut targets = vec![workspace.path.join(".codex").join("auth.json")];
-
-    if let Ok(value) = std::env::var("CODEX_AUTH_FILE")
-        && let Some(cleaned) = trimmed_nonempty(&value)
-    {
-        let mapped = map_workspace_internal_path(workspace, &cleaned);
-        push_unique_path(&mut targets, mapped);
-    }
-
-    targets
-}
-
-fn gh_keyring_token(host: &str) -> Option<String> {
+pub(super) fn gh_keyring_token(host: &str) -> Option<String> {
     if !command_exists("gh") {
         return None;
     }
@@ -396,27 +340,11 @@ fn gh_keyring_token(host: &str) -> Option<String> {
     trimmed_nonempty(String::from_utf8_lossy(&output.stdout).as_ref())
 }
 
-pub(super) fn resolve_workspace_for_auth(name: Option<&str>) -> Result<Workspace, String> {
-    if let Some(name) = name.and_then(trimmed_nonempty) {
-        return match resolve_workspace(&name)? {
-            Some(workspace) => Ok(workspace),
-            None => Err(format!("workspace not found: {name}")),
-        };
-    }
-
-    let workspaces = list_workspaces_on_disk()?;
-    match workspaces.as_slice() {
-        [] => Err(String::from("no workspaces found")),
-        [single] => Ok(single.clone()),
-        _ => Err(format!(
-            "multiple workspaces found; specify one: {}",
-            workspaces
-                .iter()
-                .map(|workspace| workspace.name.as_str())
-                .collect::<Vec<_>>()
-                .join(", ")
-        )),
-    }
+pub(super) fn resolve_workspace_for_auth(
+    backend: &dyn WorkspaceBackend,
+    name: Option<&str>,
+) -> Result<String, String> {
+    resolve_ready_workspace(backend, name)
 }
 
 fn print_auth_usage() {
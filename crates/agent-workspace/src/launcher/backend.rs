@@ -0,0 +1,1215 @@
+use std::ffi::{OsStr, OsString};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::EXIT_RUNTIME;
+
+use super::archive::{SnapshotManifest, finish_tar_stream, spawn_tar_stream, spawn_untar_stream};
+use super::local_sync::copy_like_docker_cp;
+use super::{RepoSpec, scratch_dir, trimmed_nonempty};
+
+pub(super) const DEFAULT_REF: &str = "origin/main";
+
+pub(super) const RESET_REPO_SCRIPT: &str = r#"
+set -euo pipefail
+
+repo_dir="${1:?missing repo_dir}"
+ref="${2:-origin/main}"
+
+if [[ ! -e "$repo_dir/.git" ]]; then
+  echo "error: not a git repo: $repo_dir" >&2
+  exit 1
+fi
+
+cd "$repo_dir"
+
+remote="${ref%%/*}"
+branch="${ref#*/}"
+if [[ "$remote" == "$ref" || -z "$remote" || -z "$branch" ]]; then
+  echo "error: invalid ref (expected remote/branch): $ref" >&2
+  exit 2
+fi
+
+# Keep shallow and single-branch clones (see CLONE_MODE_SCRIPT) in the mode `create` chose.
+fetch_args=(--prune)
+depth="$(git config --get agent-workspace.depth || true)"
+if [[ -n "$depth" ]]; then
+  fetch_args+=(--depth "$depth")
+fi
+if [[ "$(git config --get agent-workspace.single-branch || true)" == "true" ]] &&
+  git fetch "${fetch_args[@]}" -- "$remote" "+refs/heads/$branch:refs/remotes/$remote/$branch" >/dev/null 2>&1; then
+  :
+else
+  git fetch "${fetch_args[@]}" -- "$remote" >/dev/null 2>&1 || git fetch "${fetch_args[@]}" -- "$remote"
+fi
+
+resolved="$remote/$branch"
+if ! git show-ref --verify --quiet "refs/remotes/$resolved"; then
+  default_ref="$(git symbolic-ref -q --short "refs/remotes/$remote/HEAD" 2>/dev/null || true)"
+  if [[ -n "$default_ref" ]] && git show-ref --verify --quiet "refs/remotes/$default_ref"; then
+    echo "warn: $resolved not found; using $default_ref (from $remote/HEAD)" >&2
+    resolved="$default_ref"
+  elif git show-ref --verify --quiet "refs/remotes/$remote/master"; then
+    echo "warn: $resolved not found; using $remote/master" >&2
+    resolved="$remote/master"
+  else
+    echo "error: remote branch not found: $resolved" >&2
+    exit 1
+  fi
+fi
+
+target_branch="${resolved#*/}"
+git_dir="$(cd "$(git rev-parse --git-dir)" && pwd -P)"
+common_dir="$(cd "$(git rev-parse --git-common-dir)" && pwd -P)"
+if [[ "$git_dir" != "$common_dir" ]]; then
+  # Linked worktree: other workspaces may have $target_branch checked out, so keep this one's branch.
+  target_branch="$(git symbolic-ref -q --short HEAD || true)"
+fi
+echo "+ reset $repo_dir -> $resolved"
+
+if [[ -z "$target_branch" ]]; then
+  git checkout --force --detach "$resolved"
+elif git show-ref --verify --quiet "refs/heads/$target_branch"; then
+  git checkout --force "$target_branch" >/dev/null 2>&1 || {
+    git clean -fd >/dev/null 2>&1 || true
+    git checkout --force "$target_branch"
+  }
+else
+  git checkout --force -B "$target_branch" "$resolved" >/dev/null 2>&1 || {
+    git clean -fd >/dev/null 2>&1 || true
+    git checkout --force -B "$target_branch" "$resolved"
+  }
+fi
+
+if command -v git-reset-remote >/dev/null 2>&1; then
+  git-reset-remote --ref "$resolved" --no-fetch --clean --yes
+else
+  git reset --hard "$resolved"
+  git clean -fd
+  echo "✅ Done. '${target_branch:-HEAD}' now matches '$resolved'."
+fi
+"#;
+
+/// Run in a fresh clone made with `CloneOptions::clone_flags`: narrows a `--sparse` checkout and
+/// records the clone mode under `agent-workspace.*` in the repo config for `RESET_REPO_SCRIPT`.
+/// Depth and single-branch are only recorded when the clone really is shallow / narrowed.
+pub(super) const CLONE_MODE_SCRIPT: &str = r#"
+set -euo pipefail
+
+dest="${1:?missing dest}"
+depth="${2:-}"
+filter="${3:-}"
+single_branch="${4:-}"
+shift 4
+
+cd "$dest"
+if [[ $# -gt 0 ]]; then
+  git sparse-checkout set "$@"
+  git config --unset-all agent-workspace.sparse >/dev/null 2>&1 || true
+  for path in "$@"; do
+    git config --add agent-workspace.sparse "$path"
+  done
+fi
+if [[ -n "$depth" && "$(git rev-parse --is-shallow-repository)" == "true" ]]; then
+  git config agent-workspace.depth "$depth"
+fi
+if [[ -n "$filter" ]]; then
+  git config agent-workspace.filter "$filter"
+fi
+if [[ -n "$single_branch$depth" ]] && ! git config --get-all remote.origin.fetch | grep -qF '*'; then
+  git config agent-workspace.single-branch true
+fi
+"#;
+
+pub(super) const LIST_GIT_REPOS_SCRIPT: &str = r#"
+set -euo pipefail
+
+root="${1:?missing root}"
+depth="${2:?missing depth}"
+
+if ! [[ "$depth" =~ ^[0-9]+$ ]] || [[ "$depth" -le 0 ]]; then
+  echo "error: --depth must be a positive integer (got: $depth)" >&2
+  exit 2
+fi
+
+if [[ ! -d "$root" ]]; then
+  exit 0
+fi
+
+git_depth=$((depth + 1))
+find -L "$root" -maxdepth "$git_depth" -mindepth 2 \( -type d -o -type f \) -name .git -print0 2>/dev/null \
+  | while IFS= read -r -d '' git_entry; do
+      printf '%s\n' "${git_entry%/.git}"
+    done \
+  | sort -u
+"#;
+
+/// `cp` into a workspace: `<dest> <name> <contents-only 0|1> [<owner>]`, with a tar of `<name>`
+/// on stdin. Applies `docker cp` destination rules and prints the path written.
+pub(super) const COPY_IN_SCRIPT: &str = r#"
+set -euo pipefail
+
+dest="$1"
+name="$2"
+contents="$3"
+owner="${4:-}"
+
+fail() {
+  echo "error: $*" >&2
+  exit 1
+}
+
+staging="$(mktemp -d)"
+trap 'rm -rf "$staging"' EXIT
+tar -x --no-same-owner -C "$staging" -f -
+src="$staging/$name"
+[[ -e "$src" || -L "$src" ]] || fail "archive does not contain $name"
+
+if [[ -d "$src" && ! -L "$src" ]]; then
+  [[ -e "$dest" && ! -d "$dest" ]] && fail "cannot copy a directory to a non-directory: $dest"
+  if [[ -d "$dest" ]]; then
+    if [[ "$contents" == "1" ]]; then
+      target="$dest"
+    else
+      target="${dest%/}/$name"
+    fi
+  else
+    [[ -d "$(dirname "$dest")" ]] || fail "no such directory: $(dirname "$dest")"
+    target="${dest%/}"
+  fi
+  mkdir -p "$target"
+  cp -a "$src/." "$target/"
+else
+  if [[ -d "$dest" ]]; then
+    target="${dest%/}/$name"
+  elif [[ "$dest" == */ ]]; then
+    fail "destination directory does not exist: $dest"
+  else
+    [[ -d "$(dirname "$dest")" ]] || fail "no such directory: $(dirname "$dest")"
+    target="$dest"
+  fi
+  [[ -d "$target" && ! -L "$target" ]] && fail "cannot overwrite directory $target with a file"
+  rm -f "$target"
+  cp -a "$src" "$target"
+fi
+
+if [[ -n "$owner" ]]; then
+  id "$owner" >/dev/null 2>&1 || owner="codex"
+  # Only what was copied changes owner, not the rest of an existing destination directory.
+  if [[ "$contents" != "1" || ! -d "$src" ]]; then
+    chown -h "$owner:" "$target" 2>/dev/null || true
+  fi
+  if [[ -d "$src" && ! -L "$src" ]]; then
+    (cd "$src" && find . -mindepth 1 -print0) | (cd "$target" && xargs -0 -r chown -h "$owner:" 2>/dev/null) || true
+  fi
+fi
+
+printf '%s\n' "$target"
+"#;
+
+/// Log and state record of a detached tunnel, inside the workspace dir for host and ssh.
+pub(super) const CODE_TUNNEL_LOG_FILE: &str = "code-tunnel.log";
+pub(super) const CODE_TUNNEL_STATE_FILE: &str = "code-tunnel.state";
+
+/// `tunnel --detach`: `<dir> <log> <state> <name|"">`. Starts `code tunnel` in its own session
+/// (so `--stop` can signal the whole group), writes the state record, and prints the pid.
+pub(super) const DETACHED_TUNNEL_SCRIPT: &str = r#"
+set -euo pipefail
+
+dir="${1:?missing dir}"
+log="${2:?missing log path}"
+state="${3:?missing state path}"
+name="${4:-}"
+
+if ! command -v code >/dev/null 2>&1; then
+  echo "'code' command not found (required for tunnel)" >&2
+  exit 127
+fi
+
+args=(tunnel --accept-server-license-terms)
+[[ -n "$name" ]] && args+=(--name "$name")
+launcher=(nohup)
+command -v setsid >/dev/null 2>&1 && launcher=(setsid)
+
+cd "$dir"
+mkdir -p "$(dirname "$log")"
+"${launcher[@]}" code "${args[@]}" >"$log" 2>&1 </dev/null &
+pid=$!
+printf 'pid=%s\nname=%s\nlog=%s\nstarted_unix=%s\n' "$pid" "$name" "$log" "$(date +%s)" >"$state"
+echo "$pid"
+"#;
+
+/// `tunnel --status|--logs|--stop`: `<state> <status|logs|stop> <follow 0|1>`, run where the
+/// detached tunnel runs; `<state>` must end in `.state` beside its `.log`. `status` prints `key=value` lines for `TunnelStatus::parse`.
+pub(super) const TUNNEL_CONTROL_SCRIPT: &str = r#"
+set -euo pipefail
+
+state="${1:?missing state path}"
+action="${2:?missing action}"
+follow="${3:-0}"
+
+recorded=0
+pid=""
+name=""
+log=""
+started_unix=""
+if [[ -f "$state" ]]; then
+  recorded=1
+  while IFS='=' read -r key value; do
+    case "$key" in
+      pid) pid="$value" ;;
+      name) name="$value" ;;
+      log) log="$value" ;;
+      started_unix) started_unix="$value" ;;
+    esac
+  done <"$state"
+fi
+# The log sits next to the record, so it outlives `--stop`.
+log="${log:-${state%.state}.log}"
+
+running=0
+if [[ -n "$pid" ]] && kill -0 "$pid" 2>/dev/null; then
+  # A recycled pid (or a zombie) is not the tunnel.
+  if [[ ! -d /proc ]] || tr '\0' ' ' <"/proc/$pid/cmdline" 2>/dev/null | grep -q tunnel; then
+    running=1
+  fi
+fi
+
+case "$action" in
+  status)
+    login_code=""
+    if [[ -n "$log" && -f "$log" ]]; then
+      login_code="$(grep -oE 'use code [A-Z0-9]{4}-[A-Z0-9]{4}' "$log" | tail -n 1 | cut -d' ' -f3 || true)"
+    fi
+    printf 'recorded=%s\nrunning=%s\npid=%s\nname=%s\nlog=%s\nstarted_unix=%s\nlogin_code=%s\n' \
+      "$recorded" "$running" "$pid" "$name" "$log" "$started_unix" "$login_code"
+    ;;
+  logs)
+    if [[ -z "$log" || ! -f "$log" ]]; then
+      echo "no tunnel log found; start one with 'tunnel --detach'" >&2
+      exit 1
+    fi
+    if [[ "$follow" == "1" && "$running" == "1" ]]; then
+      tail -n +1 --pid="$pid" -f "$log" 2>/dev/null || exec tail -n +1 -f "$log"
+      exit 0
+    fi
+    exec cat "$log"
+    ;;
+  stop)
+    if [[ "$running" != "1" ]]; then
+      rm -f "$state"
+      echo "not-running"
+      exit 0
+    fi
+    target="$pid"
+    pgid="$(sed 's/.*) //' "/proc/$pid/stat" 2>/dev/null | cut -d' ' -f3 || true)"
+    [[ "$pgid" == "$pid" ]] && target="-$pid"
+    kill -TERM -- "$target" 2>/dev/null || true
+    for _ in $(seq 1 50); do
+      kill -0 "$pid" 2>/dev/null || break
+      sleep 0.1
+    done
+    kill -KILL -- "$target" 2>/dev/null || true
+    rm -f "$state"
+    echo "stopped"
+    ;;
+  *)
+    echo "unknown tunnel action: $action" >&2
+    exit 1
+    ;;
+esac
+"#;
+
+/// `forward` bridge: relays stdin/stdout to `127.0.0.1:<port>` inside the workspace, with socat
+/// when the image has it and bash's `/dev/tcp` otherwise.
+pub(super) const FORWARD_BRIDGE_SCRIPT: &str = r#"
+set -uo pipefail
+
+port="$1"
+
+if command -v socat >/dev/null 2>&1; then
+  exec socat - "TCP:127.0.0.1:$port"
+fi
+
+if ! { exec 3<>"/dev/tcp/127.0.0.1/$port"; } 2>/dev/null; then
+  echo "nothing is listening on port $port" >&2
+  exit 1
+fi
+# Background jobs get /dev/null as stdin, so the writer reads the client through fd 4.
+exec 4<&0
+cat <&3 &
+reader=$!
+cat <&4 >&3 &
+writer=$!
+wait -n
+if kill -0 "$reader" 2>/dev/null; then
+  # The client is done sending; like socat's default -t 0.5, the reply gets half a second more.
+  (sleep 0.5 && kill "$reader" 2>/dev/null) >/dev/null &
+  wait "$reader"
+fi
+kill "$writer" 2>/dev/null
+exit 0
+"#;
+
+/// `cp` out of a workspace: `<src> <contents-only 0|1>`; writes a tar of `<src>` (as its
+/// basename), or of its contents, to stdout.
+pub(super) const COPY_OUT_SCRIPT: &str = r#"
+set -euo pipefail
+
+src="$1"
+contents="$2"
+
+if [[ ! -e "$src" && ! -L "$src" ]]; then
+  echo "error: no such file or directory: $src" >&2
+  exit 1
+fi
+if [[ "$contents" == "1" ]]; then
+  [[ -d "$src" ]] || { echo "error: not a directory: $src" >&2; exit 1; }
+  tar -C "$src" -cf - .
+else
+  tar -C "$(dirname "$src")" -cf - "$(basename "$src")"
+fi
+"#;
+
+/// Status probe for `inspect`: `<meta-file> <depth> <repo-root>... -- <auth-file>...`.
+///
+/// Prints tab-separated `meta`, `auth` and `repo` records; an empty meta path skips the
+/// metadata file.
+pub(super) const WORKSPACE_STATUS_SCRIPT: &str = r#"
+set -euo pipefail
+
+meta="${1-}"
+depth="${2:?missing depth}"
+shift 2
+
+roots=()
+while [[ $# -gt 0 && "$1" != "--" ]]; do
+  roots+=("$1")
+  shift
+done
+[[ $# -gt 0 ]] && shift
+
+if [[ -n "$meta" && -f "$meta" ]]; then
+  while IFS= read -r line; do
+    printf 'meta\t%s\n' "$line"
+  done < "$meta"
+fi
+
+for auth_file in "$@"; do
+  if [[ -f "$auth_file" ]]; then
+    printf 'auth\t%s\n' "$auth_file"
+  fi
+done
+
+for root in ${roots[@]+"${roots[@]}"}; do
+  [[ -d "$root" ]] || continue
+  find -L "$root" -maxdepth "$((depth + 1))" -mindepth 2 \( -type d -o -type f \) -name .git -print0 2>/dev/null \
+    | while IFS= read -r -d '' git_entry; do
+        printf '%s\n' "${git_entry%/.git}"
+      done
+done | sort -u | while IFS= read -r repo; do
+  branch="$(git -C "$repo" symbolic-ref -q --short HEAD 2>/dev/null || true)"
+  dirty=0
+  if [[ -n "$(git -C "$repo" status --porcelain 2>/dev/null || true)" ]]; then
+    dirty=1
+  fi
+  upstream="$(git -C "$repo" rev-parse --abbrev-ref --symbolic-full-name '@{upstream}' 2>/dev/null || true)"
+  ahead=""
+  behind=""
+  if [[ -n "$upstream" ]]; then
+    counts="$(git -C "$repo" rev-list --left-right --count "HEAD...$upstream" 2>/dev/null || true)"
+    if [[ -n "$counts" ]]; then
+      read -r ahead behind <<<"$counts"
+    fi
+  fi
+  printf 'repo\t%s\t%s\t%s\t%s\t%s\t%s\n' "$repo" "$branch" "$dirty" "$upstream" "$ahead" "$behind"
+done
+"#;
+
+/// A workspace as reported by `ls`; fields a runtime does not track stay `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct WorkspaceEntry {
+    pub(super) name: String,
+    /// Only known for backends with a host directory.
+    pub(super) path: Option<PathBuf>,
+    pub(super) repo: Option<String>,
+    /// `running`, `paused` or `stopped` for containers; host directories have none.
+    pub(super) state: Option<String>,
+    pub(super) created_unix: Option<u64>,
+    pub(super) image: Option<String>,
+}
+
+/// Apply one `key=value` line of `.workspace-meta` to the creation time and primary repo.
+pub(super) fn apply_meta_line(
+    line: &str,
+    created_unix: &mut Option<u64>,
+    primary_repo: &mut Option<String>,
+) {
+    match line.split_once('=') {
+        Some(("created_unix", value)) => *created_unix = value.trim().parse().ok(),
+        Some(("primary_repo", value)) => {
+            *primary_repo = trimmed_nonempty(value).filter(|value| value != "none");
+        }
+        _ => {}
+    }
+}
+
+/// Everything `inspect` reports about one workspace; `None` means the runtime does not track it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct WorkspaceDetails {
+    pub(super) name: String,
+    pub(super) path: String,
+    pub(super) created_unix: Option<u64>,
+    pub(super) primary_repo: Option<String>,
+    pub(super) image: Option<String>,
+    pub(super) image_digest: Option<String>,
+    /// Container state (`running`, `exited`, ...); host directories have none.
+    pub(super) state: Option<String>,
+    pub(super) volumes: Vec<String>,
+    /// Names of the variables `create` added (values stay in the container env).
+    pub(super) env_names: Vec<String>,
+    pub(super) mounts: Vec<String>,
+    pub(super) publish: Vec<String>,
+    pub(super) cpus: Option<String>,
+    pub(super) memory: Option<String>,
+    pub(super) auth_files: Vec<String>,
+    pub(super) repos: Vec<RepoStatus>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct RepoStatus {
+    pub(super) path: String,
+    /// Checked-out branch; `None` when HEAD is detached.
+    pub(super) branch: Option<String>,
+    pub(super) dirty: bool,
+    pub(super) upstream: Option<String>,
+    pub(super) ahead: Option<u32>,
+    pub(super) behind: Option<u32>,
+}
+
+impl WorkspaceDetails {
+    /// Fold `WORKSPACE_STATUS_SCRIPT` output into these details.
+    pub(super) fn apply_status_output(&mut self, output: &str) {
+        for line in output.lines() {
+            let mut fields = line.split('\t');
+            match fields.next() {
+                Some("meta") => apply_meta_line(
+                    fields.next().unwrap_or_default(),
+                    &mut self.created_unix,
+                    &mut self.primary_repo,
+                ),
+                Some("auth") => {
+                    if let Some(path) = fields.next().and_then(trimmed_nonempty) {
+                        self.auth_files.push(path);
+                    }
+                }
+                Some("repo") => {
+                    let mut next = || fields.next().and_then(trimmed_nonempty);
+                    let Some(path) = next() else {
+                        continue;
+                    };
+                    self.repos.push(RepoStatus {
+                        path,
+                        branch: next(),
+                        dirty: next().is_some_and(|value| value == "1"),
+                        upstream: next(),
+                        ahead: next().and_then(|value| value.parse().ok()),
+                        behind: next().and_then(|value| value.parse().ok()),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct NewWorkspace<'a> {
+    pub(super) name: &'a str,
+    pub(super) primary_repo: Option<&'a RepoSpec>,
+    pub(super) image: Option<&'a str>,
+    pub(super) pull: bool,
+    /// Variables exported to every command run in the workspace.
+    pub(super) env: &'a [(String, String)],
+    /// Seconds without exec sessions or tunnels before `stop --idle` may stop the workspace.
+    pub(super) idle_timeout: Option<u64>,
+    /// Container-only `run` options: `source:target[:mode]` binds, `--publish` specs, and limits.
+    pub(super) mounts: &'a [String],
+    pub(super) publish: &'a [String],
+    pub(super) cpus: Option<&'a str>,
+    pub(super) memory: Option<&'a str>,
+}
+
+/// `restore` options that are not kept in a snapshot and must be given again.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct RestoreOptions<'a> {
+    /// Values for (or additions to) the `create --env` names of a snapshot taken without secrets.
+    pub(super) env: &'a [(String, String)],
+    /// Container-only binds and `--publish` specs; the snapshot's own are not reapplied.
+    pub(super) mounts: &'a [String],
+    pub(super) publish: &'a [String],
+}
+
+/// `upgrade`: recreate a workspace container on a newer image, keeping its volumes.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct UpgradeOptions<'a> {
+    /// Image to move to; defaults to the image reference the workspace was created from.
+    pub(super) image: Option<&'a str>,
+    pub(super) pull: bool,
+    pub(super) sync_baseline: bool,
+}
+
+/// Image digests (or ids when the image has no repo digest) around an `upgrade`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct UpgradeOutcome {
+    pub(super) previous: String,
+    pub(super) current: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CreatedWorkspace {
+    pub(super) name: String,
+    pub(super) path: String,
+}
+
+/// Where a repo is checked out inside a workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum RepoSlot {
+    Work,
+    Private,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct CloneOptions<'a> {
+    pub(super) refspec: Option<&'a str>,
+    /// Check out as a `git worktree` of a shared bare mirror instead of a full clone.
+    pub(super) worktree: bool,
+    /// Capture git's output instead of streaming it, so concurrent clones do not interleave
+    /// progress bars; a failure then carries git's last error line.
+    pub(super) quiet: bool,
+    /// `--depth`: shallow-clone to this many commits.
+    pub(super) depth: Option<u32>,
+    /// `--filter`: partial-clone filter spec such as `blob:none`.
+    pub(super) filter: Option<&'a str>,
+    pub(super) single_branch: bool,
+    /// `--sparse`: cone-mode paths to check out; empty means the whole tree.
+    pub(super) sparse: &'a [String],
+}
+
+impl CloneOptions<'_> {
+    /// Whether any shallow, partial, single-branch, or sparse option is set.
+    pub(super) fn has_clone_mode(&self) -> bool {
+        self.depth.is_some()
+            || self.filter.is_some()
+            || self.single_branch
+            || !self.sparse.is_empty()
+    }
+
+    /// Extra `git clone` flags for the clone mode. A narrowed clone of `remote/branch` fetches that
+    /// branch directly, since it would not be there to check out afterwards.
+    pub(super) fn clone_flags(&self) -> Vec<String> {
+        let mut flags = Vec::new();
+        if let Some(depth) = self.depth {
+            flags.push(format!("--depth={depth}"));
+        }
+        if let Some(filter) = self.filter {
+            flags.push(format!("--filter={filter}"));
+        }
+        if self.single_branch {
+            flags.push(String::from("--single-branch"));
+        }
+        if !self.sparse.is_empty() {
+            flags.push(String::from("--sparse"));
+        }
+        if (self.depth.is_some() || self.single_branch)
+            && let Some((_, branch)) = self.refspec.and_then(|refspec| refspec.split_once('/'))
+            && !branch.is_empty()
+        {
+            flags.push(format!("--branch={branch}"));
+        }
+        flags
+    }
+
+    /// Arguments for `CLONE_MODE_SCRIPT` run in `destination`.
+    pub(super) fn clone_mode_args(&self, destination: &str) -> Vec<String> {
+        let mut args = vec![
+            destination.to_string(),
+            self.depth
+                .map(|depth| depth.to_string())
+                .unwrap_or_default(),
+            self.filter.unwrap_or_default().to_string(),
+            if self.single_branch { "true" } else { "" }.to_string(),
+        ];
+        args.extend(self.sparse.iter().cloned());
+        args
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct RemoveOptions {
+    pub(super) keep_volumes: bool,
+}
+
+/// Result of probing a workspace for `stop --idle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum IdleCheck {
+    /// Created without `--idle-timeout`.
+    NoPolicy,
+    NotRunning,
+    /// An exec session or tunnel is running.
+    Busy,
+    Idle {
+        idle_secs: u64,
+        timeout_secs: u64,
+    },
+}
+
+/// Outcome of one `doctor` check; `Skip` means the check does not apply to this setup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum CheckStatus {
+    Ok,
+    Skip,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Skip => "skip",
+            Self::Warn => "warn",
+            Self::Fail => "fail",
+        }
+    }
+}
+
+/// One `doctor` finding; `hint` tells the user how to fix a warning or failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct DoctorCheck {
+    pub(super) name: &'static str,
+    pub(super) status: CheckStatus,
+    pub(super) detail: String,
+    pub(super) hint: Option<String>,
+}
+
+impl DoctorCheck {
+    pub(super) fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Ok, detail.into(), None)
+    }
+
+    pub(super) fn skip(name: &'static str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Skip, detail.into(), None)
+    }
+
+    pub(super) fn warn(
+        name: &'static str,
+        detail: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Self::new(name, CheckStatus::Warn, detail.into(), Some(hint.into()))
+    }
+
+    pub(super) fn fail(
+        name: &'static str,
+        detail: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Self::new(name, CheckStatus::Fail, detail.into(), Some(hint.into()))
+    }
+
+    fn new(name: &'static str, status: CheckStatus, detail: String, hint: Option<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail,
+            hint,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct ExecRequest<'a> {
+    pub(super) user: Option<&'a OsStr>,
+    pub(super) command: &'a [OsString],
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct TunnelRequest<'a> {
+    pub(super) name: Option<&'a str>,
+    pub(super) detach: bool,
+}
+
+/// What `tunnel --status|--logs|--stop` asks of the detached tunnel's record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TunnelAction {
+    Status,
+    Logs { follow: bool },
+    Stop,
+}
+
+impl TunnelAction {
+    /// The `<action> <follow>` arguments of `TUNNEL_CONTROL_SCRIPT`.
+    pub(super) fn script_args(self) -> [&'static str; 2] {
+        match self {
+            Self::Status => ["status", "0"],
+            Self::Logs { follow: true } => ["logs", "1"],
+            Self::Logs { follow: false } => ["logs", "0"],
+            Self::Stop => ["stop", "0"],
+        }
+    }
+}
+
+/// `tunnel --status` as reported by `TUNNEL_CONTROL_SCRIPT`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct TunnelStatus {
+    pub(super) recorded: bool,
+    pub(super) running: bool,
+    pub(super) pid: Option<u32>,
+    pub(super) name: Option<String>,
+    pub(super) log_path: Option<String>,
+    pub(super) started_unix: Option<u64>,
+    pub(super) login_code: Option<String>,
+}
+
+impl TunnelStatus {
+    pub(super) fn parse(output: &str) -> Self {
+        let mut status = Self::default();
+        for line in output.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key {
+                "recorded" => status.recorded = value.trim() == "1",
+                "running" => status.running = value.trim() == "1",
+                "pid" => status.pid = value.trim().parse().ok(),
+                "name" => status.name = trimmed_nonempty(value),
+                "log" => status.log_path = trimmed_nonempty(value),
+                "started_unix" => status.started_unix = value.trim().parse().ok(),
+                "login_code" => status.login_code = trimmed_nonempty(value),
+                _ => {}
+            }
+        }
+        status
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum TunnelOutcome {
+    Detached {
+        tunnel_name: Option<String>,
+        pid: Option<u32>,
+        log_path: Option<String>,
+    },
+    Exited {
+        tunnel_name: Option<String>,
+        exit_code: i32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum RsyncDirection {
+    Push,
+    Pull,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct RsyncTransfer {
+    pub(super) direction: RsyncDirection,
+    pub(super) src: String,
+    pub(super) dest: String,
+    pub(super) user: String,
+    pub(super) delete: bool,
+    pub(super) dry_run: bool,
+    /// Skip `.gitignore`d paths (rsync `:- .gitignore` dir-merge) and `.git/`.
+    pub(super) gitignore: bool,
+    pub(super) rsync_args: Vec<String>,
+    /// When non-empty, only these paths (relative to `src`, which is then a directory) are sent
+    /// through `--files-from`; `push --watch` uses this for each batch of changes.
+    pub(super) paths: Vec<String>,
+}
+
+impl RsyncTransfer {
+    /// Local rsync flags shared by every runtime; callers add `-e` and the operands.
+    pub(super) fn rsync_flags(&self) -> Vec<String> {
+        let mut argv: Vec<String> = vec![
+            String::from("-rlpt"),
+            String::from("--partial"),
+            String::from("--progress"),
+        ];
+        if self.delete {
+            argv.push(String::from("--delete"));
+        }
+        if self.dry_run {
+            argv.push(String::from("--dry-run"));
+        }
+        if self.gitignore {
+            argv.push(String::from("--filter=:- .gitignore"));
+            argv.push(String::from("--exclude=.git/"));
+        }
+        if !self.paths.is_empty() {
+            argv.push(String::from("--files-from=-"));
+            // Paths removed since the last batch: delete them remotely only under `--delete`.
+            argv.push(String::from(if self.delete {
+                "--delete-missing-args"
+            } else {
+                "--ignore-missing-args"
+            }));
+        }
+        argv.extend(self.rsync_args.iter().cloned());
+        argv
+    }
+}
+
+/// Run local `rsync` with `argv`, feeding `transfer.paths` on stdin for `--files-from=-`.
+pub(super) fn run_rsync(
+    argv: &[String],
+    env: &[(&str, &str)],
+    transfer: &RsyncTransfer,
+) -> Result<(), String> {
+    println!("+ rsync {}", argv.join(" "));
+
+    let mut cmd = Command::new("rsync");
+    cmd.args(argv).envs(env.iter().copied());
+    let status = if transfer.paths.is_empty() {
+        cmd.status()
+    } else {
+        cmd.stdin(Stdio::piped()).spawn().and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                let list = transfer.paths.join("\n") + "\n";
+                stdin.write_all(list.as_bytes())?;
+            }
+            child.wait()
+        })
+    };
+
+    match status {
+        Ok(result) if result.success() => Ok(()),
+        Ok(result) => Err(format!(
+            "rsync command failed (exit {})",
+            result.code().unwrap_or(EXIT_RUNTIME)
+        )),
+        Err(err) => Err(format!("failed to execute rsync: {err}")),
+    }
+}
+
+/// Operands of `cp`; a `src` written as `dir/.` arrives as `dir` with `contents_only` set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CopyRequest {
+    pub(super) src: String,
+    pub(super) dest: String,
+    pub(super) contents_only: bool,
+}
+
+impl CopyRequest {
+    fn contents_flag(&self) -> &'static str {
+        if self.contents_only { "1" } else { "0" }
+    }
+}
+
+/// `copy_in` for runtimes reached through a remote shell: `run` executes `COPY_IN_SCRIPT` with
+/// the given args, reading a tar of the local `src` from the given stdin.
+pub(super) fn stream_copy_in(
+    request: &CopyRequest,
+    owner: &str,
+    run: impl FnOnce(&[&str], Stdio) -> Result<String, String>,
+) -> Result<String, String> {
+    let src = std::path::absolute(&request.src)
+        .map_err(|err| format!("failed to resolve {}: {err}", request.src))?;
+    let (mut tar, name) = spawn_tar_stream(&src)?;
+    let archive = tar
+        .stdout
+        .take()
+        .ok_or_else(|| String::from("failed to read tar output"))?;
+    let result = run(
+        &[&request.dest, &name, request.contents_flag(), owner],
+        Stdio::from(archive),
+    );
+    let packed = finish_tar_stream(tar);
+    let target = result?;
+    packed?;
+    trimmed_nonempty(&target).ok_or_else(|| String::from("copy did not report a target path"))
+}
+
+/// `copy_out` counterpart of `stream_copy_in`: `run` executes `COPY_OUT_SCRIPT`, writing its
+/// tar to the given stdout, which is unpacked into a scratch dir and then placed locally.
+pub(super) fn stream_copy_out(
+    request: &CopyRequest,
+    run: impl FnOnce(&[&str], Stdio) -> Result<String, String>,
+) -> Result<String, String> {
+    let staging = scratch_dir("cp")?;
+    let result = unpack_and_place(request, &staging, run);
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+fn unpack_and_place(
+    request: &CopyRequest,
+    staging: &Path,
+    run: impl FnOnce(&[&str], Stdio) -> Result<String, String>,
+) -> Result<String, String> {
+    let mut untar = spawn_untar_stream(staging)?;
+    let archive = untar
+        .stdin
+        .take()
+        .ok_or_else(|| String::from("failed to open tar input"))?;
+    let result = run(
+        &[&request.src, request.contents_flag()],
+        Stdio::from(archive),
+    );
+    let unpacked = finish_tar_stream(untar);
+    result?;
+    unpacked?;
+
+    let staged = if request.contents_only {
+        staging.to_path_buf()
+    } else {
+        let name = Path::new(request.src.trim_end_matches('/'))
+            .file_name()
+            .ok_or_else(|| format!("cannot copy {}: path has no file name", request.src))?;
+        staging.join(name)
+    };
+    copy_like_docker_cp(&staged, request.contents_only, &request.dest)
+        .map(|target| target.display().to_string())
+}
+
+/// Runtime-specific workspace operations.
+///
+/// Workspaces are identified by the canonical name returned from `resolve`, `list`, or
+/// `create_workspace`. Argument parsing, confirmation prompts, and output formatting live
+/// in the shared subcommand modules, so a runtime only has to implement these operations.
+pub(super) trait WorkspaceBackend: Sync {
+    /// Runtime name reported in JSON output.
+    fn runtime_name(&self) -> &'static str;
+
+    /// Check that the tooling this backend shells out to is usable.
+    fn ensure_available(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Hint printed after a failed subcommand when the backend tooling is missing.
+    fn unavailable_hint(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn list(&self) -> Result<Vec<WorkspaceEntry>, String>;
+
+    /// Map a user-supplied name (with or without workspace prefixes) to a canonical name.
+    fn resolve(&self, name: &str) -> Result<Option<String>, String>;
+
+    /// Prepare a resolved workspace for exec-style operations (e.g. start a stopped container).
+    fn ensure_ready(&self, _workspace: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Whether the workspace can run commands without `ensure_ready` starting anything.
+    fn is_ready(&self, _workspace: &str) -> bool {
+        true
+    }
+
+    fn create_workspace(&self, request: &NewWorkspace<'_>) -> Result<CreatedWorkspace, String>;
+
+    /// Workspace directory as seen by commands run through `exec`.
+    fn workspace_dir(&self, workspace: &str) -> Result<String, String>;
+
+    /// Workspace directory on this machine, for runtimes that keep one.
+    fn local_dir(&self, _workspace: &str) -> Option<PathBuf> {
+        None
+    }
+
+    /// Clone `repo` into the given slot and return the checkout path.
+    fn clone_repo(
+        &self,
+        workspace: &str,
+        repo: &RepoSpec,
+        slot: RepoSlot,
+        options: &CloneOptions<'_>,
+    ) -> Result<String, String>;
+
+    /// Whether `create_workspace` honors `NewWorkspace::image` and the container-only options
+    /// (`mounts`, `publish`, `cpus`, `memory`).
+    fn supports_images(&self) -> bool {
+        false
+    }
+
+    /// Whether `clone_repo` honors `CloneOptions::worktree`.
+    fn supports_worktrees(&self) -> bool {
+        false
+    }
+
+    fn remove(&self, workspace: &str, options: &RemoveOptions) -> Result<(), String>;
+
+    /// Copy `source` into a new workspace `new_name` with fresh metadata; no repos are cloned.
+    fn clone_workspace(&self, source: &str, new_name: &str) -> Result<CreatedWorkspace, String>;
+
+    /// Write the workspace's snapshot parts as `<part>.tar` files into `staging`.
+    fn snapshot(
+        &self,
+        workspace: &str,
+        staging: &Path,
+        include_secrets: bool,
+    ) -> Result<SnapshotManifest, String>;
+
+    /// Rebuild a snapshot unpacked into `staging` as a new workspace `new_name`.
+    fn restore(
+        &self,
+        manifest: &SnapshotManifest,
+        staging: &Path,
+        new_name: &str,
+        options: &RestoreOptions<'_>,
+    ) -> Result<CreatedWorkspace, String>;
+
+    /// Move `workspace` to `new_name` (already normalized for create); returns the canonical new name.
+    fn rename(&self, workspace: &str, new_name: &str) -> Result<String, String>;
+
+    /// Run a command (or a login shell when `command` is empty) and return its exit code.
+    fn exec(&self, workspace: &str, request: &ExecRequest<'_>) -> Result<i32, String>;
+
+    /// Default `--depth` for `reset work-repos`.
+    fn default_work_repos_depth(&self) -> u32;
+
+    /// Map a repo path as seen inside the workspace (`/work/...`, `/opt/...`, relative).
+    fn map_repo_path(&self, workspace: &str, raw: &str) -> Result<String, String>;
+
+    /// Resolve the repo targeted by `reset repo`, failing when it is not a git checkout.
+    fn repo_path(&self, workspace: &str, raw: &str) -> Result<String, String> {
+        self.map_repo_path(workspace, raw)
+    }
+
+    fn list_repos(&self, workspace: &str, root: &str, depth: u32) -> Result<Vec<String>, String>;
+
+    fn private_repo(&self, workspace: &str) -> Result<Option<String>, String>;
+
+    fn reset_repo(&self, workspace: &str, repo_dir: &str, refspec: &str) -> Result<(), String>;
+
+    fn store_github_token(&self, workspace: &str, host: &str, token: &str) -> Result<(), String>;
+
+    fn store_codex_auth(&self, workspace: &str, auth_data: &[u8]) -> Result<(), String>;
+
+    fn verify_gpg_key(&self, workspace: &str, key: &str) -> Result<(), String>;
+
+    fn store_gpg_key(&self, workspace: &str, key: &str) -> Result<(), String>;
+
+    fn tunnel(&self, workspace: &str, request: &TunnelRequest<'_>)
+    -> Result<TunnelOutcome, String>;
+
+    /// Run `TUNNEL_CONTROL_SCRIPT` against the workspace's detached tunnel record. `Logs`
+    /// streams to stdout and returns an empty string; the other actions return its output.
+    fn tunnel_control(&self, workspace: &str, action: TunnelAction) -> Result<String, String>;
+
+    /// Whether `start`/`stop` apply; host-layout workspaces have nothing to stop.
+    fn supports_start_stop(&self) -> bool {
+        false
+    }
+
+    fn start(&self, _workspace: &str) -> Result<(), String> {
+        Err(format!(
+            "start is not supported by the {} runtime",
+            self.runtime_name()
+        ))
+    }
+
+    fn stop(&self, _workspace: &str) -> Result<(), String> {
+        Err(format!(
+            "stop is not supported by the {} runtime",
+            self.runtime_name()
+        ))
+    }
+
+    fn idle_check(&self, _workspace: &str) -> Result<IdleCheck, String> {
+        Ok(IdleCheck::NoPolicy)
+    }
+
+    /// Volumes named like a workspace's (`<container>-work`, ...) whose container is gone, for `gc`.
+    fn orphaned_volumes(&self) -> Result<Vec<String>, String> {
+        Ok(Vec::new())
+    }
+
+    fn remove_volume(&self, _volume: &str) -> Result<(), String> {
+        Err(format!(
+            "volumes are not supported by the {} runtime",
+            self.runtime_name()
+        ))
+    }
+
+    /// Directories under the workspace root without `.workspace-meta`; `gc` only reports them.
+    fn unmanaged_dirs(&self) -> Result<Vec<String>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Runtime checks for `doctor` (engine reachability, workspace root, ...), run before
+    /// the shared tool and credential checks. Must not fail fast: report every finding.
+    fn doctor_checks(&self) -> Vec<DoctorCheck> {
+        Vec::new()
+    }
+
+    /// Disk space used by the workspace in bytes, for `ls --long`; `None` when unknown.
+    fn disk_usage(&self, _workspace: &str) -> Result<Option<u64>, String> {
+        Ok(None)
+    }
+
+    /// Collect metadata, auth presence and per-repo git status for `inspect`.
+    fn inspect(&self, workspace: &str) -> Result<WorkspaceDetails, String>;
+
+    fn supports_rsync(&self) -> bool {
+        false
+    }
+
+    fn rsync(&self, _workspace: &str, _transfer: &RsyncTransfer) -> Result<(), String> {
+        Err(format!(
+            "rsync is not supported by the {} runtime",
+            self.runtime_name()
+        ))
+    }
+
+    fn supports_forward(&self) -> bool {
+        false
+    }
+
+    /// Command whose stdin/stdout carry one TCP connection to `127.0.0.1:<port>` inside the
+    /// workspace; `forward` spawns one per accepted host connection.
+    fn forward_command(&self, _workspace: &str, _port: u16) -> Result<Command, String> {
+        Err(format!(
+            "forward is not supported by the {} runtime",
+            self.runtime_name()
+        ))
+    }
+
+    /// Move the workspace onto a new image, rolling back to the previous one when the new
+    /// container does not come up.
+    fn upgrade(
+        &self,
+        _workspace: &str,
+        _options: &UpgradeOptions<'_>,
+    ) -> Result<UpgradeOutcome, String> {
+        Err(format!(
+            "upgrade is not supported by the {} runtime",
+            self.runtime_name()
+        ))
+    }
+
+    /// `cp <local> <workspace>:<dest>` with `docker cp` semantics; returns the workspace path written.
+    fn copy_in(&self, workspace: &str, request: &CopyRequest) -> Result<String, String>;
+
+    /// `cp <workspace>:<src> <local>` with `docker cp` semantics; returns the local path written.
+    fn copy_out(&self, workspace: &str, request: &CopyRequest) -> Result<String, String>;
+
+    /// `rsync push --watch`: run `transfer`, then each batch (a transfer limited to changed
+    /// `paths`) from `next_batch` until it returns `None`. A failed batch is reported and the
+    /// session goes on.
+    fn rsync_watch(
+        &self,
+        workspace: &str,
+        transfer: &RsyncTransfer,
+        next_batch: &mut dyn FnMut() -> Option<RsyncTransfer>,
+    ) -> Result<(), String> {
+        self.rsync(workspace, transfer)?;
+        while let Some(batch) = next_batch() {
+            if let Err(err) = self.rsync(workspace, &batch) {
+                eprintln!("warn: {err}");
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use std::ffi::OsString;
+
+use crate::EXIT_RUNTIME;
+
+use super::backend::WorkspaceBackend;
+use super::{PRIMARY_COMMAND_NAME, normalize_workspace_name_for_create, trimmed_nonempty};
+
+#[derive(Debug, Default, Clone)]
+pub(super) struct ParsedClone {
+    pub(super) show_help: bool,
+    pub(super) source: Option<String>,
+    pub(super) new_name: Option<String>,
+}
+
+pub(super) fn parse_clone_args(args: &[OsString]) -> Result<ParsedClone, String> {
+    let mut parsed = ParsedClone::default();
+
+    for arg in args {
+        let text = arg.to_string_lossy();
+        match text.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            _ if text.starts_with('-') => {
+                return Err(format!("unknown option for clone: {text}"));
+            }
+            _ if parsed.source.is_none() => parsed.source = trimmed_nonempty(&text),
+            _ if parsed.new_name.is_none() => parsed.new_name = trimmed_nonempty(&text),
+            _ => return Err(format!("unexpected arg for clone: {text}")),
+        }
+    }
+
+    if !parsed.show_help && (parsed.source.is_none() || parsed.new_name.is_none()) {
+        return Err(String::from("clone requires <source> and <new-name>"));
+    }
+
+    Ok(parsed)
+}
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_clone_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_clone_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if parsed.show_help {
+        print_clone_usage();
+        return 0;
+    }
+
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    let name = parsed.source.unwrap_or_default();
+    let source = match backend.resolve(&name) {
+        Ok(Some(workspace)) => workspace,
+        Ok(None) => {
+            eprintln!("error: workspace not found: {name}");
+            return EXIT_RUNTIME;
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+
+    let new_name = normalize_workspace_name_for_create(&parsed.new_name.unwrap_or_default());
+    let created = match backend.clone_workspace(&source, &new_name) {
+        Ok(created) => created,
+        Err(err) => {
+            eprintln!("error: failed to clone workspace {source}: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+
+    println!("workspace: {}", created.name);
+    println!("path: {}", created.path);
+    0
+}
+
+fn print_clone_usage() {
+    eprintln!(
+        "usage: {PRIMARY_COMMAND_NAME} clone [--runtime <container|host|podman|ssh://host>] <source> <new-name>"
+    );
+}
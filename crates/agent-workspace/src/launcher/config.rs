@@ -0,0 +1,187 @@
+use std::ffi::OsString;
+
+use crate::EXIT_RUNTIME;
+use crate::config::{self, SETTINGS, Source};
+use crate::runtime::parse_runtime_value;
+
+use super::PRIMARY_COMMAND_NAME;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigAction {
+    Get,
+    Set,
+    List,
+    Path,
+}
+
+#[derive(Debug, Default, Clone)]
+struct ParsedConfig {
+    show_help: bool,
+    action: Option<ConfigAction>,
+    show_source: bool,
+    runtime_flag: Option<String>,
+    positionals: Vec<String>,
+}
+
+pub(super) fn run(profile: Option<&str>, args: &[OsString]) -> i32 {
+    let parsed = match parse_config_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_config_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    let Some(action) = parsed.action.filter(|_| !parsed.show_help) else {
+        print_config_usage();
+        return 0;
+    };
+
+    let expected_args = match action {
+        ConfigAction::Get => 1,
+        ConfigAction::Set => 2,
+        ConfigAction::List | ConfigAction::Path => 0,
+    };
+    if parsed.positionals.len() != expected_args {
+        eprintln!(
+            "error: unexpected arguments: {}",
+            parsed.positionals.join(" ")
+        );
+        print_config_usage();
+        return EXIT_RUNTIME;
+    }
+
+    if action == ConfigAction::Path {
+        println!("{}", config::config_path().display());
+        return 0;
+    }
+
+    if action == ConfigAction::Set {
+        return run_config_set(profile, &parsed.positionals[0], &parsed.positionals[1]);
+    }
+
+    if let Err(err) = config::activate(profile) {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    if action == ConfigAction::Get {
+        let key = parsed.positionals[0].as_str();
+        if config::find_setting(key).is_none() {
+            eprintln!("error: unknown config key: {key}");
+            eprintln!("hint: run '{PRIMARY_COMMAND_NAME} config list' to see supported keys");
+            return EXIT_RUNTIME;
+        }
+        return match effective(key, parsed.runtime_flag.as_deref()) {
+            Some((value, source)) if parsed.show_source => {
+                println!("{value}\t{source}");
+                0
+            }
+            Some((value, _)) => {
+                println!("{value}");
+                0
+            }
+            None => EXIT_RUNTIME,
+        };
+    }
+
+    if let Some(profile) = config::active_profile() {
+        println!("# profile: {profile}");
+    }
+    for setting in SETTINGS {
+        match effective(setting.key, parsed.runtime_flag.as_deref()) {
+            Some((value, source)) => println!("{} = {value:?}  # {source}", setting.key),
+            None => println!("# {} (unset)", setting.key),
+        }
+    }
+    0
+}
+
+fn run_config_set(profile: Option<&str>, key: &str, value: &str) -> i32 {
+    if config::find_setting(key).is_none() {
+        eprintln!("error: unknown config key: {key}");
+        eprintln!("hint: run '{PRIMARY_COMMAND_NAME} config list' to see supported keys");
+        return EXIT_RUNTIME;
+    }
+
+    if key == "runtime"
+        && let Err(err) = parse_runtime_value(value)
+    {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    match config::set_value(profile, key, value) {
+        Ok(path) => {
+            println!(
+                "{}: [{}] {key} = {value:?}",
+                path.display(),
+                config::section_name(profile)
+            );
+            0
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            EXIT_RUNTIME
+        }
+    }
+}
+
+/// `--runtime` is the only setting that also has a global flag.
+fn effective(key: &str, runtime_flag: Option<&str>) -> Option<(String, Source)> {
+    if key == "runtime"
+        && let Some(runtime) = runtime_flag
+    {
+        return Some((runtime.to_string(), Source::Flag("--runtime")));
+    }
+    config::lookup(key)
+}
+
+fn parse_config_args(args: &[OsString]) -> Result<ParsedConfig, String> {
+    let mut parsed = ParsedConfig::default();
+
+    let mut idx = 0usize;
+    while idx < args.len() {
+        let current = args[idx].to_string_lossy().into_owned();
+        match current.as_str() {
+            "-h" | "--help" => parsed.show_help = true,
+            "--show-source" => parsed.show_source = true,
+            "--runtime" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(String::from("missing value for --runtime"));
+                }
+                parsed.runtime_flag = Some(args[idx].to_string_lossy().trim().to_string());
+            }
+            _ if current.starts_with("--runtime=") => {
+                parsed.runtime_flag = Some(current["--runtime=".len()..].trim().to_string());
+            }
+            "help" if parsed.action.is_none() => parsed.show_help = true,
+            "get" if parsed.action.is_none() => parsed.action = Some(ConfigAction::Get),
+            "set" if parsed.action.is_none() => parsed.action = Some(ConfigAction::Set),
+            "list" | "ls" if parsed.action.is_none() => parsed.action = Some(ConfigAction::List),
+            "path" if parsed.action.is_none() => parsed.action = Some(ConfigAction::Path),
+            _ if parsed.action.is_none() => {
+                return Err(format!(
+                    "unknown config subcommand: {current} (expected: get|set|list|path)"
+                ));
+            }
+            _ if current.starts_with('-') && parsed.action != Some(ConfigAction::Set) => {
+                return Err(format!("unknown option: {current}"));
+            }
+            _ => parsed.positionals.push(current),
+        }
+        idx += 1;
+    }
+
+    Ok(parsed)
+}
+
+fn print_config_usage() {
+    eprintln!("usage:");
+    eprintln!("  {PRIMARY_COMMAND_NAME} [--profile <name>] config get [--show-source] <key>");
+    eprintln!("  {PRIMARY_COMMAND_NAME} [--profile <name>] config set <key> <value>");
+    eprintln!("  {PRIMARY_COMMAND_NAME} [--profile <name>] config list");
+    eprintln!("  {PRIMARY_COMMAND_NAME} config path");
+}
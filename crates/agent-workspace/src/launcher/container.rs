@@ -1,22 +1,108 @@
-use std::ffi::OsString;
+use std::ffi::OsStr;
+use std::fs;
 use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::time::{SystemTime, UNIX_EPOCH};
-use std::{fs, path::PathBuf};
 
 use crate::EXIT_RUNTIME;
 
+use super::archive::{CONTAINER_LAYOUT, SnapshotManifest, part_path};
+use super::backend::{
+    CLONE_MODE_SCRIPT, COPY_IN_SCRIPT, COPY_OUT_SCRIPT, CloneOptions, CopyRequest,
+    CreatedWorkspace, DETACHED_TUNNEL_SCRIPT, DoctorCheck, ExecRequest, FORWARD_BRIDGE_SCRIPT,
+    IdleCheck, LIST_GIT_REPOS_SCRIPT, NewWorkspace, RESET_REPO_SCRIPT, RemoveOptions, RepoSlot,
+    RestoreOptions, RsyncDirection, RsyncTransfer, TUNNEL_CONTROL_SCRIPT, TunnelAction,
+    TunnelOutcome, TunnelRequest, UpgradeOptions, UpgradeOutcome, WORKSPACE_STATUS_SCRIPT,
+    WorkspaceBackend, WorkspaceDetails, WorkspaceEntry, run_rsync, stream_copy_in, stream_copy_out,
+};
+use super::engine_api::{ContainerSummary, EngineApi, ExecError, ExecOutput};
+use super::host::parse_du_kib;
+use super::json::{JsonValue, parse_json};
 use super::{
-    PRIMARY_COMMAND_NAME, RepoSpec, command_exists, confirm_or_abort, default_gpg_signing_key,
-    json_escape, normalize_workspace_name_for_create, parse_repo_spec, resolve_codex_auth_file,
-    resolve_codex_profile_auth_files, slugify_name, trimmed_nonempty, workspace_prefixes,
-    workspace_resolution_candidates,
+    RepoSpec, command_exists, trimmed_nonempty, workspace_prefixes, workspace_resolution_candidates,
 };
 
 const DEFAULT_CONTAINER_IMAGE: &str = "graysurf/agent-env:latest";
 const WORKSPACE_LABEL: &str = "agent-kit.workspace=1";
-const DEFAULT_REF: &str = "origin/main";
+/// Label holding the `create --idle-timeout` threshold in seconds.
+const IDLE_TIMEOUT_LABEL: &str = "agent-kit.idle-timeout";
+/// `create` options kept as labels so `run_workspace_container` can re-apply them on rename,
+/// clone, and restore. List values are comma-separated; env values stay in the container env.
+const ENV_LABEL: &str = "agent-kit.env";
+const MOUNTS_LABEL: &str = "agent-kit.mounts";
+const PUBLISH_LABEL: &str = "agent-kit.publish";
+const CPUS_LABEL: &str = "agent-kit.cpus";
+const MEMORY_LABEL: &str = "agent-kit.memory";
+/// Image reference `upgrade` tracks, and the digest it replaced.
+const IMAGE_LABEL: &str = "agent-kit.image";
+const PREVIOUS_IMAGE_LABEL: &str = "agent-kit.previous-image";
+/// Per-user activity marker prefix on `/dev/shm` (cleared by a restart); holds a unix time.
+/// Each user writes its own file, since sticky `/dev/shm` may refuse writes to another user's.
+const ACTIVITY_MARKER_PREFIX: &str = "/dev/shm/agent-workspace-last-active";
+
+/// Reports `busy` while any exec session or tunnel runs (and records that activity), otherwise
+/// `idle <seconds>` since the latest activity: container start, the end of a launcher `exec`,
+/// or a probe that found the container busy.
+const IDLE_PROBE_SCRIPT: &str = r#"
+set -uo pipefail
+
+marker_prefix="${1:?missing marker prefix}"
+self=$$
+
+busy=0
+for dir in /proc/[0-9]*; do
+  pid="${dir#/proc/}"
+  [[ "$pid" == 1 || "$pid" == "$self" ]] && continue
+  read -r stat 2>/dev/null <"$dir/stat" || continue
+  comm="${stat#*(}"
+  comm="${comm%)*}"
+  read -r _ ppid _ <<<"${stat##*) }"
+  [[ "$ppid" == "$self" ]] && continue
+  # The entrypoint's `sleep infinity` is not a session.
+  [[ "$ppid" == 1 && "$comm" == sleep ]] && continue
+  busy=1
+  break
+done
+
+now="$(date +%s)"
+if [[ "$busy" == 1 ]]; then
+  printf '%s\n' "$now" >"$marker_prefix.$(id -u)" 2>/dev/null || true
+  echo busy
+  exit 0
+fi
+
+# PID 1 start time: boot time plus its start offset (field 22 of /proc/1/stat) in clock ticks.
+read -r stat </proc/1/stat
+read -r -a fields <<<"${stat##*) }"
+boot="$(awk '/^btime/ { print $2 }' /proc/stat)"
+ticks="$(getconf CLK_TCK 2>/dev/null || echo 100)"
+last=$((boot + fields[19] / ticks))
+
+for marker in "$marker_prefix".*; do
+  [[ -s "$marker" ]] || continue
+  read -r touched <"$marker" || continue
+  [[ "$touched" =~ ^[0-9]+$ ]] && ((touched > last)) && last="$touched"
+done
+idle=$((now - last))
+echo "idle $((idle < 0 ? 0 : idle))"
+"#;
+
+/// Auth files written by the `auth` subcommand, as seen inside the container.
+const CONTAINER_AUTH_FILES: [&str; 4] = [
+    "/home/agent/.agents/auth/github.env",
+    "/home/agent/.agents/auth/gpg-key.txt",
+    "/home/agent/.codex/auth.json",
+    "/home/agent/.agents/auth.json",
+];
+/// Snapshot parts in `volume_names` order, with the auth material each one skips by default.
+const SNAPSHOT_PARTS: [(&str, &[&str]); 3] = [
+    ("work", &[]),
+    ("home", &["./.codex/*"]),
+    ("agent-home", &["./auth/*", "./auth.json"]),
+];
 const CODE_TUNNEL_LOG_PATH: &str = "/home/agent/.agents-env/logs/code-tunnel.log";
+const CODE_TUNNEL_STATE_PATH: &str = "/home/agent/.agents-env/logs/code-tunnel.state";
 const RSYNC_RSH_WRAPPER_SCRIPT: &str = r#"#!/bin/sh
 set -eu
 
@@ -41,91 +127,7 @@ while [ $# -gt 0 ]; do
   esac
 done
 
-exec docker exec -u "$user" -i "$container" "$@"
-"#;
-
-const RESET_REPO_SCRIPT: &str = r#"
-set -euo pipefail
-
-repo_dir="${1:?missing repo_dir}"
-ref="${2:-origin/main}"
-
-if [[ ! -e "$repo_dir/.git" ]]; then
-  echo "error: not a git repo: $repo_dir" >&2
-  exit 1
-fi
-
-cd "$repo_dir"
-
-remote="${ref%%/*}"
-branch="${ref#*/}"
-if [[ "$remote" == "$ref" || -z "$remote" || -z "$branch" ]]; then
-  echo "error: invalid ref (expected remote/branch): $ref" >&2
-  exit 2
-fi
-
-git fetch --prune -- "$remote" >/dev/null 2>&1 || git fetch --prune -- "$remote"
-
-resolved="$remote/$branch"
-if ! git show-ref --verify --quiet "refs/remotes/$resolved"; then
-  default_ref="$(git symbolic-ref -q --short "refs/remotes/$remote/HEAD" 2>/dev/null || true)"
-  if [[ -n "$default_ref" ]] && git show-ref --verify --quiet "refs/remotes/$default_ref"; then
-    echo "warn: $resolved not found; using $default_ref (from $remote/HEAD)" >&2
-    resolved="$default_ref"
-  elif git show-ref --verify --quiet "refs/remotes/$remote/master"; then
-    echo "warn: $resolved not found; using $remote/master" >&2
-    resolved="$remote/master"
-  else
-    echo "error: remote branch not found: $resolved" >&2
-    exit 1
-  fi
-fi
-
-target_branch="${resolved#*/}"
-echo "+ reset $repo_dir -> $resolved"
-
-if git show-ref --verify --quiet "refs/heads/$target_branch"; then
-  git checkout --force "$target_branch" >/dev/null 2>&1 || {
-    git clean -fd >/dev/null 2>&1 || true
-    git checkout --force "$target_branch"
-  }
-else
-  git checkout --force -B "$target_branch" "$resolved" >/dev/null 2>&1 || {
-    git clean -fd >/dev/null 2>&1 || true
-    git checkout --force -B "$target_branch" "$resolved"
-  }
-fi
-
-if command -v git-reset-remote >/dev/null 2>&1; then
-  git-reset-remote --ref "$resolved" --no-fetch --clean --yes
-else
-  git reset --hard "$resolved"
-  git clean -fd
-  echo "✅ Done. '$target_branch' now matches '$resolved'."
-fi
-"#;
-
-const LIST_GIT_REPOS_SCRIPT: &str = r#"
-set -euo pipefail
-
-root="${1:?missing root}"
-depth="${2:?missing depth}"
-
-if ! [[ "$depth" =~ ^[0-9]+$ ]] || [[ "$depth" -le 0 ]]; then
-  echo "error: --depth must be a positive integer (got: $depth)" >&2
-  exit 2
-fi
-
-if [[ ! -d "$root" ]]; then
-  exit 0
-fi
-
-git_depth=$((depth + 1))
-find -L "$root" -maxdepth "$git_depth" -mindepth 2 \( -type d -o -type f \) -name .git -print0 2>/dev/null \
-  | while IFS= read -r -d '' git_entry; do
-      printf '%s\n' "${git_entry%/.git}"
-    done \
-  | sort -u
+exec "${AGENT_WORKSPACE_RSYNC_ENGINE:-docker}" exec -u "$user" -i "$container" "$@"
 "#;
 
 const CLONE_SCRIPT: &str = r#"
@@ -133,6 +135,9 @@ set -euo pipefail
 repo_url="$1"
 dest="$2"
 ref="${3:-}"
+shift 3
+# Remaining args are `git clone` flags for a shallow, partial, single-branch, or sparse clone.
+clone_flags=("$@")
 
 if [[ -d "$dest/.git" ]]; then
   exit 0
@@ -158,27 +163,30 @@ esac
 EOS
     chmod 700 "$askpass"
     if [[ -n "$ref" ]]; then
-      GIT_TERMINAL_PROMPT=0 GIT_ASKPASS="$askpass" git clone "$repo_url" "$dest"
+      GIT_TERMINAL_PROMPT=0 GIT_ASKPASS="$askpass" git clone ${clone_flags[@]+"${clone_flags[@]}"} "$repo_url" "$dest"
     else
-      GIT_TERMINAL_PROMPT=0 GIT_ASKPASS="$askpass" git clone --branch main "$repo_url" "$dest"
+      GIT_TERMINAL_PROMPT=0 GIT_ASKPASS="$askpass" git clone ${clone_flags[@]+"${clone_flags[@]}"} --branch main "$repo_url" "$dest"
     fi
     rm -f "$askpass"
   else
     if [[ -n "$ref" ]]; then
-      GIT_TERMINAL_PROMPT=0 git clone "$repo_url" "$dest"
+      GIT_TERMINAL_PROMPT=0 git clone ${clone_flags[@]+"${clone_flags[@]}"} "$repo_url" "$dest"
     else
-      GIT_TERMINAL_PROMPT=0 git clone --branch main "$repo_url" "$dest"
+      GIT_TERMINAL_PROMPT=0 git clone ${clone_flags[@]+"${clone_flags[@]}"} --branch main "$repo_url" "$dest"
     fi
   fi
 }
 
 clone_repo
 
-git -C "$dest" config --unset-all remote.origin.fetch || true
-git -C "$dest" config --add remote.origin.fetch "+refs/heads/*:refs/remotes/origin/*"
-if [[ -n "${GH_TOKEN:-${GITHUB_TOKEN:-}}" ]]; then
-  askpass="/tmp/agent-workspace-git-askpass"
-  cat >"$askpass" <<EOS
+# A --depth or --single-branch clone keeps its narrow refspec instead of fetching every branch.
+narrow_refspec="$(git -C "$dest" config --get-all remote.origin.fetch | grep -vF '*' || true)"
+if [[ -z "$narrow_refspec" ]]; then
+  git -C "$dest" config --unset-all remote.origin.fetch || true
+  git -C "$dest" config --add remote.origin.fetch "+refs/heads/*:refs/remotes/origin/*"
+  if [[ -n "${GH_TOKEN:-${GITHUB_TOKEN:-}}" ]]; then
+    askpass="/tmp/agent-workspace-git-askpass"
+    cat >"$askpass" <<EOS
 #!/usr/bin/env bash
 case "${1-}" in
   *Username*) echo "x-access-token" ;;
@@ -186,11 +194,12 @@ case "${1-}" in
   *) echo "" ;;
 esac
 EOS
-  chmod 700 "$askpass"
-  GIT_TERMINAL_PROMPT=0 GIT_ASKPASS="$askpass" git -C "$dest" fetch --prune origin "+refs/heads/*:refs/remotes/origin/*"
-  rm -f "$askpass"
-else
-  GIT_TERMINAL_PROMPT=0 git -C "$dest" fetch --prune origin "+refs/heads/*:refs/remotes/origin/*"
+    chmod 700 "$askpass"
+    GIT_TERMINAL_PROMPT=0 GIT_ASKPASS="$askpass" git -C "$dest" fetch --prune origin "+refs/heads/*:refs/remotes/origin/*"
+    rm -f "$askpass"
+  else
+    GIT_TERMINAL_PROMPT=0 git -C "$dest" fetch --prune origin "+refs/heads/*:refs/remotes/origin/*"
+  fi
 fi
 
 if [[ -n "$ref" ]]; then
@@ -266,1616 +275,965 @@ if command -v brew >/dev/null 2>&1; then
 fi
 "#;
 
-#[derive(Debug, Default, Clone)]
-struct ParsedCreate {
-    show_help: bool,
-    no_extras: bool,
-    no_work_repos: bool,
-    private_repo: Option<String>,
-    workspace_name: Option<String>,
-    primary_repo: Option<String>,
-    extra_repos: Vec<String>,
-    ignored_options: Vec<String>,
-    image: Option<String>,
-    no_pull: bool,
-    refspec: Option<String>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ContainerEngine {
+    Docker,
+    Podman,
 }
 
-#[derive(Debug, Default, Clone)]
-struct ParsedRm {
-    show_help: bool,
-    all: bool,
-    yes: bool,
-    keep_volumes: bool,
-    workspace: Option<String>,
-}
+impl ContainerEngine {
+    pub(super) fn binary(self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+        }
+    }
 
-#[derive(Debug, Default, Clone)]
-struct ParsedAuth {
-    show_help: bool,
-    provider: Option<String>,
-    workspace: Option<String>,
-    profile: Option<String>,
-    host: Option<String>,
-    key: Option<String>,
-}
+    fn runtime_name(self) -> &'static str {
+        match self {
+            Self::Docker => "container",
+            Self::Podman => "podman",
+        }
+    }
 
-#[derive(Debug, Default, Clone)]
-struct ParsedResetRepo {
-    show_help: bool,
-    workspace: Option<String>,
-    repo_dir: Option<String>,
-    yes: bool,
-    refspec: String,
-}
+    fn command(self) -> Command {
+        Command::new(self.binary())
+    }
 
-#[derive(Debug, Clone)]
-struct ParsedResetWorkRepos {
-    show_help: bool,
-    workspace: Option<String>,
-    yes: bool,
-    depth: u32,
-    root: String,
-    refspec: String,
-}
+    /// Podman resolves short image names through `registries.conf` and may prompt,
+    /// so unqualified Docker Hub references are pinned to `docker.io`.
+    pub(super) fn qualify_image(self, image: &str) -> String {
+        if self == Self::Docker {
+            return image.to_string();
+        }
+
+        let first = image.split('/').next().unwrap_or_default();
+        let has_registry = image.contains('/')
+            && (first.contains('.') || first.contains(':') || first == "localhost");
+        if has_registry {
+            image.to_string()
+        } else if image.contains('/') {
+            format!("docker.io/{image}")
+        } else {
+            format!("docker.io/library/{image}")
+        }
+    }
 
-impl Default for ParsedResetWorkRepos {
-    fn default() -> Self {
-        Self {
-            show_help: false,
-            workspace: None,
-            yes: false,
-            depth: 4,
-            root: String::from("/work"),
-            refspec: String::from(DEFAULT_REF),
+    /// Rootless Podman maps the invoking user to container root unless asked to keep
+    /// the uid, which would leave bind-mounted and volume files owned by a subuid.
+    fn default_userns(self) -> Option<String> {
+        if self == Self::Docker {
+            return None;
+        }
+
+        // An empty env value explicitly disables the userns default.
+        if let Ok(value) = std::env::var("AGENT_WORKSPACE_PODMAN_USERNS") {
+            return trimmed_nonempty(&value);
+        }
+        if let Some(value) = crate::config::value("podman_userns") {
+            return trimmed_nonempty(&value);
+        }
+
+        if current_uid() == Some(0) {
+            None
+        } else {
+            Some(String::from("keep-id"))
         }
     }
 }
 
-#[derive(Debug, Default, Clone)]
-struct ParsedResetSimple {
-    show_help: bool,
-    workspace: Option<String>,
-    yes: bool,
-    refspec: String,
+#[cfg(unix)]
+fn current_uid() -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+
+    fs::metadata("/proc/self").ok().map(|meta| meta.uid())
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum RsyncDirection {
-    Push,
-    Pull,
+#[cfg(not(unix))]
+fn current_uid() -> Option<u32> {
+    None
 }
 
-#[derive(Debug, Clone)]
-struct ParsedRsync {
-    show_help: bool,
-    direction: Option<RsyncDirection>,
-    workspace: Option<String>,
-    src: Option<String>,
-    dest: Option<String>,
-    user: String,
-    delete: bool,
-    dry_run: bool,
-    rsync_args: Vec<String>,
+/// Container workspaces: one long-running container (plus named volumes) per workspace.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct ContainerBackend {
+    pub(super) engine: ContainerEngine,
 }
 
-impl Default for ParsedRsync {
-    fn default() -> Self {
-        Self {
-            show_help: false,
-            direction: None,
-            workspace: None,
-            src: None,
-            dest: None,
-            user: String::from("agent"),
-            delete: false,
-            dry_run: false,
-            rsync_args: Vec::new(),
+impl WorkspaceBackend for ContainerBackend {
+    fn runtime_name(&self) -> &'static str {
+        self.engine.runtime_name()
+    }
+
+    fn ensure_available(&self) -> Result<(), String> {
+        if engine_api(self.engine).is_some() || command_exists(self.engine.binary()) {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} command not found in PATH",
+                self.engine.binary()
+            ))
         }
     }
-}
 
-pub(super) fn dispatch(subcommand: &str, args: &[OsString]) -> i32 {
-    match subcommand {
-        "create" => run_create(args),
-        "rsync" => run_rsync(args),
-        "ls" => run_ls(args),
-        "exec" => run_exec(args),
-        "rm" => run_rm(args),
-        "tunnel" => run_tunnel(args),
-        "auth" => run_auth(args),
-        "reset" => run_reset(args),
-        _ => {
-            eprintln!("error: unknown subcommand: {subcommand}");
-            EXIT_RUNTIME
+    fn unavailable_hint(&self) -> Option<&'static str> {
+        if command_exists(self.engine.binary()) {
+            return None;
         }
+
+        Some(match self.engine {
+            ContainerEngine::Docker => {
+                "install/start Docker or retry with '--runtime podman' / '--runtime host' (or AGENT_WORKSPACE_RUNTIME=host)"
+            }
+            ContainerEngine::Podman => {
+                "install Podman or retry with '--runtime container' / '--runtime host' (or AGENT_WORKSPACE_RUNTIME=host)"
+            }
+        })
     }
-}
 
-fn run_create(args: &[OsString]) -> i32 {
-    if !ensure_docker_available() {
-        return EXIT_RUNTIME;
+    fn doctor_checks(&self) -> Vec<DoctorCheck> {
+        let binary = self.engine.binary();
+        let version = match engine_server_version(self.engine) {
+            Ok(version) => version,
+            Err(err) => {
+                let hint = self.unavailable_hint().unwrap_or(match self.engine {
+                    ContainerEngine::Docker => {
+                        "start the Docker daemon (Docker Desktop or 'systemctl start docker') and check DOCKER_HOST / 'docker context ls'"
+                    }
+                    ContainerEngine::Podman => {
+                        "run 'podman info' to diagnose; on macOS start the VM with 'podman machine start'"
+                    }
+                });
+                return vec![DoctorCheck::fail(
+                    binary,
+                    format!("daemon not reachable: {err}"),
+                    hint,
+                )];
+            }
+        };
+        let mut checks = vec![DoctorCheck::ok(
+            binary,
+            format!("daemon reachable (version {version})"),
+        )];
+
+        let image = crate::config::value("image")
+            .and_then(|value| trimmed_nonempty(&value))
+            .unwrap_or_else(|| String::from(DEFAULT_CONTAINER_IMAGE));
+        let image = self.engine.qualify_image(&image);
+        checks.push(match inspect_json(self.engine, "image", &image) {
+            Ok(Some(_)) => DoctorCheck::ok("image", format!("{image} is present")),
+            Ok(None) => DoctorCheck::warn(
+                "image",
+                format!("{image} is not pulled yet"),
+                format!("run '{binary} pull {image}' (create pulls it on first use)"),
+            ),
+            Err(err) => DoctorCheck::fail(
+                "image",
+                err,
+                format!("run '{binary} image inspect {image}' to diagnose"),
+            ),
+        });
+        checks
     }
 
-    let parsed = match parse_create_args(args) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            eprintln!("error: {err}");
-            print_create_usage();
-            return EXIT_RUNTIME;
-        }
-    };
+    fn list(&self) -> Result<Vec<WorkspaceEntry>, String> {
+        Ok(list_workspace_containers(self.engine)?
+            .into_iter()
+            .map(|container| WorkspaceEntry {
+                name: container.name,
+                path: None,
+                repo: container.repo,
+                state: container.state.as_deref().map(workspace_state),
+                created_unix: container.created_at.and_then(|value| value.parse().ok()),
+                image: container.image,
+            })
+            .collect())
+    }
 
-    if parsed.show_help {
-        print_create_usage();
-        return 0;
+    fn resolve(&self, name: &str) -> Result<Option<String>, String> {
+        resolve_container(self.engine, name)
     }
 
-    if !parsed.ignored_options.is_empty() {
-        eprintln!(
-            "warn: ignoring unsupported create options in container mode: {}",
-            parsed.ignored_options.join(" ")
-        );
+    fn ensure_ready(&self, workspace: &str) -> Result<(), String> {
+        ensure_container_running(self.engine, workspace)
     }
 
-    let default_host = std::env::var("GITHUB_HOST").unwrap_or_else(|_| String::from("github.com"));
+    fn is_ready(&self, workspace: &str) -> bool {
+        container_running(self.engine, workspace)
+    }
 
-    let primary_spec = if let Some(primary_repo) = parsed.primary_repo.as_deref() {
-        match parse_repo_spec(primary_repo, &default_host) {
-            Some(spec) => Some(spec),
-            None => {
-                eprintln!(
-                    "error: invalid primary repo (expected OWNER/REPO or URL): {primary_repo}"
-                );
-                return EXIT_RUNTIME;
-            }
+    fn create_workspace(&self, request: &NewWorkspace<'_>) -> Result<CreatedWorkspace, String> {
+        let container = normalize_container_name(request.name);
+        if container_exists(self.engine, &container) {
+            return Err(format!("workspace already exists: {container}"));
         }
-    } else {
-        None
-    };
 
-    let mut workspace_name = parsed
-        .workspace_name
-        .clone()
-        .or_else(|| {
-            primary_spec
-                .as_ref()
-                .map(|spec| format!("ws-{}", slugify_name(&spec.repo)))
+        let image = request
+            .image
+            .map(str::to_string)
+            .or_else(|| crate::config::value("image"))
+            .and_then(|v| trimmed_nonempty(&v))
+            .unwrap_or_else(|| String::from(DEFAULT_CONTAINER_IMAGE));
+        let image = self.engine.qualify_image(&image);
+
+        ensure_image(self.engine, &image, request.pull)?;
+        create_workspace_container(self.engine, &container, &image, request)?;
+        sync_container_baseline(self.engine, &container)
+            .map_err(|err| format!("failed to sync container baseline: {err}"))?;
+
+        Ok(CreatedWorkspace {
+            name: container,
+            path: String::from("/work"),
         })
-        .unwrap_or_else(generate_workspace_name);
-    workspace_name = normalize_workspace_name_for_create(&workspace_name);
-    if workspace_name.is_empty() {
-        workspace_name = generate_workspace_name();
     }
 
-    let container = normalize_container_name(&workspace_name);
-    if container_exists(&container) {
-        eprintln!("error: workspace already exists: {container}");
-        return EXIT_RUNTIME;
+    fn workspace_dir(&self, _workspace: &str) -> Result<String, String> {
+        Ok(String::from("/work"))
     }
 
-    let image = parsed
-        .image
-        .clone()
-        .or_else(|| std::env::var("AGENT_ENV_IMAGE").ok())
-        .or_else(|| std::env::var("CODEX_ENV_IMAGE").ok())
-        .and_then(|v| trimmed_nonempty(&v))
-        .unwrap_or_else(|| String::from(DEFAULT_CONTAINER_IMAGE));
-
-    if let Err(err) = ensure_image(&image, !parsed.no_pull) {
-        eprintln!("error: {err}");
-        return EXIT_RUNTIME;
+    fn clone_repo(
+        &self,
+        workspace: &str,
+        repo: &RepoSpec,
+        slot: RepoSlot,
+        options: &CloneOptions<'_>,
+    ) -> Result<String, String> {
+        let destination = match slot {
+            RepoSlot::Work => format!("/work/{}/{}", repo.owner, repo.repo),
+            RepoSlot::Private => format!("/work/private/{}/{}", repo.owner, repo.repo),
+        };
+        clone_repo_into_container(self.engine, workspace, repo, &destination, options)?;
+        if options.has_clone_mode() {
+            let args = options.clone_mode_args(&destination);
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            exec_user_script(self.engine, workspace, CLONE_MODE_SCRIPT, &args, false)
+                .map_err(|err| format!("clone mode setup failed for {}: {err}", repo.owner_repo))?;
+        }
+        Ok(destination)
     }
 
-    if let Err(err) = create_workspace_container(&container, &image, primary_spec.as_ref()) {
-        eprintln!("error: {err}");
-        return EXIT_RUNTIME;
+    fn supports_images(&self) -> bool {
+        true
     }
 
-    if let Err(err) = sync_container_baseline(&container) {
-        eprintln!("error: failed to sync container baseline: {err}");
-        return EXIT_RUNTIME;
+    fn remove(&self, workspace: &str, options: &RemoveOptions) -> Result<(), String> {
+        engine_status(self.engine, &["rm", "-f", workspace])?;
+        if !options.keep_volumes {
+            remove_workspace_volumes(self.engine, workspace);
+        }
+        Ok(())
     }
 
-    let mut repo_path = String::from("/work");
+    fn clone_workspace(&self, source: &str, new_name: &str) -> Result<CreatedWorkspace, String> {
+        let target = normalize_container_name(new_name);
+        ensure_workspace_name_free(self.engine, &target)?;
 
-    if !parsed.no_work_repos
-        && let Some(spec) = primary_spec.as_ref()
-    {
-        repo_path = format!("/work/{}/{}", spec.owner, spec.repo);
+        // The source keeps running; its volumes are copied as they are right now.
+        let info = inspect_json(self.engine, "container", source)?
+            .ok_or_else(|| format!("workspace not found: {source}"))?;
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0)
+            .to_string();
         if let Err(err) =
-            clone_repo_into_container(&container, spec, &repo_path, parsed.refspec.as_deref())
+            recreate_workspace_container(self.engine, &info, source, &target, Some(&created_at))
         {
-            eprintln!(
-                "error: failed to clone primary repo {}: {err}",
-                spec.owner_repo
-            );
-            return EXIT_RUNTIME;
-        }
-    }
-
-    if !parsed.no_extras {
-        if let Some(private_repo_raw) = parsed.private_repo.as_deref() {
-            if let Some(spec) = parse_repo_spec(private_repo_raw, &default_host) {
-                let destination = format!("/work/private/{}/{}", spec.owner, spec.repo);
-                if let Err(err) = clone_repo_into_container(
-                    &container,
-                    &spec,
-                    &destination,
-                    parsed.refspec.as_deref(),
-                ) {
-                    eprintln!(
-                        "warn: failed to clone private repo {}: {err}",
-                        spec.owner_repo
-                    );
-                }
-            } else {
-                eprintln!(
-                    "warn: invalid private repo (expected OWNER/REPO or URL): {private_repo_raw}"
-                );
-            }
+            let _ = engine_output(self.engine, &["rm", "-f", &target]);
+            remove_workspace_volumes(self.engine, &target);
+            return Err(err);
         }
 
-        for extra_repo_raw in &parsed.extra_repos {
-            if let Some(spec) = parse_repo_spec(extra_repo_raw, &default_host) {
-                let destination = format!("/work/{}/{}", spec.owner, spec.repo);
-                if let Err(err) = clone_repo_into_container(
-                    &container,
-                    &spec,
-                    &destination,
-                    parsed.refspec.as_deref(),
-                ) {
-                    eprintln!(
-                        "warn: failed to clone extra repo {}: {err}",
-                        spec.owner_repo
-                    );
+        Ok(CreatedWorkspace {
+            name: target,
+            path: String::from("/work"),
+        })
+    }
+
+    fn snapshot(
+        &self,
+        workspace: &str,
+        staging: &Path,
+        include_secrets: bool,
+    ) -> Result<SnapshotManifest, String> {
+        let info = inspect_json(self.engine, "container", workspace)?
+            .ok_or_else(|| format!("workspace not found: {workspace}"))?;
+        let spec = container_spec(self.engine, &info, workspace)?;
+
+        let (work, home, agent_home) = volume_names(workspace);
+        for ((part, excludes), volume) in SNAPSHOT_PARTS.iter().zip([work, home, agent_home]) {
+            let mut script = String::from("tar -C /from -cf -");
+            if !include_secrets {
+                for pattern in excludes.iter() {
+                    script.push_str(&format!(" --exclude='{pattern}'"));
                 }
-            } else {
-                eprintln!("warn: invalid repo (expected OWNER/REPO or URL): {extra_repo_raw}");
             }
-        }
+            script.push_str(" .");
+
+            let tarball = part_path(staging, part);
+            let file = fs::File::create(&tarball)
+                .map_err(|err| format!("failed to create {}: {err}", tarball.display()))?;
+            run_volume_helper(
+                self.engine,
+                &spec.image_id,
+                spec.userns.as_deref(),
+                &[format!("{volume}:/from:ro")],
+                &script,
+                Stdio::null(),
+                Stdio::from(file),
+            )
+            .map_err(|err| format!("failed to archive volume {volume}: {err}"))?;
+        }
+
+        Ok(SnapshotManifest {
+            layout: String::from(CONTAINER_LAYOUT),
+            name: workspace.to_string(),
+            image: Some(spec.image),
+            env: if include_secrets {
+                spec.env
+            } else {
+                redact_create_env(spec.env, &spec.labels)
+            },
+            labels: spec.labels,
+            parts: SNAPSHOT_PARTS
+                .iter()
+                .map(|(part, _)| part.to_string())
+                .collect(),
+            secrets: include_secrets,
+        })
     }
 
-    println!("workspace: {container}");
-    println!("path: {repo_path}");
-    0
-}
-
-fn run_ls(args: &[OsString]) -> i32 {
-    if !ensure_docker_available() {
-        return EXIT_RUNTIME;
+    fn restore(
+        &self,
+        manifest: &SnapshotManifest,
+        staging: &Path,
+        new_name: &str,
+        options: &RestoreOptions<'_>,
+    ) -> Result<CreatedWorkspace, String> {
+        manifest.expect_layout(CONTAINER_LAYOUT, self.runtime_name())?;
+        let image = manifest
+            .image
+            .as_deref()
+            .ok_or_else(|| String::from("snapshot manifest has no image"))?;
+        let image = self.engine.qualify_image(image);
+        let target = normalize_container_name(new_name);
+        ensure_workspace_name_free(self.engine, &target)?;
+        ensure_image(self.engine, &image, true)?;
+        let (labels, env) = restored_options(manifest, options);
+
+        let userns = self.engine.default_userns();
+        let restored =
+            restore_snapshot_volumes(self.engine, staging, &target, &image, userns.as_deref())
+                .and_then(|()| {
+                    run_workspace_container(
+                        self.engine,
+                        &target,
+                        &image,
+                        &labels,
+                        &env,
+                        userns.as_deref(),
+                    )
+                });
+        if let Err(err) = restored {
+            let _ = engine_output(self.engine, &["rm", "-f", &target]);
+            remove_workspace_volumes(self.engine, &target);
+            return Err(err);
+        }
+
+        Ok(CreatedWorkspace {
+            name: target,
+            path: String::from("/work"),
+        })
     }
 
-    let parsed = match super::ls::parse_ls_args(args) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            eprintln!("error: {err}");
-            print_ls_usage();
-            return EXIT_RUNTIME;
+    fn rename(&self, workspace: &str, new_name: &str) -> Result<String, String> {
+        let target = normalize_container_name(new_name);
+        if target == workspace {
+            return Err(format!("workspace is already named {target}"));
         }
-    };
+        ensure_workspace_name_free(self.engine, &target)?;
 
-    if parsed.show_help {
-        print_ls_usage();
-        return 0;
-    }
-
-    let mut workspaces = match list_workspace_containers() {
-        Ok(items) => items,
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
+        let info = inspect_json(self.engine, "container", workspace)?
+            .ok_or_else(|| format!("workspace not found: {workspace}"))?;
+        let was_running = info
+            .pointer(&["State", "Running"])
+            .and_then(JsonValue::as_bool)
+            == Some(true);
+        if was_running {
+            self.stop(workspace)?;
         }
-    };
-    workspaces.sort();
 
-    if parsed.json {
-        let mut out = String::from("{\"runtime\":\"container\",\"workspaces\":[");
-        for (idx, name) in workspaces.iter().enumerate() {
-            if idx > 0 {
-                out.push(',');
+        if let Err(err) = recreate_workspace_container(self.engine, &info, workspace, &target, None)
+        {
+            remove_workspace_volumes(self.engine, &target);
+            if was_running && let Err(restart_err) = self.start(workspace) {
+                eprintln!("warn: failed to restart {workspace}: {restart_err}");
             }
-            out.push_str(&format!("{{\"name\":\"{}\"}}", json_escape(name)));
+            return Err(err);
         }
-        out.push_str("]}");
-        println!("{out}");
-    } else {
-        for workspace in workspaces {
-            println!("{workspace}");
+        if !was_running && let Err(err) = self.stop(&target) {
+            eprintln!("warn: failed to stop {target}: {err}");
         }
-    }
-
-    0
-}
-
-fn run_rsync(args: &[OsString]) -> i32 {
-    if !ensure_docker_available() {
-        return EXIT_RUNTIME;
-    }
 
-    let parsed = match parse_rsync_args(args) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            eprintln!("error: {err}");
-            print_rsync_usage();
-            return EXIT_RUNTIME;
+        // The new workspace is complete; a leftover old container must not turn that into an error.
+        if let Err(err) = engine_output(self.engine, &["rm", "-f", workspace]) {
+            eprintln!(
+                "warn: failed to remove the old container {workspace}: {err}; remove it with '{} rm -f {workspace}'",
+                self.engine.binary()
+            );
+            return Ok(target);
         }
-    };
-
-    if parsed.show_help {
-        print_rsync_usage();
-        return 0;
+        remove_workspace_volumes(self.engine, workspace);
+        Ok(target)
     }
 
-    let direction = match parsed.direction {
-        Some(direction) => direction,
-        None => {
-            print_rsync_usage();
-            return 0;
-        }
-    };
-
-    if !command_exists("rsync") {
-        eprintln!("error: rsync not found on host");
-        return EXIT_RUNTIME;
-    }
+    fn exec(&self, workspace: &str, request: &ExecRequest<'_>) -> Result<i32, String> {
+        let mut command = self.engine.command();
+        command.arg("exec");
 
-    let container = match resolve_container_for_rsync(parsed.workspace.as_deref()) {
-        Ok(container) => container,
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
+        if std::io::stdin().is_terminal() {
+            command.arg("-i");
+        }
+        if std::io::stdout().is_terminal() {
+            command.arg("-t");
         }
-    };
 
-    if let Err(err) = ensure_container_running(&container) {
-        eprintln!("error: {err}");
-        return EXIT_RUNTIME;
-    }
+        if let Some(user) = request.user {
+            command.arg("-u").arg(user);
+        }
 
-    let rsync_available = Command::new("docker")
-        .args(["exec", "-u", &parsed.user, &container, "rsync", "--version"])
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .map(|status| status.success())
-        .unwrap_or(false);
-    if !rsync_available {
-        eprintln!(
-            "error: rsync not available in container: {container} (user: {})",
-            parsed.user
-        );
-        eprintln!("hint: install rsync in the container image");
-        return EXIT_RUNTIME;
-    }
+        command.arg("-w").arg("/work");
+        command.arg(workspace);
 
-    let wrapper = match create_rsync_rsh_wrapper() {
-        Ok(path) => path,
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
+        if request.command.is_empty() {
+            command.arg("zsh").arg("-l");
+        } else {
+            command.args(request.command);
         }
-    };
 
-    let src = parsed.src.unwrap_or_default();
-    let dest = parsed.dest.unwrap_or_default();
+        command.stdin(Stdio::inherit());
+        command.stdout(Stdio::inherit());
+        command.stderr(Stdio::inherit());
 
-    let mut command = Command::new("rsync");
-    command
-        .arg("-rlpt")
-        .arg("--partial")
-        .arg("--progress")
-        .arg("-e")
-        .arg(wrapper.as_os_str());
-
-    if parsed.delete {
-        command.arg("--delete");
-    }
-    if parsed.dry_run {
-        command.arg("--dry-run");
-    }
-    for extra in &parsed.rsync_args {
-        command.arg(extra);
+        let code = command
+            .status()
+            .map(|status| status.code().unwrap_or(EXIT_RUNTIME))
+            .map_err(|err| format!("failed to run command in {workspace}: {err}"))?;
+        record_activity(self.engine, workspace, request.user);
+        Ok(code)
     }
 
-    match direction {
-        RsyncDirection::Push => {
-            command.arg(&src);
-            command.arg(format!("{container}:{dest}"));
-        }
-        RsyncDirection::Pull => {
-            command.arg(format!("{container}:{src}"));
-            command.arg(&dest);
-        }
+    fn default_work_repos_depth(&self) -> u32 {
+        4
     }
 
-    command
-        .env("AGENT_WORKSPACE_RSYNC_CONTAINER", &container)
-        .env("AGENT_WORKSPACE_RSYNC_USER", &parsed.user);
-
-    let mut display: Vec<String> = vec![
-        String::from("rsync"),
-        String::from("-rlpt"),
-        String::from("--partial"),
-        String::from("--progress"),
-        String::from("-e"),
-        wrapper.display().to_string(),
-    ];
-    if parsed.delete {
-        display.push(String::from("--delete"));
-    }
-    if parsed.dry_run {
-        display.push(String::from("--dry-run"));
+    fn map_repo_path(&self, _workspace: &str, raw: &str) -> Result<String, String> {
+        Ok(map_container_repo_path(raw, "/work"))
     }
-    display.extend(parsed.rsync_args.iter().cloned());
-    match direction {
-        RsyncDirection::Push => {
-            display.push(src);
-            display.push(format!("{container}:{dest}"));
-        }
-        RsyncDirection::Pull => {
-            display.push(format!("{container}:{src}"));
-            display.push(dest);
-        }
+
+    fn list_repos(&self, workspace: &str, root: &str, depth: u32) -> Result<Vec<String>, String> {
+        list_git_repos_in_container(self.engine, workspace, root, depth)
     }
-    println!("+ {}", display.join(" "));
 
-    let status = command.status();
-    let _ = fs::remove_file(&wrapper);
+    fn private_repo(&self, workspace: &str) -> Result<Option<String>, String> {
+        detect_private_repo_dir(self.engine, workspace)
+    }
 
-    match status {
-        Ok(result) if result.success() => 0,
-        Ok(result) => {
-            eprintln!(
-                "error: rsync command failed (exit {})",
-                result.code().unwrap_or(EXIT_RUNTIME)
-            );
-            EXIT_RUNTIME
-        }
-        Err(err) => {
-            eprintln!("error: failed to execute rsync: {err}");
-            EXIT_RUNTIME
-        }
+    fn reset_repo(&self, workspace: &str, repo_dir: &str, refspec: &str) -> Result<(), String> {
+        reset_repo_in_container(self.engine, workspace, repo_dir, refspec)
     }
-}
 
-fn run_exec(args: &[OsString]) -> i32 {
-    if !ensure_docker_available() {
-        return EXIT_RUNTIME;
+    fn store_github_token(&self, workspace: &str, host: &str, token: &str) -> Result<(), String> {
+        let content = format!("host={host}\ntoken={token}\n");
+        let target = "/home/agent/.agents/auth/github.env";
+        write_container_file(self.engine, workspace, target, content.as_bytes())
+            .map_err(|err| format!("failed to write GitHub auth file in container: {err}"))
     }
 
-    let parsed = match super::exec::parse_exec_args(args) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            eprintln!("error: {err}");
-            print_exec_usage();
-            return EXIT_RUNTIME;
+    fn store_codex_auth(&self, workspace: &str, auth_data: &[u8]) -> Result<(), String> {
+        let mut wrote_any_target = false;
+        for target in [
+            "/home/agent/.codex/auth.json",
+            "/home/agent/.agents/auth.json",
+        ] {
+            if let Err(err) = write_container_file(self.engine, workspace, target, auth_data) {
+                eprintln!("warn: failed to sync codex auth into container target {target}: {err}");
+                continue;
+            }
+            wrote_any_target = true;
         }
-    };
 
-    if parsed.show_help {
-        print_exec_usage();
-        return 0;
+        if wrote_any_target {
+            Ok(())
+        } else {
+            Err(String::from(
+                "no known codex auth target in container was writable",
+            ))
+        }
     }
 
-    let workspace_name = parsed
-        .workspace
-        .as_ref()
-        .map(|name| name.to_string_lossy().into_owned())
-        .unwrap_or_default();
-
-    let container = match resolve_container(&workspace_name) {
-        Ok(Some(container)) => container,
-        Ok(None) => {
-            eprintln!("error: workspace not found: {workspace_name}");
-            return EXIT_RUNTIME;
-        }
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
+    fn verify_gpg_key(&self, workspace: &str, key: &str) -> Result<(), String> {
+        let mut verify = self.engine.command();
+        verify
+            .arg("exec")
+            .arg(workspace)
+            .arg("bash")
+            .arg("-lc")
+            .arg("if command -v gpg >/dev/null 2>&1; then gpg --batch --list-secret-keys \"$1\" >/dev/null 2>&1; else exit 0; fi")
+            .arg("--")
+            .arg(key);
+
+        match verify.status() {
+            Ok(status) if status.success() => Ok(()),
+            Ok(_) => Err(format!("gpg key not found in container keyring: {key}")),
+            Err(err) => Err(format!("failed to run gpg key lookup in container: {err}")),
+        }
+    }
+
+    fn store_gpg_key(&self, workspace: &str, key: &str) -> Result<(), String> {
+        let target = "/home/agent/.agents/auth/gpg-key.txt";
+        write_container_file(
+            self.engine,
+            workspace,
+            target,
+            format!("{key}\n").as_bytes(),
+        )
+        .map_err(|err| format!("failed to write gpg auth file in container: {err}"))
+    }
+
+    fn tunnel(
+        &self,
+        workspace: &str,
+        request: &TunnelRequest<'_>,
+    ) -> Result<TunnelOutcome, String> {
+        if !engine_exec_success(
+            self.engine,
+            workspace,
+            "command -v code >/dev/null 2>&1",
+            &[],
+        ) {
+            return Err(String::from(
+                "'code' command not found in container (required for tunnel)",
+            ));
         }
-    };
 
-    if let Err(err) = ensure_container_running(&container) {
-        eprintln!("error: {err}");
-        return EXIT_RUNTIME;
+        let tunnel_name = request
+            .name
+            .map(sanitize_tunnel_name)
+            .unwrap_or_else(|| default_tunnel_name(workspace));
+
+        if request.detach {
+            let pid = exec_user_script(
+                self.engine,
+                workspace,
+                DETACHED_TUNNEL_SCRIPT,
+                &[
+                    "/work",
+                    CODE_TUNNEL_LOG_PATH,
+                    CODE_TUNNEL_STATE_PATH,
+                    &tunnel_name,
+                ],
+                false,
+            )
+            .map_err(|err| format!("failed to launch detached tunnel: {err}"))?;
+            return Ok(TunnelOutcome::Detached {
+                tunnel_name: Some(tunnel_name),
+                pid: pid.trim().parse().ok(),
+                log_path: Some(String::from(CODE_TUNNEL_LOG_PATH)),
+            });
+        }
+
+        let mut cmd = self.engine.command();
+        cmd.arg("exec");
+        if std::io::stdin().is_terminal() {
+            cmd.arg("-i");
+        }
+        if std::io::stdout().is_terminal() {
+            cmd.arg("-t");
+        }
+        cmd.arg(workspace);
+        cmd.arg("code")
+            .arg("tunnel")
+            .arg("--accept-server-license-terms")
+            .arg("--name")
+            .arg(&tunnel_name);
+
+        cmd.stdin(Stdio::inherit());
+        cmd.stdout(Stdio::inherit());
+        cmd.stderr(Stdio::inherit());
+
+        let status = cmd
+            .status()
+            .map_err(|err| format!("failed to run tunnel command: {err}"))?;
+        Ok(TunnelOutcome::Exited {
+            tunnel_name: Some(tunnel_name),
+            exit_code: status.code().unwrap_or(EXIT_RUNTIME),
+        })
     }
 
-    let mut command = Command::new("docker");
-    command.arg("exec");
-
-    if std::io::stdin().is_terminal() {
-        command.arg("-i");
-    }
-    if std::io::stdout().is_terminal() {
-        command.arg("-t");
+    fn tunnel_control(&self, workspace: &str, action: TunnelAction) -> Result<String, String> {
+        let [action_arg, follow] = action.script_args();
+        exec_user_script(
+            self.engine,
+            workspace,
+            TUNNEL_CONTROL_SCRIPT,
+            &[CODE_TUNNEL_STATE_PATH, action_arg, follow],
+            matches!(action, TunnelAction::Logs { .. }),
+        )
     }
 
-    if let Some(user) = parsed.user.as_ref() {
-        command.arg("-u").arg(user);
+    fn supports_start_stop(&self) -> bool {
+        true
     }
 
-    command.arg("-w").arg("/work");
-    command.arg(&container);
-
-    if parsed.command.is_empty() {
-        command.arg("zsh").arg("-l");
-    } else {
-        command.args(&parsed.command);
+    fn start(&self, workspace: &str) -> Result<(), String> {
+        ensure_container_running(self.engine, workspace)
     }
 
-    command.stdin(Stdio::inherit());
-    command.stdout(Stdio::inherit());
-    command.stderr(Stdio::inherit());
-
-    match command.status() {
-        Ok(status) => status.code().unwrap_or(EXIT_RUNTIME),
-        Err(err) => {
-            eprintln!("error: failed to run command in {container}: {err}");
-            EXIT_RUNTIME
+    fn stop(&self, workspace: &str) -> Result<(), String> {
+        if let Some(api) = engine_api(self.engine) {
+            return api.stop_container(workspace);
         }
-    }
-}
-
-fn run_rm(args: &[OsString]) -> i32 {
-    if !ensure_docker_available() {
-        return EXIT_RUNTIME;
+        engine_output(self.engine, &["stop", workspace]).map(|_| ())
     }
 
-    let parsed = match parse_rm_args(args) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            eprintln!("error: {err}");
-            print_rm_usage();
-            return EXIT_RUNTIME;
+    fn idle_check(&self, workspace: &str) -> Result<IdleCheck, String> {
+        let info = inspect_json(self.engine, "container", workspace)?
+            .ok_or_else(|| format!("workspace not found: {workspace}"))?;
+        let Some(timeout_secs) = info
+            .pointer(&["Config", "Labels", IDLE_TIMEOUT_LABEL])
+            .and_then(JsonValue::as_str)
+            .and_then(|value| value.trim().parse().ok())
+        else {
+            return Ok(IdleCheck::NoPolicy);
+        };
+        if info
+            .pointer(&["State", "Running"])
+            .and_then(JsonValue::as_bool)
+            != Some(true)
+        {
+            return Ok(IdleCheck::NotRunning);
         }
-    };
-
-    if parsed.show_help {
-        print_rm_usage();
-        return 0;
-    }
 
-    if parsed.all && parsed.workspace.is_some() {
-        eprintln!("error: rm --all does not accept a workspace name");
-        print_rm_usage();
-        return EXIT_RUNTIME;
-    }
-
-    let targets: Vec<String> = if parsed.all {
-        match list_workspace_containers() {
-            Ok(items) => items,
-            Err(err) => {
-                eprintln!("error: {err}");
-                return EXIT_RUNTIME;
+        let argv = [
+            "bash",
+            "-c",
+            IDLE_PROBE_SCRIPT,
+            "idle-probe",
+            ACTIVITY_MARKER_PREFIX,
+        ];
+        let output = match engine_api_exec(self.engine, workspace, &argv) {
+            Some(output) => output?.stdout,
+            None => {
+                let mut args = vec!["exec", workspace];
+                args.extend(argv);
+                engine_output(self.engine, &args)?
             }
-        }
-    } else if let Some(workspace_name) = parsed.workspace.as_deref() {
-        match resolve_container(workspace_name) {
-            Ok(Some(container)) => vec![container],
-            Ok(None) => {
-                eprintln!("error: workspace not found: {workspace_name}");
-                return EXIT_RUNTIME;
+        };
+        match output.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["busy"] => Ok(IdleCheck::Busy),
+            ["idle", secs] => Ok(IdleCheck::Idle {
+                idle_secs: secs.parse().unwrap_or(0),
+                timeout_secs,
+            }),
+            _ => Err(format!(
+                "unexpected idle probe output from {workspace}: {}",
+                output.trim()
+            )),
+        }
+    }
+
+    fn orphaned_volumes(&self) -> Result<Vec<String>, String> {
+        let prefix = format!("{}-", primary_workspace_prefix());
+        let output = engine_output(self.engine, &["volume", "ls", "--format", "{{.Name}}"])?;
+
+        let mut orphaned: Vec<String> = Vec::new();
+        let mut live: Vec<String> = Vec::new();
+        for volume in output.lines().filter_map(trimmed_nonempty) {
+            // `-agent-home` first: it also ends with `-home`.
+            let Some(container) = ["-agent-home", "-work", "-home"]
+                .iter()
+                .find_map(|suffix| volume.strip_suffix(suffix))
+                .filter(|container| container.starts_with(&prefix))
+                .map(str::to_string)
+            else {
+                continue;
+            };
+            if live.contains(&container) {
+                continue;
             }
-            Err(err) => {
-                eprintln!("error: {err}");
-                return EXIT_RUNTIME;
+            if container_exists(self.engine, &container) {
+                live.push(container);
+            } else {
+                orphaned.push(volume);
             }
         }
-    } else {
-        eprintln!("error: missing workspace name or --all");
-        print_rm_usage();
-        return EXIT_RUNTIME;
-    };
-
-    if targets.is_empty() {
-        return 0;
+        orphaned.sort();
+        Ok(orphaned)
     }
 
-    if !parsed.yes {
-        if parsed.all {
-            println!("This will remove {} workspace(s):", targets.len());
-        } else {
-            println!("This will remove workspace:");
-        }
-        for target in &targets {
-            println!("  - {target}");
-        }
-        if !confirm_or_abort("Proceed? [y/N] ") {
-            println!("Aborted");
-            return EXIT_RUNTIME;
+    fn remove_volume(&self, volume: &str) -> Result<(), String> {
+        if let Some(api) = engine_api(self.engine) {
+            return api.remove_volume(volume);
         }
+        engine_output(self.engine, &["volume", "rm", volume]).map(|_| ())
     }
 
-    for target in targets {
-        if let Err(err) = docker_status(&["rm", "-f", &target]) {
-            eprintln!("error: failed to remove workspace {target}: {err}");
-            return EXIT_RUNTIME;
-        }
-
-        if !parsed.keep_volumes {
-            let (work, home, codex) = volume_names(&target);
-            let _ = docker_status(&["volume", "rm", &work, &home, &codex]);
+    fn disk_usage(&self, workspace: &str) -> Result<Option<u64>, String> {
+        // Volumes are only measurable from inside; a stopped workspace reports no size.
+        if !container_running(self.engine, workspace) {
+            return Ok(None);
         }
-
-        println!("removed: {target}");
+        let script =
+            "du -skx /work /home/agent 2>/dev/null | awk '{ total += $1 } END { print total }'";
+        let output = match engine_api_exec(self.engine, workspace, &["bash", "-c", script]) {
+            Some(output) => output?.stdout,
+            None => engine_output(self.engine, &["exec", workspace, "bash", "-c", script])?,
+        };
+        Ok(parse_du_kib(&output))
     }
 
-    0
-}
-
-fn run_tunnel(args: &[OsString]) -> i32 {
-    if !ensure_docker_available() {
-        return EXIT_RUNTIME;
-    }
+    fn inspect(&self, workspace: &str) -> Result<WorkspaceDetails, String> {
+        let info = inspect_json(self.engine, "container", workspace)?
+            .ok_or_else(|| format!("workspace not found: {workspace}"))?;
+        let text = |path: &[&str]| {
+            info.pointer(path)
+                .and_then(JsonValue::as_str)
+                .and_then(trimmed_nonempty)
+        };
 
-    let parsed = match super::tunnel::parse_tunnel_args(args) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            eprintln!("error: {err}");
-            print_tunnel_usage();
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if parsed.show_help {
-        print_tunnel_usage();
-        return 0;
-    }
-
-    let workspace_name = if let Some(name) = parsed.workspace.as_deref() {
-        name
-    } else {
-        eprintln!("error: missing workspace name");
-        print_tunnel_usage();
-        return EXIT_RUNTIME;
-    };
-
-    let container = match resolve_container(workspace_name) {
-        Ok(Some(container)) => container,
-        Ok(None) => {
-            eprintln!("error: workspace not found: {workspace_name}");
-            return EXIT_RUNTIME;
-        }
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if let Err(err) = ensure_container_running(&container) {
-        eprintln!("error: {err}");
-        return EXIT_RUNTIME;
-    }
-
-    if !docker_exec_success(&container, "command -v code >/dev/null 2>&1", &[]) {
-        eprintln!("error: 'code' command not found in container (required for tunnel)");
-        return EXIT_RUNTIME;
-    }
-
-    let tunnel_name = parsed
-        .tunnel_name
-        .as_deref()
-        .map(sanitize_tunnel_name)
-        .unwrap_or_else(|| default_tunnel_name(&container));
-
-    if parsed.detach {
-        let script = format!(
-            "mkdir -p \"$(dirname \"{}\")\" && : >\"{}\" && code tunnel --accept-server-license-terms --name \"{}\" >\"{}\" 2>&1",
-            CODE_TUNNEL_LOG_PATH, CODE_TUNNEL_LOG_PATH, tunnel_name, CODE_TUNNEL_LOG_PATH
-        );
-
-        let mut cmd = Command::new("docker");
-        cmd.arg("exec")
-            .arg("-d")
-            .arg(&container)
-            .arg("bash")
-            .arg("-lc")
-            .arg(script);
-
-        match cmd.status() {
-            Ok(status) if status.success() => {
-                if parsed.output_json {
-                    println!(
-                        "{{\"workspace\":\"{}\",\"runtime\":\"container\",\"detached\":true,\"tunnel_name\":\"{}\",\"log_path\":\"{}\"}}",
-                        json_escape(&container),
-                        json_escape(&tunnel_name),
-                        json_escape(CODE_TUNNEL_LOG_PATH)
-                    );
+        let (work, home, agent_home) = volume_names(workspace);
+        let mut details = WorkspaceDetails {
+            name: workspace.to_string(),
+            path: String::from("/work"),
+            created_unix: text(&["Config", "Labels", "agent-kit.created-at"])
+                .and_then(|value| value.parse().ok()),
+            primary_repo: text(&["Config", "Labels", "agent-kit.repo"]),
+            image: text(&["Config", "Image"]),
+            state: text(&["State", "Status"]),
+            volumes: vec![work, home, agent_home],
+            cpus: text(&["Config", "Labels", CPUS_LABEL]),
+            memory: text(&["Config", "Labels", MEMORY_LABEL]),
+            ..WorkspaceDetails::default()
+        };
+        let list = |label: &str| -> Vec<String> {
+            text(&["Config", "Labels", label])
+                .map(|value| value.split(',').map(str::to_string).collect())
+                .unwrap_or_default()
+        };
+        details.env_names = list(ENV_LABEL);
+        details.mounts = list(MOUNTS_LABEL);
+        details.publish = list(PUBLISH_LABEL);
+        if let Some(image_id) = text(&["Image"]) {
+            details.image_digest = Some(image_digest(self.engine, &image_id));
+        }
+
+        // Repo and auth probes need a live container; a stopped workspace is not started.
+        if info
+            .pointer(&["State", "Running"])
+            .and_then(JsonValue::as_bool)
+            != Some(true)
+        {
+            return Ok(details);
+        }
+
+        let depth = self.default_work_repos_depth().to_string();
+        let mut argv = vec!["bash", "-lc", WORKSPACE_STATUS_SCRIPT, "--", "", &depth];
+        argv.extend(["/work", "--"]);
+        argv.extend(CONTAINER_AUTH_FILES);
+        let output = match engine_api_exec(self.engine, workspace, &argv) {
+            Some(Ok(output)) if output.exit_code == 0 => output.stdout,
+            Some(Err(err)) => return Err(err),
+            Some(Ok(output)) => {
+                let stderr = output.stderr.trim();
+                return Err(if stderr.is_empty() {
+                    format!("failed to inspect {workspace} (exit {})", output.exit_code)
                 } else {
-                    println!("tunnel: {container} detached");
-                    println!("log: {CODE_TUNNEL_LOG_PATH}");
-                }
-                return 0;
+                    stderr.to_string()
+                });
             }
-            Ok(status) => {
-                eprintln!(
-                    "error: failed to launch detached tunnel (exit {})",
-                    status.code().unwrap_or(EXIT_RUNTIME)
-                );
-                return EXIT_RUNTIME;
-            }
-            Err(err) => {
-                eprintln!("error: failed to launch detached tunnel: {err}");
-                return EXIT_RUNTIME;
-            }
-        }
-    }
-
-    let mut cmd = Command::new("docker");
-    cmd.arg("exec");
-    if std::io::stdin().is_terminal() {
-        cmd.arg("-i");
-    }
-    if std::io::stdout().is_terminal() {
-        cmd.arg("-t");
-    }
-    cmd.arg(&container);
-    cmd.arg("code")
-        .arg("tunnel")
-        .arg("--accept-server-license-terms")
-        .arg("--name")
-        .arg(&tunnel_name);
-
-    cmd.stdin(Stdio::inherit());
-    cmd.stdout(Stdio::inherit());
-    cmd.stderr(Stdio::inherit());
-
-    match cmd.status() {
-        Ok(status) => {
-            let code = status.code().unwrap_or(EXIT_RUNTIME);
-            if parsed.output_json {
-                println!(
-                    "{{\"workspace\":\"{}\",\"runtime\":\"container\",\"detached\":false,\"exit_code\":{},\"tunnel_name\":\"{}\"}}",
-                    json_escape(&container),
-                    code,
-                    json_escape(&tunnel_name)
-                );
+            None => {
+                let mut args = vec!["exec", workspace];
+                args.extend(&argv);
+                engine_output(self.engine, &args)?
             }
-            code
-        }
-        Err(err) => {
-            eprintln!("error: failed to run tunnel command: {err}");
-            EXIT_RUNTIME
-        }
-    }
-}
-
-fn run_auth(args: &[OsString]) -> i32 {
-    if !ensure_docker_available() {
-        return EXIT_RUNTIME;
-    }
-
-    let parsed = match parse_auth_args(args) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            eprintln!("error: {err}");
-            print_auth_usage();
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if parsed.show_help || parsed.provider.is_none() {
-        print_auth_usage();
-        return 0;
-    }
-
-    let container = match resolve_container_for_auth(parsed.workspace.as_deref()) {
-        Ok(container) => container,
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if let Err(err) = ensure_container_running(&container) {
-        eprintln!("error: {err}");
-        return EXIT_RUNTIME;
-    }
-
-    let provider = parsed
-        .provider
-        .as_deref()
-        .unwrap_or_default()
-        .to_ascii_lowercase();
-
-    match provider.as_str() {
-        "github" => run_auth_github(&container, parsed.host.as_deref()),
-        "codex" => run_auth_codex(&container, parsed.profile.as_deref()),
-        "gpg" => run_auth_gpg(&container, parsed.key.as_deref()),
-        _ => {
-            eprintln!("error: unknown auth provider: {provider}");
-            eprintln!("hint: expected: codex|github|gpg");
-            EXIT_RUNTIME
-        }
-    }
-}
-
-fn run_reset(args: &[OsString]) -> i32 {
-    if !ensure_docker_available() {
-        return EXIT_RUNTIME;
-    }
-
-    if args.is_empty() {
-        print_reset_usage();
-        return 0;
-    }
-
-    let subcommand = args[0].to_string_lossy();
-    if matches!(subcommand.as_ref(), "-h" | "--help") {
-        print_reset_usage();
-        return 0;
-    }
-
-    match subcommand.as_ref() {
-        "repo" => run_reset_repo(&args[1..]),
-        "work-repos" => run_reset_work_repos(&args[1..]),
-        "opt-repos" => run_reset_opt_repos(&args[1..]),
-        "private-repo" => run_reset_private_repo(&args[1..]),
-        _ => {
-            eprintln!("error: unknown reset subcommand: {subcommand}");
-            eprintln!("hint: {PRIMARY_COMMAND_NAME} reset --help");
-            EXIT_RUNTIME
-        }
-    }
-}
-
-fn run_reset_repo(args: &[OsString]) -> i32 {
-    let parsed = match parse_reset_repo_args(args) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            eprintln!("error: {err}");
-            print_reset_repo_usage();
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if parsed.show_help {
-        print_reset_repo_usage();
-        return 0;
-    }
-
-    let workspace_name = if let Some(workspace) = parsed.workspace.as_deref() {
-        workspace
-    } else {
-        eprintln!("error: missing workspace");
-        print_reset_repo_usage();
-        return EXIT_RUNTIME;
-    };
-
-    let repo_dir = if let Some(repo_dir) = parsed.repo_dir.as_deref() {
-        repo_dir
-    } else {
-        eprintln!("error: missing repo_dir");
-        print_reset_repo_usage();
-        return EXIT_RUNTIME;
-    };
-
-    let container = match resolve_container(workspace_name) {
-        Ok(Some(container)) => container,
-        Ok(None) => {
-            eprintln!("error: workspace not found: {workspace_name}");
-            return EXIT_RUNTIME;
-        }
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if let Err(err) = ensure_container_running(&container) {
-        eprintln!("error: {err}");
-        return EXIT_RUNTIME;
-    }
-
-    let target_repo = map_container_repo_path(repo_dir, "/work");
-    if !parsed.yes {
-        println!("This will reset a repo in workspace: {container}");
-        println!("  - {target_repo}");
-        if !confirm_or_abort("Proceed? [y/N] ") {
-            println!("Aborted");
-            return EXIT_RUNTIME;
-        }
-    }
-
-    match reset_repo_in_container(&container, &target_repo, &parsed.refspec) {
-        Ok(()) => 0,
-        Err(err) => {
-            eprintln!("error: {err}");
-            EXIT_RUNTIME
-        }
-    }
-}
-
-fn run_reset_work_repos(args: &[OsString]) -> i32 {
-    let parsed = match parse_reset_work_repos_args(args) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            eprintln!("error: {err}");
-            print_reset_work_repos_usage();
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if parsed.show_help {
-        print_reset_work_repos_usage();
-        return 0;
-    }
-
-    let workspace_name = if let Some(workspace) = parsed.workspace.as_deref() {
-        workspace
-    } else {
-        eprintln!("error: missing workspace");
-        print_reset_work_repos_usage();
-        return EXIT_RUNTIME;
-    };
-
-    let container = match resolve_container(workspace_name) {
-        Ok(Some(container)) => container,
-        Ok(None) => {
-            eprintln!("error: workspace not found: {workspace_name}");
-            return EXIT_RUNTIME;
-        }
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if let Err(err) = ensure_container_running(&container) {
-        eprintln!("error: {err}");
-        return EXIT_RUNTIME;
-    }
-
-    let root = map_container_repo_path(&parsed.root, "/work");
-    let repos = match list_git_repos_in_container(&container, &root, parsed.depth) {
-        Ok(repos) => repos,
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if repos.is_empty() {
-        eprintln!(
-            "warn: no git repos found under {} (depth={}) in {}",
-            root, parsed.depth, container
-        );
-        return 0;
-    }
-
-    if !parsed.yes {
-        println!(
-            "This will reset {} repo(s) inside workspace: {}",
-            repos.len(),
-            container
-        );
-        for repo in &repos {
-            println!("  - {repo}");
-        }
-        if !confirm_or_abort("Proceed? [y/N] ") {
-            println!("Aborted");
-            return EXIT_RUNTIME;
-        }
-    }
-
-    let mut failed = 0usize;
-    for repo in repos {
-        if let Err(err) = reset_repo_in_container(&container, &repo, &parsed.refspec) {
-            eprintln!("error: {err}");
-            failed += 1;
-        }
-    }
-
-    if failed > 0 {
-        eprintln!("error: failed to reset {failed} repo(s)");
-        return EXIT_RUNTIME;
-    }
-
-    0
-}
-
-fn run_reset_opt_repos(args: &[OsString]) -> i32 {
-    let parsed = match parse_reset_simple_args(args, String::from(DEFAULT_REF)) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            eprintln!("error: {err}");
-            print_reset_opt_repos_usage();
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if parsed.show_help {
-        print_reset_opt_repos_usage();
-        return 0;
-    }
-
-    let workspace_name = if let Some(workspace) = parsed.workspace.as_deref() {
-        workspace
-    } else {
-        eprintln!("error: missing workspace");
-        print_reset_opt_repos_usage();
-        return EXIT_RUNTIME;
-    };
-
-    let container = match resolve_container(workspace_name) {
-        Ok(Some(container)) => container,
-        Ok(None) => {
-            eprintln!("error: workspace not found: {workspace_name}");
-            return EXIT_RUNTIME;
-        }
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if let Err(err) = ensure_container_running(&container) {
-        eprintln!("error: {err}");
-        return EXIT_RUNTIME;
-    }
-
-    let repos = match list_git_repos_in_container(&container, "/opt", 4) {
-        Ok(repos) => repos,
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if repos.is_empty() {
-        eprintln!("warn: no git repos found under /opt");
-        return 0;
-    }
-
-    if !parsed.yes {
-        println!("This will reset /opt-style repos in workspace: {container}");
-        for repo in &repos {
-            println!("  - {repo}");
-        }
-        if !confirm_or_abort("Proceed? [y/N] ") {
-            println!("Aborted");
-            return EXIT_RUNTIME;
-        }
-    }
-
-    for repo in repos {
-        if let Err(err) = reset_repo_in_container(&container, &repo, &parsed.refspec) {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
-        }
-    }
-
-    0
-}
-
-fn run_reset_private_repo(args: &[OsString]) -> i32 {
-    let parsed = match parse_reset_simple_args(args, String::from(DEFAULT_REF)) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            eprintln!("error: {err}");
-            print_reset_private_repo_usage();
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if parsed.show_help {
-        print_reset_private_repo_usage();
-        return 0;
-    }
-
-    let workspace_name = if let Some(workspace) = parsed.workspace.as_deref() {
-        workspace
-    } else {
-        eprintln!("error: missing workspace");
-        print_reset_private_repo_usage();
-        return EXIT_RUNTIME;
-    };
-
-    let container = match resolve_container(workspace_name) {
-        Ok(Some(container)) => container,
-        Ok(None) => {
-            eprintln!("error: workspace not found: {workspace_name}");
-            return EXIT_RUNTIME;
-        }
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if let Err(err) = ensure_container_running(&container) {
-        eprintln!("error: {err}");
-        return EXIT_RUNTIME;
-    }
-
-    let private_repo = match detect_private_repo_dir(&container) {
-        Ok(Some(path)) => path,
-        Ok(None) => {
-            eprintln!("warn: no private git repo found in workspace: {container}");
-            eprintln!(
-                "hint: seed it with: AGENT_WORKSPACE_PRIVATE_REPO=OWNER/REPO {PRIMARY_COMMAND_NAME} create ..."
-            );
-            return 0;
-        }
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
-        }
-    };
-
-    if !parsed.yes {
-        println!("This will reset private repo in workspace: {container}");
-        println!("  - {private_repo}");
-        if !confirm_or_abort("Proceed? [y/N] ") {
-            println!("Aborted");
-            return EXIT_RUNTIME;
-        }
-    }
-
-    match reset_repo_in_container(&container, &private_repo, &parsed.refspec) {
-        Ok(()) => 0,
-        Err(err) => {
-            eprintln!("error: {err}");
-            EXIT_RUNTIME
-        }
-    }
-}
-
-fn parse_rsync_args(args: &[OsString]) -> Result<ParsedRsync, String> {
-    let mut parsed = ParsedRsync::default();
-    if args.is_empty() {
-        parsed.show_help = true;
-        return Ok(parsed);
-    }
-
-    let subcmd = args[0].to_string_lossy();
-    match subcmd.as_ref() {
-        "-h" | "--help" | "help" => {
-            parsed.show_help = true;
-            return Ok(parsed);
-        }
-        "push" => parsed.direction = Some(RsyncDirection::Push),
-        "pull" => parsed.direction = Some(RsyncDirection::Pull),
-        _ => {
-            return Err(format!(
-                "unknown rsync subcommand: {subcmd} (expected: push|pull)"
-            ));
-        }
-    }
-
-    let mut idx = 1usize;
-    while idx < args.len() {
-        let current = args[idx].to_string_lossy();
-        match current.as_ref() {
-            "-h" | "--help" => parsed.show_help = true,
-            "--delete" => parsed.delete = true,
-            "-n" | "--dry-run" => parsed.dry_run = true,
-            "--root" => parsed.user = String::from("root"),
-            "--user" | "-u" => {
-                idx += 1;
-                if idx >= args.len() {
-                    return Err(format!("missing value for {current}"));
+        };
+        details.apply_status_output(&output);
+        Ok(details)
+    }
+
+    fn upgrade(
+        &self,
+        workspace: &str,
+        options: &UpgradeOptions<'_>,
+    ) -> Result<UpgradeOutcome, String> {
+        let info = inspect_json(self.engine, "container", workspace)?
+            .ok_or_else(|| format!("workspace not found: {workspace}"))?;
+        let spec = container_spec(self.engine, &info, workspace)?;
+        let was_running = info
+            .pointer(&["State", "Running"])
+            .and_then(JsonValue::as_bool)
+            == Some(true);
+
+        // A rolled-back container runs on an image id; the label keeps the tag it follows.
+        let tracked = label_value(&spec.labels, IMAGE_LABEL).unwrap_or_else(|| spec.image.clone());
+        let image = self
+            .engine
+            .qualify_image(options.image.unwrap_or(tracked.as_str()));
+        if options.pull {
+            engine_status(self.engine, &["pull", &image])?;
+        }
+        let image_id = inspect_json(self.engine, "image", &image)?
+            .and_then(|info| {
+                info.get("Id")
+                    .and_then(JsonValue::as_str)
+                    .map(str::to_string)
+            })
+            .ok_or_else(|| format!("image not found locally: {image} (re-run with --pull)"))?;
+
+        let previous = image_digest(self.engine, &spec.image_id);
+        let mut outcome = UpgradeOutcome {
+            previous: previous.clone(),
+            current: previous.clone(),
+        };
+        if image_id != spec.image_id {
+            engine_output(self.engine, &["rm", "-f", workspace])?;
+
+            let mut labels = spec.labels.clone();
+            set_label(&mut labels, IMAGE_LABEL, &image);
+            set_label(&mut labels, PREVIOUS_IMAGE_LABEL, &previous);
+            let started = run_workspace_container(
+                self.engine,
+                workspace,
+                &image,
+                &labels,
+                &spec.env,
+                spec.userns.as_deref(),
+            )
+            .and_then(|()| {
+                // `run -d` returns once started; give a broken entrypoint a moment to exit.
+                std::thread::sleep(std::time::Duration::from_secs(1));
+                if container_running(self.engine, workspace) {
+                    Ok(())
+                } else {
+                    Err(format!("{workspace} did not stay running on {image}"))
                 }
-                let value = args[idx].to_string_lossy().into_owned();
-                let Some(cleaned) = trimmed_nonempty(&value) else {
-                    return Err(format!("missing value for {current}"));
-                };
-                parsed.user = cleaned;
-            }
-            _ if current.starts_with("--user=") => {
-                let value = current["--user=".len()..].to_string();
-                let Some(cleaned) = trimmed_nonempty(&value) else {
-                    return Err(String::from("missing value for --user"));
-                };
-                parsed.user = cleaned;
-            }
-            "--" => {
-                return Err(String::from(
-                    "unexpected -- (pass rsync flags after source/destination paths)",
-                ));
+            });
+            if let Err(err) = started {
+                let _ = engine_output(self.engine, &["rm", "-f", workspace]);
+                let mut labels = spec.labels.clone();
+                set_label(&mut labels, IMAGE_LABEL, &tracked);
+                run_workspace_container(
+                    self.engine,
+                    workspace,
+                    &spec.image_id,
+                    &labels,
+                    &spec.env,
+                    spec.userns.as_deref(),
+                )
+                .map_err(|rollback_err| {
+                    format!("{err}; rollback to {previous} failed: {rollback_err}")
+                })?;
+                if !was_running {
+                    let _ = self.stop(workspace);
+                }
+                return Err(format!("{err}; rolled back to {previous}"));
             }
-            _ if current.starts_with('-') => return Err(format!("unknown option: {current}")),
-            _ => break,
+            outcome.current = image_digest(self.engine, &image_id);
         }
-        idx += 1;
-    }
-
-    let mut rest: Vec<String> = Vec::new();
-    for arg in &args[idx..] {
-        rest.push(arg.to_string_lossy().into_owned());
-    }
-    if rest.len() < 2 {
-        return Err(String::from("missing args"));
-    }
-
-    if rest.len() == 2 {
-        parsed.src = Some(rest[0].clone());
-        parsed.dest = Some(rest[1].clone());
-        return Ok(parsed);
-    }
-
-    if rest[2].starts_with('-') {
-        parsed.src = Some(rest[0].clone());
-        parsed.dest = Some(rest[1].clone());
-        parsed.rsync_args = rest[2..].to_vec();
-        return Ok(parsed);
-    }
-
-    parsed.workspace = Some(rest[0].clone());
-    parsed.src = Some(rest[1].clone());
-    parsed.dest = Some(rest[2].clone());
-    if rest.len() > 3 {
-        parsed.rsync_args = rest[3..].to_vec();
-    }
 
-    Ok(parsed)
-}
-
-fn parse_create_args(args: &[OsString]) -> Result<ParsedCreate, String> {
-    let mut parsed = ParsedCreate::default();
-    let mut idx = 0usize;
-    let mut positional_only = false;
-
-    while idx < args.len() {
-        let current = args[idx].clone();
-        let text = current.to_string_lossy().into_owned();
-
-        if !positional_only {
-            match text.as_str() {
-                "-h" | "--help" => {
-                    parsed.show_help = true;
-                    idx += 1;
-                    continue;
-                }
-                "--no-work-repos" => {
-                    parsed.no_work_repos = true;
-                    idx += 1;
-                    continue;
-                }
-                "--no-extras" => {
-                    parsed.no_extras = true;
-                    idx += 1;
-                    continue;
-                }
-                "--no-pull" => {
-                    parsed.no_pull = true;
-                    idx += 1;
-                    continue;
-                }
-                "--private-repo" => {
-                    idx += 1;
-                    if idx >= args.len() {
-                        return Err(String::from("missing value for --private-repo"));
-                    }
-                    parsed.private_repo = trimmed_nonempty(args[idx].to_string_lossy().as_ref());
-                    idx += 1;
-                    continue;
-                }
-                "--name" => {
-                    idx += 1;
-                    if idx >= args.len() {
-                        return Err(String::from("missing value for --name"));
-                    }
-                    let value = args[idx].to_string_lossy().into_owned();
-                    let normalized = normalize_workspace_name_for_create(&value);
-                    parsed.workspace_name = trimmed_nonempty(&normalized);
-                    idx += 1;
-                    continue;
-                }
-                "--image" => {
-                    idx += 1;
-                    if idx >= args.len() {
-                        return Err(String::from("missing value for --image"));
-                    }
-                    parsed.image = trimmed_nonempty(args[idx].to_string_lossy().as_ref());
-                    idx += 1;
-                    continue;
-                }
-                "--ref" => {
-                    idx += 1;
-                    if idx >= args.len() {
-                        return Err(String::from("missing value for --ref"));
-                    }
-                    parsed.refspec = trimmed_nonempty(args[idx].to_string_lossy().as_ref());
-                    idx += 1;
-                    continue;
-                }
-                "--" => {
-                    positional_only = true;
-                    idx += 1;
-                    continue;
-                }
-                _ if text.starts_with("--private-repo=") => {
-                    parsed.private_repo = trimmed_nonempty(text["--private-repo=".len()..].trim());
-                    idx += 1;
-                    continue;
-                }
-                _ if text.starts_with("--name=") => {
-                    let value = text["--name=".len()..].trim();
-                    let normalized = normalize_workspace_name_for_create(value);
-                    parsed.workspace_name = trimmed_nonempty(&normalized);
-                    idx += 1;
-                    continue;
-                }
-                _ if text.starts_with("--image=") => {
-                    parsed.image = trimmed_nonempty(text["--image=".len()..].trim());
-                    idx += 1;
-                    continue;
-                }
-                _ if text.starts_with("--ref=") => {
-                    parsed.refspec = trimmed_nonempty(text["--ref=".len()..].trim());
-                    idx += 1;
-                    continue;
-                }
-                _ if text.starts_with('-') => {
-                    parsed.ignored_options.push(text);
-                    idx += 1;
-                    continue;
-                }
-                _ => {}
+        if options.sync_baseline {
+            ensure_container_running(self.engine, workspace)?;
+            if let Err(err) = sync_container_baseline(self.engine, workspace) {
+                eprintln!("warn: failed to sync container baseline for {workspace}: {err}");
             }
         }
-
-        if parsed.primary_repo.is_none() {
-            parsed.primary_repo = Some(text);
-        } else {
-            parsed.extra_repos.push(text);
+        if !was_running && container_running(self.engine, workspace) {
+            self.stop(workspace)?;
         }
-        idx += 1;
+        Ok(outcome)
     }
 
-    if parsed.no_work_repos && (parsed.primary_repo.is_some() || !parsed.extra_repos.is_empty()) {
-        return Err(String::from("--no-work-repos does not accept repo args"));
-    }
-
-    Ok(parsed)
-}
-
-fn parse_rm_args(args: &[OsString]) -> Result<ParsedRm, String> {
-    let mut parsed = ParsedRm {
-        keep_volumes: false,
-        ..ParsedRm::default()
-    };
-
-    for arg in args {
-        let text = arg.to_string_lossy();
-        match text.as_ref() {
-            "-h" | "--help" => parsed.show_help = true,
-            "--all" => parsed.all = true,
-            "-y" | "--yes" => parsed.yes = true,
-            "--keep-volumes" => parsed.keep_volumes = true,
-            "--volumes" => parsed.keep_volumes = false,
-            _ if text.starts_with('-') => return Err(format!("unknown option for rm: {text}")),
-            _ => {
-                if parsed.workspace.is_some() {
-                    return Err(String::from("rm accepts at most one workspace name"));
-                }
-                parsed.workspace = Some(text.to_string());
-            }
-        }
+    fn supports_forward(&self) -> bool {
+        true
     }
 
-    Ok(parsed)
-}
-
-fn parse_auth_args(args: &[OsString]) -> Result<ParsedAuth, String> {
-    let mut parsed = ParsedAuth::default();
-    let mut idx = 0usize;
-
-    while idx < args.len() {
-        let current = args[idx].to_string_lossy();
-        match current.as_ref() {
-            "-h" | "--help" => parsed.show_help = true,
-            "--container" | "--workspace" => {
-                idx += 1;
-                if idx >= args.len() {
-                    return Err(format!("missing value for {}", current));
-                }
-                parsed.workspace = Some(args[idx].to_string_lossy().into_owned());
-            }
-            "--profile" => {
-                idx += 1;
-                if idx >= args.len() {
-                    return Err(String::from("missing value for --profile"));
-                }
-                parsed.profile = Some(args[idx].to_string_lossy().into_owned());
-            }
-            "--host" => {
-                idx += 1;
-                if idx >= args.len() {
-                    return Err(String::from("missing value for --host"));
-                }
-                parsed.host = Some(args[idx].to_string_lossy().into_owned());
-            }
-            "--key" => {
-                idx += 1;
-                if idx >= args.len() {
-                    return Err(String::from("missing value for --key"));
-                }
-                parsed.key = Some(args[idx].to_string_lossy().into_owned());
-            }
-            _ if current.starts_with("--container=") => {
-                parsed.workspace = Some(current["--container=".len()..].to_string());
-            }
-            _ if current.starts_with("--workspace=") => {
-                parsed.workspace = Some(current["--workspace=".len()..].to_string());
-            }
-            _ if current.starts_with("--profile=") => {
-                parsed.profile = Some(current["--profile=".len()..].to_string());
-            }
-            _ if current.starts_with("--host=") => {
-                parsed.host = Some(current["--host=".len()..].to_string());
-            }
-            _ if current.starts_with("--key=") => {
-                parsed.key = Some(current["--key=".len()..].to_string());
-            }
-            _ if current.starts_with('-') => {
-                return Err(format!("unknown option for auth: {current}"));
-            }
-            _ => {
-                let text = current.to_string();
-                if parsed.provider.is_none() {
-                    parsed.provider = Some(text);
-                } else if parsed.workspace.is_none() {
-                    parsed.workspace = Some(text);
-                } else {
-                    return Err(format!("unexpected arg: {current}"));
-                }
-            }
-        }
-        idx += 1;
+    fn forward_command(&self, workspace: &str, port: u16) -> Result<Command, String> {
+        let mut command = self.engine.command();
+        command
+            .args([
+                "exec",
+                "-i",
+                workspace,
+                "bash",
+                "-c",
+                FORWARD_BRIDGE_SCRIPT,
+                "--",
+            ])
+            .arg(port.to_string());
+        Ok(command)
+    }
+
+    fn copy_in(&self, workspace: &str, request: &CopyRequest) -> Result<String, String> {
+        // Extracted as root, then handed to the workspace user like files it created itself.
+        stream_copy_in(request, "agent", |args, archive| {
+            exec_script_streamed(
+                self.engine,
+                workspace,
+                COPY_IN_SCRIPT,
+                args,
+                archive,
+                Stdio::piped(),
+            )
+        })
     }
 
-    Ok(parsed)
-}
-
-fn parse_reset_repo_args(args: &[OsString]) -> Result<ParsedResetRepo, String> {
-    let mut parsed = ParsedResetRepo {
-        refspec: String::from(DEFAULT_REF),
-        ..ParsedResetRepo::default()
-    };
-    let mut idx = 0usize;
-
-    while idx < args.len() {
-        let current = args[idx].to_string_lossy();
-        match current.as_ref() {
-            "-h" | "--help" => parsed.show_help = true,
-            "-y" | "--yes" => parsed.yes = true,
-            "--ref" => {
-                idx += 1;
-                if idx >= args.len() {
-                    return Err(String::from("missing value for --ref"));
-                }
-                parsed.refspec = args[idx].to_string_lossy().into_owned();
-            }
-            _ if current.starts_with("--ref=") => {
-                parsed.refspec = current["--ref=".len()..].to_string();
-            }
-            _ if current.starts_with('-') => {
-                return Err(format!("unknown option for reset repo: {current}"));
-            }
-            _ => {
-                if parsed.workspace.is_none() {
-                    parsed.workspace = Some(current.to_string());
-                } else if parsed.repo_dir.is_none() {
-                    parsed.repo_dir = Some(current.to_string());
-                } else {
-                    return Err(format!("unexpected arg for reset repo: {current}"));
-                }
-            }
-        }
-        idx += 1;
+    fn copy_out(&self, workspace: &str, request: &CopyRequest) -> Result<String, String> {
+        stream_copy_out(request, |args, archive| {
+            exec_script_streamed(
+                self.engine,
+                workspace,
+                COPY_OUT_SCRIPT,
+                args,
+                Stdio::null(),
+                archive,
+            )
+        })
     }
 
-    Ok(parsed)
-}
-
-fn parse_reset_work_repos_args(args: &[OsString]) -> Result<ParsedResetWorkRepos, String> {
-    let mut parsed = ParsedResetWorkRepos::default();
-    let mut idx = 0usize;
-
-    while idx < args.len() {
-        let current = args[idx].to_string_lossy();
-        match current.as_ref() {
-            "-h" | "--help" => parsed.show_help = true,
-            "-y" | "--yes" => parsed.yes = true,
-            "--depth" => {
-                idx += 1;
-                if idx >= args.len() {
-                    return Err(String::from("missing value for --depth"));
-                }
-                parsed.depth = parse_positive_u32(args[idx].to_string_lossy().as_ref(), "--depth")?;
-            }
-            "--root" => {
-                idx += 1;
-                if idx >= args.len() {
-                    return Err(String::from("missing value for --root"));
-                }
-                parsed.root = args[idx].to_string_lossy().into_owned();
-            }
-            "--ref" => {
-                idx += 1;
-                if idx >= args.len() {
-                    return Err(String::from("missing value for --ref"));
-                }
-                parsed.refspec = args[idx].to_string_lossy().into_owned();
-            }
-            _ if current.starts_with("--depth=") => {
-                parsed.depth = parse_positive_u32(&current["--depth=".len()..], "--depth")?;
-            }
-            _ if current.starts_with("--root=") => {
-                parsed.root = current["--root=".len()..].to_string()
-            }
-            _ if current.starts_with("--ref=") => {
-                parsed.refspec = current["--ref=".len()..].to_string()
-            }
-            _ if current.starts_with('-') => {
-                return Err(format!("unknown option for reset work-repos: {current}"));
-            }
-            _ => {
-                if parsed.workspace.is_some() {
-                    return Err(format!("unexpected arg for reset work-repos: {current}"));
-                }
-                parsed.workspace = Some(current.to_string());
-            }
-        }
-        idx += 1;
+    fn supports_rsync(&self) -> bool {
+        true
     }
 
-    Ok(parsed)
-}
-
-fn parse_reset_simple_args(
-    args: &[OsString],
-    default_ref: String,
-) -> Result<ParsedResetSimple, String> {
-    let mut parsed = ParsedResetSimple {
-        refspec: default_ref,
-        ..ParsedResetSimple::default()
-    };
-    let mut idx = 0usize;
-
-    while idx < args.len() {
-        let current = args[idx].to_string_lossy();
-        match current.as_ref() {
-            "-h" | "--help" => parsed.show_help = true,
-            "-y" | "--yes" => parsed.yes = true,
-            "--ref" => {
-                idx += 1;
-                if idx >= args.len() {
-                    return Err(String::from("missing value for --ref"));
-                }
-                parsed.refspec = args[idx].to_string_lossy().into_owned();
-            }
-            _ if current.starts_with("--ref=") => {
-                parsed.refspec = current["--ref=".len()..].to_string()
-            }
-            _ if current.starts_with('-') => return Err(format!("unknown option: {current}")),
-            _ => {
-                if parsed.workspace.is_some() {
-                    return Err(format!("unexpected arg: {current}"));
+    fn rsync(&self, workspace: &str, transfer: &RsyncTransfer) -> Result<(), String> {
+        ensure_container_rsync(self.engine, workspace, &transfer.user)?;
+        let wrapper = create_rsync_rsh_wrapper()?;
+        let result = rsync_through_wrapper(self.engine, workspace, transfer, &wrapper);
+        let _ = fs::remove_file(&wrapper);
+        result
+    }
+
+    fn rsync_watch(
+        &self,
+        workspace: &str,
+        transfer: &RsyncTransfer,
+        next_batch: &mut dyn FnMut() -> Option<RsyncTransfer>,
+    ) -> Result<(), String> {
+        ensure_container_rsync(self.engine, workspace, &transfer.user)?;
+        // One wrapper for the whole session; if the session is interrupted, the next
+        // `create_rsync_rsh_wrapper` sweeps it.
+        let wrapper = create_rsync_rsh_wrapper()?;
+        let result = rsync_through_wrapper(self.engine, workspace, transfer, &wrapper);
+        if result.is_ok() {
+            while let Some(batch) = next_batch() {
+                if let Err(err) = rsync_through_wrapper(self.engine, workspace, &batch, &wrapper) {
+                    eprintln!("warn: {err}");
                 }
-                parsed.workspace = Some(current.to_string());
             }
         }
-        idx += 1;
-    }
-
-    Ok(parsed)
-}
-
-fn parse_positive_u32(raw: &str, option_name: &str) -> Result<u32, String> {
-    let value = raw
-        .trim()
-        .parse::<u32>()
-        .map_err(|_| format!("{option_name} must be a positive integer (got: {raw})"))?;
-    if value == 0 {
-        return Err(format!(
-            "{option_name} must be a positive integer (got: {raw})"
-        ));
+        let _ = fs::remove_file(&wrapper);
+        result
     }
-    Ok(value)
 }
 
 fn primary_workspace_prefix() -> String {
@@ -1902,45 +1260,117 @@ fn normalize_container_name(name: &str) -> String {
     format!("{prefix}-{normalized}")
 }
 
-fn generate_workspace_name() -> String {
-    let suffix = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_secs())
-        .unwrap_or(0);
-    format!("ws-{suffix}")
+/// Engine API client for runtimes that expose a docker-compatible daemon socket.
+///
+/// Only docker is routed through the API; podman keeps using its CLI.
+fn engine_api(engine: ContainerEngine) -> Option<EngineApi> {
+    match engine {
+        ContainerEngine::Docker => EngineApi::from_env(),
+        ContainerEngine::Podman => None,
+    }
 }
 
-fn ensure_docker_available() -> bool {
-    if command_exists("docker") {
-        true
-    } else {
-        eprintln!("error: docker command not found in PATH");
-        false
+/// Daemon version, which also proves the daemon answers (not just that the CLI is installed).
+fn engine_server_version(engine: ContainerEngine) -> Result<String, String> {
+    let mut api_error = None;
+    if let Some(api) = engine_api(engine) {
+        match api.server_version() {
+            Ok(version) => return Ok(version),
+            Err(err) => api_error = Some(err),
+        }
+    }
+    if !command_exists(engine.binary()) {
+        return Err(
+            api_error.unwrap_or_else(|| format!("{} command not found in PATH", engine.binary()))
+        );
+    }
+
+    let args: &[&str] = match engine {
+        ContainerEngine::Docker => &["version", "--format", "{{.Server.Version}}"],
+        ContainerEngine::Podman => &["info", "--format", "{{.Version.Version}}"],
+    };
+    let output = engine_output(engine, args)?;
+    trimmed_nonempty(&output)
+        .ok_or_else(|| format!("{} reported no server version", engine.binary()))
+}
+
+/// Run a captured, non-interactive exec through the Engine API when available.
+///
+/// Returns `None` when the API is unavailable or the exec could not be created, so callers
+/// can retry through the CLI; an error after the exec started is returned instead, since a
+/// retry would run the command a second time.
+fn engine_api_exec(
+    engine: ContainerEngine,
+    container: &str,
+    cmd: &[&str],
+) -> Option<Result<ExecOutput, String>> {
+    match engine_api(engine)?.exec(container, cmd) {
+        Ok(output) => Some(Ok(output)),
+        Err(ExecError::NotStarted(_)) => None,
+        Err(ExecError::Started(err)) => Some(Err(err)),
+    }
+}
+
+/// `<kind> inspect` through the Engine API when available; `None` when the object is missing.
+fn inspect_json(
+    engine: ContainerEngine,
+    kind: &str,
+    name: &str,
+) -> Result<Option<JsonValue>, String> {
+    if let Some(api) = engine_api(engine) {
+        let result = match kind {
+            "image" => api.inspect_image(name),
+            _ => api.inspect_container(name),
+        };
+        if let Ok(value) = result {
+            return Ok(value);
+        }
     }
+
+    let output = match engine.command().args([kind, "inspect", name]).output() {
+        Ok(output) if output.status.success() => output,
+        Ok(_) => return Ok(None),
+        Err(err) => {
+            return Err(format!(
+                "failed to run {} {kind} inspect: {err}",
+                engine.binary()
+            ));
+        }
+    };
+    let value = parse_json(&String::from_utf8_lossy(&output.stdout))
+        .map_err(|err| format!("invalid {} {kind} inspect output: {err}", engine.binary()))?;
+    Ok(value.as_array().and_then(|items| items.first()).cloned())
 }
 
-fn docker_status(args: &[&str]) -> Result<(), String> {
-    let status = Command::new("docker")
-        .args(args)
-        .status()
-        .map_err(|err| format!("failed to run docker {}: {err}", args.join(" ")))?;
+fn engine_status(engine: ContainerEngine, args: &[&str]) -> Result<(), String> {
+    let status = engine.command().args(args).status().map_err(|err| {
+        format!(
+            "failed to run {} {}: {err}",
+            engine.binary(),
+            args.join(" ")
+        )
+    })?;
 
     if status.success() {
         Ok(())
     } else {
         Err(format!(
-            "docker {} failed (exit {})",
+            "{} {} failed (exit {})",
+            engine.binary(),
             args.join(" "),
             status.code().unwrap_or(EXIT_RUNTIME)
         ))
     }
 }
 
-fn docker_output(args: &[&str]) -> Result<String, String> {
-    let output = Command::new("docker")
-        .args(args)
-        .output()
-        .map_err(|err| format!("failed to run docker {}: {err}", args.join(" ")))?;
+fn engine_output(engine: ContainerEngine, args: &[&str]) -> Result<String, String> {
+    let output = engine.command().args(args).output().map_err(|err| {
+        format!(
+            "failed to run {} {}: {err}",
+            engine.binary(),
+            args.join(" ")
+        )
+    })?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
@@ -1948,7 +1378,8 @@ fn docker_output(args: &[&str]) -> Result<String, String> {
         let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
         if stderr.is_empty() {
             Err(format!(
-                "docker {} failed (exit {})",
+                "{} {} failed (exit {})",
+                engine.binary(),
                 args.join(" "),
                 output.status.code().unwrap_or(EXIT_RUNTIME)
             ))
@@ -1958,8 +1389,97 @@ fn docker_output(args: &[&str]) -> Result<String, String> {
     }
 }
 
-fn docker_exec_success(container: &str, script: &str, extra_args: &[&str]) -> bool {
-    let mut cmd = Command::new("docker");
+/// Run `script` as root in `/work` of `container` with stdin/stdout wired to tar streams;
+/// returns the captured stdout when it is piped.
+fn exec_script_streamed(
+    engine: ContainerEngine,
+    container: &str,
+    script: &str,
+    args: &[&str],
+    stdin: Stdio,
+    stdout: Stdio,
+) -> Result<String, String> {
+    let output = engine
+        .command()
+        .args([
+            "exec", "-i", "-u", "0", "-w", "/work", container, "bash", "-c",
+        ])
+        .arg(script)
+        .arg("--")
+        .args(args)
+        .stdin(stdin)
+        .stdout(stdout)
+        .output()
+        .map_err(|err| format!("failed to run {} exec: {err}", engine.binary()))?;
+    exec_result(engine, container, &output)
+}
+
+/// Runs `script` as the container's default user in a login shell, where `code` is on `PATH`.
+fn exec_user_script(
+    engine: ContainerEngine,
+    container: &str,
+    script: &str,
+    args: &[&str],
+    streaming: bool,
+) -> Result<String, String> {
+    let mut cmd = engine.command();
+    cmd.arg("exec");
+    // A tty lets Ctrl-C reach a followed `tail` instead of leaving it running in the container.
+    if streaming && std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+        cmd.arg("-it");
+    }
+    cmd.arg(container)
+        .args(["bash", "-lc", script, "--"])
+        .args(args)
+        .stdin(if streaming {
+            Stdio::inherit()
+        } else {
+            Stdio::null()
+        })
+        .stdout(if streaming {
+            Stdio::inherit()
+        } else {
+            Stdio::piped()
+        });
+    let output = cmd
+        .output()
+        .map_err(|err| format!("failed to run {} exec: {err}", engine.binary()))?;
+    exec_result(engine, container, &output)
+}
+
+fn exec_result(
+    engine: ContainerEngine,
+    container: &str,
+    output: &std::process::Output,
+) -> Result<String, String> {
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if stderr.is_empty() {
+        Err(format!(
+            "{} exec {container} failed (exit {})",
+            engine.binary(),
+            output.status.code().unwrap_or(EXIT_RUNTIME)
+        ))
+    } else {
+        Err(stderr)
+    }
+}
+
+fn engine_exec_success(
+    engine: ContainerEngine,
+    container: &str,
+    script: &str,
+    extra_args: &[&str],
+) -> bool {
+    let mut argv: Vec<&str> = vec!["bash", "-lc", script, "--"];
+    argv.extend_from_slice(extra_args);
+    if let Some(output) = engine_api_exec(engine, container, &argv) {
+        return output.is_ok_and(|output| output.exit_code == 0);
+    }
+
+    let mut cmd = engine.command();
     cmd.arg("exec")
         .arg(container)
         .arg("bash")
@@ -1972,33 +1492,74 @@ fn docker_exec_success(container: &str, script: &str, extra_args: &[&str]) -> bo
     cmd.status().map(|s| s.success()).unwrap_or(false)
 }
 
-fn list_workspace_containers() -> Result<Vec<String>, String> {
-    let output = docker_output(&[
-        "ps",
-        "-a",
-        "--filter",
-        &format!("label={WORKSPACE_LABEL}"),
-        "--format",
-        "{{.Names}}",
-    ])?;
-
-    let mut names: Vec<String> = output
+fn list_workspace_containers(engine: ContainerEngine) -> Result<Vec<ContainerSummary>, String> {
+    if let Some(api) = engine_api(engine)
+        && let Ok(mut containers) = api.list_containers_by_label(WORKSPACE_LABEL)
+    {
+        containers.sort_by(|left, right| left.name.cmp(&right.name));
+        return Ok(containers);
+    }
+
+    // docker exposes labels through `.Label`; podman's `.Labels` is a plain map.
+    let label = |key: &str| match engine {
+        ContainerEngine::Docker => format!("{{{{.Label \"{key}\"}}}}"),
+        ContainerEngine::Podman => format!("{{{{index .Labels \"{key}\"}}}}"),
+    };
+    let format = format!(
+        "{{{{.Names}}}}\t{{{{.Image}}}}\t{{{{.State}}}}\t{}\t{}",
+        label("agent-kit.repo"),
+        label("agent-kit.created-at")
+    );
+    let output = engine_output(
+        engine,
+        &[
+            "ps",
+            "-a",
+            "--filter",
+            &format!("label={WORKSPACE_LABEL}"),
+            "--format",
+            &format,
+        ],
+    )?;
+
+    let mut containers: Vec<ContainerSummary> = output
         .lines()
-        .map(str::trim)
-        .filter(|line| !line.is_empty())
-        .map(ToString::to_string)
+        .filter_map(|line| {
+            let mut fields = line.split('\t').map(trimmed_nonempty);
+            let name = fields.next().flatten()?;
+            let mut next = || fields.next().flatten();
+            Some(ContainerSummary {
+                name,
+                image: next(),
+                state: next(),
+                repo: next(),
+                created_at: next(),
+            })
+        })
         .collect();
-    names.sort();
-    Ok(names)
+    containers.sort_by(|left, right| left.name.cmp(&right.name));
+    Ok(containers)
 }
 
-pub(super) fn completion_workspace_names() -> Result<Vec<String>, String> {
-    list_workspace_containers()
+/// Collapse engine container states into the `running`/`paused`/`stopped` shown by `ls`.
+fn workspace_state(state: &str) -> String {
+    match state.to_ascii_lowercase().as_str() {
+        "running" | "restarting" => String::from("running"),
+        "paused" => String::from("paused"),
+        _ => String::from("stopped"),
+    }
 }
 
-fn container_exists(name: &str) -> bool {
-    Command::new("docker")
-        .args(["inspect", name])
+fn container_exists(engine: ContainerEngine, name: &str) -> bool {
+    if let Some(api) = engine_api(engine)
+        && let Ok(exists) = api.container_exists(name)
+    {
+        return exists;
+    }
+
+    engine
+        .command()
+        .args(["container", "inspect", name])
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .status()
@@ -2006,43 +1567,77 @@ fn container_exists(name: &str) -> bool {
         .unwrap_or(false)
 }
 
-fn container_running(name: &str) -> bool {
-    if let Ok(output) = docker_output(&["inspect", "-f", "{{.State.Running}}", name]) {
+/// Stamp the end of an `exec` session for `stop --idle`; best effort and detached.
+fn record_activity(engine: ContainerEngine, container: &str, user: Option<&OsStr>) {
+    let mut cmd = engine.command();
+    cmd.args(["exec", "-d"]);
+    if let Some(user) = user {
+        cmd.arg("-u").arg(user);
+    }
+    let _ = cmd
+        .arg(container)
+        .args([
+            "sh",
+            "-c",
+            r#"date +%s >"$1.$(id -u)""#,
+            "record-activity",
+            ACTIVITY_MARKER_PREFIX,
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+fn container_running(engine: ContainerEngine, name: &str) -> bool {
+    if let Some(api) = engine_api(engine)
+        && let Ok(running) = api.container_running(name)
+    {
+        return running;
+    }
+
+    if let Ok(output) = engine_output(
+        engine,
+        &["container", "inspect", "-f", "{{.State.Running}}", name],
+    ) {
         return output.trim() == "true";
     }
     false
 }
 
-fn ensure_container_running(name: &str) -> Result<(), String> {
-    if !container_exists(name) {
+fn ensure_container_running(engine: ContainerEngine, name: &str) -> Result<(), String> {
+    if !container_exists(engine, name) {
         return Err(format!("workspace not found: {name}"));
     }
 
-    if container_running(name) {
+    if container_running(engine, name) {
         return Ok(());
     }
 
-    docker_status(&["start", name])
+    if let Some(api) = engine_api(engine) {
+        return api.start_container(name);
+    }
+    engine_status(engine, &["start", name])
 }
 
-fn resolve_container(name: &str) -> Result<Option<String>, String> {
+fn resolve_container(engine: ContainerEngine, name: &str) -> Result<Option<String>, String> {
     let cleaned = match trimmed_nonempty(name) {
         Some(name) => name,
         None => return Ok(None),
     };
 
-    if container_exists(&cleaned) {
+    if container_exists(engine, &cleaned) {
         return Ok(Some(cleaned));
     }
 
     let prefixes = workspace_prefixes();
     for candidate in workspace_resolution_candidates(&cleaned, &prefixes) {
-        if container_exists(&candidate) {
+        if container_exists(engine, &candidate) {
             return Ok(Some(candidate));
         }
 
         let normalized = normalize_container_name(&candidate);
-        if container_exists(&normalized) {
+        if container_exists(engine, &normalized) {
             return Ok(Some(normalized));
         }
     }
@@ -2050,42 +1645,61 @@ fn resolve_container(name: &str) -> Result<Option<String>, String> {
     Ok(None)
 }
 
-fn resolve_container_for_auth(name: Option<&str>) -> Result<String, String> {
-    if let Some(name) = name.and_then(trimmed_nonempty) {
-        return match resolve_container(&name)? {
-            Some(container) => Ok(container),
-            None => Err(format!("workspace not found: {name}")),
-        };
+/// Local and in-container rsync are both required: the container side runs over the wrapper.
+fn ensure_container_rsync(
+    engine: ContainerEngine,
+    workspace: &str,
+    user: &str,
+) -> Result<(), String> {
+    if !command_exists("rsync") {
+        return Err(String::from("rsync not found on host"));
     }
 
-    let workspaces = list_workspace_containers()?;
-    match workspaces.as_slice() {
-        [] => Err(String::from("no workspaces found")),
-        [single] => Ok(single.clone()),
-        _ => Err(format!(
-            "multiple workspaces found; specify one: {}",
-            workspaces.join(", ")
-        )),
+    let rsync_available = engine
+        .command()
+        .args(["exec", "-u", user, workspace, "rsync", "--version"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false);
+    if !rsync_available {
+        return Err(format!(
+            "rsync not available in container: {workspace} (user: {user}); install rsync in the container image"
+        ));
     }
+    Ok(())
 }
 
-fn resolve_container_for_rsync(name: Option<&str>) -> Result<String, String> {
-    if let Some(name) = name.and_then(trimmed_nonempty) {
-        return match resolve_container(&name)? {
-            Some(container) => Ok(container),
-            None => Err(format!("workspace not found: {name}")),
-        };
+fn rsync_through_wrapper(
+    engine: ContainerEngine,
+    workspace: &str,
+    transfer: &RsyncTransfer,
+    wrapper: &Path,
+) -> Result<(), String> {
+    let mut argv = transfer.rsync_flags();
+    argv.push(String::from("-e"));
+    argv.push(wrapper.display().to_string());
+    match transfer.direction {
+        RsyncDirection::Push => {
+            argv.push(transfer.src.clone());
+            argv.push(format!("{workspace}:{}", transfer.dest));
+        }
+        RsyncDirection::Pull => {
+            argv.push(format!("{workspace}:{}", transfer.src));
+            argv.push(transfer.dest.clone());
+        }
     }
 
-    let workspaces = list_workspace_containers()?;
-    match workspaces.as_slice() {
-        [] => Err(String::from("no workspaces found")),
-        [single] => Ok(single.clone()),
-        _ => Err(format!(
-            "multiple workspaces found; specify one: {}",
-            workspaces.join(", ")
-        )),
-    }
+    run_rsync(
+        &argv,
+        &[
+            ("AGENT_WORKSPACE_RSYNC_CONTAINER", workspace),
+            ("AGENT_WORKSPACE_RSYNC_USER", &transfer.user),
+            ("AGENT_WORKSPACE_RSYNC_ENGINE", engine.binary()),
+        ],
+        transfer,
+    )
 }
 
 fn create_rsync_rsh_wrapper() -> Result<PathBuf, String> {
@@ -2096,6 +1710,8 @@ fn create_rsync_rsh_wrapper() -> Result<PathBuf, String> {
         .map(|duration| duration.as_nanos())
         .unwrap_or(0);
 
+    sweep_stale_rsync_wrappers(&base);
+
     for attempt in 0..16u32 {
         let path = base.join(format!(
             "agent-workspace-rsync-rsh-{pid}-{now}-{attempt}.sh"
@@ -2113,6 +1729,31 @@ fn create_rsync_rsh_wrapper() -> Result<PathBuf, String> {
     Err(String::from("failed to create temporary rsync wrapper"))
 }
 
+/// Remove wrappers whose launcher process is gone (e.g. an interrupted `rsync push --watch`).
+///
+/// Liveness is read from `/proc`, so this is a no-op where `/proc` is unavailable.
+fn sweep_stale_rsync_wrappers(base: &Path) {
+    if !Path::new("/proc/self").exists() {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(base) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(pid) = name
+            .strip_prefix("agent-workspace-rsync-rsh-")
+            .and_then(|rest| rest.split('-').next())
+            .filter(|pid| !pid.is_empty() && pid.chars().all(|ch| ch.is_ascii_digit()))
+        else {
+            continue;
+        };
+        if !Path::new("/proc").join(pid).exists() {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
 #[cfg(unix)]
 fn set_executable_permissions(path: &PathBuf) {
     use std::os::unix::fs::PermissionsExt;
@@ -2123,8 +1764,9 @@ fn set_executable_permissions(path: &PathBuf) {
 #[cfg(not(unix))]
 fn set_executable_permissions(_path: &PathBuf) {}
 
-fn ensure_image(image: &str, pull: bool) -> Result<(), String> {
-    let inspect = Command::new("docker")
+fn ensure_image(engine: ContainerEngine, image: &str, pull: bool) -> Result<(), String> {
+    let inspect = engine
+        .command()
         .args(["image", "inspect", image])
         .stdout(Stdio::null())
         .stderr(Stdio::null())
@@ -2140,13 +1782,14 @@ fn ensure_image(image: &str, pull: bool) -> Result<(), String> {
         ));
     }
 
-    docker_status(&["pull", image])
+    engine_status(engine, &["pull", image])
 }
 
 fn create_workspace_container(
+    engine: ContainerEngine,
     container: &str,
     image: &str,
-    primary_repo: Option<&RepoSpec>,
+    request: &NewWorkspace<'_>,
 ) -> Result<(), String> {
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -2154,44 +1797,148 @@ fn create_workspace_container(
         .unwrap_or(0)
         .to_string();
 
+    let mut labels = vec![
+        WORKSPACE_LABEL.to_string(),
+        format!("agent-kit.created-at={timestamp}"),
+    ];
+    if let Some(repo) = request.primary_repo {
+        labels.push(format!("agent-kit.repo={}", repo.owner_repo));
+    }
+    if let Some(timeout) = request.idle_timeout {
+        labels.push(format!("{IDLE_TIMEOUT_LABEL}={timeout}"));
+    }
+    let env_names: Vec<&str> = request.env.iter().map(|(name, _)| name.as_str()).collect();
+    for (label, values) in [
+        (ENV_LABEL, env_names.join(",")),
+        (MOUNTS_LABEL, request.mounts.join(",")),
+        (PUBLISH_LABEL, request.publish.join(",")),
+        (CPUS_LABEL, request.cpus.unwrap_or_default().to_string()),
+        (MEMORY_LABEL, request.memory.unwrap_or_default().to_string()),
+    ] {
+        if !values.is_empty() {
+            labels.push(format!("{label}={values}"));
+        }
+    }
+
+    let mut env_args: Vec<String> = [
+        "HOME=/home/agent",
+        "AGENT_HOME=/home/agent/.agents",
+        "CODEX_AUTH_FILE=/home/agent/.agents/auth.json",
+        "ZSH_KIT_DIR=/home/agent/.config/zsh",
+        "AGENT_KIT_DIR=/home/agent/.agents",
+        "ZDOTDIR=/home/agent/.config/zsh",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect();
+    env_args.extend(
+        request
+            .env
+            .iter()
+            .map(|(name, value)| format!("{name}={value}")),
+    );
+
+    run_workspace_container(
+        engine,
+        container,
+        image,
+        &labels,
+        &env_args,
+        engine.default_userns().as_deref(),
+    )
+}
+
+fn label_value(labels: &[String], key: &str) -> Option<String> {
+    labels.iter().find_map(|label| {
+        label
+            .strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix('='))
+            .and_then(trimmed_nonempty)
+    })
+}
+
+fn set_label(labels: &mut Vec<String>, key: &str, value: &str) {
+    labels.retain(|label| label.split_once('=').map(|(name, _)| name) != Some(key));
+    labels.push(format!("{key}={value}"));
+}
+
+/// The image's first repo digest, or `image_id` itself for a local-only image.
+fn image_digest(engine: ContainerEngine, image_id: &str) -> String {
+    inspect_json(engine, "image", image_id)
+        .unwrap_or_default()
+        .as_ref()
+        .and_then(|image| image.get("RepoDigests"))
+        .and_then(JsonValue::as_array)
+        .and_then(|digests| digests.first())
+        .and_then(JsonValue::as_str)
+        .map_or_else(|| image_id.to_string(), str::to_string)
+}
+
+/// `run` flags for the `create` options recorded in `labels`.
+fn container_option_args(labels: &[String]) -> Vec<String> {
+    let mut args = Vec::new();
+    for label in labels {
+        let Some((key, value)) = label.split_once('=') else {
+            continue;
+        };
+        let values = value.split(',').filter(|value| !value.is_empty());
+        match key {
+            MOUNTS_LABEL => {
+                for mount in values {
+                    args.extend([String::from("-v"), mount.to_string()]);
+                }
+            }
+            PUBLISH_LABEL => {
+                for publish in values {
+                    args.extend([String::from("-p"), publish.to_string()]);
+                }
+            }
+            CPUS_LABEL => args.push(format!("--cpus={value}")),
+            MEMORY_LABEL => args.push(format!("--memory={value}")),
+            _ => {}
+        }
+    }
+    args
+}
+
+/// `run -d` a workspace container on its three `volume_names` volumes, plus the `create`
+/// options recorded in `labels`.
+fn run_workspace_container(
+    engine: ContainerEngine,
+    container: &str,
+    image: &str,
+    labels: &[String],
+    env: &[String],
+    userns: Option<&str>,
+) -> Result<(), String> {
     let (vol_work, vol_home, vol_codex) = volume_names(container);
-    let mut cmd = Command::new("docker");
+    let mut cmd = engine.command();
     cmd.arg("run")
         .arg("-d")
         .arg("--name")
         .arg(container)
         .arg("--hostname")
-        .arg(container)
-        .arg("--label")
-        .arg(WORKSPACE_LABEL)
-        .arg("--label")
-        .arg(format!("agent-kit.created-at={timestamp}"))
-        .arg("-e")
-        .arg("HOME=/home/agent")
-        .arg("-e")
-        .arg("AGENT_HOME=/home/agent/.agents")
-        .arg("-e")
-        .arg("CODEX_AUTH_FILE=/home/agent/.agents/auth.json")
-        .arg("-e")
-        .arg("ZSH_KIT_DIR=/home/agent/.config/zsh")
-        .arg("-e")
-        .arg("AGENT_KIT_DIR=/home/agent/.agents")
-        .arg("-e")
-        .arg("ZDOTDIR=/home/agent/.config/zsh")
-        .arg("-v")
+        .arg(container);
+    for label in labels {
+        cmd.arg("--label").arg(label);
+    }
+    for entry in env {
+        cmd.arg("-e").arg(entry);
+    }
+    cmd.arg("-v")
         .arg(format!("{vol_work}:/work"))
         .arg("-v")
         .arg(format!("{vol_home}:/home/agent"))
         .arg("-v")
         .arg(format!("{vol_codex}:/home/agent/.agents"))
+        .args(container_option_args(labels))
         .arg("-w")
         .arg("/work")
         .arg("--entrypoint")
         .arg("bash");
 
-    if let Some(repo) = primary_repo {
-        cmd.arg("--label")
-            .arg(format!("agent-kit.repo={}", repo.owner_repo));
+    if let Some(userns) = userns {
+        cmd.arg(format!("--userns={userns}"));
     }
 
     cmd.arg(image).arg("-lc").arg("sleep infinity");
@@ -2209,7 +1956,8 @@ fn create_workspace_container(
         return Err(stderr);
     }
 
-    let _ = Command::new("docker")
+    let _ = engine
+        .command()
         .args([
             "exec",
             "-u",
@@ -2226,13 +1974,311 @@ fn create_workspace_container(
     Ok(())
 }
 
+/// Refuse a target container name that is taken, or whose volumes already exist.
+fn ensure_workspace_name_free(engine: ContainerEngine, container: &str) -> Result<(), String> {
+    if container_exists(engine, container) {
+        return Err(format!("workspace already exists: {container}"));
+    }
+    let (work, home, agent_home) = volume_names(container);
+    for volume in [&work, &home, &agent_home] {
+        if volume_exists(engine, volume) {
+            return Err(format!("volume already exists: {volume}"));
+        }
+    }
+    Ok(())
+}
+
+/// How a workspace container was started, read back from its `container inspect` output.
+struct ContainerSpec {
+    image: String,
+    image_id: String,
+    labels: Vec<String>,
+    /// Env that `create` added; the image's own env is re-applied by the image.
+    env: Vec<String>,
+    userns: Option<String>,
+}
+
+fn container_spec(
+    engine: ContainerEngine,
+    info: &JsonValue,
+    container: &str,
+) -> Result<ContainerSpec, String> {
+    let text = |path: &[&str]| {
+        info.pointer(path)
+            .and_then(JsonValue::as_str)
+            .and_then(trimmed_nonempty)
+    };
+    let image = text(&["Config", "Image"])
+        .ok_or_else(|| format!("failed to read the image of {container}"))?;
+    let image_id = text(&["Image"]).unwrap_or_else(|| image.clone());
+    let userns = text(&["HostConfig", "UsernsMode"]).or_else(|| engine.default_userns());
+
+    let labels: Vec<String> = match info.pointer(&["Config", "Labels"]) {
+        Some(JsonValue::Object(entries)) => entries
+            .iter()
+            .filter_map(|(key, value)| Some(format!("{key}={}", value.as_str()?)))
+            .collect(),
+        _ => vec![WORKSPACE_LABEL.to_string()],
+    };
+
+    let strings = |value: Option<&JsonValue>| -> Vec<String> {
+        value
+            .and_then(JsonValue::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(JsonValue::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let image_env = inspect_json(engine, "image", &image_id)
+        .unwrap_or_default()
+        .map(|image| strings(image.pointer(&["Config", "Env"])))
+        .unwrap_or_default();
+    let env: Vec<String> = strings(info.pointer(&["Config", "Env"]))
+        .into_iter()
+        .filter(|entry| !image_env.contains(entry))
+        .collect();
+
+    Ok(ContainerSpec {
+        image,
+        image_id,
+        labels,
+        env,
+        userns,
+    })
+}
+
+/// Drop the values of the `create --env` entries named by the `agent-kit.env` label, keeping
+/// the names so `restore` can say which ones to pass again.
+fn redact_create_env(env: Vec<String>, labels: &[String]) -> Vec<String> {
+    let names = label_value(labels, ENV_LABEL).unwrap_or_default();
+    let names: Vec<&str> = names.split(',').collect();
+    env.into_iter()
+        .map(|entry| match entry.split_once('=') {
+            Some((name, _)) if names.contains(&name) => name.to_string(),
+            _ => entry,
+        })
+        .collect()
+}
+
+/// Labels and env for a restored container: binds and published ports come only from
+/// `options`, and env names saved without a value are filled from `options.env` or dropped.
+fn restored_options(
+    manifest: &SnapshotManifest,
+    options: &RestoreOptions<'_>,
+) -> (Vec<String>, Vec<String>) {
+    let mut labels = manifest.labels.clone();
+    for (label, flag, given) in [
+        (MOUNTS_LABEL, "--mount", options.mounts),
+        (PUBLISH_LABEL, "--publish", options.publish),
+    ] {
+        if let Some(saved) = label_value(&labels, label)
+            && given.is_empty()
+        {
+            eprintln!(
+                "warn: not reapplying {flag} {saved} from the snapshot; pass {flag} to restore to keep it"
+            );
+        }
+        labels.retain(|entry| entry.split_once('=').map(|(key, _)| key) != Some(label));
+        if !given.is_empty() {
+            labels.push(format!("{label}={}", given.join(",")));
+        }
+    }
+
+    let given_value = |name: &str| options.env.iter().any(|(given, _)| given == name);
+    let mut missing = Vec::new();
+    let mut env: Vec<String> = Vec::new();
+    for entry in &manifest.env {
+        let name = entry
+            .split_once('=')
+            .map_or(entry.as_str(), |(name, _)| name);
+        if given_value(name) {
+            continue;
+        }
+        if entry.contains('=') {
+            env.push(entry.clone());
+        } else {
+            missing.push(name);
+        }
+    }
+    env.extend(
+        options
+            .env
+            .iter()
+            .map(|(name, value)| format!("{name}={value}")),
+    );
+    if !missing.is_empty() {
+        eprintln!(
+            "warn: the snapshot has no values for {}; pass --env NAME=value to restore to set them",
+            missing.join(", ")
+        );
+    }
+
+    let mut names: Vec<String> = label_value(&labels, ENV_LABEL)
+        .unwrap_or_default()
+        .split(',')
+        .filter(|name| !name.is_empty() && !missing.contains(name))
+        .map(str::to_string)
+        .collect();
+    for (name, _) in options.env {
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+    if names.is_empty() {
+        labels.retain(|entry| entry.split_once('=').map(|(key, _)| key) != Some(ENV_LABEL));
+    } else {
+        set_label(&mut labels, ENV_LABEL, &names.join(","));
+    }
+    (labels, env)
+}
+
+/// Copy the volumes of `from` into fresh volumes for `to` and start `to` on them with the
+/// same image, labels, env, and userns; volumes cannot be renamed and mounts are fixed at run.
+/// `created_at` marks a clone: it replaces the `agent-kit.created-at` label, and published
+/// ports are dropped because the running source still holds them.
+fn recreate_workspace_container(
+    engine: ContainerEngine,
+    info: &JsonValue,
+    from: &str,
+    to: &str,
+    created_at: Option<&str>,
+) -> Result<(), String> {
+    let mut spec = container_spec(engine, info, from)?;
+
+    let (old_work, old_home, old_agent_home) = volume_names(from);
+    let (new_work, new_home, new_agent_home) = volume_names(to);
+    for (source, destination) in [
+        (old_work, new_work),
+        (old_home, new_home),
+        (old_agent_home, new_agent_home),
+    ] {
+        run_volume_helper(
+            engine,
+            &spec.image_id,
+            spec.userns.as_deref(),
+            &[format!("{source}:/from"), format!("{destination}:/to")],
+            "cp -a /from/. /to/",
+            Stdio::null(),
+            Stdio::piped(),
+        )
+        .map_err(|err| format!("failed to copy volume {source}: {err}"))?;
+    }
+
+    if let Some(created_at) = created_at {
+        for label in &mut spec.labels {
+            if label.starts_with("agent-kit.created-at=") {
+                *label = format!("agent-kit.created-at={created_at}");
+            }
+        }
+        let before = spec.labels.len();
+        spec.labels
+            .retain(|label| !label.starts_with(&format!("{PUBLISH_LABEL}=")));
+        if spec.labels.len() != before {
+            eprintln!("warn: published ports are not copied to {to}; the source keeps them");
+        }
+    }
+    run_workspace_container(
+        engine,
+        to,
+        &spec.image,
+        &spec.labels,
+        &spec.env,
+        spec.userns.as_deref(),
+    )
+}
+
+/// `run --rm -i` a root `bash -c <script>` helper on `image` with `mounts` (`-v` values),
+/// wiring its stdin/stdout to the given handles; used to copy and stream workspace volumes.
+fn run_volume_helper(
+    engine: ContainerEngine,
+    image: &str,
+    userns: Option<&str>,
+    mounts: &[String],
+    script: &str,
+    stdin: Stdio,
+    stdout: Stdio,
+) -> Result<(), String> {
+    let mut cmd = engine.command();
+    cmd.args(["run", "--rm", "-i", "--user", "0:0"]);
+    for mount in mounts {
+        cmd.arg("-v").arg(mount);
+    }
+    if let Some(userns) = userns {
+        cmd.arg(format!("--userns={userns}"));
+    }
+    cmd.args(["--entrypoint", "bash", image, "-c", script]);
+
+    let output = cmd
+        .stdin(stdin)
+        .stdout(stdout)
+        .output()
+        .map_err(|err| format!("failed to run {} run: {err}", engine.binary()))?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if stderr.is_empty() {
+        Err(format!(
+            "{} run failed (exit {})",
+            engine.binary(),
+            output.status.code().unwrap_or(EXIT_RUNTIME)
+        ))
+    } else {
+        Err(stderr)
+    }
+}
+
+/// Fill the `volume_names` volumes of `container` from the part tarballs of an unpacked snapshot.
+fn restore_snapshot_volumes(
+    engine: ContainerEngine,
+    staging: &Path,
+    container: &str,
+    image: &str,
+    userns: Option<&str>,
+) -> Result<(), String> {
+    let (work, home, agent_home) = volume_names(container);
+    for ((part, _), volume) in SNAPSHOT_PARTS.iter().zip([work, home, agent_home]) {
+        let tarball = part_path(staging, part);
+        let file = fs::File::open(&tarball)
+            .map_err(|err| format!("failed to open {}: {err}", tarball.display()))?;
+        run_volume_helper(
+            engine,
+            image,
+            userns,
+            &[format!("{volume}:/to")],
+            "tar -C /to -xpf -",
+            Stdio::from(file),
+            Stdio::piped(),
+        )
+        .map_err(|err| format!("failed to restore volume {volume}: {err}"))?;
+    }
+    Ok(())
+}
+
+fn volume_exists(engine: ContainerEngine, volume: &str) -> bool {
+    engine
+        .command()
+        .args(["volume", "inspect", volume])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
 fn clone_repo_into_container(
+    engine: ContainerEngine,
     container: &str,
     repo: &RepoSpec,
     destination: &str,
-    refspec: Option<&str>,
+    options: &CloneOptions<'_>,
 ) -> Result<(), String> {
-    let mut cmd = Command::new("docker");
+    let mut cmd = engine.command();
     cmd.arg("exec");
 
     if let Ok(token) = std::env::var("GH_TOKEN")
@@ -2253,7 +2299,8 @@ fn clone_repo_into_container(
         .arg("--")
         .arg(&repo.clone_url)
         .arg(destination)
-        .arg(refspec.unwrap_or(""));
+        .arg(options.refspec.unwrap_or(""))
+        .args(options.clone_flags());
 
     let output = cmd
         .output()
@@ -2275,16 +2322,16 @@ fn clone_repo_into_container(
     }
 }
 
-fn sync_container_baseline(container: &str) -> Result<(), String> {
-    let mut cmd = Command::new("docker");
+fn sync_container_baseline(engine: ContainerEngine, container: &str) -> Result<(), String> {
+    let mut cmd = engine.command();
     cmd.arg("exec");
 
-    for env_name in [
-        "AGENT_WORKSPACE_ZSH_KIT_REPO",
-        "AGENT_WORKSPACE_AGENT_KIT_REPO",
-        "AGENT_WORKSPACE_NILS_CLI_FORMULA",
+    for (key, env_name) in [
+        ("zsh_kit_repo", "AGENT_WORKSPACE_ZSH_KIT_REPO"),
+        ("agent_kit_repo", "AGENT_WORKSPACE_AGENT_KIT_REPO"),
+        ("nils_cli_formula", "AGENT_WORKSPACE_NILS_CLI_FORMULA"),
     ] {
-        if let Ok(value) = std::env::var(env_name)
+        if let Some(value) = crate::config::value(key)
             && !value.trim().is_empty()
         {
             cmd.arg("-e").arg(format!("{env_name}={value}"));
@@ -2318,216 +2365,13 @@ fn sync_container_baseline(container: &str) -> Result<(), String> {
     }
 }
 
-fn run_auth_github(container: &str, host: Option<&str>) -> i32 {
-    let gh_host = host
-        .and_then(trimmed_nonempty)
-        .or_else(|| std::env::var("GITHUB_HOST").ok())
-        .filter(|value| !value.trim().is_empty())
-        .unwrap_or_else(|| String::from("github.com"));
-
-    let auth_mode = std::env::var("AGENT_WORKSPACE_AUTH")
-        .ok()
-        .filter(|value| !value.trim().is_empty())
-        .or_else(|| std::env::var("CODEX_WORKSPACE_AUTH").ok())
-        .unwrap_or_else(|| String::from("auto"));
-
-    let env_token = std::env::var("GH_TOKEN")
-        .ok()
-        .filter(|value| !value.trim().is_empty())
-        .or_else(|| {
-            std::env::var("GITHUB_TOKEN")
-                .ok()
-                .filter(|value| !value.trim().is_empty())
-        });
-
-    let keyring_token = gh_keyring_token(&gh_host);
-
-    let (chosen_token, chosen_source) = match auth_mode.as_str() {
-        "none" => (None, "none"),
-        "env" => (env_token, "env"),
-        "gh" | "keyring" => {
-            if let Some(token) = keyring_token {
-                (Some(token), "gh")
-            } else {
-                eprintln!(
-                    "warn: AGENT_WORKSPACE_AUTH={auth_mode} but no gh keyring token found; falling back to GH_TOKEN/GITHUB_TOKEN"
-                );
-                (env_token, "env")
-            }
-        }
-        "auto" | "" => {
-            if let Some(token) = keyring_token {
-                (Some(token), "gh")
-            } else {
-                (env_token, "env")
-            }
-        }
-        _ => {
-            eprintln!(
-                "error: unknown AGENT_WORKSPACE_AUTH={auth_mode} (expected: auto|gh|env|none)"
-            );
-            return EXIT_RUNTIME;
-        }
-    };
-
-    let token = if let Some(token) = chosen_token {
-        token
-    } else {
-        if auth_mode == "none" {
-            eprintln!("error: AGENT_WORKSPACE_AUTH=none; no token to apply");
-        } else {
-            eprintln!("error: no GitHub token found (gh keyring or GH_TOKEN/GITHUB_TOKEN)");
-        }
-        eprintln!("hint: run 'gh auth login' or export GH_TOKEN/GITHUB_TOKEN");
-        return EXIT_RUNTIME;
-    };
-
-    let content = format!("host={gh_host}\ntoken={token}\n");
-    let target = "/home/agent/.agents/auth/github.env";
-    if let Err(err) = write_container_file(container, target, content.as_bytes()) {
-        eprintln!("error: failed to write GitHub auth file in container: {err}");
-        return EXIT_RUNTIME;
-    }
-
-    println!(
-        "auth: github -> {} ({gh_host}; source={chosen_source})",
-        container
-    );
-    0
-}
-
-fn run_auth_codex(container: &str, profile_arg: Option<&str>) -> i32 {
-    let profile = profile_arg
-        .and_then(trimmed_nonempty)
-        .or_else(|| {
-            std::env::var("AGENT_WORKSPACE_CODEX_PROFILE")
-                .ok()
-                .and_then(|value| trimmed_nonempty(&value))
-        })
-        .or_else(|| {
-            std::env::var("CODEX_WORKSPACE_CODEX_PROFILE")
-                .ok()
-                .and_then(|value| trimmed_nonempty(&value))
-        });
-
-    if let Some(profile) = profile.as_deref()
-        && (profile.contains('/')
-            || profile.contains("..")
-            || profile.chars().any(char::is_whitespace))
-    {
-        eprintln!("error: invalid codex profile name: {profile}");
-        return EXIT_RUNTIME;
-    }
-
-    let mut candidate_files: Vec<String> = Vec::new();
-    if let Some(profile) = profile.as_deref() {
-        candidate_files.extend(resolve_codex_profile_auth_files(profile));
-    }
-    candidate_files.push(resolve_codex_auth_file());
-
-    for candidate in candidate_files {
-        if !std::path::Path::new(&candidate).is_file() {
-            continue;
-        }
-
-        let auth_data = match std::fs::read(&candidate) {
-            Ok(data) => data,
-            Err(err) => {
-                eprintln!(
-                    "warn: failed to read codex auth candidate {}: {err}",
-                    candidate
-                );
-                continue;
-            }
-        };
-
-        let mut wrote_any_target = false;
-        for target in [
-            "/home/agent/.codex/auth.json",
-            "/home/agent/.agents/auth.json",
-        ] {
-            if let Err(err) = write_container_file(container, target, &auth_data) {
-                eprintln!(
-                    "warn: failed to sync codex auth into container target {}: {err}",
-                    target
-                );
-                continue;
-            }
-            wrote_any_target = true;
-        }
-
-        if !wrote_any_target {
-            eprintln!(
-                "warn: failed to sync codex auth into any known target for source={}",
-                candidate
-            );
-            continue;
-        }
-
-        if let Some(profile) = profile.as_deref() {
-            println!(
-                "auth: codex -> {} (profile={profile}; source={})",
-                container, candidate
-            );
-        } else {
-            println!("auth: codex -> {} (source={})", container, candidate);
-        }
-        return 0;
-    }
-
-    eprintln!("error: unable to resolve codex auth file");
-    eprintln!("hint: set CODEX_AUTH_FILE or pass --profile <name>");
-    EXIT_RUNTIME
-}
-
-fn run_auth_gpg(container: &str, key_arg: Option<&str>) -> i32 {
-    let key = key_arg
-        .and_then(trimmed_nonempty)
-        .or_else(default_gpg_signing_key);
-
-    let key = if let Some(key) = key {
-        key
-    } else {
-        eprintln!("error: missing gpg signing key");
-        eprintln!("hint: pass --key <fingerprint> or set AGENT_WORKSPACE_GPG_KEY");
-        eprintln!("hint: or set: git config --global user.signingkey <keyid>");
-        return EXIT_RUNTIME;
-    };
-
-    let mut verify = Command::new("docker");
-    verify
-        .arg("exec")
-        .arg(container)
-        .arg("bash")
-        .arg("-lc")
-        .arg("if command -v gpg >/dev/null 2>&1; then gpg --batch --list-secret-keys \"$1\" >/dev/null 2>&1; else exit 0; fi")
-        .arg("--")
-        .arg(&key);
-
-    match verify.status() {
-        Ok(status) if status.success() => {}
-        Ok(_) => {
-            eprintln!("error: gpg key not found in container keyring: {key}");
-            return EXIT_RUNTIME;
-        }
-        Err(err) => {
-            eprintln!("error: failed to run gpg key lookup in container: {err}");
-            return EXIT_RUNTIME;
-        }
-    }
-
-    let target = "/home/agent/.agents/auth/gpg-key.txt";
-    if let Err(err) = write_container_file(container, target, format!("{key}\n").as_bytes()) {
-        eprintln!("error: failed to write gpg auth file in container: {err}");
-        return EXIT_RUNTIME;
-    }
-
-    println!("auth: gpg -> {} (key={key})", container);
-    0
-}
-
-fn write_container_file(container: &str, path: &str, contents: &[u8]) -> Result<(), String> {
-    let mut child = Command::new("docker")
+fn write_container_file(
+    engine: ContainerEngine,
+    container: &str,
+    path: &str,
+    contents: &[u8],
+) -> Result<(), String> {
+    let mut child = engine.command()
         .arg("exec")
         .arg("-i")
         .arg(container)
@@ -2564,26 +2408,13 @@ fn write_container_file(container: &str, path: &str, contents: &[u8]) -> Result<
     }
 }
 
-fn gh_keyring_token(host: &str) -> Option<String> {
-    if !command_exists("gh") {
-        return None;
-    }
-
-    let output = Command::new("gh")
-        .args(["auth", "token", "-h", host])
-        .env_remove("GH_TOKEN")
-        .env_remove("GITHUB_TOKEN")
-        .output()
-        .ok()?;
-    if !output.status.success() {
-        return None;
-    }
-
-    trimmed_nonempty(String::from_utf8_lossy(&output.stdout).as_ref())
-}
-
-fn reset_repo_in_container(container: &str, repo_dir: &str, refspec: &str) -> Result<(), String> {
-    let mut cmd = Command::new("docker");
+fn reset_repo_in_container(
+    engine: ContainerEngine,
+    container: &str,
+    repo_dir: &str,
+    refspec: &str,
+) -> Result<(), String> {
+    let mut cmd = engine.command();
     cmd.arg("exec")
         .arg(container)
         .arg("bash")
@@ -2608,33 +2439,45 @@ fn reset_repo_in_container(container: &str, repo_dir: &str, refspec: &str) -> Re
 }
 
 fn list_git_repos_in_container(
+    engine: ContainerEngine,
     container: &str,
     root: &str,
     depth: u32,
 ) -> Result<Vec<String>, String> {
-    let mut cmd = Command::new("docker");
-    cmd.arg("exec")
-        .arg(container)
-        .arg("bash")
-        .arg("-lc")
-        .arg(LIST_GIT_REPOS_SCRIPT)
-        .arg("--")
-        .arg(root)
-        .arg(depth.to_string());
-
-    let output = cmd
-        .output()
-        .map_err(|err| format!("failed to list git repos in container: {err}"))?;
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-        return if stderr.is_empty() {
-            Err(String::from("failed to list git repos in container"))
-        } else {
-            Err(stderr)
-        };
-    }
+    let depth = depth.to_string();
+    let argv = ["bash", "-lc", LIST_GIT_REPOS_SCRIPT, "--", root, &depth];
+
+    let stdout = if let Some(output) = engine_api_exec(engine, container, &argv) {
+        let output = output?;
+        if output.exit_code != 0 {
+            let stderr = output.stderr.trim();
+            return if stderr.is_empty() {
+                Err(String::from("failed to list git repos in container"))
+            } else {
+                Err(stderr.to_string())
+            };
+        }
+        output.stdout
+    } else {
+        let output = engine
+            .command()
+            .arg("exec")
+            .arg(container)
+            .args(argv)
+            .output()
+            .map_err(|err| format!("failed to list git repos in container: {err}"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return if stderr.is_empty() {
+                Err(String::from("failed to list git repos in container"))
+            } else {
+                Err(stderr)
+            };
+        }
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
 
-    let mut repos: Vec<String> = String::from_utf8_lossy(&output.stdout)
+    let mut repos: Vec<String> = stdout
         .lines()
         .map(str::trim)
         .filter(|line| !line.is_empty())
@@ -2644,14 +2487,28 @@ fn list_git_repos_in_container(
     Ok(repos)
 }
 
-fn detect_private_repo_dir(container: &str) -> Result<Option<String>, String> {
-    let output = docker_output(&[
-        "exec",
-        container,
-        "bash",
-        "-lc",
-        "set -euo pipefail; find -L /work/private -maxdepth 4 -mindepth 2 \\( -type d -o -type f \\) -name .git -print 2>/dev/null | head -n 1",
-    ])?;
+fn detect_private_repo_dir(
+    engine: ContainerEngine,
+    container: &str,
+) -> Result<Option<String>, String> {
+    let script = "set -euo pipefail; find -L /work/private -maxdepth 4 -mindepth 2 \\( -type d -o -type f \\) -name .git -print 2>/dev/null | head -n 1";
+    let output = match engine_api_exec(engine, container, &["bash", "-lc", script]) {
+        Some(Ok(output)) if output.exit_code == 0 => output.stdout,
+        Some(Err(err)) => return Err(err),
+        Some(Ok(output)) => {
+            let stderr = output.stderr.trim();
+            return Err(if stderr.is_empty() {
+                format!(
+                    "{} exec {container} failed (exit {})",
+                    engine.binary(),
+                    output.exit_code
+                )
+            } else {
+                stderr.to_string()
+            });
+        }
+        None => engine_output(engine, &["exec", container, "bash", "-lc", script])?,
+    };
 
     let entry = output.trim();
     if entry.is_empty() {
@@ -2675,6 +2532,38 @@ fn map_container_repo_path(raw: &str, default_root: &str) -> String {
     format!("{root}/{without_prefix}")
 }
 
+fn remove_workspace_volumes(engine: ContainerEngine, container: &str) {
+    let (work, home, codex) = volume_names(container);
+    match engine {
+        ContainerEngine::Docker => {
+            if let Some(api) = engine_api(engine) {
+                for volume in [&work, &home, &codex] {
+                    if let Err(err) = api.remove_volume(volume) {
+                        eprintln!("warn: {err}");
+                    }
+                }
+                return;
+            }
+            let _ = engine_status(engine, &["volume", "rm", &work, &home, &codex]);
+        }
+        ContainerEngine::Podman => {
+            // `podman volume rm` aborts the whole batch when one name is missing,
+            // so remove each volume on its own and skip the ones that never existed.
+            for volume in [work, home, codex] {
+                let exists = engine
+                    .command()
+                    .args(["volume", "exists", &volume])
+                    .status()
+                    .map(|status| status.success())
+                    .unwrap_or(false);
+                if exists {
+                    let _ = engine_status(engine, &["volume", "rm", "--force", &volume]);
+                }
+            }
+        }
+    }
+}
+
 fn volume_names(container: &str) -> (String, String, String) {
     (
         format!("{container}-work"),
@@ -2715,89 +2604,3 @@ fn default_tunnel_name(container: &str) -> String {
 
     sanitize_tunnel_name(&candidate)
 }
-
-fn print_create_usage() {
-    eprintln!(
-        "usage: {PRIMARY_COMMAND_NAME} create [--runtime <container|host>] [--name <workspace>] [--image <image>] [--ref <git-ref>] [--private-repo OWNER/REPO] [--no-work-repos] [--no-extras] [--no-pull] [repo] [extra_repos...]"
-    );
-}
-
-fn print_ls_usage() {
-    eprintln!(
-        "usage: {PRIMARY_COMMAND_NAME} ls [--runtime <container|host>] [--json|--output json]"
-    );
-}
-
-fn print_rsync_usage() {
-    eprintln!("usage:");
-    eprintln!(
-        "  {PRIMARY_COMMAND_NAME} rsync push [--runtime container] [--user <user>|--root] [--delete] [--dry-run] [<workspace>] <host_src> <container_dest> [<rsync_args...>]"
-    );
-    eprintln!(
-        "  {PRIMARY_COMMAND_NAME} rsync pull [--runtime container] [--user <user>|--root] [--delete] [--dry-run] [<workspace>] <container_src> <host_dest> [<rsync_args...>]"
-    );
-}
-
-fn print_exec_usage() {
-    eprintln!(
-        "usage: {PRIMARY_COMMAND_NAME} exec [--runtime <container|host>] [--root|--user <user>] <workspace> [command ...]"
-    );
-}
-
-fn print_rm_usage() {
-    eprintln!(
-        "usage: {PRIMARY_COMMAND_NAME} rm [--runtime <container|host>] [--all] [--yes] [--keep-volumes] <workspace>"
-    );
-}
-
-fn print_tunnel_usage() {
-    println!("usage:");
-    println!(
-        "  {PRIMARY_COMMAND_NAME} tunnel [--runtime <container|host>] <workspace> [--name <tunnel_name>] [--detach] [--output json]"
-    );
-}
-
-fn print_auth_usage() {
-    eprintln!("usage:");
-    eprintln!("  {PRIMARY_COMMAND_NAME} auth codex [--profile <name>] [--container <workspace>]");
-    eprintln!("  {PRIMARY_COMMAND_NAME} auth github [--host <host>] [--container <workspace>]");
-    eprintln!(
-        "  {PRIMARY_COMMAND_NAME} auth gpg [--key <keyid|fingerprint>] [--container <workspace>]"
-    );
-}
-
-fn print_reset_usage() {
-    eprintln!("usage:");
-    eprintln!(
-        "  {PRIMARY_COMMAND_NAME} reset repo <workspace> <repo_dir> [--ref <remote/branch>] [--yes]"
-    );
-    eprintln!(
-        "  {PRIMARY_COMMAND_NAME} reset work-repos <workspace> [--root <path>] [--depth <n>] [--ref <remote/branch>] [--yes]"
-    );
-    eprintln!("  {PRIMARY_COMMAND_NAME} reset opt-repos <workspace> [--yes]");
-    eprintln!(
-        "  {PRIMARY_COMMAND_NAME} reset private-repo <workspace> [--ref <remote/branch>] [--yes]"
-    );
-}
-
-fn print_reset_repo_usage() {
-    eprintln!(
-        "usage: {PRIMARY_COMMAND_NAME} reset repo <workspace> <repo_dir> [--ref <remote/branch>] [--yes]"
-    );
-}
-
-fn print_reset_work_repos_usage() {
-    eprintln!(
-        "usage: {PRIMARY_COMMAND_NAME} reset work-repos <workspace> [--root <path>] [--depth <n>] [--ref <remote/branch>] [--yes]"
-    );
-}
-
-fn print_reset_opt_repos_usage() {
-    eprintln!("usage: {PRIMARY_COMMAND_NAME} reset opt-repos <workspace> [--yes]");
-}
-
-fn print_reset_private_repo_usage() {
-    eprintln!(
-        "usage: {PRIMARY_COMMAND_NAME} reset private-repo <workspace> [--ref <remote/branch>] [--yes]"
-    );
-}
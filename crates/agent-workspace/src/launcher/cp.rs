@@ -0,0 +1,149 @@
+use std::ffi::OsString;
+
+use crate::EXIT_RUNTIME;
+
+use super::backend::{CopyRequest, WorkspaceBackend};
+use super::{PRIMARY_COMMAND_NAME, resolve_ready_workspace};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(super) struct ParsedCp {
+    pub(super) show_help: bool,
+    /// Workspace named by the `<workspace>:<path>` operand; empty for `:<path>`.
+    pub(super) workspace: String,
+    /// `true` for `<local> <workspace>:<path>`.
+    pub(super) into_workspace: bool,
+    pub(super) request: Option<CopyRequest>,
+}
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_cp_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_cp_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    let Some(request) = parsed.request.as_ref().filter(|_| !parsed.show_help) else {
+        print_cp_usage();
+        return 0;
+    };
+
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    let workspace = match resolve_ready_workspace(backend, Some(&parsed.workspace)) {
+        Ok(workspace) => workspace,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+
+    let result = if parsed.into_workspace {
+        backend
+            .copy_in(&workspace, request)
+            .map(|target| format!("{} -> {workspace}:{target}", request.src))
+    } else {
+        backend
+            .copy_out(&workspace, request)
+            .map(|target| format!("{workspace}:{} -> {target}", request.src))
+    };
+    match result {
+        Ok(summary) => {
+            println!("copied: {summary}");
+            0
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            EXIT_RUNTIME
+        }
+    }
+}
+
+pub(super) fn parse_cp_args(args: &[OsString]) -> Result<ParsedCp, String> {
+    let mut parsed = ParsedCp::default();
+    let mut operands: Vec<String> = Vec::new();
+
+    for arg in args {
+        let arg = arg.to_string_lossy();
+        match arg.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(format!("unknown option: {arg}"));
+            }
+            _ => operands.push(arg.into_owned()),
+        }
+    }
+
+    if parsed.show_help || operands.is_empty() {
+        parsed.show_help = true;
+        return Ok(parsed);
+    }
+    let [src, dest] = operands.as_slice() else {
+        return Err(format!(
+            "expected <src> <dest>, got {} operand(s)",
+            operands.len()
+        ));
+    };
+
+    let (src, dest) = match (split_workspace_operand(src), split_workspace_operand(dest)) {
+        (Some((workspace, path)), None) => {
+            parsed.workspace = workspace;
+            (path, dest.clone())
+        }
+        (None, Some((workspace, path))) => {
+            parsed.workspace = workspace;
+            parsed.into_workspace = true;
+            (src.clone(), path)
+        }
+        (Some(_), Some(_)) => {
+            return Err(String::from(
+                "copying between workspaces is not supported; one side must be a local path",
+            ));
+        }
+        (None, None) => {
+            return Err(String::from(
+                "one side must be <workspace>:<path> (use ./name:x for a local path with a colon)",
+            ));
+        }
+    };
+    if src.is_empty() || dest.is_empty() {
+        return Err(String::from("empty path in cp operand"));
+    }
+
+    // `dir/.` copies the contents of `dir` rather than `dir` itself, as with `docker cp`.
+    let (src, contents_only) = match src.strip_suffix("/.") {
+        Some("") => (String::from("/"), true),
+        Some(dir) => (dir.to_string(), true),
+        None => (src, false),
+    };
+    parsed.request = Some(CopyRequest {
+        src,
+        dest,
+        contents_only,
+    });
+    Ok(parsed)
+}
+
+/// `<workspace>:<path>` -> `(workspace, path)`; anything with a `/` before the first `:` is local.
+fn split_workspace_operand(operand: &str) -> Option<(String, String)> {
+    let (workspace, path) = operand.split_once(':')?;
+    if workspace.contains('/') {
+        return None;
+    }
+    Some((workspace.to_string(), path.to_string()))
+}
+
+fn print_cp_usage() {
+    eprintln!("usage:");
+    eprintln!(
+        "  {PRIMARY_COMMAND_NAME} cp [--runtime container|podman|host|ssh://host] <workspace>:<src_path> <local_dest>"
+    );
+    eprintln!(
+        "  {PRIMARY_COMMAND_NAME} cp [--runtime container|podman|host|ssh://host] <local_src> <workspace>:<dest_path>"
+    );
+}
@@ -1,15 +1,20 @@
 use std::ffi::OsString;
 use std::fs;
 use std::path::Path;
-use std::process::Command;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::EXIT_RUNTIME;
 
+use super::backend::{CloneOptions, ExecRequest, NewWorkspace, RepoSlot, WorkspaceBackend};
+use super::hooks::{Hook, run_hook};
+use super::manifest::{WorkspaceManifest, fetch_manifest, is_valid_env_name, manifest_runtime};
 use super::{
-    PRIMARY_COMMAND_NAME, RepoSpec, WORKSPACE_META_FILE, command_exists, ensure_workspace_root,
-    generate_workspace_name, normalize_workspace_name_for_create, parse_repo_spec, slugify_name,
-    trimmed_nonempty, workspace_repo_destination,
+    PRIMARY_COMMAND_NAME, RepoSpec, backend_for, generate_workspace_name,
+    normalize_workspace_name_for_create, parse_duration, parse_repo_spec, slugify_name,
+    trimmed_nonempty,
 };
 
 #[derive(Debug, Default, Clone)]
@@ -22,8 +27,45 @@ pub(super) struct ParsedCreate {
     primary_repo: Option<String>,
     extra_repos: Vec<String>,
     ignored_options: Vec<String>,
+    image: Option<String>,
+    no_pull: bool,
+    worktree: bool,
+    refspec: Option<String>,
+    no_manifest: bool,
+    /// `--trust-manifest`: allow manifest `env` and `post_create` outside a container.
+    trust_manifest: bool,
+    idle_timeout: Option<u64>,
+    /// `--env` entries, applied after `--env-file` and the repo manifest.
+    env: Vec<(String, String)>,
+    env_files: Vec<String>,
+    mounts: Vec<String>,
+    publish: Vec<String>,
+    cpus: Option<String>,
+    memory: Option<String>,
+    /// `--jobs`: repos cloned at once.
+    jobs: Option<usize>,
+    depth: Option<u32>,
+    filter: Option<String>,
+    single_branch: bool,
+    sparse: Vec<String>,
 }
 
+/// Repos cloned at once when `--jobs` is not given.
+const DEFAULT_CLONE_JOBS: usize = 4;
+
+/// Value flags handled by `apply_clone_mode_option`.
+const CLONE_MODE_FLAGS: [&str; 3] = ["--depth", "--filter", "--sparse"];
+
+/// Value flags handled by `apply_container_option`; all but `--env`/`--env-file` are container-only.
+const CONTAINER_OPTION_FLAGS: [&str; 6] = [
+    "--env",
+    "--env-file",
+    "--mount",
+    "--publish",
+    "--cpus",
+    "--memory",
+];
+
 pub(super) fn parse_create_args(args: &[OsString]) -> Result<ParsedCreate, String> {
     let mut parsed = ParsedCreate::default();
     let mut idx = 0usize;
@@ -50,6 +92,31 @@ pub(super) fn parse_create_args(args: &[OsString]) -> Result<ParsedCreate, Strin
                     idx += 1;
                     continue;
                 }
+                "--no-pull" => {
+                    parsed.no_pull = true;
+                    idx += 1;
+                    continue;
+                }
+                "--worktree" => {
+                    parsed.worktree = true;
+                    idx += 1;
+                    continue;
+                }
+                "--single-branch" => {
+                    parsed.single_branch = true;
+                    idx += 1;
+                    continue;
+                }
+                "--no-manifest" => {
+                    parsed.no_manifest = true;
+                    idx += 1;
+                    continue;
+                }
+                "--trust-manifest" => {
+                    parsed.trust_manifest = true;
+                    idx += 1;
+                    continue;
+                }
                 "--private-repo" => {
                     idx += 1;
                     if idx >= args.len() {
@@ -70,6 +137,80 @@ pub(super) fn parse_create_args(args: &[OsString]) -> Result<ParsedCreate, Strin
                     idx += 1;
                     continue;
                 }
+                "--image" => {
+                    idx += 1;
+                    if idx >= args.len() {
+                        return Err(String::from("missing value for --image"));
+                    }
+                    parsed.image = trimmed_nonempty(args[idx].to_string_lossy().as_ref());
+                    idx += 1;
+                    continue;
+                }
+                "--ref" => {
+                    idx += 1;
+                    if idx >= args.len() {
+                        return Err(String::from("missing value for --ref"));
+                    }
+                    parsed.refspec = trimmed_nonempty(args[idx].to_string_lossy().as_ref());
+                    idx += 1;
+                    continue;
+                }
+                "--jobs" => {
+                    idx += 1;
+                    if idx >= args.len() {
+                        return Err(format!("missing value for {text}"));
+                    }
+                    parsed.jobs = Some(parse_jobs(&args[idx].to_string_lossy())?);
+                    idx += 1;
+                    continue;
+                }
+                "--idle-timeout" => {
+                    idx += 1;
+                    if idx >= args.len() {
+                        return Err(String::from("missing value for --idle-timeout"));
+                    }
+                    parsed.idle_timeout = Some(parse_duration(&args[idx].to_string_lossy())?);
+                    idx += 1;
+                    continue;
+                }
+                _ if CLONE_MODE_FLAGS.contains(&text.as_str()) => {
+                    idx += 1;
+                    if idx >= args.len() {
+                        return Err(format!("missing value for {text}"));
+                    }
+                    let value = args[idx].to_string_lossy().into_owned();
+                    apply_clone_mode_option(&mut parsed, &text, &value)?;
+                    idx += 1;
+                    continue;
+                }
+                _ if CLONE_MODE_FLAGS
+                    .iter()
+                    .any(|flag| text.starts_with(&format!("{flag}="))) =>
+                {
+                    let (flag, value) = text.split_once('=').unwrap_or_default();
+                    apply_clone_mode_option(&mut parsed, flag, value)?;
+                    idx += 1;
+                    continue;
+                }
+                _ if CONTAINER_OPTION_FLAGS.contains(&text.as_str()) => {
+                    idx += 1;
+                    if idx >= args.len() {
+                        return Err(format!("missing value for {text}"));
+                    }
+                    let value = args[idx].to_string_lossy().into_owned();
+                    apply_container_option(&mut parsed, &text, &value)?;
+                    idx += 1;
+                    continue;
+                }
+                _ if CONTAINER_OPTION_FLAGS
+                    .iter()
+                    .any(|flag| text.starts_with(&format!("{flag}="))) =>
+                {
+                    let (flag, value) = text.split_once('=').unwrap_or_default();
+                    apply_container_option(&mut parsed, flag, value)?;
+                    idx += 1;
+                    continue;
+                }
                 "--" => {
                     positional_only = true;
                     idx += 1;
@@ -87,6 +228,26 @@ pub(super) fn parse_create_args(args: &[OsString]) -> Result<ParsedCreate, Strin
                     idx += 1;
                     continue;
                 }
+                _ if text.starts_with("--image=") => {
+                    parsed.image = trimmed_nonempty(text["--image=".len()..].trim());
+                    idx += 1;
+                    continue;
+                }
+                _ if text.starts_with("--ref=") => {
+                    parsed.refspec = trimmed_nonempty(text["--ref=".len()..].trim());
+                    idx += 1;
+                    continue;
+                }
+                _ if text.starts_with("--jobs=") => {
+                    parsed.jobs = Some(parse_jobs(&text["--jobs=".len()..])?);
+                    idx += 1;
+                    continue;
+                }
+                _ if text.starts_with("--idle-timeout=") => {
+                    parsed.idle_timeout = Some(parse_duration(&text["--idle-timeout=".len()..])?);
+                    idx += 1;
+                    continue;
+                }
                 _ if text.starts_with('-') => {
                     parsed.ignored_options.push(text);
                     idx += 1;
@@ -107,11 +268,326 @@ pub(super) fn parse_create_args(args: &[OsString]) -> Result<ParsedCreate, Strin
     if parsed.no_work_repos && (parsed.primary_repo.is_some() || !parsed.extra_repos.is_empty()) {
         return Err(String::from("--no-work-repos does not accept repo args"));
     }
+    if parsed.worktree
+        && (parsed.depth.is_some()
+            || parsed.filter.is_some()
+            || parsed.single_branch
+            || !parsed.sparse.is_empty())
+    {
+        return Err(String::from(
+            "--worktree cannot be combined with --depth, --filter, --single-branch, or --sparse",
+        ));
+    }
 
     Ok(parsed)
 }
 
-pub(super) fn run(args: &[OsString]) -> i32 {
+fn apply_container_option(
+    parsed: &mut ParsedCreate,
+    flag: &str,
+    value: &str,
+) -> Result<(), String> {
+    match flag {
+        "--env" => parsed
+            .env
+            .push(parse_env_entry(value).map_err(|err| format!("--env: {err}"))?),
+        "--env-file" => parsed.env_files.push(value.to_string()),
+        "--mount" => parsed.mounts.push(parse_mount(value)?),
+        "--publish" => parsed.publish.push(parse_publish(value)?),
+        "--cpus" => {
+            if !value.parse::<f64>().is_ok_and(|cpus| cpus > 0.0) {
+                return Err(format!(
+                    "invalid --cpus value (expected a positive number): {value}"
+                ));
+            }
+            parsed.cpus = Some(value.to_string());
+        }
+        _ => {
+            let digits = value.trim_end_matches(['b', 'k', 'm', 'g', 'B', 'K', 'M', 'G']);
+            if digits.is_empty()
+                || value.len() - digits.len() > 1
+                || !digits.chars().all(|ch| ch.is_ascii_digit())
+            {
+                return Err(format!(
+                    "invalid --memory value (expected e.g. 512m or 8g): {value}"
+                ));
+            }
+            parsed.memory = Some(value.to_lowercase());
+        }
+    }
+    Ok(())
+}
+
+fn apply_clone_mode_option(
+    parsed: &mut ParsedCreate,
+    flag: &str,
+    value: &str,
+) -> Result<(), String> {
+    match flag {
+        "--depth" => match value.trim().parse::<u32>() {
+            Ok(depth) if depth > 0 => parsed.depth = Some(depth),
+            _ => {
+                return Err(format!(
+                    "invalid --depth value (expected a positive integer): {value}"
+                ));
+            }
+        },
+        "--filter" => {
+            let filter = value.trim();
+            if !filter.contains(':') || filter.contains(char::is_whitespace) {
+                return Err(format!(
+                    "invalid --filter value (expected e.g. blob:none or tree:0): {value}"
+                ));
+            }
+            parsed.filter = Some(filter.to_string());
+        }
+        _ => {
+            for path in value
+                .split(',')
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+            {
+                let path = path.trim_end_matches('/');
+                if path.is_empty()
+                    || path.starts_with(['/', '-'])
+                    || path.split('/').any(|part| part == "..")
+                {
+                    return Err(format!(
+                        "invalid --sparse path (expected a repo-relative directory): {path}"
+                    ));
+                }
+                if !parsed.sparse.iter().any(|existing| existing == path) {
+                    parsed.sparse.push(path.to_string());
+                }
+            }
+            if parsed.sparse.is_empty() {
+                return Err(String::from("--sparse requires at least one path"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_jobs(value: &str) -> Result<usize, String> {
+    match value.trim().parse::<usize>() {
+        Ok(jobs) if jobs > 0 => Ok(jobs),
+        _ => Err(format!(
+            "invalid --jobs value (expected a positive integer): {value}"
+        )),
+    }
+}
+
+/// One repo `create` checks out; `primary` failures fail the command.
+pub(super) struct CloneJob<'a> {
+    pub(super) spec: RepoSpec,
+    pub(super) slot: RepoSlot,
+    /// Per-repo ref from the manifest, over `--ref`.
+    pub(super) refspec: Option<&'a str>,
+    pub(super) primary: bool,
+}
+
+pub(super) struct CloneResult {
+    pub(super) outcome: Result<String, String>,
+    pub(super) elapsed: Duration,
+}
+
+/// Clone `jobs` with up to `parallel` at a time, printing a progress line as each starts and
+/// ends; results come back in job order.
+pub(super) fn clone_repos(
+    backend: &dyn WorkspaceBackend,
+    workspace: &str,
+    jobs: &[CloneJob<'_>],
+    options: &CloneOptions<'_>,
+    parallel: usize,
+) -> Vec<CloneResult> {
+    let total = jobs.len();
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<CloneResult>>> = Mutex::new(jobs.iter().map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..parallel.clamp(1, total.max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let idx = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(job) = jobs.get(idx) else {
+                        break;
+                    };
+                    let label = format!("[{}/{total}] {}", idx + 1, job.spec.owner_repo);
+                    if total > 1 {
+                        eprintln!("clone: {label} ...");
+                    }
+                    let started = Instant::now();
+                    let options = CloneOptions {
+                        refspec: job.refspec.or(options.refspec),
+                        ..*options
+                    };
+                    let outcome = backend.clone_repo(workspace, &job.spec, job.slot, &options);
+                    let elapsed = started.elapsed();
+                    if total > 1 {
+                        let status = if outcome.is_ok() { "done" } else { "failed" };
+                        eprintln!("clone: {label} {status} ({:.1}s)", elapsed.as_secs_f64());
+                    }
+                    if let Ok(mut results) = results.lock() {
+                        results[idx] = Some(CloneResult { outcome, elapsed });
+                    }
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap_or_else(|poison| poison.into_inner())
+        .into_iter()
+        .map(|result| {
+            result.unwrap_or_else(|| CloneResult {
+                outcome: Err(String::from("clone did not run")),
+                elapsed: Duration::ZERO,
+            })
+        })
+        .collect()
+}
+
+fn print_clone_summary(jobs: &[CloneJob<'_>], results: &[CloneResult]) {
+    let repo_width = jobs
+        .iter()
+        .map(|job| job.spec.owner_repo.len())
+        .max()
+        .unwrap_or(0)
+        .max("REPO".len());
+    eprintln!(
+        "{:<repo_width$}  {:<7}  {:<6}  {:>6}  DETAIL",
+        "REPO", "SLOT", "STATUS", "TIME"
+    );
+    for (job, result) in jobs.iter().zip(results) {
+        let slot = match (job.slot, job.primary) {
+            (_, true) => "primary",
+            (RepoSlot::Private, _) => "private",
+            (RepoSlot::Work, _) => "work",
+        };
+        let (status, detail) = match &result.outcome {
+            Ok(destination) => ("ok", destination.as_str()),
+            Err(err) => ("failed", err.lines().next().unwrap_or_default()),
+        };
+        eprintln!(
+            "{:<repo_width$}  {slot:<7}  {status:<6}  {:>5.1}s  {detail}",
+            job.spec.owner_repo,
+            result.elapsed.as_secs_f64()
+        );
+    }
+}
+
+/// Later entries replace earlier ones of the same name in place.
+fn merge_env(env: &mut Vec<(String, String)>, entries: impl IntoIterator<Item = (String, String)>) {
+    for (name, value) in entries {
+        match env.iter_mut().find(|(existing, _)| *existing == name) {
+            Some(entry) => entry.1 = value,
+            None => env.push((name, value)),
+        }
+    }
+}
+
+/// `NAME=value`, or a bare `NAME` taken from the launcher's own environment (as `docker run -e`).
+pub(super) fn parse_env_entry(entry: &str) -> Result<(String, String), String> {
+    let (name, value) = match entry.split_once('=') {
+        Some((name, value)) => (name, value.to_string()),
+        None => (
+            entry,
+            std::env::var(entry).map_err(|_| format!("{entry} is not set in the environment"))?,
+        ),
+    };
+    if !is_valid_env_name(name) {
+        return Err(format!("invalid variable name: {name}"));
+    }
+    if value.contains('\n') {
+        return Err(format!("{name}: value must be a single line"));
+    }
+    Ok((name.to_string(), value))
+}
+
+/// `docker run --env-file` format: one entry per line, blank lines and `#` comments skipped.
+fn read_env_file_entries(path: &str) -> Result<Vec<(String, String)>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("failed to read env file {path}: {err}"))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(idx, line)| {
+            parse_env_entry(line.trim_start()).map_err(|err| format!("{path}:{}: {err}", idx + 1))
+        })
+        .collect()
+}
+
+/// `/host/path:/container/path[:ro|:rw]`; a relative host path is taken from the current
+/// directory and must exist, so the engine does not create it as an empty root-owned dir.
+pub(super) fn parse_mount(value: &str) -> Result<String, String> {
+    let invalid = || format!("invalid --mount value (expected /host/path:/path[:ro]): {value}");
+    let mut parts = value.split(':');
+    let (Some(source), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    let mode = parts.next();
+    if source.is_empty()
+        || !target.starts_with('/')
+        || parts.next().is_some()
+        || mode.is_some_and(|mode| mode != "ro" && mode != "rw")
+    {
+        return Err(invalid());
+    }
+    let target = target.trim_end_matches('/');
+    if matches!(target, "" | "/work" | "/home/agent" | "/home/agent/.agents") {
+        return Err(format!(
+            "--mount cannot replace a workspace volume: {}",
+            if target.is_empty() { "/" } else { target }
+        ));
+    }
+
+    let source = std::path::absolute(Path::new(source))
+        .map_err(|err| format!("invalid --mount source {source}: {err}"))?;
+    if !source.exists() {
+        return Err(format!("--mount source not found: {}", source.display()));
+    }
+    let source = source.display().to_string();
+    if source.contains([':', ',']) {
+        return Err(format!(
+            "--mount source cannot contain ':' or ',': {source}"
+        ));
+    }
+    Ok(match mode {
+        Some(mode) => format!("{source}:{target}:{mode}"),
+        None => format!("{source}:{target}"),
+    })
+}
+
+/// `[ip:][host_port:]container_port[/tcp|/udp]`, as `docker run --publish`; ports may be ranges.
+pub(super) fn parse_publish(value: &str) -> Result<String, String> {
+    let is_ports = |text: &str| {
+        let mut bounds = text.splitn(2, '-');
+        bounds.all(|port| port.parse::<u16>().is_ok_and(|port| port > 0))
+    };
+    let (ports, proto) = match value.rsplit_once('/') {
+        Some((ports, proto)) => (ports, Some(proto)),
+        None => (value, None),
+    };
+    let mut fields = ports.rsplitn(3, ':');
+    let container_port = fields.next().unwrap_or_default();
+    let host_port = fields.next();
+    if !is_ports(container_port)
+        || host_port.is_some_and(|port| !port.is_empty() && !is_ports(port))
+        || proto.is_some_and(|proto| !matches!(proto, "tcp" | "udp" | "sctp"))
+        || value.contains(',')
+    {
+        return Err(format!(
+            "invalid --publish value (expected [ip:]host_port:container_port): {value}"
+        ));
+    }
+    Ok(value.to_string())
+}
+
+/// `runtime_pinned` is set when `--runtime`, env, or the config file chose the runtime, so a
+/// repo manifest may not override it.
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString], runtime_pinned: bool) -> i32 {
     let parsed = match parse_create_args(args) {
         Ok(parsed) => parsed,
         Err(err) => {
@@ -126,14 +602,8 @@ pub(super) fn run(args: &[OsString]) -> i32 {
         return 0;
     }
 
-    if !parsed.ignored_options.is_empty() {
-        eprintln!(
-            "warn: ignoring unsupported create options in host-native mode: {}",
-            parsed.ignored_options.join(" ")
-        );
-    }
-
-    let default_host = std::env::var("GITHUB_HOST").unwrap_or_else(|_| String::from("github.com"));
+    let default_host =
+        crate::config::value("github_host").unwrap_or_else(|| String::from("github.com"));
 
     let primary_spec = if let Some(primary_repo) = parsed.primary_repo.as_deref() {
         match parse_repo_spec(primary_repo, &default_host) {
@@ -149,6 +619,112 @@ pub(super) fn run(args: &[OsString]) -> i32 {
         None
     };
 
+    let manifest = match primary_spec.as_ref() {
+        Some(spec) if !parsed.no_manifest => {
+            match fetch_manifest(spec, parsed.refspec.as_deref()) {
+                Ok(Some((path, manifest))) => {
+                    eprintln!("info: applying {path} from {}", spec.owner_repo);
+                    manifest
+                }
+                Ok(None) => WorkspaceManifest::default(),
+                Err(err) => {
+                    eprintln!("warn: skipping repo manifest: {err}");
+                    WorkspaceManifest::default()
+                }
+            }
+        }
+        _ => WorkspaceManifest::default(),
+    };
+    if !manifest.ignored_keys.is_empty() {
+        eprintln!(
+            "warn: ignoring unsupported manifest keys: {}",
+            manifest.ignored_keys.join(" ")
+        );
+    }
+
+    let manifest_backend;
+    let backend = match manifest.runtime.as_deref() {
+        Some(raw) if !runtime_pinned => match manifest_runtime(raw) {
+            Ok(runtime) => {
+                manifest_backend = backend_for(runtime);
+                manifest_backend.as_ref()
+            }
+            Err(err) => {
+                eprintln!("warn: ignoring manifest runtime: {err}");
+                backend
+            }
+        },
+        _ => backend,
+    };
+
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    // Outside a container, manifest env is exported by every `exec` (`BASH_ENV`, `PATH`, ...),
+    // so it needs the same trust as `post_create`.
+    if (!manifest.post_create.is_empty() || !manifest.env.is_empty())
+        && !backend.supports_images()
+        && !parsed.trust_manifest
+    {
+        eprintln!(
+            "error: the repo manifest sets env or post_create on the {} runtime, outside a container:",
+            backend.runtime_name()
+        );
+        for (name, value) in &manifest.env {
+            eprintln!("  env: {name}={value}");
+        }
+        for command in &manifest.post_create {
+            eprintln!("  post_create: {command}");
+        }
+        eprintln!("hint: review them, then retry with --trust-manifest (or --no-manifest)");
+        return EXIT_RUNTIME;
+    }
+
+    if parsed.worktree && !backend.supports_worktrees() {
+        eprintln!("error: --worktree is only available in host runtime");
+        eprintln!("hint: retry with '--runtime host'");
+        return EXIT_RUNTIME;
+    }
+
+    if parsed.idle_timeout.is_some() && !backend.supports_start_stop() {
+        eprintln!(
+            "warn: --idle-timeout is ignored by the {} runtime",
+            backend.runtime_name()
+        );
+    }
+
+    let has_container_options = !parsed.mounts.is_empty()
+        || !parsed.publish.is_empty()
+        || parsed.cpus.is_some()
+        || parsed.memory.is_some();
+    if has_container_options && !backend.supports_images() {
+        eprintln!(
+            "warn: --mount, --publish, --cpus, and --memory are ignored by the {} runtime",
+            backend.runtime_name()
+        );
+    }
+
+    let mut env = manifest.env.clone();
+    for path in &parsed.env_files {
+        match read_env_file_entries(path) {
+            Ok(entries) => merge_env(&mut env, entries),
+            Err(err) => {
+                eprintln!("error: {err}");
+                return EXIT_RUNTIME;
+            }
+        }
+    }
+    merge_env(&mut env, parsed.env.iter().cloned());
+
+    if !parsed.ignored_options.is_empty() {
+        eprintln!(
+            "warn: ignoring unsupported create options: {}",
+            parsed.ignored_options.join(" ")
+        );
+    }
+
     let mut workspace_name = parsed
         .workspace_name
         .clone()
@@ -163,51 +739,53 @@ pub(super) fn run(args: &[OsString]) -> i32 {
         workspace_name = generate_workspace_name();
     }
 
-    let root = match ensure_workspace_root() {
-        Ok(root) => root,
+    let image = parsed.image.as_deref().or(manifest
+        .image
+        .as_deref()
+        .filter(|_| backend.supports_images()));
+    let created = match backend.create_workspace(&NewWorkspace {
+        name: &workspace_name,
+        primary_repo: primary_spec.as_ref(),
+        image,
+        pull: !parsed.no_pull,
+        env: &env,
+        idle_timeout: parsed.idle_timeout,
+        mounts: &parsed.mounts,
+        publish: &parsed.publish,
+        cpus: parsed.cpus.as_deref(),
+        memory: parsed.memory.as_deref(),
+    }) {
+        Ok(created) => created,
         Err(err) => {
             eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
     };
 
-    let workspace_path = root.join(&workspace_name);
-    if workspace_path.exists() {
-        eprintln!("error: workspace already exists: {workspace_name}");
-        return EXIT_RUNTIME;
-    }
-
-    if let Err(err) =
-        create_workspace_skeleton(&workspace_path, &workspace_name, primary_spec.as_ref())
-    {
-        eprintln!("error: {err}");
-        return EXIT_RUNTIME;
-    }
-
+    let mut clone_jobs: Vec<CloneJob<'_>> = Vec::new();
     if !parsed.no_work_repos
-        && let Some(spec) = primary_spec.as_ref()
+        && let Some(spec) = primary_spec.clone()
     {
-        let destination = workspace_repo_destination(&workspace_path.join("work"), spec);
-        if let Err(err) = clone_repo_into(spec, &destination) {
-            eprintln!(
-                "error: failed to clone primary repo {}: {err}",
-                spec.owner_repo
-            );
-            return EXIT_RUNTIME;
-        }
+        clone_jobs.push(CloneJob {
+            spec,
+            slot: RepoSlot::Work,
+            refspec: None,
+            primary: true,
+        });
     }
-
     if !parsed.no_extras {
-        if let Some(private_repo_raw) = parsed.private_repo.as_deref() {
+        let private_repo = parsed
+            .private_repo
+            .as_deref()
+            .or(manifest.private_repo.as_deref());
+        if let Some(private_repo_raw) = private_repo {
             if let Some(spec) = parse_repo_spec(private_repo_raw, &default_host) {
-                let destination =
-                    workspace_repo_destination(&workspace_path.join("private"), &spec);
-                if let Err(err) = clone_repo_into(&spec, &destination) {
-                    eprintln!(
-                        "warn: failed to clone private repo {}: {err}",
-                        spec.owner_repo
-                    );
-                }
+                clone_jobs.push(CloneJob {
+                    spec,
+                    slot: RepoSlot::Private,
+                    refspec: None,
+                    primary: false,
+                });
             } else {
                 eprintln!(
                     "warn: invalid private repo (expected OWNER/REPO or URL): {private_repo_raw}"
@@ -215,110 +793,112 @@ pub(super) fn run(args: &[OsString]) -> i32 {
             }
         }
 
-        for extra_repo_raw in &parsed.extra_repos {
-            if let Some(spec) = parse_repo_spec(extra_repo_raw, &default_host) {
-                let destination = workspace_repo_destination(&workspace_path.join("work"), &spec);
-                if let Err(err) = clone_repo_into(&spec, &destination) {
-                    eprintln!(
-                        "warn: failed to clone extra repo {}: {err}",
-                        spec.owner_repo
-                    );
-                }
-            } else {
+        let mut cloned: Vec<String> = primary_spec
+            .iter()
+            .map(|spec| spec.owner_repo.clone())
+            .collect();
+        let extra_repos = manifest
+            .repos
+            .iter()
+            .map(|(raw, refspec)| (raw.as_str(), refspec.as_deref()))
+            .chain(parsed.extra_repos.iter().map(|raw| (raw.as_str(), None)));
+        for (extra_repo_raw, refspec) in extra_repos {
+            let Some(spec) = parse_repo_spec(extra_repo_raw, &default_host) else {
                 eprintln!("warn: invalid repo (expected OWNER/REPO or URL): {extra_repo_raw}");
+                continue;
+            };
+            if cloned.contains(&spec.owner_repo) {
+                continue;
             }
+            cloned.push(spec.owner_repo.clone());
+            clone_jobs.push(CloneJob {
+                spec,
+                slot: RepoSlot::Work,
+                refspec,
+                primary: false,
+            });
         }
     }
 
-    println!("workspace: {workspace_name}");
-    println!("path: {}", workspace_path.display());
-    0
-}
-
-fn create_workspace_skeleton(
-    workspace_path: &Path,
-    workspace_name: &str,
-    primary_repo: Option<&RepoSpec>,
-) -> Result<(), String> {
-    fs::create_dir_all(workspace_path).map_err(|err| {
-        format!(
-            "failed to create workspace directory {}: {err}",
-            workspace_path.display()
-        )
-    })?;
-
-    for subdir in ["work", "opt", "private", "auth", ".codex"] {
-        fs::create_dir_all(workspace_path.join(subdir)).map_err(|err| {
-            format!(
-                "failed to create workspace subdir {}: {err}",
-                workspace_path.join(subdir).display()
-            )
-        })?;
-    }
-
-    let created_unix = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_secs())
-        .unwrap_or(0);
-
-    let metadata = format!(
-        "name={workspace_name}\ncreated_unix={created_unix}\nprimary_repo={}\n",
-        primary_repo
-            .map(|repo| repo.owner_repo.as_str())
-            .unwrap_or("none")
-    );
-    fs::write(workspace_path.join(WORKSPACE_META_FILE), metadata).map_err(|err| {
-        format!(
-            "failed to write workspace metadata {}: {err}",
-            workspace_path.join(WORKSPACE_META_FILE).display()
-        )
-    })?;
-
-    Ok(())
-}
-
-fn clone_repo_into(repo: &RepoSpec, destination: &Path) -> Result<(), String> {
-    if destination.join(".git").is_dir() {
-        return Ok(());
+    let jobs = parsed.jobs.unwrap_or(DEFAULT_CLONE_JOBS);
+    let clone_options = CloneOptions {
+        refspec: parsed.refspec.as_deref(),
+        worktree: parsed.worktree,
+        quiet: jobs > 1 && clone_jobs.len() > 1,
+        depth: parsed.depth,
+        filter: parsed.filter.as_deref(),
+        single_branch: parsed.single_branch,
+        sparse: &parsed.sparse,
+    };
+    let results = clone_repos(backend, &created.name, &clone_jobs, &clone_options, jobs);
+    if clone_jobs.len() > 1 {
+        print_clone_summary(&clone_jobs, &results);
     }
 
-    if destination.exists() {
-        return Err(format!(
-            "destination exists but is not a git repo: {}",
-            destination.display()
-        ));
+    let mut report_path = created.path.clone();
+    for (job, result) in clone_jobs.iter().zip(&results) {
+        match (&result.outcome, job.primary) {
+            (Ok(destination), true) => report_path = destination.clone(),
+            (Err(err), true) => {
+                eprintln!(
+                    "error: failed to clone primary repo {}: {err}",
+                    job.spec.owner_repo
+                );
+                return EXIT_RUNTIME;
+            }
+            (Err(err), false) if clone_jobs.len() == 1 => {
+                eprintln!("warn: failed to clone {}: {err}", job.spec.owner_repo);
+            }
+            _ => {}
+        }
     }
 
-    if !command_exists("git") {
-        return Err(String::from("git not found in PATH"));
+    println!("workspace: {}", created.name);
+    println!("path: {report_path}");
+
+    for command in &manifest.post_create {
+        eprintln!("+ {command}");
+        let argv: Vec<OsString> = [
+            "bash",
+            "-lc",
+            r#"cd "$1" && eval "$2""#,
+            "post_create",
+            report_path.as_str(),
+            command.as_str(),
+        ]
+        .into_iter()
+        .map(OsString::from)
+        .collect();
+        let status = backend.exec(
+            &created.name,
+            &ExecRequest {
+                user: None,
+                command: &argv,
+            },
+        );
+        match status {
+            Ok(0) => {}
+            Ok(code) => {
+                eprintln!("error: post_create command failed (exit {code}): {command}");
+                return EXIT_RUNTIME;
+            }
+            Err(err) => {
+                eprintln!("error: post_create command failed: {err}");
+                return EXIT_RUNTIME;
+            }
+        }
     }
 
-    if let Some(parent) = destination.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|err| format!("failed to create clone parent {}: {err}", parent.display()))?;
+    if let Err(err) = run_hook(backend, &created.name, Hook::PostCreate) {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
     }
 
-    let status = Command::new("git")
-        .arg("clone")
-        .arg("--progress")
-        .arg(&repo.clone_url)
-        .arg(destination)
-        .status()
-        .map_err(|err| format!("failed to run git clone for {}: {err}", repo.owner_repo))?;
-
-    if status.success() {
-        Ok(())
-    } else {
-        Err(format!(
-            "git clone failed for {} (exit {})",
-            repo.owner_repo,
-            status.code().unwrap_or(EXIT_RUNTIME)
-        ))
-    }
+    0
 }
 
 fn print_create_usage() {
     eprintln!(
-        "usage: {PRIMARY_COMMAND_NAME} create [--name <workspace>] [--private-repo OWNER/REPO] [--no-work-repos] [--no-extras] [repo] [extra_repos...]"
+        "usage: {PRIMARY_COMMAND_NAME} create [--runtime <container|host|podman|ssh://host>] [--name <workspace>] [--image <image>] [--ref <git-ref>] [--private-repo OWNER/REPO] [--no-work-repos] [--no-extras] [--no-pull] [--worktree] [--no-manifest] [--trust-manifest] [--idle-timeout <duration>] [--jobs <n>] [--depth <n>] [--filter <spec>] [--single-branch] [--sparse <path,...>] [--env NAME=value] [--env-file <path>] [--mount /host/path:/path[:ro]] [--publish <host_port>:<port>] [--cpus <n>] [--memory <size>] [repo] [extra_repos...]"
     );
 }
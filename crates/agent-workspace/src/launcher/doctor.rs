@@ -0,0 +1,277 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::EXIT_RUNTIME;
+
+use super::auth::gh_keyring_token;
+use super::backend::{CheckStatus, DoctorCheck, WorkspaceBackend};
+use super::{
+    PRIMARY_COMMAND_NAME, command_exists, default_gpg_signing_key, json_escape, push_unique_path,
+    resolve_codex_auth_file, resolve_codex_profile_auth_files, trimmed_nonempty,
+};
+
+/// Host tools `doctor` reports, with what each one is needed for.
+const TOOLS: [(&str, &str); 5] = [
+    (
+        "git",
+        "install git; host workspaces and 'auth gpg' key lookup use it",
+    ),
+    (
+        "gh",
+        "install the GitHub CLI (https://cli.github.com) so 'auth github' can reuse its keyring token",
+    ),
+    (
+        "gpg",
+        "install GnuPG to export signing keys with 'auth gpg'",
+    ),
+    (
+        "code",
+        "install VS Code and its 'code' shell command to run 'tunnel' on the host runtime",
+    ),
+    ("rsync", "install rsync to use the 'rsync' subcommand"),
+];
+
+#[derive(Debug, Default, Clone)]
+pub(super) struct ParsedDoctor {
+    pub(super) show_help: bool,
+    pub(super) json: bool,
+}
+
+pub(super) fn parse_doctor_args(args: &[OsString]) -> Result<ParsedDoctor, String> {
+    let mut parsed = ParsedDoctor::default();
+    let mut idx = 0usize;
+
+    while idx < args.len() {
+        let arg = args[idx].to_string_lossy();
+        match arg.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            "--json" => parsed.json = true,
+            "--output" => {
+                idx += 1;
+                let Some(value) = args.get(idx) else {
+                    return Err(String::from("missing value for --output"));
+                };
+                parsed.json = parse_output(&value.to_string_lossy())?;
+            }
+            _ if arg.starts_with("--output=") => {
+                parsed.json = parse_output(&arg["--output=".len()..])?;
+            }
+            _ => return Err(format!("unexpected arg for doctor: {arg}")),
+        }
+        idx += 1;
+    }
+
+    Ok(parsed)
+}
+
+fn parse_output(value: &str) -> Result<bool, String> {
+    match value {
+        "json" => Ok(true),
+        "text" => Ok(false),
+        _ => Err(format!("unsupported --output value: {value}")),
+    }
+}
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_doctor_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_doctor_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if parsed.show_help {
+        print_doctor_usage();
+        return 0;
+    }
+
+    // No `ensure_available` here: a missing or unreachable runtime is one of the findings.
+    let mut checks = backend.doctor_checks();
+    checks.extend(TOOLS.iter().map(|(tool, hint)| tool_check(tool, hint)));
+    checks.push(github_token_check());
+    checks.push(codex_auth_check());
+    checks.push(gpg_key_check());
+
+    if parsed.json {
+        print_checks_json(backend.runtime_name(), &checks);
+    } else {
+        print_checks_text(backend.runtime_name(), &checks);
+    }
+
+    if checks.iter().any(|check| check.status == CheckStatus::Fail) {
+        EXIT_RUNTIME
+    } else {
+        0
+    }
+}
+
+fn tool_check(tool: &'static str, hint: &str) -> DoctorCheck {
+    if !command_exists(tool) {
+        return DoctorCheck::warn(tool, "not found in PATH", hint);
+    }
+
+    let version = Command::new(tool)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find_map(trimmed_nonempty)
+        });
+    match version {
+        Some(version) => DoctorCheck::ok(tool, version),
+        None => DoctorCheck::warn(
+            tool,
+            format!("'{tool} --version' failed"),
+            format!("reinstall {tool} or fix its PATH entry"),
+        ),
+    }
+}
+
+fn github_token_check() -> DoctorCheck {
+    let host = crate::config::value("github_host")
+        .and_then(|value| trimmed_nonempty(&value))
+        .unwrap_or_else(|| String::from("github.com"));
+
+    if gh_keyring_token(&host).is_some() {
+        return DoctorCheck::ok("github-token", format!("gh keyring token for {host}"));
+    }
+    let env_token = ["GH_TOKEN", "GITHUB_TOKEN"]
+        .into_iter()
+        .find(|name| std::env::var(name).is_ok_and(|value| !value.trim().is_empty()));
+    match env_token {
+        Some(name) => DoctorCheck::warn(
+            "github-token",
+            format!("no gh keyring token for {host}; 'auth github' falls back to {name}"),
+            format!("run 'gh auth login -h {host}' to use the keyring instead"),
+        ),
+        None => DoctorCheck::warn(
+            "github-token",
+            format!("no gh keyring token for {host} and GH_TOKEN/GITHUB_TOKEN are unset"),
+            format!("run 'gh auth login -h {host}' or export GH_TOKEN"),
+        ),
+    }
+}
+
+fn codex_auth_check() -> DoctorCheck {
+    let profile = crate::config::value("codex_profile").and_then(|value| trimmed_nonempty(&value));
+
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if let Some(profile) = profile.as_deref() {
+        for candidate in resolve_codex_profile_auth_files(profile) {
+            push_unique_path(&mut candidates, PathBuf::from(candidate));
+        }
+    }
+    push_unique_path(&mut candidates, PathBuf::from(resolve_codex_auth_file()));
+
+    match candidates.iter().find(|candidate| candidate.is_file()) {
+        Some(path) => match profile.as_deref() {
+            Some(profile) => DoctorCheck::ok(
+                "codex-auth",
+                format!("{} (profile={profile})", path.display()),
+            ),
+            None => DoctorCheck::ok("codex-auth", path.display().to_string()),
+        },
+        None => DoctorCheck::warn(
+            "codex-auth",
+            format!(
+                "no codex auth file found; checked: {}",
+                candidates
+                    .iter()
+                    .map(|candidate| candidate.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            "run 'codex login', or set CODEX_AUTH_FILE / AGENT_WORKSPACE_CODEX_PROFILE",
+        ),
+    }
+}
+
+fn gpg_key_check() -> DoctorCheck {
+    let Some(key) = default_gpg_signing_key() else {
+        return DoctorCheck::skip(
+            "gpg-key",
+            "no signing key configured (AGENT_WORKSPACE_GPG_KEY or git user.signingkey)",
+        );
+    };
+    if !command_exists("gpg") {
+        return DoctorCheck::fail(
+            "gpg-key",
+            format!("signing key {key} is configured but gpg is not installed"),
+            "install GnuPG or unset AGENT_WORKSPACE_GPG_KEY / git user.signingkey",
+        );
+    }
+
+    let found = Command::new("gpg")
+        .args(["--batch", "--list-secret-keys", &key])
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if found {
+        DoctorCheck::ok("gpg-key", format!("secret key {key} is in the keyring"))
+    } else {
+        DoctorCheck::fail(
+            "gpg-key",
+            format!("secret key {key} is not in the local keyring"),
+            "import it with 'gpg --import', or fix AGENT_WORKSPACE_GPG_KEY / git user.signingkey",
+        )
+    }
+}
+
+fn print_checks_text(runtime: &str, checks: &[DoctorCheck]) {
+    println!("runtime: {runtime}");
+    for check in checks {
+        println!(
+            "{:<6} {}: {}",
+            format!("[{}]", check.status.as_str()),
+            check.name,
+            check.detail
+        );
+        if let Some(hint) = check.hint.as_deref() {
+            println!("       hint: {hint}");
+        }
+    }
+
+    let count = |status: CheckStatus| checks.iter().filter(|check| check.status == status).count();
+    let (failed, warned) = (count(CheckStatus::Fail), count(CheckStatus::Warn));
+    if failed == 0 && warned == 0 {
+        println!("doctor: all checks passed");
+    } else {
+        println!("doctor: {failed} failed, {warned} warnings");
+    }
+}
+
+fn print_checks_json(runtime: &str, checks: &[DoctorCheck]) {
+    let items: Vec<String> = checks
+        .iter()
+        .map(|check| {
+            format!(
+                "{{\"name\":\"{}\",\"status\":\"{}\",\"detail\":\"{}\",\"hint\":{}}}",
+                json_escape(check.name),
+                check.status.as_str(),
+                json_escape(&check.detail),
+                check.hint.as_deref().map_or_else(
+                    || String::from("null"),
+                    |hint| format!("\"{}\"", json_escape(hint))
+                ),
+            )
+        })
+        .collect();
+    let ok = !checks.iter().any(|check| check.status == CheckStatus::Fail);
+
+    println!(
+        "{{\"runtime\":\"{}\",\"ok\":{ok},\"checks\":[{}]}}",
+        json_escape(runtime),
+        items.join(",")
+    );
+}
+
+fn print_doctor_usage() {
+    eprintln!(
+        "usage: {PRIMARY_COMMAND_NAME} doctor [--runtime <container|host|podman|ssh://host>] [--output text|json]"
+    );
+}
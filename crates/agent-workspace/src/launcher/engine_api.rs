@@ -0,0 +1,533 @@
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::json::{JsonValue, parse_json};
+use super::{json_escape, trimmed_nonempty};
+
+const DEFAULT_DOCKER_SOCKET: &str = "/var/run/docker.sock";
+const API_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Minimal Docker Engine API client speaking HTTP/1.1 over the daemon unix socket.
+///
+/// Only the handful of endpoints the launcher needs for hot paths (inspect, list,
+/// start, captured exec, volume removal) are covered; everything interactive still
+/// goes through the `docker` CLI.
+#[derive(Debug, Clone)]
+pub(super) struct EngineApi {
+    socket: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ApiResponse {
+    status: u16,
+    body: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct ExecOutput {
+    pub(super) exit_code: i64,
+    pub(super) stdout: String,
+    pub(super) stderr: String,
+}
+
+/// Why `EngineApi::exec` returned no output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum ExecError {
+    /// Connecting or creating the exec failed, so the command never ran and may be retried.
+    NotStarted(String),
+    /// The exec was started; retrying could run the command twice.
+    Started(String),
+}
+
+/// One container from `GET /containers/json`, with the launcher's labels pulled out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct ContainerSummary {
+    pub(super) name: String,
+    pub(super) image: Option<String>,
+    pub(super) state: Option<String>,
+    pub(super) repo: Option<String>,
+    pub(super) created_at: Option<String>,
+}
+
+impl ApiResponse {
+    fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    fn json(&self) -> Result<JsonValue, String> {
+        parse_json(&String::from_utf8_lossy(&self.body))
+            .map_err(|err| format!("invalid docker api response: {err}"))
+    }
+
+    fn error_message(&self, context: &str) -> String {
+        let message = self
+            .json()
+            .ok()
+            .and_then(|value| {
+                value
+                    .get("message")
+                    .and_then(JsonValue::as_str)
+                    .map(str::to_string)
+            })
+            .and_then(|message| trimmed_nonempty(&message));
+        match message {
+            Some(message) => format!("{context}: {message}"),
+            None => format!("{context} (http {})", self.status),
+        }
+    }
+}
+
+impl EngineApi {
+    pub(super) fn new(socket: impl Into<PathBuf>) -> Self {
+        Self {
+            socket: socket.into(),
+        }
+    }
+
+    /// Resolve the daemon socket the `docker` CLI would talk to.
+    ///
+    /// Returns `None` when the API is disabled via `AGENT_WORKSPACE_DOCKER_API`, when
+    /// `DOCKER_HOST`/contexts point somewhere other than a local unix socket, or when the
+    /// socket does not exist; callers then fall back to spawning the CLI.
+    pub(super) fn from_env() -> Option<Self> {
+        if let Some(value) = crate::config::value("docker_api")
+            && matches!(
+                value.trim().to_ascii_lowercase().as_str(),
+                "0" | "off" | "false" | "no"
+            )
+        {
+            return None;
+        }
+
+        let socket = match std::env::var("DOCKER_HOST")
+            .ok()
+            .and_then(|value| trimmed_nonempty(&value))
+        {
+            Some(host) => PathBuf::from(host.strip_prefix("unix://")?),
+            None => {
+                if !docker_cli_uses_default_context() {
+                    return None;
+                }
+                PathBuf::from(DEFAULT_DOCKER_SOCKET)
+            }
+        };
+
+        if socket.as_os_str().is_empty() || !socket_exists(&socket) {
+            return None;
+        }
+        Some(Self::new(socket))
+    }
+
+    fn request(&self, method: &str, path: &str, body: Option<&str>) -> Result<ApiResponse, String> {
+        self.request_with_timeout(method, path, body, Some(API_REQUEST_TIMEOUT))
+    }
+
+    fn request_with_timeout(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&str>,
+        timeout: Option<Duration>,
+    ) -> Result<ApiResponse, String> {
+        let mut stream = UnixStream::connect(&self.socket).map_err(|err| {
+            format!(
+                "failed to connect to docker socket {}: {err}",
+                self.socket.display()
+            )
+        })?;
+        stream
+            .set_read_timeout(timeout)
+            .and_then(|_| stream.set_write_timeout(timeout))
+            .map_err(|err| format!("failed to configure docker socket: {err}"))?;
+
+        let mut request = format!(
+            "{method} {path} HTTP/1.1\r\nHost: docker\r\nUser-Agent: agent-workspace-launcher\r\nConnection: close\r\n"
+        );
+        if let Some(body) = body {
+            request.push_str("Content-Type: application/json\r\n");
+            request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+        }
+        request.push_str("\r\n");
+        if let Some(body) = body {
+            request.push_str(body);
+        }
+
+        stream
+            .write_all(request.as_bytes())
+            .map_err(|err| format!("failed to send docker api request {method} {path}: {err}"))?;
+
+        let mut raw: Vec<u8> = Vec::new();
+        stream
+            .read_to_end(&mut raw)
+            .map_err(|err| format!("failed to read docker api response {method} {path}: {err}"))?;
+
+        parse_http_response(&raw)
+    }
+
+    /// Daemon version from `GET /version`; doubles as a reachability check for `doctor`.
+    pub(super) fn server_version(&self) -> Result<String, String> {
+        let response = self.request("GET", "/version", None)?;
+        if !response.is_success() {
+            return Err(response.error_message("docker version failed"));
+        }
+        response
+            .json()?
+            .get("Version")
+            .and_then(JsonValue::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| String::from("docker version response has no Version"))
+    }
+
+    pub(super) fn inspect_container(&self, name: &str) -> Result<Option<JsonValue>, String> {
+        let response = self.request(
+            "GET",
+            &format!("/containers/{}/json", percent_encode(name)),
+            None,
+        )?;
+        if response.status == 404 {
+            return Ok(None);
+        }
+        if !response.is_success() {
+            return Err(response.error_message(&format!("docker inspect {name} failed")));
+        }
+        response.json().map(Some)
+    }
+
+    pub(super) fn inspect_image(&self, name: &str) -> Result<Option<JsonValue>, String> {
+        let response = self.request(
+            "GET",
+            &format!("/images/{}/json", percent_encode(name)),
+            None,
+        )?;
+        if response.status == 404 {
+            return Ok(None);
+        }
+        if !response.is_success() {
+            return Err(response.error_message(&format!("docker image inspect {name} failed")));
+        }
+        response.json().map(Some)
+    }
+
+    pub(super) fn container_exists(&self, name: &str) -> Result<bool, String> {
+        self.inspect_container(name).map(|value| value.is_some())
+    }
+
+    pub(super) fn container_running(&self, name: &str) -> Result<bool, String> {
+        Ok(self
+            .inspect_container(name)?
+            .and_then(|value| {
+                value
+                    .pointer(&["State", "Running"])
+                    .and_then(JsonValue::as_bool)
+            })
+            .unwrap_or(false))
+    }
+
+    /// List container names (all states) carrying the given `key=value` label.
+    pub(super) fn list_containers_by_label(
+        &self,
+        label: &str,
+    ) -> Result<Vec<ContainerSummary>, String> {
+        let filters = format!("{{\"label\":[\"{}\"]}}", json_escape(label));
+        let response = self.request(
+            "GET",
+            &format!(
+                "/containers/json?all=1&filters={}",
+                percent_encode(&filters)
+            ),
+            None,
+        )?;
+        if !response.is_success() {
+            return Err(response.error_message("docker ps failed"));
+        }
+
+        let value = response.json()?;
+        let entries = value
+            .as_array()
+            .ok_or_else(|| String::from("invalid docker api response: expected array"))?;
+
+        let mut containers: Vec<ContainerSummary> = Vec::new();
+        for entry in entries {
+            let Some(first) = entry
+                .get("Names")
+                .and_then(JsonValue::as_array)
+                .and_then(|names| names.first())
+                .and_then(JsonValue::as_str)
+            else {
+                continue;
+            };
+            let name = first.trim_start_matches('/');
+            if name.is_empty() {
+                continue;
+            }
+            let text = |key: &str| {
+                entry
+                    .get(key)
+                    .and_then(JsonValue::as_str)
+                    .and_then(trimmed_nonempty)
+            };
+            let label = |key: &str| {
+                entry
+                    .pointer(&["Labels", key])
+                    .and_then(JsonValue::as_str)
+                    .and_then(trimmed_nonempty)
+            };
+            containers.push(ContainerSummary {
+                name: name.to_string(),
+                image: text("Image"),
+                state: text("State"),
+                repo: label("agent-kit.repo"),
+                created_at: label("agent-kit.created-at"),
+            });
+        }
+        Ok(containers)
+    }
+
+    pub(super) fn start_container(&self, name: &str) -> Result<(), String> {
+        let response = self.request(
+            "POST",
+            &format!("/containers/{}/start", percent_encode(name)),
+            None,
+        )?;
+        // 304 means the container was already running.
+        if response.is_success() || response.status == 304 {
+            Ok(())
+        } else {
+            Err(response.error_message(&format!("docker start {name} failed")))
+        }
+    }
+
+    pub(super) fn stop_container(&self, name: &str) -> Result<(), String> {
+        let response = self.request(
+            "POST",
+            &format!("/containers/{}/stop", percent_encode(name)),
+            None,
+        )?;
+        // 304 means the container was already stopped.
+        if response.is_success() || response.status == 304 {
+            Ok(())
+        } else {
+            Err(response.error_message(&format!("docker stop {name} failed")))
+        }
+    }
+
+    /// Run a non-interactive command in a container and collect its output and exit code.
+    pub(super) fn exec(&self, container: &str, cmd: &[&str]) -> Result<ExecOutput, ExecError> {
+        let cmd_json = cmd
+            .iter()
+            .map(|arg| format!("\"{}\"", json_escape(arg)))
+            .collect::<Vec<_>>()
+            .join(",");
+        let create_body = format!(
+            "{{\"AttachStdin\":false,\"AttachStdout\":true,\"AttachStderr\":true,\"Tty\":false,\"Cmd\":[{cmd_json}]}}"
+        );
+        let exec_id = self
+            .request(
+                "POST",
+                &format!("/containers/{}/exec", percent_encode(container)),
+                Some(&create_body),
+            )
+            .and_then(|response| {
+                if !response.is_success() {
+                    return Err(response.error_message(&format!("docker exec {container} failed")));
+                }
+                response
+                    .json()?
+                    .get("Id")
+                    .and_then(JsonValue::as_str)
+                    .map(str::to_string)
+                    .ok_or_else(|| String::from("invalid docker api response: missing exec id"))
+            })
+            .map_err(ExecError::NotStarted)?;
+
+        // The attached stream stays open for as long as the command runs. A failed start
+        // request may still have run the command, so from here on errors are `Started`.
+        let response = self
+            .request_with_timeout(
+                "POST",
+                &format!("/exec/{exec_id}/start"),
+                Some("{\"Detach\":false,\"Tty\":false}"),
+                None,
+            )
+            .map_err(ExecError::Started)?;
+        if !response.is_success() {
+            return Err(ExecError::Started(
+                response.error_message(&format!("docker exec {container} failed")),
+            ));
+        }
+        let (stdout, stderr) = demux_exec_stream(&response.body);
+
+        let exit_code = self
+            .request("GET", &format!("/exec/{exec_id}/json"), None)
+            .and_then(|response| {
+                if !response.is_success() {
+                    return Err(response.error_message(&format!("docker exec {container} failed")));
+                }
+                response
+                    .json()?
+                    .get("ExitCode")
+                    .and_then(JsonValue::as_i64)
+                    .ok_or_else(|| String::from("invalid docker api response: missing exit code"))
+            })
+            .map_err(ExecError::Started)?;
+
+        Ok(ExecOutput {
+            exit_code,
+            stdout: String::from_utf8_lossy(&stdout).to_string(),
+            stderr: String::from_utf8_lossy(&stderr).to_string(),
+        })
+    }
+
+    /// Remove a volume; a volume that does not exist counts as removed.
+    pub(super) fn remove_volume(&self, name: &str) -> Result<(), String> {
+        let response = self.request(
+            "DELETE",
+            &format!("/volumes/{}", percent_encode(name)),
+            None,
+        )?;
+        if response.is_success() || response.status == 404 {
+            Ok(())
+        } else {
+            Err(response.error_message(&format!("docker volume rm {name} failed")))
+        }
+    }
+}
+
+fn socket_exists(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    std::fs::metadata(path)
+        .map(|meta| meta.file_type().is_socket())
+        .unwrap_or(false)
+}
+
+fn docker_cli_uses_default_context() -> bool {
+    if let Some(context) = std::env::var("DOCKER_CONTEXT")
+        .ok()
+        .and_then(|value| trimmed_nonempty(&value))
+    {
+        return context == "default";
+    }
+
+    let config_dir = match std::env::var_os("DOCKER_CONFIG").filter(|value| !value.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => match std::env::var_os("HOME").filter(|value| !value.is_empty()) {
+            Some(home) => PathBuf::from(home).join(".docker"),
+            None => return true,
+        },
+    };
+
+    let Ok(contents) = std::fs::read_to_string(config_dir.join("config.json")) else {
+        return true;
+    };
+    match parse_json(&contents) {
+        Ok(config) => config
+            .get("currentContext")
+            .and_then(JsonValue::as_str)
+            .map(|context| context.is_empty() || context == "default")
+            .unwrap_or(true),
+        Err(_) => true,
+    }
+}
+
+fn parse_http_response(raw: &[u8]) -> Result<ApiResponse, String> {
+    let header_end = raw
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| String::from("invalid docker api response: missing header terminator"))?;
+    let head = String::from_utf8_lossy(&raw[..header_end]);
+    let rest = &raw[header_end + 4..];
+
+    let mut lines = head.split("\r\n");
+    let status_line = lines.next().unwrap_or_default();
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| format!("invalid docker api status line: {status_line}"))?;
+
+    let mut content_length: Option<usize> = None;
+    let mut chunked = false;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.trim().to_ascii_lowercase();
+        let value = value.trim();
+        if name == "content-length" {
+            content_length = value.parse::<usize>().ok();
+        } else if name == "transfer-encoding" && value.eq_ignore_ascii_case("chunked") {
+            chunked = true;
+        }
+    }
+
+    let body = if chunked {
+        decode_chunked(rest)?
+    } else if let Some(length) = content_length {
+        rest[..length.min(rest.len())].to_vec()
+    } else {
+        rest.to_vec()
+    };
+
+    Ok(ApiResponse { status, body })
+}
+
+fn decode_chunked(mut raw: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out: Vec<u8> = Vec::new();
+    loop {
+        let line_end = raw
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .ok_or_else(|| String::from("invalid chunked docker api response"))?;
+        let size_field = String::from_utf8_lossy(&raw[..line_end]);
+        let size_hex = size_field.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size_hex, 16)
+            .map_err(|_| format!("invalid chunk size in docker api response: {size_hex}"))?;
+        raw = &raw[line_end + 2..];
+        if size == 0 {
+            return Ok(out);
+        }
+        if raw.len() < size {
+            return Err(String::from("truncated chunked docker api response"));
+        }
+        out.extend_from_slice(&raw[..size]);
+        raw = raw[size..].strip_prefix(b"\r\n").unwrap_or(&raw[size..]);
+    }
+}
+
+/// Split the multiplexed stdout/stderr stream returned by a non-TTY exec.
+///
+/// Each frame is an 8-byte header (`stream, 0, 0, 0, size_be_u32`) followed by payload.
+fn demux_exec_stream(mut raw: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut stdout: Vec<u8> = Vec::new();
+    let mut stderr: Vec<u8> = Vec::new();
+
+    while raw.len() >= 8 {
+        let stream = raw[0];
+        let size = u32::from_be_bytes([raw[4], raw[5], raw[6], raw[7]]) as usize;
+        let payload_end = (8 + size).min(raw.len());
+        let payload = &raw[8..payload_end];
+        if stream == 2 {
+            stderr.extend_from_slice(payload);
+        } else {
+            stdout.extend_from_slice(payload);
+        }
+        raw = &raw[payload_end..];
+    }
+
+    (stdout, stderr)
+}
+
+fn percent_encode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
@@ -1,10 +1,10 @@
 use std::ffi::OsString;
-use std::io::IsTerminal;
-use std::process::{Command, Stdio};
 
 use crate::EXIT_RUNTIME;
 
-use super::{PRIMARY_COMMAND_NAME, resolve_workspace};
+use super::backend::{ExecRequest, WorkspaceBackend};
+use super::hooks::{Hook, run_hook};
+use super::{PRIMARY_COMMAND_NAME, resolve_ready_workspace};
 
 #[derive(Debug, Default, Clone)]
 pub(super) struct ParsedExec {
@@ -63,7 +63,7 @@ pub(super) fn parse_exec_args(args: &[OsString]) -> Result<ParsedExec, String> {
     Ok(parsed)
 }
 
-pub(super) fn run(args: &[OsString]) -> i32 {
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
     let parsed = match parse_exec_args(args) {
         Ok(parsed) => parsed,
         Err(err) => {
@@ -78,54 +78,37 @@ pub(super) fn run(args: &[OsString]) -> i32 {
         return 0;
     }
 
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
     let workspace_name = parsed
         .workspace
         .as_ref()
         .map(|name| name.to_string_lossy().into_owned())
         .unwrap_or_default();
-    let workspace = match resolve_workspace(&workspace_name) {
-        Ok(Some(workspace)) => workspace,
-        Ok(None) => {
-            eprintln!("error: workspace not found: {workspace_name}");
-            return EXIT_RUNTIME;
-        }
+    let workspace = match resolve_ready_workspace(backend, Some(&workspace_name)) {
+        Ok(workspace) => workspace,
         Err(err) => {
             eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
     };
 
-    if parsed.user.is_some() {
-        eprintln!("warn: --root/--user is ignored in host-native exec mode");
+    if let Err(err) = run_hook(backend, &workspace, Hook::PreExec) {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
     }
 
-    let mut command = if parsed.command.is_empty() {
-        let shell = std::env::var_os("SHELL").unwrap_or_else(|| OsString::from("/bin/bash"));
-        let mut cmd = Command::new(shell);
-        if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
-            cmd.arg("-l");
-        }
-        cmd
-    } else {
-        let mut cmd = Command::new(&parsed.command[0]);
-        if parsed.command.len() > 1 {
-            cmd.args(&parsed.command[1..]);
-        }
-        cmd
+    let request = ExecRequest {
+        user: parsed.user.as_deref(),
+        command: &parsed.command,
     };
-
-    command.current_dir(&workspace.path);
-    command.stdin(Stdio::inherit());
-    command.stdout(Stdio::inherit());
-    command.stderr(Stdio::inherit());
-
-    match command.status() {
-        Ok(status) => status.code().unwrap_or(EXIT_RUNTIME),
+    match backend.exec(&workspace, &request) {
+        Ok(code) => code,
         Err(err) => {
-            eprintln!(
-                "error: failed to run command in {}: {err}",
-                workspace.path.display()
-            );
+            eprintln!("error: {err}");
             EXIT_RUNTIME
         }
     }
@@ -133,6 +116,6 @@ pub(super) fn run(args: &[OsString]) -> i32 {
 
 fn print_exec_usage() {
     eprintln!(
-        "usage: {PRIMARY_COMMAND_NAME} exec [--root|--user <user>] <workspace> [command ...]"
+        "usage: {PRIMARY_COMMAND_NAME} exec [--runtime <container|host|podman|ssh://host>] [--root|--user <user>] <workspace> [command ...]"
     );
 }
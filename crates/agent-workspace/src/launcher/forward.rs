@@ -0,0 +1,509 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use crate::EXIT_RUNTIME;
+
+use super::backend::WorkspaceBackend;
+use super::{PRIMARY_COMMAND_NAME, resolve_ready_workspace, workspace_storage_root};
+
+/// Running forwards register here (one `<pid>.forward` file each) for `--list` and `--stop`.
+const FORWARDS_DIR: &str = ".forwards";
+
+/// `<local>[:<remote>]`: listen on host port `local`, relay to workspace port `remote`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct PortSpec {
+    pub(super) local: u16,
+    pub(super) remote: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ParsedForward {
+    pub(super) show_help: bool,
+    pub(super) list: bool,
+    pub(super) stop: bool,
+    pub(super) all: bool,
+    pub(super) address: String,
+    pub(super) workspace: Option<String>,
+    pub(super) ports: Vec<PortSpec>,
+}
+
+impl Default for ParsedForward {
+    fn default() -> Self {
+        Self {
+            show_help: false,
+            list: false,
+            stop: false,
+            all: false,
+            address: String::from("127.0.0.1"),
+            workspace: None,
+            ports: Vec::new(),
+        }
+    }
+}
+
+/// One live `forward` process, as read back from its registration file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct ForwardEntry {
+    pub(super) pid: u32,
+    pub(super) runtime: String,
+    pub(super) workspace: String,
+    pub(super) address: String,
+    pub(super) ports: Vec<PortSpec>,
+}
+
+pub(super) fn parse_forward_args(args: &[OsString]) -> Result<ParsedForward, String> {
+    let mut parsed = ParsedForward::default();
+    let mut positionals: Vec<String> = Vec::new();
+    let mut idx = 0usize;
+
+    while idx < args.len() {
+        let current = args[idx].to_string_lossy();
+        match current.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            "--list" => parsed.list = true,
+            "--stop" => parsed.stop = true,
+            "--all" => parsed.all = true,
+            "--address" => {
+                idx += 1;
+                let Some(value) = args.get(idx) else {
+                    return Err(String::from("missing value for --address"));
+                };
+                parsed.address = value.to_string_lossy().trim().to_string();
+            }
+            _ if current.starts_with("--address=") => {
+                parsed.address = current["--address=".len()..].trim().to_string();
+            }
+            _ if current.starts_with('-') => {
+                return Err(format!("unknown option for forward: {current}"));
+            }
+            _ => positionals.push(current.into_owned()),
+        }
+        idx += 1;
+    }
+
+    if parsed.list && parsed.stop {
+        return Err(String::from("--list and --stop are mutually exclusive"));
+    }
+    if parsed.address.is_empty() {
+        return Err(String::from("missing value for --address"));
+    }
+
+    // A lone port spec forwards into the only workspace; otherwise the workspace comes first.
+    let mut positionals = positionals.into_iter();
+    let mut rest: Vec<String> = Vec::new();
+    if let Some(first) = positionals.next() {
+        if parse_port_spec(&first).is_ok() && positionals.len() == 0 {
+            rest.push(first);
+        } else {
+            parsed.workspace = Some(first);
+        }
+    }
+    rest.extend(positionals);
+    for spec in rest {
+        let spec = parse_port_spec(&spec)?;
+        if parsed.ports.iter().any(|known| known.local == spec.local) {
+            return Err(format!("host port {} is listed twice", spec.local));
+        }
+        parsed.ports.push(spec);
+    }
+
+    if parsed.all && !parsed.stop {
+        return Err(String::from("--all is only valid with --stop"));
+    }
+    if parsed.stop && parsed.all && parsed.workspace.is_some() {
+        return Err(String::from("--stop takes a workspace or --all, not both"));
+    }
+    if parsed.stop && !parsed.all && parsed.workspace.is_none() {
+        return Err(String::from("--stop needs a workspace (or --all)"));
+    }
+    if !parsed.show_help && !parsed.list && !parsed.stop && parsed.ports.is_empty() {
+        return Err(String::from("missing port (expected <local>[:<remote>])"));
+    }
+
+    Ok(parsed)
+}
+
+fn parse_port_spec(raw: &str) -> Result<PortSpec, String> {
+    let parse = |value: &str| {
+        value
+            .parse::<u16>()
+            .ok()
+            .filter(|port| *port != 0)
+            .ok_or_else(|| format!("invalid port in {raw} (expected <local>[:<remote>])"))
+    };
+    match raw.split_once(':') {
+        Some((local, remote)) => Ok(PortSpec {
+            local: parse(local)?,
+            remote: parse(remote)?,
+        }),
+        None => {
+            let port = parse(raw)?;
+            Ok(PortSpec {
+                local: port,
+                remote: port,
+            })
+        }
+    }
+}
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_forward_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_forward_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if parsed.show_help {
+        print_forward_usage();
+        return 0;
+    }
+
+    let registry = workspace_storage_root().join(FORWARDS_DIR);
+    if parsed.list || parsed.stop {
+        // Registrations hold the resolved name, so match what `forward <workspace>` would use;
+        // a workspace that no longer resolves is matched as given.
+        let target = parsed.workspace.as_deref().map(|name| {
+            let resolved = backend
+                .resolve(name)
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| name.to_string());
+            (backend.runtime_name(), resolved)
+        });
+        let target = target
+            .as_ref()
+            .map(|(runtime, name)| (*runtime, name.as_str()));
+        let entries = live_forwards(&registry, target);
+        if let Some((runtime, name)) = target
+            && entries.is_empty()
+        {
+            eprintln!("error: no active forwards for {name} on the {runtime} runtime");
+            return EXIT_RUNTIME;
+        }
+        if parsed.list {
+            print_forwards(&entries);
+            return 0;
+        }
+        return stop_forwards(&registry, entries);
+    }
+
+    if !backend.supports_forward() {
+        eprintln!(
+            "error: forward is not supported by the {} runtime",
+            backend.runtime_name()
+        );
+        eprintln!("hint: host workspaces already listen on host ports");
+        return EXIT_RUNTIME;
+    }
+
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    let workspace = match resolve_ready_workspace(backend, parsed.workspace.as_deref()) {
+        Ok(workspace) => workspace,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+
+    // Bind every port before relaying anything, so a taken port fails the whole command.
+    let mut listeners: Vec<(PortSpec, TcpListener)> = Vec::new();
+    for spec in &parsed.ports {
+        match TcpListener::bind((parsed.address.as_str(), spec.local)) {
+            Ok(listener) => listeners.push((*spec, listener)),
+            Err(err) => {
+                eprintln!(
+                    "error: failed to listen on {}:{}: {err}",
+                    parsed.address, spec.local
+                );
+                return EXIT_RUNTIME;
+            }
+        }
+    }
+
+    let entry = ForwardEntry {
+        pid: std::process::id(),
+        runtime: backend.runtime_name().to_string(),
+        workspace: workspace.clone(),
+        address: parsed.address.clone(),
+        ports: parsed.ports.clone(),
+    };
+    if let Err(err) = register_forward(&registry, &entry) {
+        eprintln!("warn: {err}; this forward will not show up in --list");
+    }
+    for spec in &parsed.ports {
+        println!(
+            "forward: {}:{} -> {workspace}:{}",
+            parsed.address, spec.local, spec.remote
+        );
+    }
+    println!(
+        "info: relaying until interrupted (Ctrl-C, or '{PRIMARY_COMMAND_NAME} forward --stop {workspace}')"
+    );
+
+    thread::scope(|scope| {
+        for (spec, listener) in &listeners {
+            let workspace = workspace.as_str();
+            scope.spawn(move || accept_loop(backend, workspace, *spec, listener));
+        }
+    });
+    let _ = fs::remove_file(registration_path(&registry, entry.pid));
+    0
+}
+
+fn accept_loop(
+    backend: &dyn WorkspaceBackend,
+    workspace: &str,
+    spec: PortSpec,
+    listener: &TcpListener,
+) {
+    thread::scope(|scope| {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    scope.spawn(move || {
+                        if let Err(err) = relay(backend, workspace, spec.remote, stream) {
+                            eprintln!("warn: {}->{}: {err}", spec.local, spec.remote);
+                        }
+                    });
+                }
+                Err(err) => eprintln!("warn: accept on port {} failed: {err}", spec.local),
+            }
+        }
+    });
+}
+
+/// Pump one accepted connection through a bridge process until both directions are done.
+fn relay(
+    backend: &dyn WorkspaceBackend,
+    workspace: &str,
+    port: u16,
+    stream: TcpStream,
+) -> Result<(), String> {
+    let mut child = backend
+        .forward_command(workspace, port)?
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("failed to start bridge: {err}"))?;
+    let mut bridge_in = child.stdin.take().ok_or("failed to open bridge stdin")?;
+    let mut bridge_out = child.stdout.take().ok_or("failed to open bridge stdout")?;
+    let mut client_in = stream
+        .try_clone()
+        .map_err(|err| format!("failed to clone connection: {err}"))?;
+    let mut client_out = stream;
+
+    let upstream = thread::spawn(move || {
+        // Closing the bridge's stdin is how the client's half-close reaches the workspace.
+        let _ = pump(&mut client_in, &mut bridge_in);
+    });
+    let downstream = pump(&mut bridge_out, &mut client_out);
+    let _ = client_out.shutdown(Shutdown::Both);
+    if downstream.is_err() {
+        // The client went away mid-response; nothing else will stop the bridge.
+        let _ = child.kill();
+    }
+    let _ = upstream.join();
+
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("failed to wait for bridge: {err}"))?;
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if output.status.success() || downstream.is_err() || stderr.is_empty() {
+        Ok(())
+    } else {
+        Err(stderr)
+    }
+}
+
+/// Plain read/write loop rather than `io::copy`, whose Linux `splice` fast path waits to fill
+/// its whole length from a socket and would hold interactive traffic back.
+fn pump(from: &mut impl Read, to: &mut impl Write) -> io::Result<()> {
+    let mut buf = [0u8; 16 * 1024];
+    loop {
+        let read = from.read(&mut buf)?;
+        if read == 0 {
+            return Ok(());
+        }
+        to.write_all(&buf[..read])?;
+    }
+}
+
+fn registration_path(registry: &Path, pid: u32) -> PathBuf {
+    registry.join(format!("{pid}.forward"))
+}
+
+fn register_forward(registry: &Path, entry: &ForwardEntry) -> Result<(), String> {
+    fs::create_dir_all(registry)
+        .map_err(|err| format!("failed to create {}: {err}", registry.display()))?;
+    let ports: Vec<String> = entry
+        .ports
+        .iter()
+        .map(|spec| format!("{}:{}", spec.local, spec.remote))
+        .collect();
+    let contents = format!(
+        "pid={}\nruntime={}\nworkspace={}\naddress={}\nports={}\n",
+        entry.pid,
+        entry.runtime,
+        entry.workspace,
+        entry.address,
+        ports.join(",")
+    );
+    let path = registration_path(registry, entry.pid);
+    fs::write(&path, contents).map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+fn parse_registration(contents: &str) -> Option<ForwardEntry> {
+    let mut entry = ForwardEntry::default();
+    for line in contents.lines() {
+        match line.split_once('=') {
+            Some(("pid", value)) => entry.pid = value.trim().parse().ok()?,
+            Some(("runtime", value)) => entry.runtime = value.trim().to_string(),
+            Some(("workspace", value)) => entry.workspace = value.trim().to_string(),
+            Some(("address", value)) => entry.address = value.trim().to_string(),
+            Some(("ports", value)) => {
+                entry.ports = value
+                    .split(',')
+                    .filter(|spec| !spec.is_empty())
+                    .map(parse_port_spec)
+                    .collect::<Result<_, _>>()
+                    .ok()?;
+            }
+            _ => {}
+        }
+    }
+    (entry.pid != 0 && !entry.workspace.is_empty()).then_some(entry)
+}
+
+/// A registered pid still names a running `forward`; checking the command line guards against
+/// pid reuse after the relay was killed without cleaning up.
+fn forward_is_running(pid: u32) -> bool {
+    fs::read(format!("/proc/{pid}/cmdline")).is_ok_and(|cmdline| {
+        cmdline
+            .split(|byte| *byte == 0)
+            .any(|arg| arg == b"forward")
+    })
+}
+
+/// Registered forwards whose process is still alive, optionally only those for one
+/// `(runtime, workspace)`; stale registrations are removed along the way.
+pub(super) fn live_forwards(registry: &Path, workspace: Option<(&str, &str)>) -> Vec<ForwardEntry> {
+    let Ok(entries) = fs::read_dir(registry) else {
+        return Vec::new();
+    };
+    let mut live: Vec<ForwardEntry> = Vec::new();
+    for file in entries.flatten() {
+        let path = file.path();
+        if path.extension().is_none_or(|ext| ext != "forward") {
+            continue;
+        }
+        let entry = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| parse_registration(&contents));
+        match entry {
+            Some(entry) if forward_is_running(entry.pid) => {
+                if workspace.is_none_or(|(runtime, name)| {
+                    runtime == entry.runtime && name == entry.workspace
+                }) {
+                    live.push(entry);
+                }
+            }
+            _ => {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+    live.sort_by(|a, b| (&a.workspace, a.pid).cmp(&(&b.workspace, b.pid)));
+    live
+}
+
+fn print_forwards(entries: &[ForwardEntry]) {
+    if entries.is_empty() {
+        println!("no active forwards");
+        return;
+    }
+
+    let mut table: Vec<[String; 5]> = vec![[
+        String::from("WORKSPACE"),
+        String::from("RUNTIME"),
+        String::from("LISTEN"),
+        String::from("TARGET"),
+        String::from("PID"),
+    ]];
+    for entry in entries {
+        for spec in &entry.ports {
+            table.push([
+                entry.workspace.clone(),
+                entry.runtime.clone(),
+                format!("{}:{}", entry.address, spec.local),
+                spec.remote.to_string(),
+                entry.pid.to_string(),
+            ]);
+        }
+    }
+
+    let widths: Vec<usize> = (0..5)
+        .map(|column| {
+            table
+                .iter()
+                .map(|cells| cells[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    for cells in table {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+}
+
+fn stop_forwards(registry: &Path, entries: Vec<ForwardEntry>) -> i32 {
+    if entries.is_empty() {
+        println!("no active forwards");
+        return 0;
+    }
+
+    let mut status = 0;
+    for entry in entries {
+        let killed = Command::new("kill")
+            .arg(entry.pid.to_string())
+            .status()
+            .is_ok_and(|status| status.success());
+        if killed {
+            let _ = fs::remove_file(registration_path(registry, entry.pid));
+            println!("stopped: {} (pid={})", entry.workspace, entry.pid);
+        } else {
+            eprintln!(
+                "error: failed to stop forward for {} (pid={})",
+                entry.workspace, entry.pid
+            );
+            status = EXIT_RUNTIME;
+        }
+    }
+    status
+}
+
+fn print_forward_usage() {
+    eprintln!("usage:");
+    eprintln!(
+        "  {PRIMARY_COMMAND_NAME} forward [--runtime container|podman|ssh://host] [--address <addr>] [<workspace>] <local>[:<remote>]..."
+    );
+    eprintln!("  {PRIMARY_COMMAND_NAME} forward --list [<workspace>]");
+    eprintln!("  {PRIMARY_COMMAND_NAME} forward --stop <workspace>|--all");
+}
@@ -0,0 +1,190 @@
+use std::ffi::OsString;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::EXIT_RUNTIME;
+
+use super::backend::{RemoveOptions, WorkspaceBackend};
+use super::hooks::{Hook, run_hook};
+use super::{PRIMARY_COMMAND_NAME, confirm_or_abort, format_duration, parse_duration};
+
+#[derive(Debug, Default, Clone)]
+pub(super) struct ParsedGc {
+    pub(super) show_help: bool,
+    pub(super) dry_run: bool,
+    pub(super) yes: bool,
+    pub(super) older_than: Option<u64>,
+}
+
+pub(super) fn parse_gc_args(args: &[OsString]) -> Result<ParsedGc, String> {
+    let mut parsed = ParsedGc::default();
+    let mut idx = 0usize;
+
+    while idx < args.len() {
+        let arg = args[idx].to_string_lossy();
+        match arg.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            "-n" | "--dry-run" => parsed.dry_run = true,
+            "-y" | "--yes" => parsed.yes = true,
+            "--older-than" => {
+                idx += 1;
+                let Some(value) = args.get(idx) else {
+                    return Err(String::from("missing value for --older-than"));
+                };
+                parsed.older_than = Some(parse_older_than(&value.to_string_lossy())?);
+            }
+            _ if arg.starts_with("--older-than=") => {
+                parsed.older_than = Some(parse_older_than(&arg["--older-than=".len()..])?);
+            }
+            _ => return Err(format!("unexpected arg for gc: {arg}")),
+        }
+        idx += 1;
+    }
+
+    Ok(parsed)
+}
+
+fn parse_older_than(value: &str) -> Result<u64, String> {
+    parse_duration(value).map_err(|err| format!("--older-than: {err}"))
+}
+
+/// Workspaces created at least `older_than` seconds before `now`, with their age.
+pub(super) fn stale_workspaces(
+    entries: &[(String, Option<u64>)],
+    older_than: u64,
+    now: u64,
+) -> Vec<(String, u64)> {
+    entries
+        .iter()
+        .filter_map(|(name, created_unix)| {
+            let age = now.saturating_sub((*created_unix)?);
+            (age >= older_than).then(|| (name.clone(), age))
+        })
+        .collect()
+}
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_gc_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_gc_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if parsed.show_help {
+        print_gc_usage();
+        return 0;
+    }
+
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    let volumes = match backend.orphaned_volumes() {
+        Ok(volumes) => volumes,
+        Err(err) => {
+            eprintln!("error: failed to list volumes: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+    let unmanaged = match backend.unmanaged_dirs() {
+        Ok(dirs) => dirs,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+    let stale = match parsed.older_than {
+        Some(older_than) => match backend.list() {
+            Ok(entries) => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or(0);
+                let entries: Vec<(String, Option<u64>)> = entries
+                    .into_iter()
+                    .map(|entry| (entry.name, entry.created_unix))
+                    .collect();
+                stale_workspaces(&entries, older_than, now)
+            }
+            Err(err) => {
+                eprintln!("error: {err}");
+                return EXIT_RUNTIME;
+            }
+        },
+        None => Vec::new(),
+    };
+
+    if !unmanaged.is_empty() {
+        println!("Directories without workspace metadata (not removed):");
+        for dir in &unmanaged {
+            println!("  - {dir}");
+        }
+    }
+    if let Some(older_than) = parsed.older_than
+        && !stale.is_empty()
+    {
+        println!(
+            "Workspaces older than {} ({}):",
+            format_duration(older_than),
+            stale.len()
+        );
+        for (name, age) in &stale {
+            println!("  - {name} (created {} ago)", format_duration(*age));
+        }
+    }
+    if !volumes.is_empty() {
+        println!("Orphaned volumes ({}):", volumes.len());
+        for volume in &volumes {
+            println!("  - {volume}");
+        }
+    }
+
+    if stale.is_empty() && volumes.is_empty() {
+        println!("Nothing to clean up");
+        return 0;
+    }
+    if parsed.dry_run {
+        return 0;
+    }
+    if !parsed.yes && !confirm_or_abort("Remove the workspaces and volumes above? [y/N] ") {
+        println!("Aborted");
+        return EXIT_RUNTIME;
+    }
+
+    let mut failed = false;
+    for (name, _) in &stale {
+        if let Err(err) = run_hook(backend, name, Hook::PreRm) {
+            eprintln!("error: {err}");
+            eprintln!("hint: workspace {name} was not removed");
+            failed = true;
+            continue;
+        }
+        match backend.remove(name, &RemoveOptions::default()) {
+            Ok(()) => println!("removed: {name}"),
+            Err(err) => {
+                eprintln!("error: failed to remove workspace {name}: {err}");
+                failed = true;
+            }
+        }
+    }
+    for volume in &volumes {
+        match backend.remove_volume(volume) {
+            Ok(()) => println!("removed volume: {volume}"),
+            Err(err) => {
+                eprintln!("error: failed to remove volume {volume}: {err}");
+                failed = true;
+            }
+        }
+    }
+
+    if failed { EXIT_RUNTIME } else { 0 }
+}
+
+fn print_gc_usage() {
+    eprintln!(
+        "usage: {PRIMARY_COMMAND_NAME} gc [--runtime <container|host|podman|ssh://host>] [--older-than <duration>] [--dry-run] [--yes]"
+    );
+}
@@ -0,0 +1,214 @@
+use std::ffi::OsString;
+use std::process::Command;
+
+use crate::EXIT_RUNTIME;
+
+use super::backend::{ExecRequest, WorkspaceBackend};
+
+/// Runs every executable `<repo>/.awl/hooks/<hook>` found under the work root, from the repo
+/// root, with `AWL_REPOS` set to the repos discovered the same way as `reset work-repos`.
+const WORKSPACE_HOOKS_SCRIPT: &str = r#"
+set -euo pipefail
+
+hook="${1:?missing hook}"
+root="${2:?missing root}"
+depth="${3:?missing depth}"
+
+if [[ ! -d "$root" ]]; then
+  exit 0
+fi
+
+repos=()
+while IFS= read -r -d '' git_entry; do
+  repos+=("${git_entry%/.git}")
+done < <(find -L "$root" -maxdepth "$((depth + 1))" -mindepth 2 \( -type d -o -type f \) -name .git -print0 2>/dev/null)
+
+AWL_REPOS="$(printf '%s\n' ${repos[@]+"${repos[@]}"} | sort -u)"
+export AWL_REPOS
+
+for repo in ${repos[@]+"${repos[@]}"}; do
+  script="$repo/.awl/hooks/$hook"
+  if [[ -x "$script" ]]; then
+    echo "+ $hook: $script" >&2
+    (cd "$repo" && "$script")
+  fi
+done
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Hook {
+    PostCreate,
+    PreRm,
+    PostReset,
+    PreExec,
+}
+
+impl Hook {
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Self::PostCreate => "post-create",
+            Self::PreRm => "pre-rm",
+            Self::PostReset => "post-reset",
+            Self::PreExec => "pre-exec",
+        }
+    }
+
+    /// Whether the workspace's own `.awl/hooks` scripts run for this hook. `repo_hooks = "on"`
+    /// runs them in every runtime and `"off"` never; the default `auto` only runs them inside
+    /// a container, and never for `pre-exec`, which would otherwise scan the repos on every `exec`.
+    fn runs_repo_scripts(self, backend: &dyn WorkspaceBackend) -> bool {
+        match crate::config::value("repo_hooks").as_deref().map(str::trim) {
+            Some("on") => true,
+            Some("off") => false,
+            _ => backend.supports_images() && self != Self::PreExec,
+        }
+    }
+
+    /// Config key holding the host-side command for this hook.
+    fn setting_key(self) -> &'static str {
+        match self {
+            Self::PostCreate => "hook_post_create",
+            Self::PreRm => "hook_pre_rm",
+            Self::PostReset => "hook_post_reset",
+            Self::PreExec => "hook_pre_exec",
+        }
+    }
+}
+
+/// Why a hook did not complete: the workspace could not run it, or the hook itself failed.
+enum HookError {
+    Unavailable(String),
+    Failed(String),
+}
+
+/// Run the configured host command for `hook`, then the workspace's own hook scripts when
+/// `Hook::runs_repo_scripts` allows them. Any failure is returned so the caller can abort the
+/// operation; with neither configured nothing is run or scanned.
+///
+/// `pre-rm` never starts a stopped container: removal must not depend on the workspace being
+/// able to start, so its hook only runs in a workspace that is already up, and a workspace
+/// that cannot run it is skipped with a warning.
+pub(super) fn run_hook(
+    backend: &dyn WorkspaceBackend,
+    workspace: &str,
+    hook: Hook,
+) -> Result<(), String> {
+    if crate::config::value("hooks").is_some_and(|value| value.trim() == "off") {
+        return Ok(());
+    }
+
+    let command = crate::config::value(hook.setting_key());
+    let repo_scripts = hook.runs_repo_scripts(backend);
+    if command.is_none() && !repo_scripts {
+        return Ok(());
+    }
+    if hook == Hook::PreRm && !backend.is_ready(workspace) {
+        if let Some(command) = command {
+            eprintln!(
+                "warn: skipping {} hook for {workspace}: workspace is not running: {command}",
+                hook.name()
+            );
+        }
+        return Ok(());
+    }
+
+    match run_hook_steps(backend, workspace, hook, command.as_deref(), repo_scripts) {
+        Ok(()) => Ok(()),
+        Err(HookError::Unavailable(err)) if hook == Hook::PreRm => {
+            eprintln!("warn: skipping {} hook for {workspace}: {err}", hook.name());
+            Ok(())
+        }
+        Err(HookError::Unavailable(err) | HookError::Failed(err)) => Err(err),
+    }
+}
+
+fn run_hook_steps(
+    backend: &dyn WorkspaceBackend,
+    workspace: &str,
+    hook: Hook,
+    command: Option<&str>,
+    repo_scripts: bool,
+) -> Result<(), HookError> {
+    backend
+        .ensure_ready(workspace)
+        .map_err(HookError::Unavailable)?;
+    let work_root = backend
+        .map_repo_path(workspace, "/work")
+        .map_err(HookError::Unavailable)?;
+    let depth = backend.default_work_repos_depth();
+    let env: Vec<(&str, String)> = vec![
+        ("AWL_HOOK", hook.name().to_string()),
+        ("AWL_RUNTIME", backend.runtime_name().to_string()),
+        ("AWL_WORKSPACE", workspace.to_string()),
+        (
+            "AWL_WORKSPACE_PATH",
+            backend
+                .workspace_dir(workspace)
+                .map_err(HookError::Unavailable)?,
+        ),
+    ];
+
+    if let Some(command) = command {
+        let repos = backend
+            .list_repos(workspace, &work_root, depth)
+            .map_err(HookError::Unavailable)?;
+        eprintln!("+ {}: {command}", hook.name());
+        let mut cmd = Command::new("bash");
+        cmd.arg("-c")
+            .arg(command)
+            .envs(env.iter().cloned())
+            .env("AWL_REPOS", repos.join("\n"));
+        if let Some(dir) = backend.local_dir(workspace) {
+            cmd.current_dir(dir);
+        }
+        let status = cmd.status().map_err(|err| {
+            HookError::Failed(format!("failed to run {} hook: {err}", hook.name()))
+        })?;
+        if !status.success() {
+            return Err(HookError::Failed(format!(
+                "{} hook failed (exit {}): {command}",
+                hook.name(),
+                status.code().unwrap_or(EXIT_RUNTIME)
+            )));
+        }
+    }
+
+    if !repo_scripts {
+        return Ok(());
+    }
+    let mut argv: Vec<OsString> = vec![OsString::from("env")];
+    argv.extend(
+        env.iter()
+            .map(|(name, value)| OsString::from(format!("{name}={value}"))),
+    );
+    argv.extend(
+        [
+            String::from("bash"),
+            String::from("-c"),
+            String::from(WORKSPACE_HOOKS_SCRIPT),
+            String::from("awl-hook"),
+            hook.name().to_string(),
+            work_root,
+            depth.to_string(),
+        ]
+        .into_iter()
+        .map(OsString::from),
+    );
+
+    let code = backend
+        .exec(
+            workspace,
+            &ExecRequest {
+                user: None,
+                command: &argv,
+            },
+        )
+        .map_err(HookError::Unavailable)?;
+    if code != 0 {
+        return Err(HookError::Failed(format!(
+            "{} hook failed in workspace {workspace} (exit {code})",
+            hook.name()
+        )));
+    }
+    Ok(())
+}
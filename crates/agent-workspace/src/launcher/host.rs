@@ -0,0 +1,1194 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::EXIT_RUNTIME;
+
+use super::archive::{
+    HOST_LAYOUT, HOST_WORKSPACE_PART, SnapshotManifest, part_path, tar_directory, untar_directory,
+};
+use super::backend::{
+    CLONE_MODE_SCRIPT, CODE_TUNNEL_LOG_FILE, CODE_TUNNEL_STATE_FILE, CloneOptions, CopyRequest,
+    CreatedWorkspace, DETACHED_TUNNEL_SCRIPT, DoctorCheck, ExecRequest, LIST_GIT_REPOS_SCRIPT,
+    NewWorkspace, RESET_REPO_SCRIPT, RemoveOptions, RepoSlot, RestoreOptions, RsyncDirection,
+    RsyncTransfer, TUNNEL_CONTROL_SCRIPT, TunnelAction, TunnelOutcome, TunnelRequest,
+    WORKSPACE_STATUS_SCRIPT, WorkspaceBackend, WorkspaceDetails, WorkspaceEntry, apply_meta_line,
+    run_rsync,
+};
+use super::local_sync::{SyncOptions, copy_like_docker_cp, sync_paths, sync_tree};
+use super::{
+    RepoSpec, WORKSPACE_ENV_FILE, WORKSPACE_META_FILE, Workspace, command_exists,
+    ensure_workspace_root, list_workspaces_on_disk, map_workspace_internal_path, push_unique_path,
+    resolve_workspace, trimmed_nonempty, workspace_repo_destination, workspace_storage_root,
+    write_file_secure,
+};
+
+/// Snapshot exclusions for `auth` material and `create --env` values unless `--include-secrets`
+/// is given.
+pub(super) const HOST_SECRET_EXCLUDES: [&str; 3] = ["./auth/*", "./.codex/*", "./.workspace-env"];
+
+/// Bare mirrors shared by `create --worktree` checkouts, keyed by `<owner>/<repo>.git`.
+const MIRRORS_DIR: &str = ".mirrors";
+
+/// Host-native workspaces: plain directories under the workspace storage root.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct HostBackend;
+
+impl HostBackend {
+    fn workspace(&self, name: &str) -> Result<Workspace, String> {
+        let path = ensure_workspace_root()?.join(name);
+        if !path.is_dir() {
+            return Err(format!("workspace not found: {name}"));
+        }
+        Ok(Workspace {
+            name: name.to_string(),
+            path,
+        })
+    }
+}
+
+impl WorkspaceBackend for HostBackend {
+    fn runtime_name(&self) -> &'static str {
+        "host"
+    }
+
+    fn list(&self) -> Result<Vec<WorkspaceEntry>, String> {
+        Ok(list_workspaces_on_disk()?
+            .into_iter()
+            .map(|workspace| {
+                let mut entry = WorkspaceEntry::default();
+                let meta = fs::read_to_string(workspace.path.join(WORKSPACE_META_FILE))
+                    .unwrap_or_default();
+                for line in meta.lines() {
+                    apply_meta_line(line, &mut entry.created_unix, &mut entry.repo);
+                }
+                WorkspaceEntry {
+                    name: workspace.name,
+                    path: Some(workspace.path),
+                    ..entry
+                }
+            })
+            .collect())
+    }
+
+    fn resolve(&self, name: &str) -> Result<Option<String>, String> {
+        Ok(resolve_workspace(name)?.map(|workspace| workspace.name))
+    }
+
+    fn create_workspace(&self, request: &NewWorkspace<'_>) -> Result<CreatedWorkspace, String> {
+        let mut ignored: Vec<&str> = Vec::new();
+        if request.image.is_some() {
+            ignored.push("--image");
+        }
+        if !request.pull {
+            ignored.push("--no-pull");
+        }
+        if !ignored.is_empty() {
+            eprintln!(
+                "warn: ignoring container-only create options in host-native mode: {}",
+                ignored.join(" ")
+            );
+        }
+
+        let workspace_path = ensure_workspace_root()?.join(request.name);
+        if workspace_path.exists() {
+            return Err(format!("workspace already exists: {}", request.name));
+        }
+
+        create_workspace_skeleton(&workspace_path, request.name, request.primary_repo)?;
+        if !request.env.is_empty() {
+            let env_path = workspace_path.join(WORKSPACE_ENV_FILE);
+            write_file_secure(&env_path, format_env_file(request.env).as_bytes())
+                .map_err(|err| format!("failed to write workspace env: {err}"))?;
+        }
+        Ok(CreatedWorkspace {
+            name: request.name.to_string(),
+            path: workspace_path.display().to_string(),
+        })
+    }
+
+    fn workspace_dir(&self, workspace: &str) -> Result<String, String> {
+        Ok(self.workspace(workspace)?.path.display().to_string())
+    }
+
+    fn local_dir(&self, workspace: &str) -> Option<PathBuf> {
+        self.workspace(workspace)
+            .ok()
+            .map(|workspace| workspace.path)
+    }
+
+    fn clone_repo(
+        &self,
+        workspace: &str,
+        repo: &RepoSpec,
+        slot: RepoSlot,
+        options: &CloneOptions<'_>,
+    ) -> Result<String, String> {
+        let workspace = self.workspace(workspace)?;
+        let root = match slot {
+            RepoSlot::Work => workspace.path.join("work"),
+            RepoSlot::Private => workspace.path.join("private"),
+        };
+        let destination = workspace_repo_destination(&root, repo);
+        if options.worktree {
+            let mirror = ensure_repo_mirror(repo, options.quiet)?;
+            add_repo_worktree(
+                repo,
+                &mirror,
+                &destination,
+                &workspace.name,
+                options.refspec,
+            )?;
+        } else {
+            clone_repo_into(repo, &destination, options)?;
+        }
+        Ok(destination.display().to_string())
+    }
+
+    fn supports_worktrees(&self) -> bool {
+        true
+    }
+
+    fn remove(&self, workspace: &str, _options: &RemoveOptions) -> Result<(), String> {
+        let workspace = self.workspace(workspace)?;
+        let worktrees = linked_worktrees(&workspace);
+        fs::remove_dir_all(&workspace.path)
+            .map_err(|err| format!("{}: {err}", workspace.path.display()))?;
+
+        for (mirror, branch) in worktrees {
+            prune_repo_worktree(&mirror, branch.as_deref(), &workspace.name);
+        }
+        Ok(())
+    }
+
+    fn clone_workspace(&self, source: &str, new_name: &str) -> Result<CreatedWorkspace, String> {
+        let source = self.workspace(source)?;
+        let target = ensure_workspace_root()?.join(new_name);
+        if target.exists() {
+            return Err(format!("workspace already exists: {new_name}"));
+        }
+
+        let output = Command::new("cp")
+            .arg("-a")
+            .arg(&source.path)
+            .arg(&target)
+            .output()
+            .map_err(|err| format!("failed to run cp: {err}"))?;
+        if !output.status.success() {
+            let _ = fs::remove_dir_all(&target);
+            return Err(format!(
+                "failed to copy {}: {}",
+                source.path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        // The source's detached tunnel is not the clone's.
+        let _ = fs::remove_file(target.join(CODE_TUNNEL_STATE_FILE));
+
+        let mut created_unix = None;
+        let mut primary_repo = None;
+        let meta = fs::read_to_string(source.path.join(WORKSPACE_META_FILE)).unwrap_or_default();
+        for line in meta.lines() {
+            apply_meta_line(line, &mut created_unix, &mut primary_repo);
+        }
+        write_workspace_meta(&target, new_name, primary_repo.as_deref())?;
+
+        let cloned = Workspace {
+            name: new_name.to_string(),
+            path: target,
+        };
+        detach_copied_worktrees(&cloned);
+        Ok(CreatedWorkspace {
+            name: cloned.name,
+            path: cloned.path.display().to_string(),
+        })
+    }
+
+    fn snapshot(
+        &self,
+        workspace: &str,
+        staging: &Path,
+        include_secrets: bool,
+    ) -> Result<SnapshotManifest, String> {
+        let workspace = self.workspace(workspace)?;
+        let excludes: &[&str] = if include_secrets {
+            &[]
+        } else {
+            &HOST_SECRET_EXCLUDES
+        };
+        tar_directory(
+            &workspace.path,
+            &part_path(staging, HOST_WORKSPACE_PART),
+            excludes,
+        )?;
+        Ok(SnapshotManifest {
+            layout: String::from(HOST_LAYOUT),
+            name: workspace.name,
+            parts: vec![String::from(HOST_WORKSPACE_PART)],
+            secrets: include_secrets,
+            ..SnapshotManifest::default()
+        })
+    }
+
+    fn restore(
+        &self,
+        manifest: &SnapshotManifest,
+        staging: &Path,
+        new_name: &str,
+        _options: &RestoreOptions<'_>,
+    ) -> Result<CreatedWorkspace, String> {
+        manifest.expect_layout(HOST_LAYOUT, self.runtime_name())?;
+        let target = ensure_workspace_root()?.join(new_name);
+        if target.exists() {
+            return Err(format!("workspace already exists: {new_name}"));
+        }
+
+        fs::create_dir_all(&target)
+            .map_err(|err| format!("failed to create {}: {err}", target.display()))?;
+        if let Err(err) = untar_directory(&part_path(staging, HOST_WORKSPACE_PART), &target) {
+            let _ = fs::remove_dir_all(&target);
+            return Err(err);
+        }
+
+        let restored = Workspace {
+            name: new_name.to_string(),
+            path: target,
+        };
+        rewrite_meta_name(&restored)?;
+        detach_copied_worktrees(&restored);
+        Ok(CreatedWorkspace {
+            name: restored.name,
+            path: restored.path.display().to_string(),
+        })
+    }
+
+    fn rename(&self, workspace: &str, new_name: &str) -> Result<String, String> {
+        let workspace = self.workspace(workspace)?;
+        let target = ensure_workspace_root()?.join(new_name);
+        if target.exists() {
+            return Err(format!("workspace already exists: {new_name}"));
+        }
+
+        fs::rename(&workspace.path, &target).map_err(|err| {
+            format!(
+                "failed to move {} to {}: {err}",
+                workspace.path.display(),
+                target.display()
+            )
+        })?;
+        let renamed = Workspace {
+            name: new_name.to_string(),
+            path: target,
+        };
+        rewrite_meta_name(&renamed)?;
+        relink_worktrees(&renamed, &workspace.name);
+        Ok(renamed.name)
+    }
+
+    fn exec(&self, workspace: &str, request: &ExecRequest<'_>) -> Result<i32, String> {
+        let workspace = self.workspace(workspace)?;
+
+        if request.user.is_some() {
+            eprintln!("warn: --root/--user is ignored in host-native exec mode");
+        }
+
+        let mut command = if request.command.is_empty() {
+            let shell = std::env::var_os("SHELL").unwrap_or_else(|| OsString::from("/bin/bash"));
+            let mut cmd = Command::new(shell);
+            if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+                cmd.arg("-l");
+            }
+            cmd
+        } else {
+            let mut cmd = Command::new(&request.command[0]);
+            if request.command.len() > 1 {
+                cmd.args(&request.command[1..]);
+            }
+            cmd
+        };
+
+        command.current_dir(&workspace.path);
+        command.envs(read_env_file(&workspace.path.join(WORKSPACE_ENV_FILE)));
+        command.stdin(Stdio::inherit());
+        command.stdout(Stdio::inherit());
+        command.stderr(Stdio::inherit());
+
+        command
+            .status()
+            .map(|status| status.code().unwrap_or(EXIT_RUNTIME))
+            .map_err(|err| {
+                format!(
+                    "failed to run command in {}: {err}",
+                    workspace.path.display()
+                )
+            })
+    }
+
+    fn default_work_repos_depth(&self) -> u32 {
+        3
+    }
+
+    fn map_repo_path(&self, workspace: &str, raw: &str) -> Result<String, String> {
+        let workspace = self.workspace(workspace)?;
+        Ok(map_workspace_repo_path(&workspace, raw)
+            .display()
+            .to_string())
+    }
+
+    fn repo_path(&self, workspace: &str, raw: &str) -> Result<String, String> {
+        let target_repo = self.map_repo_path(workspace, raw)?;
+        if !Path::new(&target_repo).join(".git").exists() {
+            return Err(format!("not a git repo: {target_repo}"));
+        }
+        Ok(target_repo)
+    }
+
+    fn list_repos(&self, _workspace: &str, root: &str, depth: u32) -> Result<Vec<String>, String> {
+        Ok(list_git_repos_on_host(Path::new(root), depth)?
+            .into_iter()
+            .map(|path| path.display().to_string())
+            .collect())
+    }
+
+    fn private_repo(&self, workspace: &str) -> Result<Option<String>, String> {
+        let private_root = self.workspace(workspace)?.path.join("private");
+        if !private_root.exists() {
+            return Ok(None);
+        }
+
+        let repos = list_git_repos_on_host(&private_root, 4)?;
+        Ok(repos
+            .into_iter()
+            .next()
+            .map(|path| path.display().to_string()))
+    }
+
+    fn reset_repo(&self, _workspace: &str, repo_dir: &str, refspec: &str) -> Result<(), String> {
+        let status = Command::new("bash")
+            .args(["-c", RESET_REPO_SCRIPT, "--", repo_dir, refspec])
+            .status()
+            .map_err(|err| format!("failed to reset repo {repo_dir}: {err}"))?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "failed to reset repo {repo_dir} (exit {})",
+                status.code().unwrap_or(EXIT_RUNTIME)
+            ))
+        }
+    }
+
+    fn store_github_token(&self, workspace: &str, host: &str, token: &str) -> Result<(), String> {
+        let workspace = self.workspace(workspace)?;
+        let content = format!("host={host}\ntoken={token}\n");
+        let target = workspace.path.join("auth").join("github.env");
+        write_file_secure(&target, content.as_bytes()).map_err(|err| {
+            format!(
+                "failed to write GitHub auth file {}: {err}",
+                target.display()
+            )
+        })
+    }
+
+    fn store_codex_auth(&self, workspace: &str, auth_data: &[u8]) -> Result<(), String> {
+        let workspace = self.workspace(workspace)?;
+        for target in codex_auth_targets(&workspace) {
+            write_file_secure(&target, auth_data)?;
+        }
+        Ok(())
+    }
+
+    fn verify_gpg_key(&self, _workspace: &str, key: &str) -> Result<(), String> {
+        if !command_exists("gpg") {
+            eprintln!("warn: gpg not found in PATH; writing key id only");
+            return Ok(());
+        }
+
+        let status = Command::new("gpg")
+            .args(["--batch", "--list-secret-keys", key])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+
+        match status {
+            Ok(result) if result.success() => Ok(()),
+            Ok(_) => Err(format!("gpg key not found in host keyring: {key}")),
+            Err(err) => Err(format!("failed to run gpg for key lookup: {err}")),
+        }
+    }
+
+    fn store_gpg_key(&self, workspace: &str, key: &str) -> Result<(), String> {
+        let workspace = self.workspace(workspace)?;
+        let target = workspace.path.join("auth").join("gpg-key.txt");
+        write_file_secure(&target, format!("{key}\n").as_bytes())
+            .map_err(|err| format!("failed to write gpg auth file {}: {err}", target.display()))
+    }
+
+    fn unmanaged_dirs(&self) -> Result<Vec<String>, String> {
+        Ok(list_workspaces_on_disk()?
+            .into_iter()
+            .filter(|workspace| !workspace.path.join(WORKSPACE_META_FILE).is_file())
+            .map(|workspace| workspace.path.display().to_string())
+            .collect())
+    }
+
+    fn supports_rsync(&self) -> bool {
+        true
+    }
+
+    fn rsync(&self, workspace: &str, transfer: &RsyncTransfer) -> Result<(), String> {
+        let workspace = self.workspace(workspace)?;
+        if transfer.user != "agent" {
+            eprintln!("warn: --user/--root is ignored by the host runtime");
+        }
+        let (src, dest) = match transfer.direction {
+            RsyncDirection::Push => (
+                transfer.src.clone(),
+                map_workspace_operand(&workspace, &transfer.dest),
+            ),
+            RsyncDirection::Pull => (
+                map_workspace_operand(&workspace, &transfer.src),
+                transfer.dest.clone(),
+            ),
+        };
+
+        if !command_exists("rsync") {
+            if !transfer.rsync_args.is_empty() || transfer.gitignore {
+                return Err(String::from(
+                    "rsync not found on host; the built-in copy does not support rsync args or --gitignore",
+                ));
+            }
+            let options = SyncOptions {
+                delete: transfer.delete,
+                dry_run: transfer.dry_run,
+            };
+            if !transfer.paths.is_empty() {
+                return sync_paths(&src, &dest, &transfer.paths, options);
+            }
+            println!("info: rsync not found on host; using built-in copy");
+            return sync_tree(&src, &dest, options);
+        }
+
+        let mut argv = transfer.rsync_flags();
+        argv.push(src);
+        argv.push(dest);
+        run_rsync(&argv, &[], transfer)
+    }
+
+    fn copy_in(&self, workspace: &str, request: &CopyRequest) -> Result<String, String> {
+        let workspace = self.workspace(workspace)?;
+        let dest = map_workspace_operand(&workspace, &request.dest);
+        copy_like_docker_cp(Path::new(&request.src), request.contents_only, &dest)
+            .map(|target| target.display().to_string())
+    }
+
+    fn copy_out(&self, workspace: &str, request: &CopyRequest) -> Result<String, String> {
+        let workspace = self.workspace(workspace)?;
+        let src = map_workspace_operand(&workspace, &request.src);
+        copy_like_docker_cp(Path::new(&src), request.contents_only, &request.dest)
+            .map(|target| target.display().to_string())
+    }
+
+    fn doctor_checks(&self) -> Vec<DoctorCheck> {
+        let root = workspace_storage_root();
+        let probe = root.join(format!(".awl-doctor-{}", std::process::id()));
+        let writable = fs::create_dir_all(&root)
+            .and_then(|_| fs::write(&probe, b""))
+            .and_then(|_| fs::remove_file(&probe));
+        vec![match writable {
+            Ok(()) => DoctorCheck::ok("workspace-root", format!("{} is writable", root.display())),
+            Err(err) => DoctorCheck::fail(
+                "workspace-root",
+                format!("{} is not writable: {err}", root.display()),
+                "set AGENT_WORKSPACE_HOME (or `home` in the config file) to a writable directory",
+            ),
+        }]
+    }
+
+    fn disk_usage(&self, workspace: &str) -> Result<Option<u64>, String> {
+        let workspace = self.workspace(workspace)?;
+        let output = Command::new("du")
+            .arg("-sk")
+            .arg(&workspace.path)
+            .output()
+            .map_err(|err| format!("failed to run du: {err}"))?;
+        Ok(parse_du_kib(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    fn inspect(&self, workspace: &str) -> Result<WorkspaceDetails, String> {
+        let workspace = self.workspace(workspace)?;
+        let output = Command::new("bash")
+            .arg("-c")
+            .arg(WORKSPACE_STATUS_SCRIPT)
+            .arg("--")
+            .args(workspace_status_args(
+                &workspace,
+                self.default_work_repos_depth(),
+            ))
+            .output()
+            .map_err(|err| format!("failed to inspect {}: {err}", workspace.name))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Err(format!(
+                "failed to inspect {} (exit {}): {stderr}",
+                workspace.name,
+                output.status.code().unwrap_or(EXIT_RUNTIME)
+            ));
+        }
+
+        let mut details = WorkspaceDetails {
+            name: workspace.name.clone(),
+            path: workspace.path.display().to_string(),
+            ..WorkspaceDetails::default()
+        };
+        details.apply_status_output(&String::from_utf8_lossy(&output.stdout));
+        Ok(details)
+    }
+
+    fn tunnel(
+        &self,
+        workspace: &str,
+        request: &TunnelRequest<'_>,
+    ) -> Result<TunnelOutcome, String> {
+        let workspace = self.workspace(workspace)?;
+
+        if !command_exists("code") {
+            return Err(String::from(
+                "'code' command not found in PATH (required for tunnel)",
+            ));
+        }
+
+        if request.detach {
+            let log_path = workspace.path.join(CODE_TUNNEL_LOG_FILE);
+            let output = Command::new("bash")
+                .arg("-c")
+                .arg(DETACHED_TUNNEL_SCRIPT)
+                .arg("--")
+                .arg(&workspace.path)
+                .arg(&log_path)
+                .arg(workspace.path.join(CODE_TUNNEL_STATE_FILE))
+                .arg(request.name.unwrap_or_default())
+                .stdin(Stdio::null())
+                .output()
+                .map_err(|err| format!("failed to launch tunnel: {err}"))?;
+            if !output.status.success() {
+                return Err(format!(
+                    "failed to launch tunnel: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            return Ok(TunnelOutcome::Detached {
+                tunnel_name: request.name.map(str::to_string),
+                pid: String::from_utf8_lossy(&output.stdout).trim().parse().ok(),
+                log_path: Some(log_path.display().to_string()),
+            });
+        }
+
+        let mut cmd = Command::new("code");
+        cmd.arg("tunnel");
+        cmd.arg("--accept-server-license-terms");
+        if let Some(tunnel_name) = request.name {
+            cmd.args(["--name", tunnel_name]);
+        }
+        cmd.current_dir(&workspace.path);
+
+        cmd.stdin(Stdio::inherit());
+        cmd.stdout(Stdio::inherit());
+        cmd.stderr(Stdio::inherit());
+
+        let status = cmd
+            .status()
+            .map_err(|err| format!("failed to run tunnel command: {err}"))?;
+        Ok(TunnelOutcome::Exited {
+            tunnel_name: request.name.map(str::to_string),
+            exit_code: status.code().unwrap_or(EXIT_RUNTIME),
+        })
+    }
+
+    fn tunnel_control(&self, workspace: &str, action: TunnelAction) -> Result<String, String> {
+        let state_path = self.workspace(workspace)?.path.join(CODE_TUNNEL_STATE_FILE);
+        let [action_arg, follow] = action.script_args();
+        let streaming = matches!(action, TunnelAction::Logs { .. });
+        let output = Command::new("bash")
+            .arg("-c")
+            .arg(TUNNEL_CONTROL_SCRIPT)
+            .arg("--")
+            .arg(&state_path)
+            .args([action_arg, follow])
+            .stdin(Stdio::null())
+            .stdout(if streaming {
+                Stdio::inherit()
+            } else {
+                Stdio::piped()
+            })
+            .output()
+            .map_err(|err| format!("failed to run tunnel control script: {err}"))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+fn create_workspace_skeleton(
+    workspace_path: &Path,
+    workspace_name: &str,
+    primary_repo: Option<&RepoSpec>,
+) -> Result<(), String> {
+    fs::create_dir_all(workspace_path).map_err(|err| {
+        format!(
+            "failed to create workspace directory {}: {err}",
+            workspace_path.display()
+        )
+    })?;
+
+    for subdir in ["work", "opt", "private", "auth", ".codex"] {
+        fs::create_dir_all(workspace_path.join(subdir)).map_err(|err| {
+            format!(
+                "failed to create workspace subdir {}: {err}",
+                workspace_path.join(subdir).display()
+            )
+        })?;
+    }
+
+    write_workspace_meta(
+        workspace_path,
+        workspace_name,
+        primary_repo.map(|repo| repo.owner_repo.as_str()),
+    )
+}
+
+fn write_workspace_meta(
+    workspace_path: &Path,
+    workspace_name: &str,
+    primary_repo: Option<&str>,
+) -> Result<(), String> {
+    let created_unix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    let metadata = format!(
+        "name={workspace_name}\ncreated_unix={created_unix}\nprimary_repo={}\n",
+        primary_repo.unwrap_or("none")
+    );
+    fs::write(workspace_path.join(WORKSPACE_META_FILE), metadata).map_err(|err| {
+        format!(
+            "failed to write workspace metadata {}: {err}",
+            workspace_path.join(WORKSPACE_META_FILE).display()
+        )
+    })
+}
+
+fn format_env_file(env: &[(String, String)]) -> String {
+    env.iter()
+        .map(|(name, value)| format!("{name}={value}\n"))
+        .collect()
+}
+
+fn read_env_file(path: &Path) -> Vec<(String, String)> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+fn clone_repo_into(
+    repo: &RepoSpec,
+    destination: &Path,
+    options: &CloneOptions<'_>,
+) -> Result<(), String> {
+    if destination.join(".git").exists() {
+        return Ok(());
+    }
+
+    if destination.exists() {
+        return Err(format!(
+            "destination exists but is not a git repo: {}",
+            destination.display()
+        ));
+    }
+
+    if !command_exists("git") {
+        return Err(String::from("git not found in PATH"));
+    }
+
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("failed to create clone parent {}: {err}", parent.display()))?;
+    }
+
+    let mut clone = Command::new("git");
+    clone
+        .arg("clone")
+        .arg("--progress")
+        .args(options.clone_flags())
+        .arg(&repo.clone_url)
+        .arg(destination);
+    run_clone_step(&mut clone, options.quiet, "git clone", repo)?;
+
+    if options.has_clone_mode() {
+        let mut mode = Command::new("bash");
+        mode.args(["-c", CLONE_MODE_SCRIPT, "--"])
+            .args(options.clone_mode_args(&destination.display().to_string()));
+        run_clone_step(&mut mode, options.quiet, "clone mode setup", repo)?;
+    }
+
+    if let Some(refspec) = options.refspec {
+        let mut checkout = Command::new("git");
+        checkout
+            .arg("-C")
+            .arg(destination)
+            .args(["checkout", refspec]);
+        run_clone_step(
+            &mut checkout,
+            options.quiet,
+            &format!("git checkout {refspec}"),
+            repo,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Run one git step of a clone, streaming its output unless `quiet`.
+fn run_clone_step(
+    cmd: &mut Command,
+    quiet: bool,
+    step: &str,
+    repo: &RepoSpec,
+) -> Result<(), String> {
+    let (status, stderr) = if quiet {
+        let output = cmd
+            .output()
+            .map_err(|err| format!("failed to run {step} for {}: {err}", repo.owner_repo))?;
+        (
+            output.status,
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        )
+    } else {
+        let status = cmd
+            .status()
+            .map_err(|err| format!("failed to run {step} for {}: {err}", repo.owner_repo))?;
+        (status, String::new())
+    };
+    if status.success() {
+        return Ok(());
+    }
+
+    let mut err = format!(
+        "{step} failed for {} (exit {})",
+        repo.owner_repo,
+        status.code().unwrap_or(EXIT_RUNTIME)
+    );
+    let mut lines = stderr
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    let reason = lines
+        .clone()
+        .find(|line| line.starts_with("fatal:") || line.starts_with("error:"))
+        .or_else(|| lines.next_back());
+    if let Some(line) = reason {
+        err.push_str(&format!(": {line}"));
+    }
+    Err(err)
+}
+
+fn repo_mirror_path(root: &Path, repo: &RepoSpec) -> PathBuf {
+    root.join(MIRRORS_DIR)
+        .join(&repo.owner)
+        .join(format!("{}.git", repo.repo))
+}
+
+/// Create or refresh the bare mirror for `repo`, with origin fetched into `refs/remotes/origin/*`
+/// so worktrees see the same remote-tracking refs as a regular clone.
+fn ensure_repo_mirror(repo: &RepoSpec, quiet: bool) -> Result<PathBuf, String> {
+    if !command_exists("git") {
+        return Err(String::from("git not found in PATH"));
+    }
+
+    let mirror = repo_mirror_path(&ensure_workspace_root()?, repo);
+    if mirror.join("HEAD").is_file() {
+        let url = git_stdout(&mirror, &["config", "--get", "remote.origin.url"])?;
+        if url != repo.clone_url {
+            return Err(format!(
+                "mirror {} tracks {url}, not {}",
+                mirror.display(),
+                repo.clone_url
+            ));
+        }
+    } else {
+        if let Some(parent) = mirror.parent() {
+            fs::create_dir_all(parent).map_err(|err| {
+                format!("failed to create mirror parent {}: {err}", parent.display())
+            })?;
+        }
+
+        let mut clone = Command::new("git");
+        clone
+            .args(["clone", "--bare", "--progress"])
+            .arg(&repo.clone_url)
+            .arg(&mirror);
+        run_clone_step(&mut clone, quiet, "git clone --bare", repo)?;
+
+        git_run(
+            &mirror,
+            &[
+                "config",
+                "remote.origin.fetch",
+                "+refs/heads/*:refs/remotes/origin/*",
+            ],
+        )?;
+    }
+
+    git_run(&mirror, &["fetch", "--prune", "--quiet", "origin"])
+        .map_err(|err| format!("failed to refresh mirror for {}: {err}", repo.owner_repo))?;
+    Ok(mirror)
+}
+
+/// Add `destination` as a worktree of `mirror` on a branch named after the workspace, so
+/// several workspaces can track the same upstream branch without fighting over it.
+fn add_repo_worktree(
+    repo: &RepoSpec,
+    mirror: &Path,
+    destination: &Path,
+    workspace_name: &str,
+    refspec: Option<&str>,
+) -> Result<(), String> {
+    if destination.join(".git").exists() {
+        return Ok(());
+    }
+
+    if destination.exists() {
+        return Err(format!(
+            "destination exists but is not a git repo: {}",
+            destination.display()
+        ));
+    }
+
+    let start = match refspec {
+        Some(refspec) => {
+            let remote_ref = format!("refs/remotes/origin/{refspec}");
+            if git_run(mirror, &["show-ref", "--verify", "--quiet", &remote_ref]).is_ok() {
+                format!("origin/{refspec}")
+            } else {
+                refspec.to_string()
+            }
+        }
+        None => match git_stdout(mirror, &["symbolic-ref", "--short", "HEAD"]) {
+            Ok(branch) => format!("origin/{branch}"),
+            Err(_) => String::from("HEAD"),
+        },
+    };
+
+    let destination_arg = destination.to_string_lossy();
+    git_run(
+        mirror,
+        &[
+            "worktree",
+            "add",
+            "--quiet",
+            "-B",
+            workspace_name,
+            destination_arg.as_ref(),
+            &start,
+        ],
+    )
+    .map_err(|err| format!("failed to add worktree for {}: {err}", repo.owner_repo))
+}
+
+/// Collect `(mirror, branch)` for every repo in the workspace that is a linked worktree.
+fn linked_worktrees(workspace: &Workspace) -> Vec<(PathBuf, Option<String>)> {
+    let Ok(repos) = list_git_repos_on_host(&workspace.path, 3) else {
+        return Vec::new();
+    };
+
+    repos
+        .into_iter()
+        .filter(|repo| repo.join(".git").is_file())
+        .filter_map(|repo| {
+            let common_dir = git_stdout(
+                &repo,
+                &["rev-parse", "--path-format=absolute", "--git-common-dir"],
+            )
+            .ok()?;
+            let branch = git_stdout(&repo, &["symbolic-ref", "-q", "--short", "HEAD"]).ok();
+            Some((PathBuf::from(common_dir), branch))
+        })
+        .collect()
+}
+
+fn prune_repo_worktree(mirror: &Path, branch: Option<&str>, workspace_name: &str) {
+    if let Err(err) = git_run(mirror, &["worktree", "prune"]) {
+        eprintln!(
+            "warn: failed to prune worktrees in {}: {err}",
+            mirror.display()
+        );
+        return;
+    }
+
+    if branch == Some(workspace_name)
+        && let Err(err) = git_run(mirror, &["branch", "-D", "--quiet", workspace_name])
+    {
+        eprintln!(
+            "warn: failed to delete branch {workspace_name} in {}: {err}",
+            mirror.display()
+        );
+    }
+}
+
+/// Give each worktree copied from another workspace its own registration and branch.
+///
+/// The copy's `.git` file still points at the source checkout's admin dir, so a fresh
+/// `--no-checkout` worktree is added at the same commit and its `.git` file moved over;
+/// the files on disk, including uncommitted changes, are kept as copied.
+fn detach_copied_worktrees(workspace: &Workspace) {
+    let Ok(repos) = list_git_repos_on_host(&workspace.path, 3) else {
+        return;
+    };
+    for repo in repos.iter().filter(|repo| repo.join(".git").is_file()) {
+        if let Err(err) = detach_copied_worktree(repo, &workspace.name) {
+            eprintln!(
+                "warn: failed to register worktree {}: {err}",
+                repo.display()
+            );
+        }
+    }
+}
+
+fn detach_copied_worktree(repo: &Path, workspace_name: &str) -> Result<(), String> {
+    let mirror = PathBuf::from(git_stdout(
+        repo,
+        &["rev-parse", "--path-format=absolute", "--git-common-dir"],
+    )?);
+    let head = git_stdout(repo, &["rev-parse", "HEAD"])?;
+
+    let mut staging = repo.as_os_str().to_os_string();
+    staging.push(".awl-clone");
+    let staging = PathBuf::from(staging);
+    let staging_arg = staging.to_string_lossy();
+    git_run(
+        &mirror,
+        &[
+            "worktree",
+            "add",
+            "--quiet",
+            "--no-checkout",
+            "-B",
+            workspace_name,
+            staging_arg.as_ref(),
+            &head,
+        ],
+    )?;
+
+    fs::rename(staging.join(".git"), repo.join(".git"))
+        .map_err(|err| format!("failed to move {}: {err}", staging.display()))?;
+    let _ = fs::remove_dir_all(&staging);
+    git_run(repo, &["worktree", "repair"])?;
+    git_run(repo, &["reset", "--quiet"])
+}
+
+/// Point moved worktrees back at their mirrors and carry the workspace-named branch along.
+fn relink_worktrees(workspace: &Workspace, old_name: &str) {
+    let Ok(repos) = list_git_repos_on_host(&workspace.path, 3) else {
+        return;
+    };
+    for repo in repos.iter().filter(|repo| repo.join(".git").is_file()) {
+        if let Err(err) = git_run(repo, &["worktree", "repair"]) {
+            eprintln!("warn: failed to repair worktree {}: {err}", repo.display());
+        }
+    }
+
+    for (mirror, branch) in linked_worktrees(workspace) {
+        if branch.as_deref() != Some(old_name) {
+            continue;
+        }
+        if let Err(err) = git_run(&mirror, &["branch", "-m", old_name, &workspace.name]) {
+            eprintln!(
+                "warn: failed to rename branch {old_name} in {}: {err}",
+                mirror.display()
+            );
+        }
+    }
+}
+
+fn rewrite_meta_name(workspace: &Workspace) -> Result<(), String> {
+    let meta_path = workspace.path.join(WORKSPACE_META_FILE);
+    let Ok(meta) = fs::read_to_string(&meta_path) else {
+        return Ok(());
+    };
+    let rewritten: String = meta
+        .lines()
+        .map(|line| {
+            if line.starts_with("name=") {
+                format!("name={}\n", workspace.name)
+            } else {
+                format!("{line}\n")
+            }
+        })
+        .collect();
+    fs::write(&meta_path, rewritten).map_err(|err| {
+        format!(
+            "failed to write workspace metadata {}: {err}",
+            meta_path.display()
+        )
+    })
+}
+
+fn git_run(dir: &Path, args: &[&str]) -> Result<(), String> {
+    git_stdout(dir, args).map(|_| ())
+}
+
+fn git_stdout(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|err| format!("failed to run git {}: {err}", args.join(" ")))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(format!(
+            "git {} failed (exit {}): {stderr}",
+            args.join(" "),
+            output.status.code().unwrap_or(EXIT_RUNTIME)
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// `map_workspace_internal_path` for an `rsync`/`cp` operand, keeping a trailing `/` (a directory
+/// for both); `/` alone is the workspace directory.
+pub(super) fn map_workspace_operand(workspace: &Workspace, raw: &str) -> String {
+    let mut mapped = if raw.trim_start_matches('/').is_empty() && raw.starts_with('/') {
+        workspace.path.display().to_string()
+    } else {
+        map_workspace_internal_path(workspace, raw)
+            .display()
+            .to_string()
+    };
+    if raw.ends_with('/') && !mapped.ends_with('/') {
+        mapped.push('/');
+    }
+    mapped
+}
+
+pub(super) fn codex_auth_targets(workspace: &Workspace) -> Vec<PathBuf> {
+    let mut targets = vec![workspace.path.join(".codex").join("auth.json")];
+
+    if let Some(value) = crate::config::value("codex_auth_file")
+        && let Some(cleaned) = trimmed_nonempty(&value)
+    {
+        let mapped = map_workspace_internal_path(workspace, &cleaned);
+        push_unique_path(&mut targets, mapped);
+    }
+
+    targets
+}
+
+/// Bytes from the first `du -sk` line; `du` may still print a total after permission errors.
+pub(super) fn parse_du_kib(output: &str) -> Option<u64> {
+    output
+        .split_whitespace()
+        .next()
+        .and_then(|kib| kib.parse::<u64>().ok())
+        .map(|kib| kib * 1024)
+}
+
+/// `WORKSPACE_STATUS_SCRIPT` arguments for a host-layout workspace (host and ssh runtimes).
+pub(super) fn workspace_status_args(workspace: &Workspace, depth: u32) -> Vec<String> {
+    let mut args = vec![
+        workspace.path.join(WORKSPACE_META_FILE),
+        PathBuf::from(depth.to_string()),
+        workspace.path.join("work"),
+        workspace.path.join("private"),
+        PathBuf::from("--"),
+        workspace.path.join("auth").join("github.env"),
+        workspace.path.join("auth").join("gpg-key.txt"),
+    ];
+    args.extend(codex_auth_targets(workspace));
+    args.into_iter()
+        .map(|path| path.display().to_string())
+        .collect()
+}
+
+fn list_git_repos_on_host(root: &Path, depth: u32) -> Result<Vec<PathBuf>, String> {
+    if depth == 0 {
+        return Err(String::from("--depth must be a positive integer"));
+    }
+
+    let output = Command::new("bash")
+        .args([
+            "-c",
+            LIST_GIT_REPOS_SCRIPT,
+            "--",
+            root.to_string_lossy().as_ref(),
+            &depth.to_string(),
+        ])
+        .output()
+        .map_err(|err| format!("failed to list git repos under {}: {err}", root.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(format!(
+            "failed to list git repos under {} (exit {}): {stderr}",
+            root.display(),
+            output.status.code().unwrap_or(EXIT_RUNTIME)
+        ));
+    }
+
+    let mut repos: Vec<PathBuf> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect();
+    repos.sort();
+    repos.dedup();
+    Ok(repos)
+}
+
+pub(super) fn map_workspace_repo_path(workspace: &Workspace, raw: &str) -> PathBuf {
+    let cleaned = raw.trim();
+    if cleaned.is_empty() {
+        return workspace.path.clone();
+    }
+
+    if cleaned == "/work" {
+        return workspace.path.join("work");
+    }
+    if let Some(rest) = cleaned.strip_prefix("/work/") {
+        return workspace.path.join("work").join(rest);
+    }
+
+    if cleaned == "/opt" {
+        return workspace.path.join("opt");
+    }
+    if let Some(rest) = cleaned.strip_prefix("/opt/") {
+        return workspace.path.join("opt").join(rest);
+    }
+
+    if cleaned == "~/.private" {
+        return workspace.path.join("private");
+    }
+    if let Some(rest) = cleaned.strip_prefix("~/.private/") {
+        return workspace.path.join("private").join(rest);
+    }
+
+    let path = Path::new(cleaned);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        workspace.path.join(path)
+    }
+}
@@ -0,0 +1,228 @@
+use std::ffi::OsString;
+
+use crate::EXIT_RUNTIME;
+
+use super::backend::{RepoStatus, WorkspaceBackend, WorkspaceDetails};
+use super::{PRIMARY_COMMAND_NAME, json_escape, trimmed_nonempty};
+
+#[derive(Debug, Default, Clone)]
+pub(super) struct ParsedInspect {
+    pub(super) show_help: bool,
+    pub(super) json: bool,
+    pub(super) workspace: Option<String>,
+}
+
+pub(super) fn parse_inspect_args(args: &[OsString]) -> Result<ParsedInspect, String> {
+    let mut parsed = ParsedInspect::default();
+    let mut idx = 0usize;
+
+    while idx < args.len() {
+        let arg = args[idx].to_string_lossy();
+        match arg.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            "--json" => parsed.json = true,
+            "--output" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(String::from("missing value for --output"));
+                }
+                let output = args[idx].to_string_lossy();
+                if output != "json" {
+                    return Err(format!("unsupported --output value: {output}"));
+                }
+                parsed.json = true;
+            }
+            _ if arg.starts_with("--output=") => {
+                let output = &arg["--output=".len()..];
+                if output != "json" {
+                    return Err(format!("unsupported --output value: {output}"));
+                }
+                parsed.json = true;
+            }
+            _ if arg.starts_with('-') => {
+                return Err(format!("unknown option for inspect: {arg}"));
+            }
+            _ if parsed.workspace.is_none() => parsed.workspace = trimmed_nonempty(&arg),
+            _ => return Err(format!("unexpected arg for inspect: {arg}")),
+        }
+        idx += 1;
+    }
+
+    if parsed.workspace.is_none() && !parsed.show_help {
+        return Err(String::from("missing workspace name"));
+    }
+
+    Ok(parsed)
+}
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_inspect_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_inspect_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if parsed.show_help {
+        print_inspect_usage();
+        return 0;
+    }
+
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    let name = parsed.workspace.unwrap_or_default();
+    // Resolve without `ensure_ready`: inspecting a stopped container must not start it.
+    let details = match backend.resolve(&name) {
+        Ok(Some(workspace)) => backend.inspect(&workspace),
+        Ok(None) => Err(format!("workspace not found: {name}")),
+        Err(err) => Err(err),
+    };
+    let details = match details {
+        Ok(details) => details,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if parsed.json {
+        print_details_json(backend.runtime_name(), &details);
+    } else {
+        print_details_text(backend.runtime_name(), &details);
+    }
+
+    0
+}
+
+fn print_details_text(runtime: &str, details: &WorkspaceDetails) {
+    let or_dash = |value: Option<&str>| value.unwrap_or("-").to_string();
+
+    println!("workspace: {}", details.name);
+    println!("runtime: {runtime}");
+    println!("path: {}", details.path);
+    println!(
+        "created_unix: {}",
+        or_dash(details.created_unix.map(|ts| ts.to_string()).as_deref())
+    );
+    println!("primary_repo: {}", or_dash(details.primary_repo.as_deref()));
+    if details.image.is_some() || details.state.is_some() {
+        println!("image: {}", or_dash(details.image.as_deref()));
+        println!("image_digest: {}", or_dash(details.image_digest.as_deref()));
+        println!("state: {}", or_dash(details.state.as_deref()));
+    }
+    if !details.volumes.is_empty() {
+        println!("volumes: {}", details.volumes.join(" "));
+    }
+    for (key, values) in [
+        ("env", &details.env_names),
+        ("mounts", &details.mounts),
+        ("publish", &details.publish),
+    ] {
+        if !values.is_empty() {
+            println!("{key}: {}", values.join(" "));
+        }
+    }
+    if let Some(cpus) = details.cpus.as_deref() {
+        println!("cpus: {cpus}");
+    }
+    if let Some(memory) = details.memory.as_deref() {
+        println!("memory: {memory}");
+    }
+    if details.auth_files.is_empty() {
+        println!("auth: -");
+    } else {
+        println!("auth:");
+        for path in &details.auth_files {
+            println!("  {path}");
+        }
+    }
+    if details.repos.is_empty() {
+        println!("repos: -");
+    } else {
+        println!("repos:");
+        for repo in &details.repos {
+            println!("  {}", format_repo_status(repo));
+        }
+    }
+}
+
+fn format_repo_status(repo: &RepoStatus) -> String {
+    let mut line = format!(
+        "{} [{}]",
+        repo.path,
+        repo.branch.as_deref().unwrap_or("detached")
+    );
+    if repo.dirty {
+        line.push_str(" dirty");
+    }
+    if let Some(upstream) = repo.upstream.as_deref() {
+        line.push_str(&format!(
+            " {upstream} +{}/-{}",
+            repo.ahead.unwrap_or(0),
+            repo.behind.unwrap_or(0)
+        ));
+    }
+    line
+}
+
+fn print_details_json(runtime: &str, details: &WorkspaceDetails) {
+    let string = |value: Option<&str>| match value {
+        Some(value) => format!("\"{}\"", json_escape(value)),
+        None => String::from("null"),
+    };
+    let number = |value: Option<u64>| value.map_or_else(|| String::from("null"), |n| n.to_string());
+    let strings = |values: &[String]| {
+        let items: Vec<String> = values
+            .iter()
+            .map(|value| string(Some(value.as_str())))
+            .collect();
+        format!("[{}]", items.join(","))
+    };
+
+    let repos: Vec<String> = details
+        .repos
+        .iter()
+        .map(|repo| {
+            format!(
+                "{{\"path\":{},\"branch\":{},\"dirty\":{},\"upstream\":{},\"ahead\":{},\"behind\":{}}}",
+                string(Some(repo.path.as_str())),
+                string(repo.branch.as_deref()),
+                repo.dirty,
+                string(repo.upstream.as_deref()),
+                number(repo.ahead.map(u64::from)),
+                number(repo.behind.map(u64::from)),
+            )
+        })
+        .collect();
+
+    println!(
+        "{{\"runtime\":{},\"name\":{},\"path\":{},\"created_unix\":{},\"primary_repo\":{},\"image\":{},\"image_digest\":{},\"state\":{},\"volumes\":{},\"env\":{},\"mounts\":{},\"publish\":{},\"cpus\":{},\"memory\":{},\"auth_files\":{},\"repos\":[{}]}}",
+        string(Some(runtime)),
+        string(Some(details.name.as_str())),
+        string(Some(details.path.as_str())),
+        number(details.created_unix),
+        string(details.primary_repo.as_deref()),
+        string(details.image.as_deref()),
+        string(details.image_digest.as_deref()),
+        string(details.state.as_deref()),
+        strings(&details.volumes),
+        strings(&details.env_names),
+        strings(&details.mounts),
+        strings(&details.publish),
+        string(details.cpus.as_deref()),
+        string(details.memory.as_deref()),
+        strings(&details.auth_files),
+        repos.join(","),
+    );
+}
+
+fn print_inspect_usage() {
+    eprintln!(
+        "usage: {PRIMARY_COMMAND_NAME} inspect [--runtime <container|host|podman|ssh://host>] [--json|--output json] <workspace>"
+    );
+}
@@ -0,0 +1,288 @@
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::EXIT_RUNTIME;
+
+/// Flags of the built-in copy that mirror their `rsync` counterparts.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct SyncOptions {
+    pub(super) delete: bool,
+    pub(super) dry_run: bool,
+}
+
+/// In-process stand-in for `rsync -rlpt` when rsync is not installed.
+///
+/// Follows rsync's operand rules: a `src` ending in `/` copies the directory's contents into
+/// `dest`, otherwise the directory itself lands in `dest`; a file copied onto an existing
+/// directory (or a `dest` ending in `/`) keeps its name. Files whose size and mtime match are
+/// skipped, symlinks are recreated, and permissions and mtimes are preserved. Every transferred
+/// path is printed, and deletions as `deleting <path>`, as rsync does.
+pub(super) fn sync_tree(src: &str, dest: &str, options: SyncOptions) -> Result<(), String> {
+    let src_path = Path::new(src);
+    let meta = fs::symlink_metadata(src_path)
+        .map_err(|err| format!("failed to read source {src}: {err}"))?;
+    let name = src_path.file_name().map(PathBuf::from);
+
+    let (target, rel) = if meta.is_dir() && src.ends_with('/') {
+        (PathBuf::from(dest), PathBuf::new())
+    } else {
+        let name = name.ok_or_else(|| format!("cannot copy {src}: path has no file name"))?;
+        let into_dir = dest.ends_with('/') || Path::new(dest).is_dir();
+        if meta.is_dir() || into_dir {
+            (Path::new(dest).join(&name), name)
+        } else {
+            (PathBuf::from(dest), name)
+        }
+    };
+
+    if !options.dry_run
+        && let Some(parent) = target.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("failed to create {}: {err}", parent.display()))?;
+    }
+    sync_entry(src_path, &target, &rel, options)
+}
+
+/// Built-in counterpart of `rsync --files-from`: sync each `paths` entry (relative to the
+/// directory `base`) to the same relative path under `dest`. Paths that no longer exist are
+/// removed from `dest` only under `delete`, like `--delete-missing-args`.
+pub(super) fn sync_paths(
+    base: &str,
+    dest: &str,
+    paths: &[String],
+    options: SyncOptions,
+) -> Result<(), String> {
+    for path in paths {
+        let rel = Path::new(path);
+        let src = Path::new(base).join(rel);
+        let target = Path::new(dest).join(rel);
+        if fs::symlink_metadata(&src).is_err() {
+            if options.delete && fs::symlink_metadata(&target).is_ok() {
+                println!("deleting {path}");
+                remove_path(&target, options)?;
+            }
+            continue;
+        }
+        if !options.dry_run
+            && let Some(parent) = target.parent()
+        {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("failed to create {}: {err}", parent.display()))?;
+        }
+        sync_entry(&src, &target, rel, options)?;
+    }
+    Ok(())
+}
+
+/// Local half of `cp`, with `docker cp` destination rules; returns the path written.
+///
+/// A directory lands inside an existing `dest` directory (only its contents with
+/// `contents_only`, i.e. a `src/.` operand) or becomes `dest` when that does not exist yet. A
+/// file lands inside a `dest` directory or replaces `dest`. The parent of a new `dest` must
+/// exist, a `dest/` must be a directory, and a directory never replaces a file.
+pub(super) fn copy_like_docker_cp(
+    src: &Path,
+    contents_only: bool,
+    dest: &str,
+) -> Result<PathBuf, String> {
+    let meta = fs::symlink_metadata(src)
+        .map_err(|err| format!("no such file or directory: {} ({err})", src.display()))?;
+    let dest_path = Path::new(dest);
+    let dest_meta = fs::metadata(dest_path).ok();
+    let name = src.file_name();
+    let ensure_parent = || match dest_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => {
+            Err(format!("no such directory: {}", parent.display()))
+        }
+        _ => Ok(()),
+    };
+
+    if meta.is_dir() {
+        let target =
+            match dest_meta {
+                Some(dest_meta) if !dest_meta.is_dir() => {
+                    return Err(format!(
+                        "cannot copy a directory to a non-directory: {dest}"
+                    ));
+                }
+                Some(_) if contents_only => dest_path.to_path_buf(),
+                Some(_) => dest_path.join(name.ok_or_else(|| {
+                    format!("cannot copy {}: path has no file name", src.display())
+                })?),
+                None => {
+                    ensure_parent()?;
+                    PathBuf::from(dest.trim_end_matches('/'))
+                }
+            };
+        fs::create_dir_all(&target)
+            .map_err(|err| format!("failed to create {}: {err}", target.display()))?;
+        run_cp(&src.join("."), &target)?;
+        return Ok(target);
+    }
+
+    let target = if dest_meta.as_ref().is_some_and(fs::Metadata::is_dir) {
+        dest_path.join(
+            name.ok_or_else(|| format!("cannot copy {}: path has no file name", src.display()))?,
+        )
+    } else if dest.ends_with('/') {
+        return Err(format!("destination directory does not exist: {dest}"));
+    } else {
+        ensure_parent()?;
+        dest_path.to_path_buf()
+    };
+    match fs::symlink_metadata(&target) {
+        Ok(existing) if existing.is_dir() => {
+            return Err(format!(
+                "cannot overwrite directory {} with a file",
+                target.display()
+            ));
+        }
+        Ok(_) => fs::remove_file(&target)
+            .map_err(|err| format!("failed to replace {}: {err}", target.display()))?,
+        Err(_) => {}
+    }
+    run_cp(src, &target)?;
+    Ok(target)
+}
+
+/// `cp -a`, which keeps links, modes and mtimes the same way the in-workspace copy does.
+fn run_cp(src: &Path, dest: &Path) -> Result<(), String> {
+    let output = Command::new("cp")
+        .arg("-a")
+        .arg(src)
+        .arg(dest)
+        .output()
+        .map_err(|err| format!("failed to run cp: {err}"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if stderr.is_empty() {
+        Err(format!(
+            "cp {} {} failed (exit {})",
+            src.display(),
+            dest.display(),
+            output.status.code().unwrap_or(EXIT_RUNTIME)
+        ))
+    } else {
+        Err(stderr)
+    }
+}
+
+fn sync_entry(src: &Path, dest: &Path, rel: &Path, options: SyncOptions) -> Result<(), String> {
+    let meta = fs::symlink_metadata(src)
+        .map_err(|err| format!("failed to read {}: {err}", src.display()))?;
+    let existing = fs::symlink_metadata(dest).ok();
+
+    if meta.is_dir() {
+        if existing.as_ref().is_some_and(|existing| !existing.is_dir()) {
+            remove_path(dest, options)?;
+        }
+        if !existing.as_ref().is_some_and(fs::Metadata::is_dir) {
+            if !rel.as_os_str().is_empty() {
+                println!("{}/", rel.display());
+            }
+            if !options.dry_run {
+                fs::create_dir_all(dest)
+                    .map_err(|err| format!("failed to create {}: {err}", dest.display()))?;
+            }
+        }
+
+        let mut names: Vec<OsString> = fs::read_dir(src)
+            .map_err(|err| format!("failed to read {}: {err}", src.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.file_name()))
+            .collect();
+        names.sort();
+        for name in &names {
+            sync_entry(&src.join(name), &dest.join(name), &rel.join(name), options)?;
+        }
+
+        if options.delete && dest.is_dir() {
+            let keep: HashSet<&OsString> = names.iter().collect();
+            let mut stale: Vec<OsString> = fs::read_dir(dest)
+                .map_err(|err| format!("failed to read {}: {err}", dest.display()))?
+                .filter_map(|entry| entry.ok().map(|entry| entry.file_name()))
+                .filter(|name| !keep.contains(name))
+                .collect();
+            stale.sort();
+            for name in stale {
+                println!("deleting {}", rel.join(&name).display());
+                remove_path(&dest.join(&name), options)?;
+            }
+        }
+
+        if !options.dry_run && dest.is_dir() {
+            fs::set_permissions(dest, meta.permissions())
+                .map_err(|err| format!("failed to set permissions on {}: {err}", dest.display()))?;
+        }
+        return Ok(());
+    }
+
+    if let Some(existing) = existing.as_ref() {
+        if unchanged(src, &meta, dest, existing) {
+            return Ok(());
+        }
+        remove_path(dest, options)?;
+    }
+
+    println!("{}", rel.display());
+    if options.dry_run {
+        return Ok(());
+    }
+
+    if meta.file_type().is_symlink() {
+        let link = fs::read_link(src)
+            .map_err(|err| format!("failed to read link {}: {err}", src.display()))?;
+        return std::os::unix::fs::symlink(&link, dest)
+            .map_err(|err| format!("failed to create link {}: {err}", dest.display()));
+    }
+
+    // `fs::copy` carries the permission bits over; the mtime is what the next run compares.
+    fs::copy(src, dest).map_err(|err| {
+        format!(
+            "failed to copy {} to {}: {err}",
+            src.display(),
+            dest.display()
+        )
+    })?;
+    if let Ok(modified) = meta.modified() {
+        // A read-only handle is enough for `futimens`, and works for read-only files.
+        fs::File::open(dest)
+            .and_then(|file| file.set_modified(modified))
+            .map_err(|err| format!("failed to set mtime on {}: {err}", dest.display()))?;
+    }
+    Ok(())
+}
+
+/// rsync's quick check: same kind, and for files the same size and mtime.
+fn unchanged(src: &Path, meta: &fs::Metadata, dest: &Path, existing: &fs::Metadata) -> bool {
+    if meta.file_type().is_symlink() {
+        return existing.file_type().is_symlink()
+            && fs::read_link(src)
+                .ok()
+                .is_some_and(|link| fs::read_link(dest).ok() == Some(link));
+    }
+    existing.is_file()
+        && meta.len() == existing.len()
+        && meta
+            .modified()
+            .ok()
+            .is_some_and(|time| existing.modified().ok() == Some(time))
+}
+
+fn remove_path(path: &Path, options: SyncOptions) -> Result<(), String> {
+    if options.dry_run {
+        return Ok(());
+    }
+    let result = match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(_) => return Ok(()),
+    };
+    result.map_err(|err| format!("failed to remove {}: {err}", path.display()))
+}
@@ -1,13 +1,52 @@
 use std::ffi::OsString;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::EXIT_RUNTIME;
 
-use super::{PRIMARY_COMMAND_NAME, Workspace, json_escape, list_workspaces_on_disk};
+use super::backend::{WorkspaceBackend, WorkspaceEntry};
+use super::{PRIMARY_COMMAND_NAME, json_escape};
+
+const FILTER_KEYS: &[&str] = &["name", "repo", "state", "image"];
+const FORMAT_FIELDS: &[&str] = &[
+    "Name",
+    "Path",
+    "Repo",
+    "Runtime",
+    "State",
+    "Created",
+    "CreatedUnix",
+    "Image",
+    "Size",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) enum LsSort {
+    #[default]
+    Name,
+    /// Oldest first; workspaces without a creation time sort last.
+    Created,
+}
 
 #[derive(Debug, Default, Clone)]
 pub(super) struct ParsedLs {
     pub(super) show_help: bool,
     pub(super) json: bool,
+    pub(super) long: bool,
+    pub(super) filters: Vec<(String, String)>,
+    pub(super) sort: LsSort,
+    pub(super) format: Option<String>,
+}
+
+impl ParsedLs {
+    /// Disk usage needs a `du` per workspace, so it is only measured when shown.
+    fn wants_size(&self) -> bool {
+        match self.format.as_deref() {
+            Some(template) => template_fields(template)
+                .map(|fields| fields.contains(&"Size"))
+                .unwrap_or(false),
+            None => self.long,
+        }
+    }
 }
 
 pub(super) fn parse_ls_args(args: &[OsString]) -> Result<ParsedLs, String> {
@@ -15,27 +54,58 @@ pub(super) fn parse_ls_args(args: &[OsString]) -> Result<ParsedLs, String> {
     let mut idx = 0usize;
 
     while idx < args.len() {
-        let arg = args[idx].to_string_lossy();
-        match arg.as_ref() {
+        let arg = args[idx].to_string_lossy().into_owned();
+        let (option, inline) = match arg.split_once('=') {
+            Some((option, value)) if option.starts_with("--") => (option, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || -> Result<String, String> {
+            if let Some(value) = inline.clone() {
+                return Ok(value);
+            }
+            idx += 1;
+            args.get(idx)
+                .map(|value| value.to_string_lossy().into_owned())
+                .ok_or_else(|| format!("missing value for {option}"))
+        };
+
+        match option {
             "-h" | "--help" => parsed.show_help = true,
             "--json" => parsed.json = true,
+            "-l" | "--long" => parsed.long = true,
             "--output" => {
-                idx += 1;
-                if idx >= args.len() {
-                    return Err(String::from("missing value for --output"));
-                }
-                let output = args[idx].to_string_lossy();
+                let output = value()?;
                 if output != "json" {
                     return Err(format!("unsupported --output value: {output}"));
                 }
                 parsed.json = true;
             }
-            _ if arg.starts_with("--output=") => {
-                let output = &arg["--output=".len()..];
-                if output != "json" {
-                    return Err(format!("unsupported --output value: {output}"));
+            "--filter" => {
+                let filter = value()?;
+                let Some((key, expected)) = filter.split_once('=') else {
+                    return Err(format!("invalid --filter (expected key=value): {filter}"));
+                };
+                if !FILTER_KEYS.contains(&key) {
+                    return Err(format!(
+                        "unsupported --filter key: {key} (expected one of: {})",
+                        FILTER_KEYS.join(", ")
+                    ));
                 }
-                parsed.json = true;
+                parsed
+                    .filters
+                    .push((key.to_string(), expected.trim().to_string()));
+            }
+            "--sort" => {
+                parsed.sort = match value()?.as_str() {
+                    "name" => LsSort::Name,
+                    "created" => LsSort::Created,
+                    other => return Err(format!("unsupported --sort value: {other}")),
+                };
+            }
+            "--format" => {
+                let template = value()?;
+                template_fields(&template)?;
+                parsed.format = Some(template);
             }
             _ if arg.starts_with('-') => return Err(format!("unknown option for ls: {arg}")),
             _ => return Err(format!("unexpected arg for ls: {arg}")),
@@ -43,10 +113,14 @@ pub(super) fn parse_ls_args(args: &[OsString]) -> Result<ParsedLs, String> {
         idx += 1;
     }
 
+    if parsed.json && parsed.format.is_some() {
+        return Err(String::from("--format cannot be combined with --json"));
+    }
+
     Ok(parsed)
 }
 
-pub(super) fn run(args: &[OsString]) -> i32 {
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
     let parsed = match parse_ls_args(args) {
         Ok(parsed) => parsed,
         Err(err) => {
@@ -61,7 +135,12 @@ pub(super) fn run(args: &[OsString]) -> i32 {
         return 0;
     }
 
-    let workspaces = match list_workspaces_on_disk() {
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    let mut workspaces = match backend.list() {
         Ok(workspaces) => workspaces,
         Err(err) => {
             eprintln!("error: {err}");
@@ -69,33 +148,255 @@ pub(super) fn run(args: &[OsString]) -> i32 {
         }
     };
 
+    workspaces.retain(|workspace| {
+        parsed
+            .filters
+            .iter()
+            .all(|(key, expected)| filter_value(workspace, key) == Some(expected.as_str()))
+    });
+    match parsed.sort {
+        LsSort::Name => workspaces.sort_by(|left, right| left.name.cmp(&right.name)),
+        LsSort::Created => workspaces.sort_by_key(|workspace| {
+            (
+                workspace.created_unix.is_none(),
+                workspace.created_unix,
+                workspace.name.clone(),
+            )
+        }),
+    }
+
+    let rows: Vec<LsRow> = workspaces
+        .into_iter()
+        .map(|entry| {
+            let size_bytes = if parsed.wants_size() {
+                backend.disk_usage(&entry.name).unwrap_or_else(|err| {
+                    eprintln!("warn: failed to measure {}: {err}", entry.name);
+                    None
+                })
+            } else {
+                None
+            };
+            LsRow { entry, size_bytes }
+        })
+        .collect();
+
+    let runtime = backend.runtime_name();
     if parsed.json {
-        print_workspaces_json(&workspaces);
+        print_workspaces_json(runtime, &rows);
+    } else if let Some(template) = parsed.format.as_deref() {
+        let now = now_unix();
+        for row in &rows {
+            println!("{}", render_template(template, runtime, row, now));
+        }
+    } else if parsed.long {
+        print_workspaces_long(runtime, &rows);
     } else {
-        for workspace in workspaces {
-            println!("{}", workspace.name);
+        for row in rows {
+            println!("{}", row.entry.name);
         }
     }
 
     0
 }
 
-fn print_workspaces_json(workspaces: &[Workspace]) {
-    let mut out = String::from("{\"workspaces\":[");
-    for (idx, workspace) in workspaces.iter().enumerate() {
+struct LsRow {
+    entry: WorkspaceEntry,
+    size_bytes: Option<u64>,
+}
+
+fn filter_value<'a>(workspace: &'a WorkspaceEntry, key: &str) -> Option<&'a str> {
+    match key {
+        "name" => Some(workspace.name.as_str()),
+        "repo" => workspace.repo.as_deref(),
+        "state" => workspace.state.as_deref(),
+        "image" => workspace.image.as_deref(),
+        _ => None,
+    }
+}
+
+/// Field names referenced as `{{.Field}}` in a `--format` template.
+fn template_fields(template: &str) -> Result<Vec<&str>, String> {
+    let mut fields = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            return Err(format!("unterminated placeholder in --format: {template}"));
+        };
+        let inner = rest[start + 2..start + end].trim();
+        let field = inner.strip_prefix('.').unwrap_or(inner);
+        if !FORMAT_FIELDS.contains(&field) {
+            return Err(format!(
+                "unknown --format field: {inner} (expected one of: {})",
+                FORMAT_FIELDS
+                    .iter()
+                    .map(|field| format!(".{field}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        fields.push(field);
+        rest = &rest[start + end + 2..];
+    }
+    Ok(fields)
+}
+
+fn render_template(template: &str, runtime: &str, row: &LsRow, now: u64) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        // Templates were validated by `template_fields`, so every `{{` is closed.
+        let end = rest[start..].find("}}").unwrap_or(rest.len() - start);
+        out.push_str(&rest[..start]);
+        let inner = rest[start + 2..start + end].trim();
+        let field = inner.strip_prefix('.').unwrap_or(inner);
+        out.push_str(&field_value(field, runtime, row, now).unwrap_or_default());
+        rest = rest.get(start + end + 2..).unwrap_or_default();
+    }
+    out.push_str(rest);
+    out
+}
+
+fn field_value(field: &str, runtime: &str, row: &LsRow, now: u64) -> Option<String> {
+    let entry = &row.entry;
+    match field {
+        "Name" => Some(entry.name.clone()),
+        "Path" => entry
+            .path
+            .as_ref()
+            .map(|path| path.to_string_lossy().into_owned()),
+        "Repo" => entry.repo.clone(),
+        "Runtime" => Some(runtime.to_string()),
+        "State" => entry.state.clone(),
+        "Created" => entry.created_unix.map(|created| format_age(now, created)),
+        "CreatedUnix" => entry.created_unix.map(|created| created.to_string()),
+        "Image" => entry.image.clone(),
+        "Size" => row.size_bytes.map(format_size),
+        _ => None,
+    }
+}
+
+fn print_workspaces_long(runtime: &str, rows: &[LsRow]) {
+    const COLUMNS: [(&str, &str); 7] = [
+        ("NAME", "Name"),
+        ("REPO", "Repo"),
+        ("RUNTIME", "Runtime"),
+        ("STATE", "State"),
+        ("CREATED", "Created"),
+        ("IMAGE", "Image"),
+        ("SIZE", "Size"),
+    ];
+
+    let now = now_unix();
+    let mut table: Vec<Vec<String>> = vec![
+        COLUMNS
+            .iter()
+            .map(|(header, _)| header.to_string())
+            .collect(),
+    ];
+    for row in rows {
+        table.push(
+            COLUMNS
+                .iter()
+                .map(|(_, field)| {
+                    field_value(field, runtime, row, now).unwrap_or_else(|| String::from("-"))
+                })
+                .collect(),
+        );
+    }
+
+    let widths: Vec<usize> = (0..COLUMNS.len())
+        .map(|column| {
+            table
+                .iter()
+                .map(|cells| cells[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    for cells in table {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+}
+
+fn print_workspaces_json(runtime: &str, rows: &[LsRow]) {
+    let string = |value: Option<&str>| match value {
+        Some(value) => format!("\"{}\"", json_escape(value)),
+        None => String::from("null"),
+    };
+    let number = |value: Option<u64>| value.map_or_else(|| String::from("null"), |n| n.to_string());
+
+    let mut out = format!(
+        "{{\"runtime\":\"{}\",\"workspaces\":[",
+        json_escape(runtime)
+    );
+    for (idx, row) in rows.iter().enumerate() {
+        let workspace = &row.entry;
         if idx > 0 {
             out.push(',');
         }
+        out.push_str(&format!("{{\"name\":\"{}\"", json_escape(&workspace.name)));
+        if let Some(path) = workspace.path.as_ref() {
+            out.push_str(&format!(
+                ",\"path\":\"{}\"",
+                json_escape(&path.to_string_lossy())
+            ));
+        }
         out.push_str(&format!(
-            "{{\"name\":\"{}\",\"path\":\"{}\"}}",
-            json_escape(&workspace.name),
-            json_escape(&workspace.path.to_string_lossy())
+            ",\"repo\":{},\"state\":{},\"created_unix\":{},\"image\":{},\"size_bytes\":{}",
+            string(workspace.repo.as_deref()),
+            string(workspace.state.as_deref()),
+            number(workspace.created_unix),
+            string(workspace.image.as_deref()),
+            number(row.size_bytes),
         ));
+        out.push('}');
     }
     out.push_str("]}");
     println!("{out}");
 }
 
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+fn format_age(now: u64, created: u64) -> String {
+    let elapsed = now.saturating_sub(created);
+    match elapsed {
+        0..60 => String::from("just now"),
+        60..3_600 => format!("{}m ago", elapsed / 60),
+        3_600..86_400 => format!("{}h ago", elapsed / 3_600),
+        _ => format!("{}d ago", elapsed / 86_400),
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0usize;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if value < 10.0 {
+        format!("{value:.1}{}", UNITS[unit])
+    } else {
+        format!("{value:.0}{}", UNITS[unit])
+    }
+}
+
 fn print_ls_usage() {
-    eprintln!("usage: {PRIMARY_COMMAND_NAME} ls [--json|--output json]");
+    eprintln!(
+        "usage: {PRIMARY_COMMAND_NAME} ls [--runtime <container|host|podman|ssh://host>] [--long|-l] [--filter <name|repo|state|image>=<value>]... [--sort name|created] [--format '{{{{.Name}}}} {{{{.Repo}}}}'] [--json|--output json]"
+    );
 }
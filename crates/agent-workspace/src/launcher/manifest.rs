@@ -0,0 +1,297 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::runtime::{Runtime, parse_runtime_value};
+use crate::toml::{self, Value};
+
+use super::{RepoSpec, command_exists, scratch_dir, trimmed_nonempty};
+
+/// Manifest locations checked in order at the root of the primary repo.
+const MANIFEST_PATHS: &[&str] = &["awl.toml", ".awl/workspace.toml"];
+
+/// Workspace defaults a repo declares for itself in `awl.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct WorkspaceManifest {
+    /// Extra repos as `(spec, ref)`; the ref falls back to `create --ref`.
+    pub(super) repos: Vec<(String, Option<String>)>,
+    pub(super) private_repo: Option<String>,
+    pub(super) image: Option<String>,
+    pub(super) runtime: Option<String>,
+    pub(super) env: Vec<(String, String)>,
+    /// Shell commands run inside the primary checkout once all repos are cloned.
+    pub(super) post_create: Vec<String>,
+    pub(super) ignored_keys: Vec<String>,
+}
+
+impl WorkspaceManifest {
+    pub(super) fn parse(text: &str) -> Result<Self, String> {
+        let doc = toml::parse(text)?;
+        let mut manifest = Self::default();
+
+        for (key, value) in &doc {
+            match key.as_str() {
+                "image" => manifest.image = Some(expect_string(key, value)?),
+                "runtime" => manifest.runtime = Some(expect_string(key, value)?),
+                "private_repo" => manifest.private_repo = Some(expect_string(key, value)?),
+                "repos" => manifest.repos = parse_repos(value)?,
+                "post_create" => {
+                    manifest.post_create = match value {
+                        Value::String(command) => vec![command.clone()],
+                        Value::Array(items) => items
+                            .iter()
+                            .map(|item| expect_string(key, item))
+                            .collect::<Result<_, _>>()?,
+                        other => {
+                            return Err(format!(
+                                "post_create: expected a string or array, got {}",
+                                other.type_name()
+                            ));
+                        }
+                    };
+                }
+                "env" => {
+                    let Value::Table(table) = value else {
+                        return Err(format!("env: expected a table, got {}", value.type_name()));
+                    };
+                    for (name, value) in table {
+                        if !is_valid_env_name(name) {
+                            return Err(format!("env: invalid variable name: {name}"));
+                        }
+                        let value = match value {
+                            Value::String(text) => text.clone(),
+                            Value::Integer(number) => number.to_string(),
+                            Value::Boolean(flag) => flag.to_string(),
+                            other => {
+                                return Err(format!(
+                                    "env.{name}: expected a scalar, got {}",
+                                    other.type_name()
+                                ));
+                            }
+                        };
+                        if value.contains('\n') {
+                            return Err(format!("env.{name}: value must be a single line"));
+                        }
+                        manifest.env.push((name.clone(), value));
+                    }
+                }
+                _ => manifest.ignored_keys.push(key.clone()),
+            }
+        }
+
+        Ok(manifest)
+    }
+}
+
+fn parse_repos(value: &Value) -> Result<Vec<(String, Option<String>)>, String> {
+    let Value::Array(items) = value else {
+        return Err(format!(
+            "repos: expected an array, got {}",
+            value.type_name()
+        ));
+    };
+
+    items
+        .iter()
+        .map(|item| match item {
+            Value::String(spec) => Ok((spec.clone(), None)),
+            Value::Table(table) => {
+                let spec = match toml::get(table, "repo") {
+                    Some(value) => expect_string("repos.repo", value)?,
+                    None => return Err(String::from("repos: entry is missing 'repo'")),
+                };
+                let refspec = toml::get(table, "ref")
+                    .map(|value| expect_string("repos.ref", value))
+                    .transpose()?;
+                Ok((spec, refspec.and_then(|value| trimmed_nonempty(&value))))
+            }
+            other => Err(format!(
+                "repos: expected a string or table entry, got {}",
+                other.type_name()
+            )),
+        })
+        .collect()
+}
+
+fn expect_string(key: &str, value: &Value) -> Result<String, String> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("{key}: expected a string, got {}", value.type_name()))
+}
+
+/// Resolve the manifest `runtime`. The manifest comes from the cloned repo, so it may only pick
+/// a container runtime: host and ssh would run its `post_create` outside a sandbox.
+pub(super) fn manifest_runtime(raw: &str) -> Result<Runtime, String> {
+    match parse_runtime_value(raw)? {
+        runtime @ (Runtime::Container | Runtime::Podman) => Ok(runtime),
+        _ => Err(format!(
+            "a repo manifest can only select a container runtime, not {}; pass '--runtime {}' to use it",
+            raw.trim(),
+            raw.trim()
+        )),
+    }
+}
+
+pub(super) fn is_valid_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// Read the manifest from `repo` before the workspace exists, so `image` and `runtime` can
+/// still apply. Fetches a single blobless commit on the host; returns the manifest path too.
+pub(super) fn fetch_manifest(
+    repo: &RepoSpec,
+    refspec: Option<&str>,
+) -> Result<Option<(&'static str, WorkspaceManifest)>, String> {
+    if !command_exists("git") {
+        return Err(String::from("git not found in PATH"));
+    }
+
+    let scratch = scratch_dir("manifest")?;
+    let result = fetch_manifest_into(&scratch, repo, refspec);
+    let _ = fs::remove_dir_all(&scratch);
+
+    match result? {
+        Some((path, text)) => WorkspaceManifest::parse(&text)
+            .map(|manifest| Some((path, manifest)))
+            .map_err(|err| format!("invalid {path}: {err}")),
+        None => Ok(None),
+    }
+}
+
+fn fetch_manifest_into(
+    scratch: &Path,
+    repo: &RepoSpec,
+    refspec: Option<&str>,
+) -> Result<Option<(&'static str, String)>, String> {
+    git(scratch, &["init", "--quiet"])?;
+    git(scratch, &["remote", "add", "origin", &repo.clone_url])?;
+
+    // `--ref origin/feature` names a remote-tracking branch; fetch `feature` directly.
+    let branch = refspec.map(|value| value.strip_prefix("origin/").unwrap_or(value));
+    let fetch = |target: &str| {
+        git(
+            scratch,
+            &[
+                "fetch",
+                "--quiet",
+                "--depth",
+                "1",
+                "--filter=blob:none",
+                "origin",
+                target,
+            ],
+        )
+    };
+    match branch {
+        Some(branch) if fetch(branch).is_ok() => {}
+        _ => {
+            fetch("HEAD")?;
+        }
+    }
+
+    for path in MANIFEST_PATHS {
+        if let Ok(text) = git(scratch, &["show", &format!("FETCH_HEAD:{path}")]) {
+            return Ok(Some((path, text)));
+        }
+    }
+    Ok(None)
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|err| format!("failed to run git {}: {err}", args[0]))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(format!("git {} failed: {stderr}", args[0]));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::Runtime;
+
+    use super::{WorkspaceManifest, manifest_runtime};
+
+    #[test]
+    fn parses_manifest_fields() {
+        let manifest = WorkspaceManifest::parse(
+            r#"
+image = "ghcr.io/acme/dev:latest"
+runtime = "host"
+private_repo = "acme/secrets"
+repos = ["acme/tools", { repo = "acme/docs", ref = "origin/develop" }]
+post_create = "make bootstrap"
+future_key = true
+
+[env]
+RUST_LOG = "debug"
+WORKERS = 4
+"#,
+        )
+        .expect("parse manifest");
+
+        assert_eq!(manifest.image.as_deref(), Some("ghcr.io/acme/dev:latest"));
+        assert_eq!(manifest.runtime.as_deref(), Some("host"));
+        assert_eq!(manifest.private_repo.as_deref(), Some("acme/secrets"));
+        assert_eq!(
+            manifest.repos,
+            vec![
+                (String::from("acme/tools"), None),
+                (
+                    String::from("acme/docs"),
+                    Some(String::from("origin/develop"))
+                ),
+            ]
+        );
+        assert_eq!(manifest.post_create, vec![String::from("make bootstrap")]);
+        assert_eq!(
+            manifest.env,
+            vec![
+                (String::from("RUST_LOG"), String::from("debug")),
+                (String::from("WORKERS"), String::from("4")),
+            ]
+        );
+        assert_eq!(manifest.ignored_keys, vec![String::from("future_key")]);
+    }
+
+    #[test]
+    fn accepts_array_of_repo_tables() {
+        let manifest = WorkspaceManifest::parse(
+            "[[repos]]\nrepo = \"acme/a\"\n\n[[repos]]\nrepo = \"acme/b\"\nref = \"origin/next\"\n",
+        )
+        .expect("parse manifest");
+        assert_eq!(manifest.repos.len(), 2);
+        assert_eq!(manifest.repos[1].1.as_deref(), Some("origin/next"));
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        let err = WorkspaceManifest::parse("[env]\n\"BAD-NAME\" = \"x\"\n").expect_err("env name");
+        assert_eq!(err, "env: invalid variable name: BAD-NAME");
+        let err = WorkspaceManifest::parse("[[repos]]\nref = \"main\"\n").expect_err("repo");
+        assert_eq!(err, "repos: entry is missing 'repo'");
+        let err = WorkspaceManifest::parse("image = 3\n").expect_err("image type");
+        assert_eq!(err, "image: expected a string, got integer");
+    }
+
+    #[test]
+    fn manifest_runtime_only_selects_container_runtimes() {
+        assert_eq!(manifest_runtime("container"), Ok(Runtime::Container));
+        assert_eq!(manifest_runtime("podman"), Ok(Runtime::Podman));
+        for raw in ["host", "native", "ssh://dev@example.com"] {
+            let err = manifest_runtime(raw).expect_err("refuse unsandboxed runtime");
+            assert!(err.contains("--runtime"), "{err}");
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::ffi::OsString;
+
+use crate::EXIT_RUNTIME;
+
+use super::backend::WorkspaceBackend;
+use super::{PRIMARY_COMMAND_NAME, normalize_workspace_name_for_create, trimmed_nonempty};
+
+#[derive(Debug, Default, Clone)]
+pub(super) struct ParsedRename {
+    pub(super) show_help: bool,
+    pub(super) workspace: Option<String>,
+    pub(super) new_name: Option<String>,
+}
+
+pub(super) fn parse_rename_args(args: &[OsString]) -> Result<ParsedRename, String> {
+    let mut parsed = ParsedRename::default();
+
+    for arg in args {
+        let text = arg.to_string_lossy();
+        match text.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            _ if text.starts_with('-') => {
+                return Err(format!("unknown option for rename: {text}"));
+            }
+            _ if parsed.workspace.is_none() => parsed.workspace = trimmed_nonempty(&text),
+            _ if parsed.new_name.is_none() => parsed.new_name = trimmed_nonempty(&text),
+            _ => return Err(format!("unexpected arg for rename: {text}")),
+        }
+    }
+
+    if !parsed.show_help && (parsed.workspace.is_none() || parsed.new_name.is_none()) {
+        return Err(String::from("rename requires <workspace> and <new-name>"));
+    }
+
+    Ok(parsed)
+}
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_rename_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_rename_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if parsed.show_help {
+        print_rename_usage();
+        return 0;
+    }
+
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    let name = parsed.workspace.unwrap_or_default();
+    let workspace = match backend.resolve(&name) {
+        Ok(Some(workspace)) => workspace,
+        Ok(None) => {
+            eprintln!("error: workspace not found: {name}");
+            return EXIT_RUNTIME;
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+
+    let new_name = normalize_workspace_name_for_create(&parsed.new_name.unwrap_or_default());
+    if new_name == workspace {
+        eprintln!("error: workspace is already named {workspace}");
+        return EXIT_RUNTIME;
+    }
+
+    match backend.rename(&workspace, &new_name) {
+        Ok(renamed) => {
+            println!("renamed: {workspace} -> {renamed}");
+            0
+        }
+        Err(err) => {
+            eprintln!("error: failed to rename workspace {workspace}: {err}");
+            EXIT_RUNTIME
+        }
+    }
+}
+
+fn print_rename_usage() {
+    eprintln!(
+        "usage: {PRIMARY_COMMAND_NAME} rename [--runtime <container|host|podman|ssh://host>] <workspace> <new-name>"
+    );
+}
@@ -1,98 +1,15 @@
 use std::ffi::OsString;
-use std::path::{Path, PathBuf};
-use std::process::Command;
 
 use crate::EXIT_RUNTIME;
 
-use super::{PRIMARY_COMMAND_NAME, Workspace, confirm_or_abort, resolve_workspace};
-
-const DEFAULT_REF: &str = "origin/main";
-
-const RESET_REPO_SCRIPT: &str = r#"
-set -euo pipefail
-
-repo_dir="${1:?missing repo_dir}"
-ref="${2:-origin/main}"
-
-if [[ ! -e "$repo_dir/.git" ]]; then
-  echo "error: not a git repo: $repo_dir" >&2
-  exit 1
-fi
-
-cd "$repo_dir"
-
-remote="${ref%%/*}"
-branch="${ref#*/}"
-if [[ "$remote" == "$ref" || -z "$remote" || -z "$branch" ]]; then
-  echo "error: invalid ref (expected remote/branch): $ref" >&2
-  exit 2
-fi
-
-git fetch --prune -- "$remote" >/dev/null 2>&1 || git fetch --prune -- "$remote"
-
-resolved="$remote/$branch"
-if ! git show-ref --verify --quiet "refs/remotes/$resolved"; then
-  default_ref="$(git symbolic-ref -q --short "refs/remotes/$remote/HEAD" 2>/dev/null || true)"
-  if [[ -n "$default_ref" ]] && git show-ref --verify --quiet "refs/remotes/$default_ref"; then
-    echo "warn: $resolved not found; using $default_ref (from $remote/HEAD)" >&2
-    resolved="$default_ref"
-  elif git show-ref --verify --quiet "refs/remotes/$remote/master"; then
-    echo "warn: $resolved not found; using $remote/master" >&2
-    resolved="$remote/master"
-  else
-    echo "error: remote branch not found: $resolved" >&2
-    exit 1
-  fi
-fi
-
-target_branch="${resolved#*/}"
-echo "+ reset $repo_dir -> $resolved"
-
-if git show-ref --verify --quiet "refs/heads/$target_branch"; then
-  git checkout --force "$target_branch" >/dev/null 2>&1 || {
-    git clean -fd >/dev/null 2>&1 || true
-    git checkout --force "$target_branch"
-  }
-else
-  git checkout --force -B "$target_branch" "$resolved" >/dev/null 2>&1 || {
-    git clean -fd >/dev/null 2>&1 || true
-    git checkout --force -B "$target_branch" "$resolved"
-  }
-fi
-
-if command -v git-reset-remote >/dev/null 2>&1; then
-  git-reset-remote --ref "$resolved" --no-fetch --clean --yes
-else
-  git reset --hard "$resolved"
-  git clean -fd
-  echo "✅ Done. '$target_branch' now matches '$resolved'."
-fi
-"#;
-
-const LIST_GIT_REPOS_SCRIPT: &str = r#"
-set -euo pipefail
-
-root="${1:?missing root}"
-depth="${2:?missing depth}"
-
-if ! [[ "$depth" =~ ^[0-9]+$ ]] || [[ "$depth" -le 0 ]]; then
-  echo "error: --depth must be a positive integer (got: $depth)" >&2
-  exit 2
-fi
-
-if [[ ! -d "$root" ]]; then
-  exit 0
-fi
-
-git_depth=$((depth + 1))
-find -L "$root" -maxdepth "$git_depth" -mindepth 2 \( -type d -o -type f \) -name .git -print0 2>/dev/null \
-  | while IFS= read -r -d '' git_entry; do
-      printf '%s\n' "${git_entry%/.git}"
-    done \
-  | sort -u
-"#;
-
-pub(super) fn run(args: &[OsString]) -> i32 {
+use super::backend::{DEFAULT_REF, WorkspaceBackend};
+use super::hooks::{Hook, run_hook};
+use super::{PRIMARY_COMMAND_NAME, confirm_or_abort, resolve_ready_workspace};
+
+/// Search depth for `reset opt-repos`, matching the private-repo probe.
+const OPT_REPOS_DEPTH: u32 = 4;
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
     if args.is_empty() {
         print_reset_usage();
         return 0;
@@ -104,11 +21,16 @@ pub(super) fn run(args: &[OsString]) -> i32 {
         return 0;
     }
 
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
     match subcommand.as_ref() {
-        "repo" => run_reset_repo(&args[1..]),
-        "work-repos" => run_reset_work_repos(&args[1..]),
-        "opt-repos" => run_reset_opt_repos(&args[1..]),
-        "private-repo" => run_reset_private_repo(&args[1..]),
+        "repo" => run_reset_repo(backend, &args[1..]),
+        "work-repos" => run_reset_work_repos(backend, &args[1..]),
+        "opt-repos" => run_reset_opt_repos(backend, &args[1..]),
+        "private-repo" => run_reset_private_repo(backend, &args[1..]),
         _ => {
             eprintln!("error: unknown reset subcommand: {subcommand}");
             eprintln!("hint: {PRIMARY_COMMAND_NAME} reset --help");
@@ -117,7 +39,7 @@ pub(super) fn run(args: &[OsString]) -> i32 {
     }
 }
 
-fn run_reset_repo(args: &[OsString]) -> i32 {
+fn run_reset_repo(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
     let parsed = match parse_reset_repo_args(args) {
         Ok(parsed) => parsed,
         Err(err) => {
@@ -132,7 +54,7 @@ fn run_reset_repo(args: &[OsString]) -> i32 {
         return 0;
     }
 
-    let workspace_name = if let Some(workspace) = parsed.workspace {
+    let workspace_name = if let Some(workspace) = parsed.workspace.as_deref() {
         workspace
     } else {
         eprintln!("error: missing workspace");
@@ -140,7 +62,7 @@ fn run_reset_repo(args: &[OsString]) -> i32 {
         return EXIT_RUNTIME;
     };
 
-    let repo_dir = if let Some(repo_dir) = parsed.repo_dir {
+    let repo_dir = if let Some(repo_dir) = parsed.repo_dir.as_deref() {
         repo_dir
     } else {
         eprintln!("error: missing repo_dir");
@@ -148,35 +70,33 @@ fn run_reset_repo(args: &[OsString]) -> i32 {
         return EXIT_RUNTIME;
     };
 
-    let workspace = match resolve_workspace(&workspace_name) {
-        Ok(Some(workspace)) => workspace,
-        Ok(None) => {
-            eprintln!("error: workspace not found: {workspace_name}");
+    let workspace = match resolve_ready_workspace(backend, Some(workspace_name)) {
+        Ok(workspace) => workspace,
+        Err(err) => {
+            eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
+    };
+
+    let target_repo = match backend.repo_path(&workspace, repo_dir) {
+        Ok(target_repo) => target_repo,
         Err(err) => {
             eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
     };
 
-    let target_repo = map_workspace_repo_path(&workspace, &repo_dir);
-    if !target_repo.join(".git").exists() {
-        eprintln!("error: not a git repo: {}", target_repo.display());
-        return EXIT_RUNTIME;
-    }
-
     if !parsed.yes {
-        println!("This will reset a repo in workspace: {}", workspace.name);
-        println!("  - {}", target_repo.display());
+        println!("This will reset a repo in workspace: {workspace}");
+        println!("  - {target_repo}");
         if !confirm_or_abort("Proceed? [y/N] ") {
             println!("Aborted");
             return EXIT_RUNTIME;
         }
     }
 
-    match reset_repo_on_host(&target_repo, &parsed.refspec) {
-        Ok(()) => 0,
+    match backend.reset_repo(&workspace, &target_repo, &parsed.refspec) {
+        Ok(()) => finish_reset(backend, &workspace),
         Err(err) => {
             eprintln!("error: {err}");
             EXIT_RUNTIME
@@ -184,7 +104,7 @@ fn run_reset_repo(args: &[OsString]) -> i32 {
     }
 }
 
-fn run_reset_work_repos(args: &[OsString]) -> i32 {
+fn run_reset_work_repos(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
     let parsed = match parse_reset_work_repos_args(args) {
         Ok(parsed) => parsed,
         Err(err) => {
@@ -199,7 +119,7 @@ fn run_reset_work_repos(args: &[OsString]) -> i32 {
         return 0;
     }
 
-    let workspace_name = if let Some(workspace) = parsed.workspace {
+    let workspace_name = if let Some(workspace) = parsed.workspace.as_deref() {
         workspace
     } else {
         eprintln!("error: missing workspace");
@@ -207,20 +127,25 @@ fn run_reset_work_repos(args: &[OsString]) -> i32 {
         return EXIT_RUNTIME;
     };
 
-    let workspace = match resolve_workspace(&workspace_name) {
-        Ok(Some(workspace)) => workspace,
-        Ok(None) => {
-            eprintln!("error: workspace not found: {workspace_name}");
+    let workspace = match resolve_ready_workspace(backend, Some(workspace_name)) {
+        Ok(workspace) => workspace,
+        Err(err) => {
+            eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
+    };
+
+    let depth = parsed
+        .depth
+        .unwrap_or_else(|| backend.default_work_repos_depth());
+    let root = match backend.map_repo_path(&workspace, &parsed.root) {
+        Ok(root) => root,
         Err(err) => {
             eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
     };
-
-    let root = map_workspace_repo_path(&workspace, &parsed.root);
-    let repos = match list_git_repos_on_host(&root, parsed.depth) {
+    let repos = match backend.list_repos(&workspace, &root, depth) {
         Ok(repos) => repos,
         Err(err) => {
             eprintln!("error: {err}");
@@ -229,12 +154,7 @@ fn run_reset_work_repos(args: &[OsString]) -> i32 {
     };
 
     if repos.is_empty() {
-        eprintln!(
-            "warn: no git repos found under {} (depth={}) in {}",
-            root.display(),
-            parsed.depth,
-            workspace.name
-        );
+        eprintln!("warn: no git repos found under {root} (depth={depth}) in {workspace}");
         return 0;
     }
 
@@ -242,10 +162,10 @@ fn run_reset_work_repos(args: &[OsString]) -> i32 {
         println!(
             "This will reset {} repo(s) inside workspace: {}",
             repos.len(),
-            workspace.name
+            workspace
         );
         for repo in &repos {
-            println!("  - {}", repo.display());
+            println!("  - {repo}");
         }
         if !confirm_or_abort("Proceed? [y/N] ") {
             println!("Aborted");
@@ -255,7 +175,7 @@ fn run_reset_work_repos(args: &[OsString]) -> i32 {
 
     let mut failed = 0usize;
     for repo in repos {
-        if let Err(err) = reset_repo_on_host(&repo, &parsed.refspec) {
+        if let Err(err) = backend.reset_repo(&workspace, &repo, &parsed.refspec) {
             eprintln!("error: {err}");
             failed += 1;
         }
@@ -265,11 +185,11 @@ fn run_reset_work_repos(args: &[OsString]) -> i32 {
         return EXIT_RUNTIME;
     }
 
-    0
+    finish_reset(backend, &workspace)
 }
 
-fn run_reset_opt_repos(args: &[OsString]) -> i32 {
-    let parsed = match parse_reset_opt_repos_args(args) {
+fn run_reset_opt_repos(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_reset_simple_args(args) {
         Ok(parsed) => parsed,
         Err(err) => {
             eprintln!("error: {err}");
@@ -283,7 +203,7 @@ fn run_reset_opt_repos(args: &[OsString]) -> i32 {
         return 0;
     }
 
-    let workspace_name = if let Some(workspace) = parsed.workspace {
+    let workspace_name = if let Some(workspace) = parsed.workspace.as_deref() {
         workspace
     } else {
         eprintln!("error: missing workspace");
@@ -291,20 +211,22 @@ fn run_reset_opt_repos(args: &[OsString]) -> i32 {
         return EXIT_RUNTIME;
     };
 
-    let workspace = match resolve_workspace(&workspace_name) {
-        Ok(Some(workspace)) => workspace,
-        Ok(None) => {
-            eprintln!("error: workspace not found: {workspace_name}");
+    let workspace = match resolve_ready_workspace(backend, Some(workspace_name)) {
+        Ok(workspace) => workspace,
+        Err(err) => {
+            eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
+    };
+
+    let opt_root = match backend.map_repo_path(&workspace, "/opt") {
+        Ok(opt_root) => opt_root,
         Err(err) => {
             eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
     };
-
-    let opt_root = workspace.path.join("opt");
-    let repos = match list_git_repos_on_host(&opt_root, 4) {
+    let repos = match backend.list_repos(&workspace, &opt_root, OPT_REPOS_DEPTH) {
         Ok(repos) => repos,
         Err(err) => {
             eprintln!("error: {err}");
@@ -313,17 +235,14 @@ fn run_reset_opt_repos(args: &[OsString]) -> i32 {
     };
 
     if repos.is_empty() {
-        eprintln!("warn: no git repos found under {}", opt_root.display());
+        eprintln!("warn: no git repos found under {opt_root}");
         return 0;
     }
 
     if !parsed.yes {
-        println!(
-            "This will reset /opt-style repos in workspace: {}",
-            workspace.name
-        );
+        println!("This will reset /opt-style repos in workspace: {workspace}");
         for repo in &repos {
-            println!("  - {}", repo.display());
+            println!("  - {repo}");
         }
         if !confirm_or_abort("Proceed? [y/N] ") {
             println!("Aborted");
@@ -332,17 +251,28 @@ fn run_reset_opt_repos(args: &[OsString]) -> i32 {
     }
 
     for repo in repos {
-        if let Err(err) = reset_repo_on_host(&repo, DEFAULT_REF) {
+        if let Err(err) = backend.reset_repo(&workspace, &repo, &parsed.refspec) {
             eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
     }
 
-    0
+    finish_reset(backend, &workspace)
 }
 
-fn run_reset_private_repo(args: &[OsString]) -> i32 {
-    let parsed = match parse_reset_private_repo_args(args) {
+/// Run the `post-reset` hook once the repos are back on their upstream ref.
+fn finish_reset(backend: &dyn WorkspaceBackend, workspace: &str) -> i32 {
+    match run_hook(backend, workspace, Hook::PostReset) {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("error: {err}");
+            EXIT_RUNTIME
+        }
+    }
+}
+
+fn run_reset_private_repo(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_reset_simple_args(args) {
         Ok(parsed) => parsed,
         Err(err) => {
             eprintln!("error: {err}");
@@ -356,7 +286,7 @@ fn run_reset_private_repo(args: &[OsString]) -> i32 {
         return 0;
     }
 
-    let workspace_name = if let Some(workspace) = parsed.workspace {
+    let workspace_name = if let Some(workspace) = parsed.workspace.as_deref() {
         workspace
     } else {
         eprintln!("error: missing workspace");
@@ -364,25 +294,18 @@ fn run_reset_private_repo(args: &[OsString]) -> i32 {
         return EXIT_RUNTIME;
     };
 
-    let workspace = match resolve_workspace(&workspace_name) {
-        Ok(Some(workspace)) => workspace,
-        Ok(None) => {
-            eprintln!("error: workspace not found: {workspace_name}");
-            return EXIT_RUNTIME;
-        }
+    let workspace = match resolve_ready_workspace(backend, Some(workspace_name)) {
+        Ok(workspace) => workspace,
         Err(err) => {
             eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
     };
 
-    let private_repo = match detect_private_repo_dir(&workspace) {
+    let private_repo = match backend.private_repo(&workspace) {
         Ok(Some(path)) => path,
         Ok(None) => {
-            eprintln!(
-                "warn: no private git repo found in workspace: {}",
-                workspace.name
-            );
+            eprintln!("warn: no private git repo found in workspace: {workspace}");
             eprintln!(
                 "hint: seed it with: AGENT_WORKSPACE_PRIVATE_REPO=OWNER/REPO {PRIMARY_COMMAND_NAME} create ..."
             );
@@ -395,19 +318,16 @@ fn run_reset_private_repo(args: &[OsString]) -> i32 {
     };
 
     if !parsed.yes {
-        println!(
-            "This will reset private repo in workspace: {}",
-            workspace.name
-        );
-        println!("  - {}", private_repo.display());
+        println!("This will reset private repo in workspace: {workspace}");
+        println!("  - {private_repo}");
         if !confirm_or_abort("Proceed? [y/N] ") {
             println!("Aborted");
             return EXIT_RUNTIME;
         }
     }
 
-    match reset_repo_on_host(&private_repo, &parsed.refspec) {
-        Ok(()) => 0,
+    match backend.reset_repo(&workspace, &private_repo, &parsed.refspec) {
+        Ok(()) => finish_reset(backend, &workspace),
         Err(err) => {
             eprintln!("error: {err}");
             EXIT_RUNTIME
@@ -415,110 +335,6 @@ fn run_reset_private_repo(args: &[OsString]) -> i32 {
     }
 }
 
-fn detect_private_repo_dir(workspace: &Workspace) -> Result<Option<PathBuf>, String> {
-    let private_root = workspace.path.join("private");
-    if !private_root.exists() {
-        return Ok(None);
-    }
-
-    let repos = list_git_repos_on_host(&private_root, 4)?;
-    Ok(repos.into_iter().next())
-}
-
-fn reset_repo_on_host(repo_dir: &Path, refspec: &str) -> Result<(), String> {
-    let status = Command::new("bash")
-        .args([
-            "-c",
-            RESET_REPO_SCRIPT,
-            "--",
-            repo_dir.to_string_lossy().as_ref(),
-            refspec,
-        ])
-        .status()
-        .map_err(|err| format!("failed to reset repo {}: {err}", repo_dir.display()))?;
-
-    if status.success() {
-        Ok(())
-    } else {
-        Err(format!(
-            "failed to reset repo {} (exit {})",
-            repo_dir.display(),
-            status.code().unwrap_or(EXIT_RUNTIME)
-        ))
-    }
-}
-
-fn list_git_repos_on_host(root: &Path, depth: u32) -> Result<Vec<PathBuf>, String> {
-    if depth == 0 {
-        return Err(String::from("--depth must be a positive integer"));
-    }
-
-    let output = Command::new("bash")
-        .args([
-            "-c",
-            LIST_GIT_REPOS_SCRIPT,
-            "--",
-            root.to_string_lossy().as_ref(),
-            &depth.to_string(),
-        ])
-        .output()
-        .map_err(|err| format!("failed to list git repos under {}: {err}", root.display()))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-        return Err(format!(
-            "failed to list git repos under {} (exit {}): {stderr}",
-            root.display(),
-            output.status.code().unwrap_or(EXIT_RUNTIME)
-        ));
-    }
-
-    let mut repos: Vec<PathBuf> = String::from_utf8_lossy(&output.stdout)
-        .lines()
-        .map(str::trim)
-        .filter(|line| !line.is_empty())
-        .map(PathBuf::from)
-        .collect();
-    repos.sort();
-    repos.dedup();
-    Ok(repos)
-}
-
-fn map_workspace_repo_path(workspace: &Workspace, raw: &str) -> PathBuf {
-    let cleaned = raw.trim();
-    if cleaned.is_empty() {
-        return workspace.path.clone();
-    }
-
-    if cleaned == "/work" {
-        return workspace.path.join("work");
-    }
-    if let Some(rest) = cleaned.strip_prefix("/work/") {
-        return workspace.path.join("work").join(rest);
-    }
-
-    if cleaned == "/opt" {
-        return workspace.path.join("opt");
-    }
-    if let Some(rest) = cleaned.strip_prefix("/opt/") {
-        return workspace.path.join("opt").join(rest);
-    }
-
-    if cleaned == "~/.private" {
-        return workspace.path.join("private");
-    }
-    if let Some(rest) = cleaned.strip_prefix("~/.private/") {
-        return workspace.path.join("private").join(rest);
-    }
-
-    let path = Path::new(cleaned);
-    if path.is_absolute() {
-        path.to_path_buf()
-    } else {
-        workspace.path.join(path)
-    }
-}
-
 #[derive(Debug, Clone)]
 struct ParsedResetRepo {
     show_help: bool,
@@ -581,7 +397,7 @@ pub(super) struct ParsedResetWorkRepos {
     show_help: bool,
     workspace: Option<String>,
     root: String,
-    depth: u32,
+    pub(super) depth: Option<u32>,
     refspec: String,
     yes: bool,
 }
@@ -592,7 +408,7 @@ impl Default for ParsedResetWorkRepos {
             show_help: false,
             workspace: None,
             root: String::from("/work"),
-            depth: 3,
+            depth: None,
             refspec: String::from(DEFAULT_REF),
             yes: false,
         }
@@ -621,10 +437,10 @@ pub(super) fn parse_reset_work_repos_args(
                 if idx >= args.len() {
                     return Err(String::from("missing value for --depth"));
                 }
-                parsed.depth = args[idx]
-                    .to_string_lossy()
-                    .parse::<u32>()
-                    .map_err(|_| String::from("--depth must be a positive integer"))?;
+                parsed.depth = Some(parse_positive_u32(
+                    args[idx].to_string_lossy().as_ref(),
+                    "--depth",
+                )?);
             }
             "--ref" => {
                 idx += 1;
@@ -638,9 +454,7 @@ pub(super) fn parse_reset_work_repos_args(
                 parsed.root = text["--root=".len()..].to_string();
             }
             _ if text.starts_with("--depth=") => {
-                parsed.depth = text["--depth=".len()..]
-                    .parse::<u32>()
-                    .map_err(|_| String::from("--depth must be a positive integer"))?;
+                parsed.depth = Some(parse_positive_u32(&text["--depth=".len()..], "--depth")?);
             }
             _ if text.starts_with("--ref=") => {
                 parsed.refspec = text["--ref=".len()..].to_string();
@@ -658,51 +472,18 @@ pub(super) fn parse_reset_work_repos_args(
         idx += 1;
     }
 
-    if parsed.depth == 0 {
-        return Err(String::from("--depth must be a positive integer"));
-    }
-
-    Ok(parsed)
-}
-
-#[derive(Debug, Default, Clone)]
-struct ParsedResetSimple {
-    show_help: bool,
-    workspace: Option<String>,
-    yes: bool,
-}
-
-fn parse_reset_opt_repos_args(args: &[OsString]) -> Result<ParsedResetSimple, String> {
-    let mut parsed = ParsedResetSimple::default();
-
-    for arg in args {
-        let text = arg.to_string_lossy();
-        match text.as_ref() {
-            "-h" | "--help" => parsed.show_help = true,
-            "-y" | "--yes" => parsed.yes = true,
-            _ if text.starts_with('-') => return Err(format!("unknown arg: {text}")),
-            _ => {
-                if parsed.workspace.is_none() {
-                    parsed.workspace = Some(text.to_string());
-                } else {
-                    return Err(format!("unexpected arg: {text}"));
-                }
-            }
-        }
-    }
-
     Ok(parsed)
 }
 
 #[derive(Debug, Clone)]
-struct ParsedResetPrivate {
+struct ParsedResetSimple {
     show_help: bool,
     workspace: Option<String>,
     refspec: String,
     yes: bool,
 }
 
-impl Default for ParsedResetPrivate {
+impl Default for ParsedResetSimple {
     fn default() -> Self {
         Self {
             show_help: false,
@@ -713,8 +494,8 @@ impl Default for ParsedResetPrivate {
     }
 }
 
-fn parse_reset_private_repo_args(args: &[OsString]) -> Result<ParsedResetPrivate, String> {
-    let mut parsed = ParsedResetPrivate::default();
+fn parse_reset_simple_args(args: &[OsString]) -> Result<ParsedResetSimple, String> {
+    let mut parsed = ParsedResetSimple::default();
     let mut idx = 0usize;
 
     while idx < args.len() {
@@ -747,6 +528,19 @@ fn parse_reset_private_repo_args(args: &[OsString]) -> Result<ParsedResetPrivate
     Ok(parsed)
 }
 
+fn parse_positive_u32(raw: &str, option_name: &str) -> Result<u32, String> {
+    let value = raw
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| format!("{option_name} must be a positive integer (got: {raw})"))?;
+    if value == 0 {
+        return Err(format!(
+            "{option_name} must be a positive integer (got: {raw})"
+        ));
+    }
+    Ok(value)
+}
+
 fn print_reset_usage() {
     eprintln!("usage:");
     eprintln!(
@@ -755,7 +549,9 @@ fn print_reset_usage() {
     eprintln!(
         "  {PRIMARY_COMMAND_NAME} reset work-repos <workspace> [--root <dir>] [--depth <N>] [--ref <remote/branch>] [--yes]"
     );
-    eprintln!("  {PRIMARY_COMMAND_NAME} reset opt-repos <workspace> [--yes]");
+    eprintln!(
+        "  {PRIMARY_COMMAND_NAME} reset opt-repos <workspace> [--ref <remote/branch>] [--yes]"
+    );
     eprintln!(
         "  {PRIMARY_COMMAND_NAME} reset private-repo <workspace> [--ref <remote/branch>] [--yes]"
     );
@@ -774,7 +570,9 @@ fn print_reset_work_repos_usage() {
 }
 
 fn print_reset_opt_repos_usage() {
-    eprintln!("usage: {PRIMARY_COMMAND_NAME} reset opt-repos <workspace> [--yes]");
+    eprintln!(
+        "usage: {PRIMARY_COMMAND_NAME} reset opt-repos <workspace> [--ref <remote/branch>] [--yes]"
+    );
 }
 
 fn print_reset_private_repo_usage() {
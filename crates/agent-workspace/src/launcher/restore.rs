@@ -0,0 +1,167 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::EXIT_RUNTIME;
+
+use super::archive::unpack_snapshot;
+use super::backend::{RestoreOptions, WorkspaceBackend};
+use super::create::{parse_env_entry, parse_mount, parse_publish};
+use super::{
+    PRIMARY_COMMAND_NAME, normalize_workspace_name_for_create, scratch_dir, trimmed_nonempty,
+};
+
+#[derive(Debug, Default, Clone)]
+pub(super) struct ParsedRestore {
+    pub(super) show_help: bool,
+    pub(super) name: Option<String>,
+    pub(super) archive: Option<PathBuf>,
+    /// `--env`, `--mount`, and `--publish`, as for `create`.
+    pub(super) env: Vec<(String, String)>,
+    pub(super) mounts: Vec<String>,
+    pub(super) publish: Vec<String>,
+}
+
+/// Value flags handled by `apply_restore_option`.
+const RESTORE_OPTION_FLAGS: [&str; 3] = ["--env", "--mount", "--publish"];
+
+pub(super) fn parse_restore_args(args: &[OsString]) -> Result<ParsedRestore, String> {
+    let mut parsed = ParsedRestore::default();
+    let mut idx = 0usize;
+
+    while idx < args.len() {
+        let arg = args[idx].to_string_lossy();
+        match arg.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            "--name" => {
+                idx += 1;
+                let Some(value) = args.get(idx) else {
+                    return Err(String::from("missing value for --name"));
+                };
+                parsed.name = trimmed_nonempty(&value.to_string_lossy());
+            }
+            _ if arg.starts_with("--name=") => {
+                parsed.name = trimmed_nonempty(&arg["--name=".len()..]);
+            }
+            flag if RESTORE_OPTION_FLAGS.contains(&flag) => {
+                idx += 1;
+                let Some(value) = args.get(idx) else {
+                    return Err(format!("missing value for {flag}"));
+                };
+                apply_restore_option(&mut parsed, flag, &value.to_string_lossy())?;
+            }
+            _ if RESTORE_OPTION_FLAGS
+                .iter()
+                .any(|flag| arg.starts_with(&format!("{flag}="))) =>
+            {
+                let (flag, value) = arg.split_once('=').unwrap_or_default();
+                apply_restore_option(&mut parsed, flag, value)?;
+            }
+            _ if arg.starts_with('-') => {
+                return Err(format!("unknown option for restore: {arg}"));
+            }
+            _ if parsed.archive.is_none() => parsed.archive = Some(PathBuf::from(&args[idx])),
+            _ => return Err(format!("unexpected arg for restore: {arg}")),
+        }
+        idx += 1;
+    }
+
+    if parsed.archive.is_none() && !parsed.show_help {
+        return Err(String::from("missing snapshot archive"));
+    }
+
+    Ok(parsed)
+}
+
+fn apply_restore_option(parsed: &mut ParsedRestore, flag: &str, value: &str) -> Result<(), String> {
+    match flag {
+        "--env" => parsed
+            .env
+            .push(parse_env_entry(value).map_err(|err| format!("--env: {err}"))?),
+        "--mount" => parsed.mounts.push(parse_mount(value)?),
+        _ => parsed.publish.push(parse_publish(value)?),
+    }
+    Ok(())
+}
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_restore_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_restore_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if parsed.show_help {
+        print_restore_usage();
+        return 0;
+    }
+
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    let has_options =
+        !parsed.env.is_empty() || !parsed.mounts.is_empty() || !parsed.publish.is_empty();
+    if has_options && !backend.supports_images() {
+        eprintln!(
+            "warn: --env, --mount, and --publish are ignored by the {} runtime",
+            backend.runtime_name()
+        );
+    }
+
+    let archive = parsed.archive.clone().unwrap_or_default();
+    if !archive.is_file() {
+        eprintln!("error: snapshot archive not found: {}", archive.display());
+        return EXIT_RUNTIME;
+    }
+
+    let staging = match scratch_dir("restore") {
+        Ok(staging) => staging,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+    let result = unpack_snapshot(&archive, &staging).and_then(|manifest| {
+        let name = normalize_workspace_name_for_create(
+            parsed.name.as_deref().unwrap_or(manifest.name.as_str()),
+        );
+        let options = RestoreOptions {
+            env: &parsed.env,
+            mounts: &parsed.mounts,
+            publish: &parsed.publish,
+        };
+        backend
+            .restore(&manifest, &staging, &name, &options)
+            .map(|created| (created, manifest.secrets))
+    });
+    let _ = fs::remove_dir_all(&staging);
+
+    let (created, secrets) = match result {
+        Ok(restored) => restored,
+        Err(err) => {
+            eprintln!("error: failed to restore {}: {err}", archive.display());
+            return EXIT_RUNTIME;
+        }
+    };
+
+    println!("workspace: {}", created.name);
+    println!("path: {}", created.path);
+    if !secrets {
+        eprintln!(
+            "hint: the snapshot has no auth material; run '{PRIMARY_COMMAND_NAME} auth github|codex|gpg --container {}'",
+            created.name
+        );
+    }
+    0
+}
+
+fn print_restore_usage() {
+    eprintln!(
+        "usage: {PRIMARY_COMMAND_NAME} restore [--runtime <container|host|podman|ssh://host>] [--name <workspace>] [--env NAME=value] [--mount /host/path:/path[:ro]] [--publish <host_port>:<port>] <archive>"
+    );
+}
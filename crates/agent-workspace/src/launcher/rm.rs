@@ -1,15 +1,17 @@
 use std::ffi::OsString;
-use std::fs;
 
 use crate::EXIT_RUNTIME;
 
-use super::{PRIMARY_COMMAND_NAME, confirm_or_abort, list_workspaces_on_disk, resolve_workspace};
+use super::backend::{RemoveOptions, WorkspaceBackend};
+use super::hooks::{Hook, run_hook};
+use super::{PRIMARY_COMMAND_NAME, confirm_or_abort};
 
 #[derive(Debug, Default, Clone)]
 struct ParsedRm {
     show_help: bool,
     all: bool,
     yes: bool,
+    keep_volumes: bool,
     workspace: Option<String>,
 }
 
@@ -22,6 +24,8 @@ fn parse_rm_args(args: &[OsString]) -> Result<ParsedRm, String> {
             "-h" | "--help" => parsed.show_help = true,
             "--all" => parsed.all = true,
             "-y" | "--yes" => parsed.yes = true,
+            "--keep-volumes" => parsed.keep_volumes = true,
+            "--volumes" => parsed.keep_volumes = false,
             _ if text.starts_with('-') => {
                 return Err(format!("unknown option for rm: {text}"));
             }
@@ -37,7 +41,7 @@ fn parse_rm_args(args: &[OsString]) -> Result<ParsedRm, String> {
     Ok(parsed)
 }
 
-pub(super) fn run(args: &[OsString]) -> i32 {
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
     let parsed = match parse_rm_args(args) {
         Ok(parsed) => parsed,
         Err(err) => {
@@ -58,16 +62,21 @@ pub(super) fn run(args: &[OsString]) -> i32 {
         return EXIT_RUNTIME;
     }
 
-    let targets = if parsed.all {
-        match list_workspaces_on_disk() {
-            Ok(items) => items,
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    let targets: Vec<String> = if parsed.all {
+        match backend.list() {
+            Ok(items) => items.into_iter().map(|item| item.name).collect(),
             Err(err) => {
                 eprintln!("error: {err}");
                 return EXIT_RUNTIME;
             }
         }
     } else if let Some(workspace_name) = parsed.workspace.as_deref() {
-        match resolve_workspace(workspace_name) {
+        match backend.resolve(workspace_name) {
             Ok(Some(workspace)) => vec![workspace],
             Ok(None) => {
                 eprintln!("error: workspace not found: {workspace_name}");
@@ -95,7 +104,7 @@ pub(super) fn run(args: &[OsString]) -> i32 {
             println!("This will remove workspace:");
         }
         for target in &targets {
-            println!("  - {}", target.name);
+            println!("  - {target}");
         }
         if !confirm_or_abort("Proceed? [y/N] ") {
             println!("Aborted");
@@ -103,21 +112,30 @@ pub(super) fn run(args: &[OsString]) -> i32 {
         }
     }
 
+    let options = RemoveOptions {
+        keep_volumes: parsed.keep_volumes,
+    };
+    let mut failed = false;
     for target in targets {
-        if let Err(err) = fs::remove_dir_all(&target.path) {
-            eprintln!(
-                "error: failed to remove workspace {} ({}): {err}",
-                target.name,
-                target.path.display()
-            );
-            return EXIT_RUNTIME;
+        if let Err(err) = run_hook(backend, &target, Hook::PreRm) {
+            eprintln!("error: {err}");
+            eprintln!("hint: workspace {target} was not removed");
+            failed = true;
+            continue;
+        }
+        if let Err(err) = backend.remove(&target, &options) {
+            eprintln!("error: failed to remove workspace {target}: {err}");
+            failed = true;
+            continue;
         }
-        println!("removed: {}", target.name);
+        println!("removed: {target}");
     }
 
-    0
+    if failed { EXIT_RUNTIME } else { 0 }
 }
 
 fn print_rm_usage() {
-    eprintln!("usage: {PRIMARY_COMMAND_NAME} rm [--all] [--yes] <workspace>");
+    eprintln!(
+        "usage: {PRIMARY_COMMAND_NAME} rm [--runtime <container|host|podman|ssh://host>] [--all] [--yes] [--keep-volumes] <workspace>"
+    );
 }
@@ -0,0 +1,258 @@
+use std::ffi::OsString;
+use std::path::Path;
+
+use crate::EXIT_RUNTIME;
+
+use super::backend::{RsyncDirection, RsyncTransfer, WorkspaceBackend};
+use super::watch::SourceWatcher;
+use super::{PRIMARY_COMMAND_NAME, resolve_ready_workspace, trimmed_nonempty};
+
+#[derive(Debug, Clone)]
+struct ParsedRsync {
+    show_help: bool,
+    direction: Option<RsyncDirection>,
+    workspace: Option<String>,
+    src: Option<String>,
+    dest: Option<String>,
+    user: String,
+    delete: bool,
+    dry_run: bool,
+    watch: bool,
+    gitignore: bool,
+    rsync_args: Vec<String>,
+}
+
+impl Default for ParsedRsync {
+    fn default() -> Self {
+        Self {
+            show_help: false,
+            direction: None,
+            workspace: None,
+            src: None,
+            dest: None,
+            user: String::from("agent"),
+            delete: false,
+            dry_run: false,
+            watch: false,
+            gitignore: false,
+            rsync_args: Vec::new(),
+        }
+    }
+}
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    if !backend.supports_rsync() {
+        eprintln!(
+            "error: rsync is not supported by the {} runtime",
+            backend.runtime_name()
+        );
+        eprintln!("hint: retry with '--runtime host' or '--runtime container'");
+        return EXIT_RUNTIME;
+    }
+
+    let parsed = match parse_rsync_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_rsync_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if parsed.show_help {
+        print_rsync_usage();
+        return 0;
+    }
+
+    let direction = match parsed.direction {
+        Some(direction) => direction,
+        None => {
+            print_rsync_usage();
+            return 0;
+        }
+    };
+
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    let workspace = match resolve_ready_workspace(backend, parsed.workspace.as_deref()) {
+        Ok(workspace) => workspace,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+
+    let transfer = RsyncTransfer {
+        direction,
+        src: parsed.src.unwrap_or_default(),
+        dest: parsed.dest.unwrap_or_default(),
+        user: parsed.user,
+        delete: parsed.delete,
+        dry_run: parsed.dry_run,
+        gitignore: parsed.gitignore,
+        rsync_args: parsed.rsync_args,
+        paths: Vec::new(),
+    };
+
+    let result = if parsed.watch {
+        watch_and_push(backend, &workspace, &transfer)
+    } else {
+        backend.rsync(&workspace, &transfer)
+    };
+    match result {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("error: {err}");
+            EXIT_RUNTIME
+        }
+    }
+}
+
+/// `push --watch`: one full push, then a `--files-from` push per debounced batch of changes.
+fn watch_and_push(
+    backend: &dyn WorkspaceBackend,
+    workspace: &str,
+    transfer: &RsyncTransfer,
+) -> Result<(), String> {
+    let mut watcher = SourceWatcher::start(Path::new(&transfer.src), transfer.gitignore)?;
+
+    // Batch paths are relative to the transfer source, so `dir` (rather than `dir/`) is sent
+    // from its parent with every path under `dir/`, keeping rsync's destination layout.
+    let (base, prefix) = if transfer.src.ends_with('/') {
+        (transfer.src.clone(), String::new())
+    } else {
+        let root = watcher.root();
+        let parent = root.parent().unwrap_or(root);
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        (format!("{}/", parent.display()), format!("{name}/"))
+    };
+
+    println!(
+        "info: watching {} for changes (Ctrl-C to stop)",
+        watcher.root().display()
+    );
+    let mut next_batch = || {
+        let paths = watcher.next_batch()?;
+        Some(RsyncTransfer {
+            src: base.clone(),
+            paths: paths
+                .into_iter()
+                .map(|path| format!("{prefix}{path}"))
+                .collect(),
+            ..transfer.clone()
+        })
+    };
+    backend.rsync_watch(workspace, transfer, &mut next_batch)
+}
+
+fn parse_rsync_args(args: &[OsString]) -> Result<ParsedRsync, String> {
+    let mut parsed = ParsedRsync::default();
+    if args.is_empty() {
+        parsed.show_help = true;
+        return Ok(parsed);
+    }
+
+    let subcmd = args[0].to_string_lossy();
+    match subcmd.as_ref() {
+        "-h" | "--help" | "help" => {
+            parsed.show_help = true;
+            return Ok(parsed);
+        }
+        "push" => parsed.direction = Some(RsyncDirection::Push),
+        "pull" => parsed.direction = Some(RsyncDirection::Pull),
+        _ => {
+            return Err(format!(
+                "unknown rsync subcommand: {subcmd} (expected: push|pull)"
+            ));
+        }
+    }
+
+    let mut idx = 1usize;
+    while idx < args.len() {
+        let current = args[idx].to_string_lossy();
+        match current.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            "--delete" => parsed.delete = true,
+            "-n" | "--dry-run" => parsed.dry_run = true,
+            "--watch" => parsed.watch = true,
+            "--gitignore" => parsed.gitignore = true,
+            "--root" => parsed.user = String::from("root"),
+            "--user" | "-u" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(format!("missing value for {current}"));
+                }
+                let value = args[idx].to_string_lossy().into_owned();
+                let Some(cleaned) = trimmed_nonempty(&value) else {
+                    return Err(format!("missing value for {current}"));
+                };
+                parsed.user = cleaned;
+            }
+            _ if current.starts_with("--user=") => {
+                let value = current["--user=".len()..].to_string();
+                let Some(cleaned) = trimmed_nonempty(&value) else {
+                    return Err(String::from("missing value for --user"));
+                };
+                parsed.user = cleaned;
+            }
+            "--" => {
+                return Err(String::from(
+                    "unexpected -- (pass rsync flags after source/destination paths)",
+                ));
+            }
+            _ if current.starts_with('-') => return Err(format!("unknown option: {current}")),
+            _ => break,
+        }
+        idx += 1;
+    }
+
+    if parsed.watch && parsed.direction == Some(RsyncDirection::Pull) {
+        return Err(String::from("--watch is only supported for rsync push"));
+    }
+
+    let mut rest: Vec<String> = Vec::new();
+    for arg in &args[idx..] {
+        rest.push(arg.to_string_lossy().into_owned());
+    }
+    if rest.len() < 2 {
+        return Err(String::from("missing args"));
+    }
+
+    if rest.len() == 2 {
+        parsed.src = Some(rest[0].clone());
+        parsed.dest = Some(rest[1].clone());
+        return Ok(parsed);
+    }
+
+    if rest[2].starts_with('-') {
+        parsed.src = Some(rest[0].clone());
+        parsed.dest = Some(rest[1].clone());
+        parsed.rsync_args = rest[2..].to_vec();
+        return Ok(parsed);
+    }
+
+    parsed.workspace = Some(rest[0].clone());
+    parsed.src = Some(rest[1].clone());
+    parsed.dest = Some(rest[2].clone());
+    if rest.len() > 3 {
+        parsed.rsync_args = rest[3..].to_vec();
+    }
+
+    Ok(parsed)
+}
+
+fn print_rsync_usage() {
+    eprintln!("usage:");
+    eprintln!(
+        "  {PRIMARY_COMMAND_NAME} rsync push [--runtime container|podman|host] [--user <user>|--root] [--delete] [--dry-run] [--watch] [--gitignore] [<workspace>] <host_src> <workspace_dest> [<rsync_args...>]"
+    );
+    eprintln!(
+        "  {PRIMARY_COMMAND_NAME} rsync pull [--runtime container|podman|host] [--user <user>|--root] [--delete] [--dry-run] [--gitignore] [<workspace>] <workspace_src> <host_dest> [<rsync_args...>]"
+    );
+}
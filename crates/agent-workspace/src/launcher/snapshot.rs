@@ -0,0 +1,124 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::EXIT_RUNTIME;
+
+use super::archive::pack_snapshot;
+use super::backend::WorkspaceBackend;
+use super::{PRIMARY_COMMAND_NAME, scratch_dir, trimmed_nonempty};
+
+#[derive(Debug, Default, Clone)]
+pub(super) struct ParsedSnapshot {
+    pub(super) show_help: bool,
+    pub(super) include_secrets: bool,
+    pub(super) output: Option<PathBuf>,
+    pub(super) workspace: Option<String>,
+}
+
+pub(super) fn parse_snapshot_args(args: &[OsString]) -> Result<ParsedSnapshot, String> {
+    let mut parsed = ParsedSnapshot::default();
+    let mut idx = 0usize;
+
+    while idx < args.len() {
+        let arg = args[idx].to_string_lossy();
+        match arg.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            "--include-secrets" => parsed.include_secrets = true,
+            "-o" | "--output" => {
+                idx += 1;
+                let Some(value) = args.get(idx) else {
+                    return Err(format!("missing value for {arg}"));
+                };
+                parsed.output = Some(PathBuf::from(value));
+            }
+            _ if arg.starts_with("--output=") => {
+                parsed.output = Some(PathBuf::from(&arg["--output=".len()..]));
+            }
+            _ if arg.starts_with('-') => {
+                return Err(format!("unknown option for snapshot: {arg}"));
+            }
+            _ if parsed.workspace.is_none() => parsed.workspace = trimmed_nonempty(&arg),
+            _ => return Err(format!("unexpected arg for snapshot: {arg}")),
+        }
+        idx += 1;
+    }
+
+    if !parsed.show_help {
+        if parsed.workspace.is_none() {
+            return Err(String::from("missing workspace name"));
+        }
+        if parsed
+            .output
+            .as_ref()
+            .is_none_or(|output| output.as_os_str().is_empty())
+        {
+            return Err(String::from("missing --output <archive>"));
+        }
+    }
+
+    Ok(parsed)
+}
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_snapshot_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_snapshot_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if parsed.show_help {
+        print_snapshot_usage();
+        return 0;
+    }
+
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    let name = parsed.workspace.unwrap_or_default();
+    let workspace = match backend.resolve(&name) {
+        Ok(Some(workspace)) => workspace,
+        Ok(None) => {
+            eprintln!("error: workspace not found: {name}");
+            return EXIT_RUNTIME;
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+
+    let output = parsed.output.unwrap_or_default();
+    let staging = match scratch_dir("snapshot") {
+        Ok(staging) => staging,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+    let result = backend
+        .snapshot(&workspace, &staging, parsed.include_secrets)
+        .and_then(|manifest| pack_snapshot(&output, &staging, &manifest));
+    let _ = fs::remove_dir_all(&staging);
+
+    if let Err(err) = result {
+        eprintln!("error: failed to snapshot workspace {workspace}: {err}");
+        return EXIT_RUNTIME;
+    }
+    println!("snapshot: {}", output.display());
+    if !parsed.include_secrets {
+        eprintln!("info: auth material was left out (pass --include-secrets to keep it)");
+    }
+    0
+}
+
+fn print_snapshot_usage() {
+    eprintln!(
+        "usage: {PRIMARY_COMMAND_NAME} snapshot [--runtime <container|host|podman|ssh://host>] [--include-secrets] -o <archive> <workspace>"
+    );
+}
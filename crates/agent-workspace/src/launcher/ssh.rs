@@ -0,0 +1,999 @@
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use crate::EXIT_RUNTIME;
+
+use super::archive::{HOST_LAYOUT, HOST_WORKSPACE_PART, SnapshotManifest, part_path};
+use super::backend::{
+    CLONE_MODE_SCRIPT, CODE_TUNNEL_LOG_FILE, CODE_TUNNEL_STATE_FILE, COPY_IN_SCRIPT,
+    COPY_OUT_SCRIPT, CloneOptions, CopyRequest, CreatedWorkspace, DETACHED_TUNNEL_SCRIPT,
+    DoctorCheck, ExecRequest, FORWARD_BRIDGE_SCRIPT, LIST_GIT_REPOS_SCRIPT, NewWorkspace,
+    RESET_REPO_SCRIPT, RemoveOptions, RepoSlot, RestoreOptions, TUNNEL_CONTROL_SCRIPT,
+    TunnelAction, TunnelOutcome, TunnelRequest, WORKSPACE_STATUS_SCRIPT, WorkspaceBackend,
+    WorkspaceDetails, WorkspaceEntry, apply_meta_line, stream_copy_in, stream_copy_out,
+};
+use super::host::{
+    HOST_SECRET_EXCLUDES, codex_auth_targets, map_workspace_operand, map_workspace_repo_path,
+    parse_du_kib, workspace_status_args,
+};
+use super::{
+    RepoSpec, WORKSPACE_ENV_FILE, WORKSPACE_META_FILE, Workspace, command_exists, trimmed_nonempty,
+    workspace_prefixes, workspace_repo_destination, workspace_resolution_candidates,
+};
+
+const DEFAULT_SSH_COMMAND: &str = "ssh";
+
+/// Mirrors `workspace_storage_root` on the remote side; `AGENT_WORKSPACE_SSH_HOME` pins it.
+const STORAGE_ROOT_SCRIPT: &str = r#"
+set -euo pipefail
+
+root="${1:-}"
+if [[ -z "$root" ]]; then
+  if [[ -n "${AGENT_WORKSPACE_HOME:-}" ]]; then
+    root="$AGENT_WORKSPACE_HOME"
+  elif [[ -n "${XDG_STATE_HOME:-}" ]]; then
+    root="$XDG_STATE_HOME/agent-workspace-launcher/workspaces"
+  else
+    root="$HOME/.local/state/agent-workspace-launcher/workspaces"
+  fi
+fi
+
+mkdir -p "$root"
+cd "$root"
+pwd -P
+"#;
+
+const LIST_WORKSPACES_SCRIPT: &str = r#"
+set -euo pipefail
+
+root="${1:?missing root}"
+meta_file="${2:?missing meta file}"
+for entry in "$root"/*/; do
+  [[ -d "$entry" ]] || continue
+  entry="${entry%/}"
+  printf '%s' "${entry##*/}"
+  if [[ -f "$entry/$meta_file" ]]; then
+    while IFS= read -r line; do
+      printf '\t%s' "$line"
+    done <"$entry/$meta_file"
+  fi
+  printf '\n'
+done | sort -u
+"#;
+
+const CREATE_WORKSPACE_SCRIPT: &str = r#"
+set -euo pipefail
+
+workspace="${1:?missing workspace}"
+name="${2:?missing name}"
+primary_repo="${3:-none}"
+meta_file="${4:?missing meta file}"
+
+if [[ -e "$workspace" ]]; then
+  echo "workspace already exists: $name" >&2
+  exit 3
+fi
+
+mkdir -p "$workspace"/work "$workspace"/opt "$workspace"/private "$workspace"/auth "$workspace"/.codex
+printf 'name=%s\ncreated_unix=%s\nprimary_repo=%s\n' "$name" "$(date +%s)" "$primary_repo" >"$workspace/$meta_file"
+"#;
+
+const CLONE_REPO_SCRIPT: &str = r#"
+set -euo pipefail
+
+url="${1:?missing url}"
+dest="${2:?missing dest}"
+ref="${3:-}"
+shift 3
+
+if [[ -d "$dest/.git" ]]; then
+  exit 0
+fi
+if [[ -e "$dest" ]]; then
+  echo "destination exists but is not a git repo: $dest" >&2
+  exit 1
+fi
+if ! command -v git >/dev/null 2>&1; then
+  echo "git not found in PATH" >&2
+  exit 1
+fi
+
+mkdir -p "$(dirname "$dest")"
+git clone --progress "$@" -- "$url" "$dest"
+if [[ -n "$ref" ]]; then
+  git -C "$dest" checkout "$ref"
+fi
+"#;
+
+const REQUIRE_WORKSPACE_SCRIPT: &str = r#"
+set -euo pipefail
+
+workspace="${1:?missing workspace}"
+if [[ ! -d "$workspace" ]]; then
+  echo "workspace not found: $workspace" >&2
+  exit 1
+fi
+"#;
+
+const REMOVE_WORKSPACE_SCRIPT: &str = r#"
+set -euo pipefail
+
+workspace="${1:?missing workspace}"
+if [[ ! -d "$workspace" ]]; then
+  echo "workspace not found: $workspace" >&2
+  exit 1
+fi
+rm -rf -- "$workspace"
+"#;
+
+const SNAPSHOT_WORKSPACE_SCRIPT: &str = r#"
+set -euo pipefail
+
+workspace="${1:?missing workspace}"
+shift
+if [[ ! -d "$workspace" ]]; then
+  echo "workspace not found: $workspace" >&2
+  exit 1
+fi
+tar -C "$workspace" -cf - "$@" .
+"#;
+
+const RESTORE_WORKSPACE_SCRIPT: &str = r#"
+set -euo pipefail
+
+workspace="${1:?missing workspace}"
+name="${2:?missing name}"
+meta_file="${3:?missing meta file}"
+if [[ -e "$workspace" ]]; then
+  echo "workspace already exists: $name" >&2
+  exit 1
+fi
+mkdir -p "$workspace"
+if ! tar -C "$workspace" -xf -; then
+  rm -rf -- "$workspace"
+  exit 1
+fi
+if [[ -f "$workspace/$meta_file" ]]; then
+  sed -i.bak "s|^name=.*|name=$name|" "$workspace/$meta_file"
+  rm -f -- "$workspace/$meta_file.bak"
+fi
+"#;
+
+const RENAME_WORKSPACE_SCRIPT: &str = r#"
+set -euo pipefail
+
+workspace="${1:?missing workspace}"
+target="${2:?missing target}"
+name="${3:?missing name}"
+meta_file="${4:?missing meta file}"
+if [[ ! -d "$workspace" ]]; then
+  echo "workspace not found: $workspace" >&2
+  exit 1
+fi
+if [[ -e "$target" ]]; then
+  echo "workspace already exists: $name" >&2
+  exit 1
+fi
+mv -- "$workspace" "$target"
+if [[ -f "$target/$meta_file" ]]; then
+  sed -i.bak "s|^name=.*|name=$name|" "$target/$meta_file"
+  rm -f -- "$target/$meta_file.bak"
+fi
+"#;
+
+const CLONE_WORKSPACE_SCRIPT: &str = r#"
+set -euo pipefail
+
+workspace="${1:?missing workspace}"
+target="${2:?missing target}"
+name="${3:?missing name}"
+meta_file="${4:?missing meta file}"
+if [[ ! -d "$workspace" ]]; then
+  echo "workspace not found: $workspace" >&2
+  exit 1
+fi
+if [[ -e "$target" ]]; then
+  echo "workspace already exists: $name" >&2
+  exit 1
+fi
+if ! cp -a -- "$workspace" "$target"; then
+  rm -rf -- "$target"
+  exit 1
+fi
+# The source's detached tunnel is not the clone's.
+rm -f -- "$target/code-tunnel.state"
+primary_repo="$(sed -n 's/^primary_repo=//p' "$workspace/$meta_file" 2>/dev/null | head -n 1)"
+printf 'name=%s\ncreated_unix=%s\nprimary_repo=%s\n' "$name" "$(date +%s)" "${primary_repo:-none}" \
+  >"$target/$meta_file"
+"#;
+
+const EXEC_SCRIPT: &str = r#"
+set -euo pipefail
+
+cd "${1:?missing workspace}"
+env_file="${2:?missing env file}"
+shift 2
+if [[ -f "$env_file" ]]; then
+  while IFS= read -r line; do
+    if [[ "$line" == *=* ]]; then
+      export "$line"
+    fi
+  done <"$env_file"
+fi
+if [[ $# -eq 0 ]]; then
+  exec "${SHELL:-/bin/bash}" -l
+fi
+exec "$@"
+"#;
+
+const WRITE_FILE_SCRIPT: &str = r#"
+set -euo pipefail
+
+target="${1:?missing target}"
+umask 077
+mkdir -p "$(dirname "$target")"
+cat >"$target"
+chmod 600 "$target" 2>/dev/null || true
+"#;
+
+const GPG_VERIFY_SCRIPT: &str = r#"
+if command -v gpg >/dev/null 2>&1; then
+  gpg --batch --list-secret-keys "$1" >/dev/null 2>&1
+fi
+"#;
+
+/// `doctor` probe: `<root>`. Prints `root\t<error>` (empty when writable) and `git\t<version>`.
+const DOCTOR_SCRIPT: &str = r#"
+probe="$1/.awl-doctor-$$"
+if err="$( { : >"$probe" && rm -f "$probe"; } 2>&1 )"; then
+  printf 'root\t\n'
+else
+  printf 'root\t%s\n' "${err:-permission denied}" | head -n 1
+fi
+printf 'git\t%s\n' "$(git --version 2>/dev/null | head -n 1)"
+"#;
+
+const TUNNEL_SCRIPT: &str = r#"
+set -euo pipefail
+
+workspace="${1:?missing workspace}"
+shift
+
+if ! command -v code >/dev/null 2>&1; then
+  echo "'code' command not found on remote host (required for tunnel)" >&2
+  exit 127
+fi
+
+cd "$workspace"
+exec code tunnel --accept-server-license-terms "$@"
+"#;
+
+/// Host-runtime layout on a remote machine, driven through `ssh`.
+///
+/// Every operation runs a bash script on the remote side, so the only local requirement is an
+/// ssh client (`AGENT_WORKSPACE_SSH` overrides the command, e.g. `ssh -i ~/.ssh/build`).
+#[derive(Debug)]
+pub(super) struct SshBackend {
+    destination: String,
+    port: Option<String>,
+    root: OnceLock<String>,
+}
+
+impl SshBackend {
+    /// `target` is the `[user@]host[:port]` part of `ssh://...`.
+    pub(super) fn new(target: &str) -> Self {
+        let (destination, port) = match target.rsplit_once(':') {
+            Some((destination, port))
+                if !destination.is_empty()
+                    && !port.is_empty()
+                    && port.chars().all(|ch| ch.is_ascii_digit()) =>
+            {
+                (destination.to_string(), Some(port.to_string()))
+            }
+            _ => (target.to_string(), None),
+        };
+
+        Self {
+            destination,
+            port,
+            root: OnceLock::new(),
+        }
+    }
+
+    fn ssh_program() -> Vec<String> {
+        let words: Vec<String> = crate::config::value("ssh_command")
+            .and_then(|value| trimmed_nonempty(&value))
+            .map(|value| value.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+        if words.is_empty() {
+            vec![String::from(DEFAULT_SSH_COMMAND)]
+        } else {
+            words
+        }
+    }
+
+    /// Build `ssh [opts] <destination> <remote command>` for `bash -c <script> -- <args...>`.
+    fn ssh_command<S: AsRef<str>>(&self, tty: bool, script: &str, args: &[S]) -> Command {
+        let program = Self::ssh_program();
+        let mut cmd = Command::new(&program[0]);
+        cmd.args(&program[1..]);
+        if let Some(port) = self.port.as_deref() {
+            cmd.args(["-p", port]);
+        }
+        cmd.arg(if tty { "-t" } else { "-T" });
+        cmd.arg(&self.destination);
+
+        let mut remote = format!("bash -c {} --", shell_quote(script));
+        for arg in args {
+            remote.push(' ');
+            remote.push_str(&shell_quote(arg.as_ref()));
+        }
+        cmd.arg(remote);
+        cmd
+    }
+
+    fn run_captured<S: AsRef<str>>(&self, script: &str, args: &[S]) -> Result<String, String> {
+        self.run_streamed(script, args, Stdio::null(), Stdio::piped())
+    }
+
+    /// Like `run_captured`, with the remote stdin/stdout wired to `stdin`/`stdout` (e.g. tar streams).
+    fn run_streamed<S: AsRef<str>>(
+        &self,
+        script: &str,
+        args: &[S],
+        stdin: Stdio,
+        stdout: Stdio,
+    ) -> Result<String, String> {
+        let output = self
+            .ssh_command(false, script, args)
+            .stdin(stdin)
+            .stdout(stdout)
+            .output()
+            .map_err(|err| format!("failed to run ssh {}: {err}", self.destination))?;
+
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if stderr.is_empty() {
+            Err(format!(
+                "ssh {} failed (exit {})",
+                self.destination,
+                output.status.code().unwrap_or(EXIT_RUNTIME)
+            ))
+        } else {
+            Err(stderr)
+        }
+    }
+
+    fn run_interactive<S: AsRef<str>>(
+        &self,
+        tty: bool,
+        script: &str,
+        args: &[S],
+    ) -> Result<i32, String> {
+        self.ssh_command(tty, script, args)
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()
+            .map(|status| status.code().unwrap_or(EXIT_RUNTIME))
+            .map_err(|err| format!("failed to run ssh {}: {err}", self.destination))
+    }
+
+    fn write_file(&self, path: &str, contents: &[u8]) -> Result<(), String> {
+        let mut child = self
+            .ssh_command(false, WRITE_FILE_SCRIPT, &[path])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("failed to run ssh {}: {err}", self.destination))?;
+
+        if let Some(stdin) = child.stdin.as_mut() {
+            stdin
+                .write_all(contents)
+                .map_err(|err| format!("failed to stream {path} over ssh: {err}"))?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .map_err(|err| format!("failed to wait for ssh {}: {err}", self.destination))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            if stderr.is_empty() {
+                Err(format!("failed to write {path} on {}", self.destination))
+            } else {
+                Err(stderr)
+            }
+        }
+    }
+
+    fn storage_root(&self) -> Result<&str, String> {
+        if let Some(root) = self.root.get() {
+            return Ok(root);
+        }
+
+        let pinned = crate::config::value("ssh_home")
+            .and_then(|value| trimmed_nonempty(&value))
+            .unwrap_or_default();
+        let output = self.run_captured(STORAGE_ROOT_SCRIPT, &[pinned])?;
+        let root = trimmed_nonempty(&output)
+            .ok_or_else(|| format!("failed to resolve workspace root on {}", self.destination))?;
+        Ok(self.root.get_or_init(|| root))
+    }
+
+    /// Remote workspace as a path-only `Workspace`, so host path mapping helpers apply.
+    fn workspace(&self, name: &str) -> Result<Workspace, String> {
+        Ok(Workspace {
+            name: name.to_string(),
+            path: PathBuf::from(self.storage_root()?).join(name),
+        })
+    }
+
+    fn workspace_path(&self, name: &str) -> Result<String, String> {
+        Ok(self.workspace(name)?.path.display().to_string())
+    }
+}
+
+impl WorkspaceBackend for SshBackend {
+    fn runtime_name(&self) -> &'static str {
+        "ssh"
+    }
+
+    fn ensure_available(&self) -> Result<(), String> {
+        let program = Self::ssh_program();
+        if command_exists(&program[0]) {
+            Ok(())
+        } else {
+            Err(format!("{} command not found in PATH", program[0]))
+        }
+    }
+
+    fn unavailable_hint(&self) -> Option<&'static str> {
+        if command_exists(&Self::ssh_program()[0]) {
+            None
+        } else {
+            Some(
+                "install an OpenSSH client or point AGENT_WORKSPACE_SSH at an ssh-compatible command",
+            )
+        }
+    }
+
+    fn list(&self) -> Result<Vec<WorkspaceEntry>, String> {
+        let root = self.storage_root()?;
+        let output = self.run_captured(LIST_WORKSPACES_SCRIPT, &[root, WORKSPACE_META_FILE])?;
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                // `<name>` followed by the tab-separated lines of its `.workspace-meta`.
+                let mut fields = line.split('\t');
+                let name = fields.next().and_then(trimmed_nonempty)?;
+                let mut entry = WorkspaceEntry {
+                    path: Some(PathBuf::from(root).join(&name)),
+                    name,
+                    ..WorkspaceEntry::default()
+                };
+                for meta in fields {
+                    apply_meta_line(meta, &mut entry.created_unix, &mut entry.repo);
+                }
+                Some(entry)
+            })
+            .collect())
+    }
+
+    fn resolve(&self, name: &str) -> Result<Option<String>, String> {
+        let Some(workspace_name) = trimmed_nonempty(name) else {
+            return Ok(None);
+        };
+
+        let names: Vec<String> = self.list()?.into_iter().map(|entry| entry.name).collect();
+        let prefixes = workspace_prefixes();
+        Ok(workspace_resolution_candidates(&workspace_name, &prefixes)
+            .into_iter()
+            .find(|candidate| names.contains(candidate)))
+    }
+
+    fn ensure_ready(&self, workspace: &str) -> Result<(), String> {
+        let path = self.workspace_path(workspace)?;
+        self.run_captured(REQUIRE_WORKSPACE_SCRIPT, &[path])
+            .map(|_| ())
+    }
+
+    fn create_workspace(&self, request: &NewWorkspace<'_>) -> Result<CreatedWorkspace, String> {
+        let mut ignored: Vec<&str> = Vec::new();
+        if request.image.is_some() {
+            ignored.push("--image");
+        }
+        if !request.pull {
+            ignored.push("--no-pull");
+        }
+        if !ignored.is_empty() {
+            eprintln!(
+                "warn: ignoring container-only create options in ssh mode: {}",
+                ignored.join(" ")
+            );
+        }
+
+        let path = self.workspace_path(request.name)?;
+        let primary_repo = request
+            .primary_repo
+            .map(|repo| repo.owner_repo.as_str())
+            .unwrap_or("none");
+        self.run_captured(
+            CREATE_WORKSPACE_SCRIPT,
+            &[
+                path.as_str(),
+                request.name,
+                primary_repo,
+                WORKSPACE_META_FILE,
+            ],
+        )?;
+        // Over stdin rather than argv, so the values never show up in `ps` on the remote host.
+        if !request.env.is_empty() {
+            let contents: String = request
+                .env
+                .iter()
+                .map(|(name, value)| format!("{name}={value}\n"))
+                .collect();
+            self.write_file(&format!("{path}/{WORKSPACE_ENV_FILE}"), contents.as_bytes())
+                .map_err(|err| format!("failed to write workspace env: {err}"))?;
+        }
+
+        Ok(CreatedWorkspace {
+            name: request.name.to_string(),
+            path,
+        })
+    }
+
+    fn workspace_dir(&self, workspace: &str) -> Result<String, String> {
+        self.workspace_path(workspace)
+    }
+
+    fn clone_repo(
+        &self,
+        workspace: &str,
+        repo: &RepoSpec,
+        slot: RepoSlot,
+        options: &CloneOptions<'_>,
+    ) -> Result<String, String> {
+        let workspace = self.workspace(workspace)?;
+        let root = match slot {
+            RepoSlot::Work => workspace.path.join("work"),
+            RepoSlot::Private => workspace.path.join("private"),
+        };
+        let destination = workspace_repo_destination(&root, repo)
+            .display()
+            .to_string();
+
+        let flags = options.clone_flags();
+        let mut args = vec![
+            repo.clone_url.as_str(),
+            destination.as_str(),
+            options.refspec.unwrap_or(""),
+        ];
+        args.extend(flags.iter().map(String::as_str));
+        if options.quiet {
+            self.run_captured(CLONE_REPO_SCRIPT, &args)
+                .map_err(|err| format!("git clone failed for {}: {err}", repo.owner_repo))?;
+        } else {
+            let code = self.run_interactive(false, CLONE_REPO_SCRIPT, &args)?;
+            if code != 0 {
+                return Err(format!(
+                    "git clone failed for {} on {} (exit {code})",
+                    repo.owner_repo, self.destination
+                ));
+            }
+        }
+
+        if options.has_clone_mode() {
+            let mode_args = options.clone_mode_args(&destination);
+            let mode_args: Vec<&str> = mode_args.iter().map(String::as_str).collect();
+            self.run_captured(CLONE_MODE_SCRIPT, &mode_args)
+                .map_err(|err| format!("clone mode setup failed for {}: {err}", repo.owner_repo))?;
+        }
+        Ok(destination)
+    }
+
+    fn remove(&self, workspace: &str, _options: &RemoveOptions) -> Result<(), String> {
+        let path = self.workspace_path(workspace)?;
+        self.run_captured(REMOVE_WORKSPACE_SCRIPT, &[path])
+            .map(|_| ())
+    }
+
+    fn snapshot(
+        &self,
+        workspace: &str,
+        staging: &Path,
+        include_secrets: bool,
+    ) -> Result<SnapshotManifest, String> {
+        let tarball = part_path(staging, HOST_WORKSPACE_PART);
+        let file = File::create(&tarball)
+            .map_err(|err| format!("failed to create {}: {err}", tarball.display()))?;
+        let mut args = vec![self.workspace_path(workspace)?];
+        if !include_secrets {
+            args.extend(
+                HOST_SECRET_EXCLUDES
+                    .iter()
+                    .map(|pattern| format!("--exclude={pattern}")),
+            );
+        }
+        self.run_streamed(
+            SNAPSHOT_WORKSPACE_SCRIPT,
+            &args,
+            Stdio::null(),
+            Stdio::from(file),
+        )?;
+        Ok(SnapshotManifest {
+            layout: String::from(HOST_LAYOUT),
+            name: workspace.to_string(),
+            parts: vec![String::from(HOST_WORKSPACE_PART)],
+            secrets: include_secrets,
+            ..SnapshotManifest::default()
+        })
+    }
+
+    fn restore(
+        &self,
+        manifest: &SnapshotManifest,
+        staging: &Path,
+        new_name: &str,
+        _options: &RestoreOptions<'_>,
+    ) -> Result<CreatedWorkspace, String> {
+        manifest.expect_layout(HOST_LAYOUT, self.runtime_name())?;
+        let tarball = part_path(staging, HOST_WORKSPACE_PART);
+        let file = File::open(&tarball)
+            .map_err(|err| format!("failed to open {}: {err}", tarball.display()))?;
+        let path = self.workspace_path(new_name)?;
+        let args = [
+            path.clone(),
+            new_name.to_string(),
+            WORKSPACE_META_FILE.to_string(),
+        ];
+        self.run_streamed(
+            RESTORE_WORKSPACE_SCRIPT,
+            &args,
+            Stdio::from(file),
+            Stdio::piped(),
+        )?;
+        Ok(CreatedWorkspace {
+            name: new_name.to_string(),
+            path,
+        })
+    }
+
+    fn clone_workspace(&self, source: &str, new_name: &str) -> Result<CreatedWorkspace, String> {
+        let path = self.workspace_path(new_name)?;
+        let args = [
+            self.workspace_path(source)?,
+            path.clone(),
+            new_name.to_string(),
+            WORKSPACE_META_FILE.to_string(),
+        ];
+        self.run_captured(CLONE_WORKSPACE_SCRIPT, &args)?;
+        Ok(CreatedWorkspace {
+            name: new_name.to_string(),
+            path,
+        })
+    }
+
+    fn rename(&self, workspace: &str, new_name: &str) -> Result<String, String> {
+        let args = [
+            self.workspace_path(workspace)?,
+            self.workspace_path(new_name)?,
+            new_name.to_string(),
+            WORKSPACE_META_FILE.to_string(),
+        ];
+        self.run_captured(RENAME_WORKSPACE_SCRIPT, &args)?;
+        Ok(new_name.to_string())
+    }
+
+    fn exec(&self, workspace: &str, request: &ExecRequest<'_>) -> Result<i32, String> {
+        if request.user.is_some() {
+            eprintln!("warn: --root/--user is ignored in ssh exec mode");
+        }
+
+        let mut args: Vec<String> = vec![
+            self.workspace_path(workspace)?,
+            WORKSPACE_ENV_FILE.to_string(),
+        ];
+        args.extend(
+            request
+                .command
+                .iter()
+                .map(|arg: &OsString| arg.to_string_lossy().into_owned()),
+        );
+
+        let tty = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+        self.run_interactive(tty, EXEC_SCRIPT, &args)
+    }
+
+    fn default_work_repos_depth(&self) -> u32 {
+        3
+    }
+
+    fn map_repo_path(&self, workspace: &str, raw: &str) -> Result<String, String> {
+        let workspace = self.workspace(workspace)?;
+        Ok(map_workspace_repo_path(&workspace, raw)
+            .display()
+            .to_string())
+    }
+
+    fn repo_path(&self, workspace: &str, raw: &str) -> Result<String, String> {
+        let target_repo = self.map_repo_path(workspace, raw)?;
+        self.run_captured("[[ -e \"$1/.git\" ]]", &[target_repo.as_str()])
+            .map_err(|_| format!("not a git repo: {target_repo}"))?;
+        Ok(target_repo)
+    }
+
+    fn list_repos(&self, _workspace: &str, root: &str, depth: u32) -> Result<Vec<String>, String> {
+        let output = self.run_captured(LIST_GIT_REPOS_SCRIPT, &[root, &depth.to_string()])?;
+        let mut repos: Vec<String> = output.lines().filter_map(trimmed_nonempty).collect();
+        repos.sort();
+        repos.dedup();
+        Ok(repos)
+    }
+
+    fn private_repo(&self, workspace: &str) -> Result<Option<String>, String> {
+        let private_root = self.map_repo_path(workspace, "~/.private")?;
+        Ok(self
+            .list_repos(workspace, &private_root, 4)?
+            .into_iter()
+            .next())
+    }
+
+    fn reset_repo(&self, _workspace: &str, repo_dir: &str, refspec: &str) -> Result<(), String> {
+        let code = self.run_interactive(false, RESET_REPO_SCRIPT, &[repo_dir, refspec])?;
+        if code == 0 {
+            Ok(())
+        } else {
+            Err(format!("failed to reset repo {repo_dir} (exit {code})"))
+        }
+    }
+
+    fn store_github_token(&self, workspace: &str, host: &str, token: &str) -> Result<(), String> {
+        let target = self
+            .workspace(workspace)?
+            .path
+            .join("auth")
+            .join("github.env");
+        let content = format!("host={host}\ntoken={token}\n");
+        self.write_file(&target.display().to_string(), content.as_bytes())
+            .map_err(|err| format!("failed to write GitHub auth file over ssh: {err}"))
+    }
+
+    fn store_codex_auth(&self, workspace: &str, auth_data: &[u8]) -> Result<(), String> {
+        let workspace = self.workspace(workspace)?;
+        for target in codex_auth_targets(&workspace) {
+            self.write_file(&target.display().to_string(), auth_data)?;
+        }
+        Ok(())
+    }
+
+    fn verify_gpg_key(&self, _workspace: &str, key: &str) -> Result<(), String> {
+        self.run_captured(GPG_VERIFY_SCRIPT, &[key])
+            .map(|_| ())
+            .map_err(|_| format!("gpg key not found in remote keyring: {key}"))
+    }
+
+    fn store_gpg_key(&self, workspace: &str, key: &str) -> Result<(), String> {
+        let target = self
+            .workspace(workspace)?
+            .path
+            .join("auth")
+            .join("gpg-key.txt");
+        self.write_file(&target.display().to_string(), format!("{key}\n").as_bytes())
+            .map_err(|err| format!("failed to write gpg auth file over ssh: {err}"))
+    }
+
+    fn unmanaged_dirs(&self) -> Result<Vec<String>, String> {
+        let root = self.storage_root()?;
+        let output = self.run_captured(LIST_WORKSPACES_SCRIPT, &[root, WORKSPACE_META_FILE])?;
+        Ok(output
+            .lines()
+            .filter(|line| !line.contains('\t') && !line.starts_with('.'))
+            .filter_map(trimmed_nonempty)
+            .map(|name| PathBuf::from(root).join(name).display().to_string())
+            .collect())
+    }
+
+    fn supports_forward(&self) -> bool {
+        true
+    }
+
+    /// Ports are those of the remote machine; the workspace name only has to exist.
+    fn forward_command(&self, _workspace: &str, port: u16) -> Result<Command, String> {
+        Ok(self.ssh_command(false, FORWARD_BRIDGE_SCRIPT, &[port.to_string()]))
+    }
+
+    fn copy_in(&self, workspace: &str, request: &CopyRequest) -> Result<String, String> {
+        let request = CopyRequest {
+            dest: map_workspace_operand(&self.workspace(workspace)?, &request.dest),
+            ..request.clone()
+        };
+        // No owner: files already belong to the ssh login user.
+        stream_copy_in(&request, "", |args, archive| {
+            self.run_streamed(COPY_IN_SCRIPT, args, archive, Stdio::piped())
+        })
+    }
+
+    fn copy_out(&self, workspace: &str, request: &CopyRequest) -> Result<String, String> {
+        let request = CopyRequest {
+            src: map_workspace_operand(&self.workspace(workspace)?, &request.src),
+            ..request.clone()
+        };
+        stream_copy_out(&request, |args, archive| {
+            self.run_streamed(COPY_OUT_SCRIPT, args, Stdio::null(), archive)
+        })
+    }
+
+    fn doctor_checks(&self) -> Vec<DoctorCheck> {
+        let program = Self::ssh_program();
+        if !command_exists(&program[0]) {
+            return vec![DoctorCheck::fail(
+                "ssh",
+                format!("{} command not found in PATH", program[0]),
+                self.unavailable_hint().unwrap_or_default(),
+            )];
+        }
+
+        let root = match self.storage_root() {
+            Ok(root) => root,
+            Err(err) => {
+                return vec![DoctorCheck::fail(
+                    "ssh",
+                    format!("cannot reach {}: {err}", self.destination),
+                    format!(
+                        "check that 'ssh {} true' succeeds without prompting (keys, agent, known_hosts)",
+                        self.destination
+                    ),
+                )];
+            }
+        };
+        let mut checks = vec![DoctorCheck::ok(
+            "ssh",
+            format!("connected to {}", self.destination),
+        )];
+
+        let output = match self.run_captured(DOCTOR_SCRIPT, &[root]) {
+            Ok(output) => output,
+            Err(err) => {
+                checks.push(DoctorCheck::fail(
+                    "workspace-root",
+                    format!("failed to probe {root} on {}: {err}", self.destination),
+                    "check the remote shell and AGENT_WORKSPACE_SSH_HOME",
+                ));
+                return checks;
+            }
+        };
+        for line in output.lines() {
+            match line.split_once('\t') {
+                Some(("root", "")) => checks.push(DoctorCheck::ok(
+                    "workspace-root",
+                    format!("{root} is writable on {}", self.destination),
+                )),
+                Some(("root", err)) => checks.push(DoctorCheck::fail(
+                    "workspace-root",
+                    format!("{root} is not writable on {}: {err}", self.destination),
+                    "set AGENT_WORKSPACE_SSH_HOME to a writable remote directory",
+                )),
+                Some(("git", "")) => checks.push(DoctorCheck::fail(
+                    "remote-git",
+                    format!("git not found on {}", self.destination),
+                    "install git on the remote host; workspaces clone repos with it",
+                )),
+                Some(("git", version)) => checks.push(DoctorCheck::ok("remote-git", version)),
+                _ => {}
+            }
+        }
+        checks
+    }
+
+    fn disk_usage(&self, workspace: &str) -> Result<Option<u64>, String> {
+        let path = self.workspace_path(workspace)?;
+        let output = self.run_captured("du -sk \"$1\" 2>/dev/null || true", &[path])?;
+        Ok(parse_du_kib(&output))
+    }
+
+    fn inspect(&self, workspace: &str) -> Result<WorkspaceDetails, String> {
+        let workspace = self.workspace(workspace)?;
+        let args = workspace_status_args(&workspace, self.default_work_repos_depth());
+        let output = self.run_captured(WORKSPACE_STATUS_SCRIPT, &args)?;
+
+        let mut details = WorkspaceDetails {
+            name: workspace.name.clone(),
+            path: workspace.path.display().to_string(),
+            ..WorkspaceDetails::default()
+        };
+        details.apply_status_output(&output);
+        Ok(details)
+    }
+
+    fn tunnel(
+        &self,
+        workspace: &str,
+        request: &TunnelRequest<'_>,
+    ) -> Result<TunnelOutcome, String> {
+        let workspace = self.workspace(workspace)?;
+        let path = workspace.path.display().to_string();
+
+        if request.detach {
+            let log_path = workspace
+                .path
+                .join(CODE_TUNNEL_LOG_FILE)
+                .display()
+                .to_string();
+            let state_path = workspace
+                .path
+                .join(CODE_TUNNEL_STATE_FILE)
+                .display()
+                .to_string();
+            let output = self.run_captured(
+                DETACHED_TUNNEL_SCRIPT,
+                &[
+                    path.as_str(),
+                    log_path.as_str(),
+                    state_path.as_str(),
+                    request.name.unwrap_or_default(),
+                ],
+            )?;
+            return Ok(TunnelOutcome::Detached {
+                tunnel_name: request.name.map(str::to_string),
+                pid: output.trim().parse::<u32>().ok(),
+                log_path: Some(log_path),
+            });
+        }
+
+        let mut args: Vec<&str> = vec![path.as_str()];
+        if let Some(tunnel_name) = request.name {
+            args.extend(["--name", tunnel_name]);
+        }
+
+        let tty = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+        let exit_code = self.run_interactive(tty, TUNNEL_SCRIPT, &args)?;
+        Ok(TunnelOutcome::Exited {
+            tunnel_name: request.name.map(str::to_string),
+            exit_code,
+        })
+    }
+
+    fn tunnel_control(&self, workspace: &str, action: TunnelAction) -> Result<String, String> {
+        let state_path = self
+            .workspace(workspace)?
+            .path
+            .join(CODE_TUNNEL_STATE_FILE)
+            .display()
+            .to_string();
+        let [action_arg, follow] = action.script_args();
+        let args = [state_path.as_str(), action_arg, follow];
+        if !matches!(action, TunnelAction::Logs { .. }) {
+            return self.run_captured(TUNNEL_CONTROL_SCRIPT, &args);
+        }
+
+        let tty = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+        match self.run_interactive(tty, TUNNEL_CONTROL_SCRIPT, &args)? {
+            0 => Ok(String::new()),
+            code => Err(format!("tunnel logs failed (exit {code})")),
+        }
+    }
+}
+
+/// Quote one word for the remote login shell, which re-parses the ssh command line.
+fn shell_quote(input: &str) -> String {
+    if !input.is_empty()
+        && input
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "-_./=:@,+%".contains(ch))
+    {
+        return input.to_string();
+    }
+
+    format!("'{}'", input.replace('\'', r"'\''"))
+}
@@ -0,0 +1,92 @@
+use std::ffi::OsString;
+
+use crate::EXIT_RUNTIME;
+
+use super::PRIMARY_COMMAND_NAME;
+use super::backend::WorkspaceBackend;
+
+#[derive(Debug, Default, Clone)]
+struct ParsedStart {
+    show_help: bool,
+    workspace: Option<String>,
+}
+
+fn parse_start_args(args: &[OsString]) -> Result<ParsedStart, String> {
+    let mut parsed = ParsedStart::default();
+
+    for arg in args {
+        let text = arg.to_string_lossy();
+        match text.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            _ if text.starts_with('-') => {
+                return Err(format!("unknown option for start: {text}"));
+            }
+            _ => {
+                if parsed.workspace.is_some() {
+                    return Err(String::from("start accepts one workspace name"));
+                }
+                parsed.workspace = Some(text.to_string());
+            }
+        }
+    }
+
+    Ok(parsed)
+}
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_start_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_start_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if parsed.show_help {
+        print_start_usage();
+        return 0;
+    }
+
+    let Some(workspace_name) = parsed.workspace.as_deref() else {
+        eprintln!("error: missing workspace name");
+        print_start_usage();
+        return EXIT_RUNTIME;
+    };
+
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    if !backend.supports_start_stop() {
+        eprintln!(
+            "error: start is not supported by the {} runtime",
+            backend.runtime_name()
+        );
+        return EXIT_RUNTIME;
+    }
+
+    let workspace = match backend.resolve(workspace_name) {
+        Ok(Some(workspace)) => workspace,
+        Ok(None) => {
+            eprintln!("error: workspace not found: {workspace_name}");
+            return EXIT_RUNTIME;
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if let Err(err) = backend.start(&workspace) {
+        eprintln!("error: failed to start workspace {workspace}: {err}");
+        return EXIT_RUNTIME;
+    }
+    println!("started: {workspace}");
+    0
+}
+
+fn print_start_usage() {
+    eprintln!("usage: {PRIMARY_COMMAND_NAME} start [--runtime <container|podman>] <workspace>");
+}
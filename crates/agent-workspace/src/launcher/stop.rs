@@ -0,0 +1,153 @@
+use std::ffi::OsString;
+
+use crate::EXIT_RUNTIME;
+
+use super::backend::{IdleCheck, WorkspaceBackend};
+use super::{PRIMARY_COMMAND_NAME, format_duration};
+
+#[derive(Debug, Default, Clone)]
+struct ParsedStop {
+    show_help: bool,
+    all: bool,
+    idle: bool,
+    workspace: Option<String>,
+}
+
+fn parse_stop_args(args: &[OsString]) -> Result<ParsedStop, String> {
+    let mut parsed = ParsedStop::default();
+
+    for arg in args {
+        let text = arg.to_string_lossy();
+        match text.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            "--all" => parsed.all = true,
+            "--idle" => parsed.idle = true,
+            _ if text.starts_with('-') => {
+                return Err(format!("unknown option for stop: {text}"));
+            }
+            _ => {
+                if parsed.workspace.is_some() {
+                    return Err(String::from("stop accepts at most one workspace name"));
+                }
+                parsed.workspace = Some(text.to_string());
+            }
+        }
+    }
+
+    if parsed.all && parsed.idle {
+        return Err(String::from("--all and --idle cannot be combined"));
+    }
+    if (parsed.all || parsed.idle) && parsed.workspace.is_some() {
+        return Err(String::from(
+            "stop --all/--idle does not accept a workspace name",
+        ));
+    }
+
+    Ok(parsed)
+}
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_stop_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_stop_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if parsed.show_help {
+        print_stop_usage();
+        return 0;
+    }
+
+    if !parsed.all && !parsed.idle && parsed.workspace.is_none() {
+        eprintln!("error: missing workspace name, --all, or --idle");
+        print_stop_usage();
+        return EXIT_RUNTIME;
+    }
+
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    if !backend.supports_start_stop() {
+        eprintln!(
+            "error: stop is not supported by the {} runtime",
+            backend.runtime_name()
+        );
+        return EXIT_RUNTIME;
+    }
+
+    let targets: Vec<String> = if let Some(workspace_name) = parsed.workspace.as_deref() {
+        match backend.resolve(workspace_name) {
+            Ok(Some(workspace)) => vec![workspace],
+            Ok(None) => {
+                eprintln!("error: workspace not found: {workspace_name}");
+                return EXIT_RUNTIME;
+            }
+            Err(err) => {
+                eprintln!("error: {err}");
+                return EXIT_RUNTIME;
+            }
+        }
+    } else {
+        match backend.list() {
+            Ok(items) => items
+                .into_iter()
+                .filter(|item| item.state.as_deref() != Some("stopped"))
+                .map(|item| item.name)
+                .collect(),
+            Err(err) => {
+                eprintln!("error: {err}");
+                return EXIT_RUNTIME;
+            }
+        }
+    };
+
+    let mut status = 0;
+    for target in targets {
+        if parsed.idle {
+            match backend.idle_check(&target) {
+                Ok(IdleCheck::Idle {
+                    idle_secs,
+                    timeout_secs,
+                }) if idle_secs >= timeout_secs => {
+                    eprintln!(
+                        "info: {target} idle for {} (timeout {})",
+                        format_duration(idle_secs),
+                        format_duration(timeout_secs)
+                    );
+                }
+                Ok(_) => continue,
+                Err(err) => {
+                    eprintln!("warn: skipping {target}: {err}");
+                    continue;
+                }
+            }
+        }
+
+        if let Err(err) = backend.stop(&target) {
+            eprintln!("error: failed to stop workspace {target}: {err}");
+            status = EXIT_RUNTIME;
+            continue;
+        }
+        println!("stopped: {target}");
+    }
+
+    status
+}
+
+fn print_stop_usage() {
+    eprintln!(
+        "usage: {PRIMARY_COMMAND_NAME} stop [--runtime <container|podman>] <workspace> | --all | --idle"
+    );
+    eprintln!(
+        "  --idle  stop running workspaces created with --idle-timeout once they have had no exec session or"
+    );
+    eprintln!(
+        "          tunnel for that long, counted from the latest of: container start, the end of the last"
+    );
+    eprintln!("          launcher exec, or a previous --idle run that found the workspace busy");
+}
@@ -1,11 +1,9 @@
 use std::ffi::OsString;
-use std::process::{Command, Stdio};
 
 use crate::EXIT_RUNTIME;
 
-use super::{
-    PRIMARY_COMMAND_NAME, command_exists, json_escape, resolve_workspace, trimmed_nonempty,
-};
+use super::backend::{TunnelAction, TunnelOutcome, TunnelRequest, TunnelStatus, WorkspaceBackend};
+use super::{PRIMARY_COMMAND_NAME, json_escape, resolve_ready_workspace, trimmed_nonempty};
 
 #[derive(Debug, Default, Clone)]
 pub(super) struct ParsedTunnel {
@@ -14,10 +12,13 @@ pub(super) struct ParsedTunnel {
     pub(super) tunnel_name: Option<String>,
     pub(super) detach: bool,
     pub(super) output_json: bool,
+    /// `--status`, `--logs [-f]`, or `--stop` against a detached tunnel.
+    pub(super) control: Option<TunnelAction>,
 }
 
 pub(super) fn parse_tunnel_args(args: &[OsString]) -> Result<ParsedTunnel, String> {
     let mut parsed = ParsedTunnel::default();
+    let mut follow = false;
     let mut idx = 0usize;
 
     while idx < args.len() {
@@ -25,6 +26,20 @@ pub(super) fn parse_tunnel_args(args: &[OsString]) -> Result<ParsedTunnel, Strin
         match current.as_ref() {
             "-h" | "--help" => parsed.show_help = true,
             "--detach" => parsed.detach = true,
+            "--status" | "--logs" | "--stop" => {
+                let action = match current.as_ref() {
+                    "--status" => TunnelAction::Status,
+                    "--logs" => TunnelAction::Logs { follow: false },
+                    _ => TunnelAction::Stop,
+                };
+                if parsed.control.is_some_and(|existing| existing != action) {
+                    return Err(String::from(
+                        "--status, --logs, and --stop are mutually exclusive",
+                    ));
+                }
+                parsed.control = Some(action);
+            }
+            "-f" | "--follow" => follow = true,
             "--name" => {
                 idx += 1;
                 if idx >= args.len() {
@@ -66,10 +81,23 @@ pub(super) fn parse_tunnel_args(args: &[OsString]) -> Result<ParsedTunnel, Strin
         idx += 1;
     }
 
+    match parsed.control {
+        Some(TunnelAction::Logs { .. }) => {
+            parsed.control = Some(TunnelAction::Logs { follow });
+        }
+        _ if follow => return Err(String::from("--follow requires --logs")),
+        _ => {}
+    }
+    if parsed.control.is_some() && (parsed.detach || parsed.tunnel_name.is_some()) {
+        return Err(String::from(
+            "--detach and --name cannot be combined with --status, --logs, or --stop",
+        ));
+    }
+
     Ok(parsed)
 }
 
-pub(super) fn run(args: &[OsString]) -> i32 {
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
     let parsed = match parse_tunnel_args(args) {
         Ok(parsed) => parsed,
         Err(err) => {
@@ -92,82 +120,257 @@ pub(super) fn run(args: &[OsString]) -> i32 {
         return EXIT_RUNTIME;
     };
 
-    let workspace = match resolve_workspace(workspace_name) {
-        Ok(Some(workspace)) => workspace,
-        Ok(None) => {
-            eprintln!("error: workspace not found: {workspace_name}");
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    if let Some(action) = parsed.control {
+        // Resolve without `ensure_ready`: checking or stopping a tunnel must not start a container.
+        let workspace = match backend.resolve(workspace_name) {
+            Ok(Some(workspace)) => workspace,
+            Ok(None) => {
+                eprintln!("error: workspace not found: {workspace_name}");
+                return EXIT_RUNTIME;
+            }
+            Err(err) => {
+                eprintln!("error: {err}");
+                return EXIT_RUNTIME;
+            }
+        };
+        return run_control(backend, &workspace, action, parsed.output_json);
+    }
+
+    let workspace = match resolve_ready_workspace(backend, Some(workspace_name)) {
+        Ok(workspace) => workspace,
+        Err(err) => {
+            eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
+    };
+
+    let request = TunnelRequest {
+        name: parsed.tunnel_name.as_deref(),
+        detach: parsed.detach,
+    };
+    let outcome = match backend.tunnel(&workspace, &request) {
+        Ok(outcome) => outcome,
         Err(err) => {
             eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
     };
 
-    if !command_exists("code") {
-        eprintln!("error: 'code' command not found in PATH (required for tunnel)");
-        return EXIT_RUNTIME;
-    }
-
-    let mut cmd = Command::new("code");
-    cmd.arg("tunnel");
-    cmd.arg("--accept-server-license-terms");
-    if let Some(tunnel_name) = parsed.tunnel_name.as_deref() {
-        cmd.args(["--name", tunnel_name]);
-    }
-    cmd.current_dir(&workspace.path);
-
-    if parsed.detach {
-        cmd.stdin(Stdio::null());
-        cmd.stdout(Stdio::null());
-        cmd.stderr(Stdio::null());
-
-        return match cmd.spawn() {
-            Ok(child) => {
-                if parsed.output_json {
-                    println!(
-                        "{{\"workspace\":\"{}\",\"detached\":true,\"pid\":{}}}",
-                        json_escape(&workspace.name),
-                        child.id()
-                    );
-                } else {
-                    println!("tunnel: {} detached (pid={})", workspace.name, child.id());
+    match outcome {
+        TunnelOutcome::Detached {
+            tunnel_name,
+            pid,
+            log_path,
+        } => {
+            if parsed.output_json {
+                let mut out = format!(
+                    "{{\"workspace\":\"{}\",\"runtime\":\"{}\",\"detached\":true",
+                    json_escape(&workspace),
+                    backend.runtime_name()
+                );
+                if let Some(tunnel_name) = tunnel_name.as_deref() {
+                    out.push_str(&format!(
+                        ",\"tunnel_name\":\"{}\"",
+                        json_escape(tunnel_name)
+                    ));
+                }
+                if let Some(pid) = pid {
+                    out.push_str(&format!(",\"pid\":{pid}"));
+                }
+                if let Some(log_path) = log_path.as_deref() {
+                    out.push_str(&format!(",\"log_path\":\"{}\"", json_escape(log_path)));
+                }
+                out.push('}');
+                println!("{out}");
+            } else {
+                match pid {
+                    Some(pid) => println!("tunnel: {workspace} detached (pid={pid})"),
+                    None => println!("tunnel: {workspace} detached"),
+                }
+                if let Some(log_path) = log_path.as_deref() {
+                    println!("log: {log_path}");
                 }
-                0
             }
-            Err(err) => {
-                eprintln!("error: failed to launch tunnel: {err}");
-                EXIT_RUNTIME
+            0
+        }
+        TunnelOutcome::Exited {
+            tunnel_name,
+            exit_code,
+        } => {
+            if parsed.output_json {
+                let mut out = format!(
+                    "{{\"workspace\":\"{}\",\"runtime\":\"{}\",\"detached\":false,\"exit_code\":{exit_code}",
+                    json_escape(&workspace),
+                    backend.runtime_name()
+                );
+                if let Some(tunnel_name) = tunnel_name.as_deref() {
+                    out.push_str(&format!(
+                        ",\"tunnel_name\":\"{}\"",
+                        json_escape(tunnel_name)
+                    ));
+                }
+                out.push('}');
+                println!("{out}");
             }
-        };
+            exit_code
+        }
     }
+}
 
-    cmd.stdin(Stdio::inherit());
-    cmd.stdout(Stdio::inherit());
-    cmd.stderr(Stdio::inherit());
+fn run_control(
+    backend: &dyn WorkspaceBackend,
+    workspace: &str,
+    action: TunnelAction,
+    output_json: bool,
+) -> i32 {
+    if !backend.is_ready(workspace) {
+        return report_stopped_workspace(backend, workspace, action, output_json);
+    }
 
-    match cmd.status() {
-        Ok(status) => {
-            let code = status.code().unwrap_or(EXIT_RUNTIME);
-            if parsed.output_json {
+    let output = match backend.tunnel_control(workspace, action) {
+        Ok(output) => output,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+
+    match action {
+        TunnelAction::Logs { .. } => 0,
+        TunnelAction::Status => {
+            let status = TunnelStatus::parse(&output);
+            if output_json {
+                println!("{}", tunnel_status_json(workspace, backend, &status));
+            } else {
+                print_tunnel_status(workspace, &status);
+            }
+            0
+        }
+        TunnelAction::Stop => {
+            let stopped = output.trim() == "stopped";
+            if output_json {
                 println!(
-                    "{{\"workspace\":\"{}\",\"detached\":false,\"exit_code\":{}}}",
-                    json_escape(&workspace.name),
-                    code
+                    "{{\"workspace\":\"{}\",\"runtime\":\"{}\",\"stopped\":{stopped}}}",
+                    json_escape(workspace),
+                    backend.runtime_name()
                 );
+            } else if stopped {
+                println!("tunnel: {workspace} stopped");
+            } else {
+                println!("tunnel: {workspace} has no running detached tunnel");
             }
-            code
+            0
         }
-        Err(err) => {
-            eprintln!("error: failed to run tunnel command: {err}");
+    }
+}
+
+/// A stopped workspace has no running tunnel; its log stays inside until it is started again.
+fn report_stopped_workspace(
+    backend: &dyn WorkspaceBackend,
+    workspace: &str,
+    action: TunnelAction,
+    output_json: bool,
+) -> i32 {
+    match action {
+        TunnelAction::Logs { .. } => {
+            eprintln!(
+                "error: workspace {workspace} is not running; start it to read its tunnel log"
+            );
             EXIT_RUNTIME
         }
+        TunnelAction::Status if output_json => {
+            println!(
+                "{}",
+                tunnel_status_json(workspace, backend, &TunnelStatus::default())
+            );
+            0
+        }
+        TunnelAction::Stop if output_json => {
+            println!(
+                "{{\"workspace\":\"{}\",\"runtime\":\"{}\",\"stopped\":false}}",
+                json_escape(workspace),
+                backend.runtime_name()
+            );
+            0
+        }
+        TunnelAction::Status | TunnelAction::Stop => {
+            println!("tunnel: {workspace} not running (workspace is stopped)");
+            0
+        }
     }
 }
 
+fn print_tunnel_status(workspace: &str, status: &TunnelStatus) {
+    if !status.recorded {
+        println!("tunnel: {workspace} has no detached tunnel (start one with --detach)");
+        return;
+    }
+    let state = if status.running { "running" } else { "exited" };
+    match status.pid {
+        Some(pid) => println!("tunnel: {workspace} {state} (pid={pid})"),
+        None => println!("tunnel: {workspace} {state}"),
+    }
+    if let Some(name) = status.name.as_deref() {
+        println!("name: {name}");
+    }
+    if let Some(log_path) = status.log_path.as_deref() {
+        println!("log: {log_path}");
+    }
+    if status.running
+        && let Some(code) = status.login_code.as_deref()
+    {
+        println!("login code: {code} (https://github.com/login/device)");
+    }
+}
+
+fn tunnel_status_json(
+    workspace: &str,
+    backend: &dyn WorkspaceBackend,
+    status: &TunnelStatus,
+) -> String {
+    let mut out = format!(
+        "{{\"workspace\":\"{}\",\"runtime\":\"{}\",\"recorded\":{},\"running\":{}",
+        json_escape(workspace),
+        backend.runtime_name(),
+        status.recorded,
+        status.running
+    );
+    if let Some(pid) = status.pid {
+        out.push_str(&format!(",\"pid\":{pid}"));
+    }
+    if let Some(name) = status.name.as_deref() {
+        out.push_str(&format!(",\"tunnel_name\":\"{}\"", json_escape(name)));
+    }
+    if let Some(log_path) = status.log_path.as_deref() {
+        out.push_str(&format!(",\"log_path\":\"{}\"", json_escape(log_path)));
+    }
+    if let Some(started_unix) = status.started_unix {
+        out.push_str(&format!(",\"started_unix\":{started_unix}"));
+    }
+    if let Some(code) = status.login_code.as_deref() {
+        out.push_str(&format!(",\"login_code\":\"{}\"", json_escape(code)));
+    }
+    out.push('}');
+    out
+}
+
 fn print_tunnel_usage() {
     println!("usage:");
     println!(
-        "  {PRIMARY_COMMAND_NAME} tunnel <workspace> [--name <tunnel_name>] [--detach] [--output json]"
+        "  {PRIMARY_COMMAND_NAME} tunnel [--runtime <container|host|podman|ssh://host>] <workspace> [--name <tunnel_name>] [--detach] [--output json]"
+    );
+    println!(
+        "  {PRIMARY_COMMAND_NAME} tunnel [--runtime <container|host|podman|ssh://host>] <workspace> --status [--output json]"
+    );
+    println!(
+        "  {PRIMARY_COMMAND_NAME} tunnel [--runtime <container|host|podman|ssh://host>] <workspace> --logs [-f|--follow]"
+    );
+    println!(
+        "  {PRIMARY_COMMAND_NAME} tunnel [--runtime <container|host|podman|ssh://host>] <workspace> --stop"
     );
 }
@@ -0,0 +1,153 @@
+use std::ffi::OsString;
+
+use crate::EXIT_RUNTIME;
+
+use super::backend::{UpgradeOptions, WorkspaceBackend};
+use super::{PRIMARY_COMMAND_NAME, trimmed_nonempty};
+
+#[derive(Debug, Default, Clone)]
+pub(super) struct ParsedUpgrade {
+    pub(super) show_help: bool,
+    pub(super) all: bool,
+    pub(super) workspace: Option<String>,
+    pub(super) image: Option<String>,
+    pub(super) pull: bool,
+    pub(super) sync_baseline: bool,
+}
+
+pub(super) fn parse_upgrade_args(args: &[OsString]) -> Result<ParsedUpgrade, String> {
+    let mut parsed = ParsedUpgrade::default();
+    let mut idx = 0usize;
+
+    while idx < args.len() {
+        let text = args[idx].to_string_lossy();
+        match text.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            "--all" => parsed.all = true,
+            "--pull" => parsed.pull = true,
+            "--sync-baseline" => parsed.sync_baseline = true,
+            "--image" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(String::from("missing value for --image"));
+                }
+                parsed.image = trimmed_nonempty(&args[idx].to_string_lossy());
+            }
+            _ if text.starts_with("--image=") => {
+                parsed.image = trimmed_nonempty(&text["--image=".len()..]);
+            }
+            _ if text.starts_with('-') => {
+                return Err(format!("unknown option for upgrade: {text}"));
+            }
+            _ if parsed.workspace.is_none() => parsed.workspace = trimmed_nonempty(&text),
+            _ => return Err(format!("unexpected arg for upgrade: {text}")),
+        }
+        idx += 1;
+    }
+
+    if parsed.show_help {
+        return Ok(parsed);
+    }
+    match (parsed.all, parsed.workspace.is_some()) {
+        (true, true) => Err(String::from("upgrade takes a workspace or --all, not both")),
+        (false, false) => Err(String::from("upgrade requires <workspace> or --all")),
+        _ => Ok(parsed),
+    }
+}
+
+pub(super) fn run(backend: &dyn WorkspaceBackend, args: &[OsString]) -> i32 {
+    let parsed = match parse_upgrade_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_upgrade_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if parsed.show_help {
+        print_upgrade_usage();
+        return 0;
+    }
+
+    if let Err(err) = backend.ensure_available() {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    if !backend.supports_images() {
+        eprintln!(
+            "error: upgrade is not supported by the {} runtime",
+            backend.runtime_name()
+        );
+        return EXIT_RUNTIME;
+    }
+
+    let workspaces = if parsed.all {
+        match backend.list() {
+            Ok(entries) => entries.into_iter().map(|entry| entry.name).collect(),
+            Err(err) => {
+                eprintln!("error: {err}");
+                return EXIT_RUNTIME;
+            }
+        }
+    } else {
+        let name = parsed.workspace.clone().unwrap_or_default();
+        match backend.resolve(&name) {
+            Ok(Some(workspace)) => vec![workspace],
+            Ok(None) => {
+                eprintln!("error: workspace not found: {name}");
+                return EXIT_RUNTIME;
+            }
+            Err(err) => {
+                eprintln!("error: {err}");
+                return EXIT_RUNTIME;
+            }
+        }
+    };
+    if workspaces.is_empty() {
+        println!("upgrade: no workspaces");
+        return 0;
+    }
+
+    let options = UpgradeOptions {
+        image: parsed.image.as_deref(),
+        pull: parsed.pull,
+        sync_baseline: parsed.sync_baseline,
+    };
+    let mut failed = 0usize;
+    for workspace in &workspaces {
+        match backend.upgrade(workspace, &options) {
+            Ok(outcome) if outcome.previous == outcome.current => {
+                println!("up to date: {workspace} ({})", outcome.current);
+            }
+            Ok(outcome) => {
+                println!(
+                    "upgraded: {workspace} ({} -> {})",
+                    outcome.previous, outcome.current
+                );
+            }
+            Err(err) => {
+                eprintln!("error: failed to upgrade {workspace}: {err}");
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        if workspaces.len() > 1 {
+            eprintln!(
+                "error: {failed} of {} workspace(s) failed to upgrade",
+                workspaces.len()
+            );
+        }
+        return EXIT_RUNTIME;
+    }
+    0
+}
+
+fn print_upgrade_usage() {
+    eprintln!(
+        "usage: {PRIMARY_COMMAND_NAME} upgrade [--runtime <container|podman>] <workspace>|--all [--image <image>] [--pull] [--sync-baseline]"
+    );
+}
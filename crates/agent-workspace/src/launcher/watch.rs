@@ -0,0 +1,223 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::command_exists;
+
+/// Quiet period that ends a batch of change events.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// Upper bound on how long a steady stream of events can hold a batch back.
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(2);
+/// Scan interval when `inotifywait` is not installed.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Change feed for `rsync push --watch`.
+///
+/// Uses `inotifywait -m -r` (inotify-tools) on the source tree and falls back to polling
+/// sizes and mtimes when it is not installed.
+pub(super) struct SourceWatcher {
+    root: PathBuf,
+    gitignore: bool,
+    events: Receiver<PathBuf>,
+    inotify: Option<Child>,
+}
+
+impl SourceWatcher {
+    pub(super) fn start(root: &Path, gitignore: bool) -> Result<Self, String> {
+        let root = fs::canonicalize(root)
+            .map_err(|err| format!("failed to resolve {}: {err}", root.display()))?;
+        if !root.is_dir() {
+            return Err(format!(
+                "--watch needs a directory source: {}",
+                root.display()
+            ));
+        }
+
+        let (sender, events) = mpsc::channel();
+        let inotify = if command_exists("inotifywait") {
+            Some(spawn_inotifywait(&root, sender)?)
+        } else {
+            eprintln!(
+                "info: inotifywait not found; polling {} for changes",
+                root.display()
+            );
+            eprintln!("hint: install inotify-tools for event-driven watching");
+            let poll_root = root.clone();
+            thread::spawn(move || poll_changes(&poll_root, &sender));
+            None
+        };
+
+        Ok(Self {
+            root,
+            gitignore,
+            events,
+            inotify,
+        })
+    }
+
+    pub(super) fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Block until the next debounced batch of changed paths, relative to the watched root.
+    ///
+    /// Returns `None` once the watcher stops (e.g. the source directory was removed).
+    pub(super) fn next_batch(&mut self) -> Option<Vec<String>> {
+        loop {
+            let first = self.events.recv().ok()?;
+            let started = Instant::now();
+            let mut changed: BTreeSet<String> = BTreeSet::new();
+            self.collect(&first, &mut changed);
+            while started.elapsed() < MAX_BATCH_DELAY {
+                match self.events.recv_timeout(DEBOUNCE) {
+                    Ok(path) => self.collect(&path, &mut changed),
+                    Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+                }
+            }
+
+            let mut paths: Vec<String> = changed.into_iter().collect();
+            if self.gitignore {
+                paths = self.drop_ignored(paths);
+            }
+            if !paths.is_empty() {
+                return Some(paths);
+            }
+        }
+    }
+
+    fn collect(&self, path: &Path, changed: &mut BTreeSet<String>) {
+        let Ok(rel) = path.strip_prefix(&self.root) else {
+            return;
+        };
+        if rel.as_os_str().is_empty()
+            || (self.gitignore && rel.components().any(|part| part.as_os_str() == ".git"))
+        {
+            return;
+        }
+        changed.insert(rel.to_string_lossy().into_owned());
+    }
+
+    /// Filter through `git check-ignore`; outside a git repo (or without git) nothing is dropped.
+    fn drop_ignored(&self, paths: Vec<String>) -> Vec<String> {
+        let child = Command::new("git")
+            .arg("-C")
+            .arg(&self.root)
+            .args(["check-ignore", "--stdin"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        let Ok(mut child) = child else {
+            return paths;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all((paths.join("\n") + "\n").as_bytes());
+        }
+        let Ok(output) = child.wait_with_output() else {
+            return paths;
+        };
+
+        let ignored: BTreeSet<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect();
+        paths
+            .into_iter()
+            .filter(|path| !ignored.contains(path))
+            .collect()
+    }
+}
+
+impl Drop for SourceWatcher {
+    fn drop(&mut self) {
+        if let Some(child) = self.inotify.as_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+fn spawn_inotifywait(root: &Path, sender: Sender<PathBuf>) -> Result<Child, String> {
+    let mut child = Command::new("inotifywait")
+        .args([
+            "-m",
+            "-r",
+            "-q",
+            "-e",
+            "close_write,create,delete,move,attrib",
+            "--format",
+            "%w%f",
+        ])
+        .arg(root)
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("failed to run inotifywait: {err}"))?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| String::from("failed to read inotifywait output"))?;
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if sender.send(PathBuf::from(line)).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(child)
+}
+
+type TreeState = HashMap<PathBuf, (u64, Option<SystemTime>)>;
+
+fn poll_changes(root: &Path, sender: &Sender<PathBuf>) {
+    let mut previous = scan_tree(root);
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        if !root.is_dir() {
+            return;
+        }
+        let current = scan_tree(root);
+        let added_or_changed = current
+            .iter()
+            .filter(|(path, state)| previous.get(*path) != Some(*state))
+            .map(|(path, _)| path);
+        let removed = previous.keys().filter(|path| !current.contains_key(*path));
+        for path in added_or_changed.chain(removed) {
+            if sender.send(path.clone()).is_err() {
+                return;
+            }
+        }
+        previous = current;
+    }
+}
+
+fn scan_tree(root: &Path) -> TreeState {
+    let mut state = TreeState::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let path = entry.path();
+            if meta.is_dir() {
+                pending.push(path.clone());
+                state.insert(path, (0, None));
+            } else {
+                state.insert(path, (meta.len(), meta.modified().ok()));
+            }
+        }
+    }
+    state
+}
@@ -1,7 +1,9 @@
 mod cli;
 mod completion;
+mod config;
 mod launcher;
 mod runtime;
+mod toml;
 
 use std::path::Path;
 
@@ -34,7 +36,11 @@ where
     if request.subcommand == "__complete" {
         return completion::run(&request.args);
     }
-    launcher::dispatch(request.subcommand, &request.args)
+    launcher::dispatch_with_profile(
+        request.subcommand,
+        request.profile.as_deref(),
+        &request.args,
+    )
 }
 
 fn detect_invocation_name(argv0: Option<&std::ffi::OsString>) -> Option<String> {
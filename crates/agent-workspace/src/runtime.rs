@@ -1,9 +1,14 @@
 use std::ffi::OsString;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+const SSH_SCHEME: &str = "ssh://";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Runtime {
     Container,
     Host,
+    Podman,
+    /// Host-runtime layout on a remote machine; holds the `[user@]host[:port]` target.
+    Ssh(String),
 }
 
 pub fn resolve_runtime(args: &[OsString]) -> Result<(Runtime, Vec<OsString>), String> {
@@ -33,9 +38,7 @@ pub fn resolve_runtime(args: &[OsString]) -> Result<(Runtime, Vec<OsString>), St
 
     let runtime = if let Some(runtime) = runtime_from_flag {
         runtime
-    } else if let Ok(value) = std::env::var("AGENT_WORKSPACE_RUNTIME") {
-        parse_runtime_value(&value)?
-    } else if let Ok(value) = std::env::var("AWL_RUNTIME") {
+    } else if let Some(value) = crate::config::value("runtime") {
         parse_runtime_value(&value)?
     } else {
         Runtime::Container
@@ -45,15 +48,33 @@ pub fn resolve_runtime(args: &[OsString]) -> Result<(Runtime, Vec<OsString>), St
 }
 
 pub(crate) fn parse_runtime_value(raw: &str) -> Result<Runtime, String> {
-    let normalized = raw.trim().to_ascii_lowercase();
+    let trimmed = raw.trim();
+    if trimmed
+        .get(..SSH_SCHEME.len())
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case(SSH_SCHEME))
+    {
+        let target = trimmed[SSH_SCHEME.len()..].trim_end_matches('/');
+        if target.is_empty() {
+            return Err(format!(
+                "invalid runtime value: {raw} (expected: ssh://[user@]host[:port])"
+            ));
+        }
+        return Ok(Runtime::Ssh(target.to_string()));
+    }
+
+    let normalized = trimmed.to_ascii_lowercase();
     match normalized.as_str() {
         "container" | "docker" => Ok(Runtime::Container),
         "host" | "native" => Ok(Runtime::Host),
+        "podman" => Ok(Runtime::Podman),
+        "ssh" => Err(String::from(
+            "ssh runtime requires a target (expected: ssh://[user@]host[:port])",
+        )),
         "" => Err(String::from(
-            "invalid runtime value: empty (expected: container|host)",
+            "invalid runtime value: empty (expected: container|host|podman|ssh://host)",
         )),
         _ => Err(format!(
-            "invalid runtime value: {raw} (expected: container|host)"
+            "invalid runtime value: {raw} (expected: container|host|podman|ssh://host)"
         )),
     }
 }
@@ -113,9 +134,30 @@ mod tests {
         assert_eq!(cleaned, vec!["ls", "--output", "json"]);
     }
 
+    #[test]
+    fn parses_podman_runtime() {
+        let (runtime, cleaned) =
+            resolve_runtime(&["--runtime".into(), "Podman".into(), "ls".into()])
+                .expect("resolve runtime");
+        assert_eq!(runtime, Runtime::Podman);
+        assert_eq!(cleaned, vec!["ls"]);
+    }
+
     #[test]
     fn rejects_invalid_runtime() {
         let err = resolve_runtime(&["--runtime".into(), "k8s".into()]).expect_err("invalid");
         assert!(err.contains("expected: container|host"));
     }
+
+    #[test]
+    fn parses_ssh_runtime_target() {
+        let (runtime, cleaned) =
+            resolve_runtime(&["--runtime=SSH://dev@build-box:2222".into(), "ls".into()])
+                .expect("resolve runtime");
+        assert_eq!(runtime, Runtime::Ssh(String::from("dev@build-box:2222")));
+        assert_eq!(cleaned, vec!["ls"]);
+
+        let err = resolve_runtime(&["--runtime".into(), "ssh".into()]).expect_err("missing target");
+        assert!(err.contains("ssh://[user@]host"));
+    }
 }
@@ -0,0 +1,445 @@
+//! Minimal TOML reader for repo manifests (`awl.toml` / `.awl/workspace.toml`).
+//!
+//! The user config (`config.toml`) is read by `config::ConfigFile` instead: it only holds flat
+//! `key = value` tables, accepts bare unquoted values, and is rewritten line by line by
+//! `config set`.
+//!
+//! Supports tables, arrays of tables, dotted keys, basic/literal strings, integers, booleans,
+//! arrays, and inline tables. Multi-line strings, floats, and dates are rejected.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+    Table(Table),
+}
+
+pub(crate) type Table = Vec<(String, Value)>;
+
+impl Value {
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Self::String(_) => "string",
+            Self::Integer(_) => "integer",
+            Self::Boolean(_) => "boolean",
+            Self::Array(_) => "array",
+            Self::Table(_) => "table",
+        }
+    }
+}
+
+pub(crate) fn get<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    table
+        .iter()
+        .find(|(known, _)| known == key)
+        .map(|(_, value)| value)
+}
+
+pub(crate) fn parse(text: &str) -> Result<Table, String> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+    parser
+        .parse_document()
+        .map_err(|err| format!("line {}: {err}", parser.line))
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn parse_document(&mut self) -> Result<Table, String> {
+        let mut root: Table = Vec::new();
+        let mut current: Vec<String> = Vec::new();
+
+        loop {
+            self.skip_blank_lines();
+            let Some(ch) = self.peek() else {
+                return Ok(root);
+            };
+
+            if ch == '[' {
+                self.pos += 1;
+                let array = self.peek() == Some('[');
+                if array {
+                    self.pos += 1;
+                }
+                self.skip_spaces();
+                let path = self.parse_key_path()?;
+                self.expect(']')?;
+                if array {
+                    self.expect(']')?;
+                    push_array_table(&mut root, &path)?;
+                } else {
+                    table_at(&mut root, &path)?;
+                }
+                current = path;
+                self.expect_line_end()?;
+                continue;
+            }
+
+            let path = self.parse_key_path()?;
+            self.expect('=')?;
+            self.skip_spaces();
+            let value = self.parse_value()?;
+            self.expect_line_end()?;
+
+            let (key, parents) = path.split_last().expect("key path is never empty");
+            let mut full: Vec<String> = current.clone();
+            full.extend(parents.iter().cloned());
+            let table = table_at(&mut root, &full)?;
+            if get(table, key).is_some() {
+                return Err(format!("duplicate key: {key}"));
+            }
+            table.push((key.clone(), value));
+        }
+    }
+
+    fn parse_key_path(&mut self) -> Result<Vec<String>, String> {
+        let mut path = vec![self.parse_key()?];
+        loop {
+            self.skip_spaces();
+            if self.peek() != Some('.') {
+                return Ok(path);
+            }
+            self.pos += 1;
+            self.skip_spaces();
+            path.push(self.parse_key()?);
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some('"') => self.parse_basic_string(),
+            Some('\'') => self.parse_literal_string(),
+            _ => {
+                let start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-')
+                {
+                    self.pos += 1;
+                }
+                if start == self.pos {
+                    return Err(String::from("expected a key"));
+                }
+                Ok(self.chars[start..self.pos].iter().collect())
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some('"') => {
+                if self.chars[self.pos..].starts_with(&['"', '"', '"']) {
+                    return Err(String::from("multi-line strings are not supported"));
+                }
+                self.parse_basic_string().map(Value::String)
+            }
+            Some('\'') => self.parse_literal_string().map(Value::String),
+            Some('[') => self.parse_array(),
+            Some('{') => self.parse_inline_table(),
+            Some(ch) if ch.is_ascii_alphanumeric() || ch == '+' || ch == '-' => {
+                let start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|ch| ch.is_ascii_alphanumeric() || "+-_.:".contains(ch))
+                {
+                    self.pos += 1;
+                }
+                let raw: String = self.chars[start..self.pos].iter().collect();
+                match raw.as_str() {
+                    "true" => Ok(Value::Boolean(true)),
+                    "false" => Ok(Value::Boolean(false)),
+                    _ => raw
+                        .replace('_', "")
+                        .parse::<i64>()
+                        .map(Value::Integer)
+                        .map_err(|_| format!("unsupported value: {raw}")),
+                }
+            }
+            Some(ch) => Err(format!("unexpected character: {ch}")),
+            None => Err(String::from("missing value")),
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Value, String> {
+        self.pos += 1;
+        let mut items: Vec<Value> = Vec::new();
+        loop {
+            self.skip_blank_lines();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(Value::Array(items));
+            }
+            items.push(self.parse_value()?);
+            self.skip_blank_lines();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {}
+                _ => return Err(String::from("expected ',' or ']' in array")),
+            }
+        }
+    }
+
+    fn parse_inline_table(&mut self) -> Result<Value, String> {
+        self.pos += 1;
+        let mut table: Table = Vec::new();
+        self.skip_spaces();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Value::Table(table));
+        }
+        loop {
+            self.skip_spaces();
+            let path = self.parse_key_path()?;
+            self.expect('=')?;
+            self.skip_spaces();
+            let value = self.parse_value()?;
+
+            let (key, parents) = path.split_last().expect("key path is never empty");
+            let target = table_at(&mut table, parents)?;
+            if get(target, key).is_some() {
+                return Err(format!("duplicate key: {key}"));
+            }
+            target.push((key.clone(), value));
+
+            self.skip_spaces();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Value::Table(table));
+                }
+                _ => return Err(String::from("expected ',' or '}' in inline table")),
+            }
+        }
+    }
+
+    fn parse_basic_string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let Some(ch) = self.peek() else {
+                return Err(String::from("unterminated string"));
+            };
+            self.pos += 1;
+            match ch {
+                '"' => return Ok(out),
+                '\n' => return Err(String::from("unterminated string")),
+                '\\' => {
+                    let Some(escaped) = self.peek() else {
+                        return Err(String::from("unterminated string"));
+                    };
+                    self.pos += 1;
+                    match escaped {
+                        '"' => out.push('"'),
+                        '\\' => out.push('\\'),
+                        'n' => out.push('\n'),
+                        't' => out.push('\t'),
+                        'r' => out.push('\r'),
+                        'u' => {
+                            let hex: String = self.chars
+                                [self.pos..(self.pos + 4).min(self.chars.len())]
+                                .iter()
+                                .collect();
+                            let decoded = u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| format!("invalid unicode escape: \\u{hex}"))?;
+                            out.push(decoded);
+                            self.pos += 4;
+                        }
+                        other => return Err(format!("unsupported escape: \\{other}")),
+                    }
+                }
+                _ => out.push(ch),
+            }
+        }
+    }
+
+    fn parse_literal_string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let start = self.pos;
+        while let Some(ch) = self.peek() {
+            if ch == '\'' {
+                let literal: String = self.chars[start..self.pos].iter().collect();
+                self.pos += 1;
+                return Ok(literal);
+            }
+            if ch == '\n' {
+                break;
+            }
+            self.pos += 1;
+        }
+        Err(String::from("unterminated string"))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_spaces();
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected '{expected}'"))
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(|ch| ch == ' ' || ch == '\t') {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while self.peek().is_some_and(|ch| ch != '\n') {
+                self.pos += 1;
+            }
+        }
+    }
+
+    /// Skip whitespace, newlines, and comments between statements or array items.
+    fn skip_blank_lines(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n') => {
+                    self.pos += 1;
+                    self.line += 1;
+                }
+                Some('\r') => self.pos += 1,
+                _ => return,
+            }
+        }
+    }
+
+    fn expect_line_end(&mut self) -> Result<(), String> {
+        self.skip_spaces();
+        self.skip_comment();
+        match self.peek() {
+            None | Some('\n') | Some('\r') => Ok(()),
+            Some(ch) => Err(format!("unexpected character after value: {ch}")),
+        }
+    }
+}
+
+/// Walk (creating as needed) to the table at `path`; array-of-tables segments resolve to
+/// their last element.
+fn table_at<'a>(root: &'a mut Table, path: &[String]) -> Result<&'a mut Table, String> {
+    let mut table = root;
+    for segment in path {
+        let idx = match table.iter().position(|(known, _)| known == segment) {
+            Some(idx) => idx,
+            None => {
+                table.push((segment.clone(), Value::Table(Vec::new())));
+                table.len() - 1
+            }
+        };
+        table = match &mut table[idx].1 {
+            Value::Table(inner) => inner,
+            Value::Array(items) => match items.last_mut() {
+                Some(Value::Table(inner)) => inner,
+                _ => return Err(format!("key is not a table: {segment}")),
+            },
+            _ => return Err(format!("key is not a table: {segment}")),
+        };
+    }
+    Ok(table)
+}
+
+fn push_array_table(root: &mut Table, path: &[String]) -> Result<(), String> {
+    let (key, parents) = path.split_last().expect("key path is never empty");
+    let parent = table_at(root, parents)?;
+    match parent.iter_mut().find(|(known, _)| known == key) {
+        Some((_, Value::Array(items))) => items.push(Value::Table(Vec::new())),
+        Some(_) => return Err(format!("key is not an array of tables: {key}")),
+        None => parent.push((key.clone(), Value::Array(vec![Value::Table(Vec::new())]))),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Value, get, parse};
+
+    #[test]
+    fn parses_tables_arrays_and_inline_tables() {
+        let doc = parse(
+            r#"
+image = "ghcr.io/acme/env:py" # trailing comment
+post_create = [
+  "make bootstrap",
+  'echo "ok"',
+]
+
+[env]
+RUST_LOG = "debug"
+"QUOTED.KEY" = 1
+
+[[repos]]
+repo = "acme/sidecar"
+ref = "origin/develop"
+
+[[repos]]
+repo = "acme/tools"
+extra = { shallow = true, depth = 1 }
+"#,
+        )
+        .expect("parse");
+
+        assert_eq!(
+            get(&doc, "image").and_then(Value::as_str),
+            Some("ghcr.io/acme/env:py")
+        );
+        assert_eq!(
+            get(&doc, "post_create"),
+            Some(&Value::Array(vec![
+                Value::String(String::from("make bootstrap")),
+                Value::String(String::from("echo \"ok\"")),
+            ]))
+        );
+        let Some(Value::Table(env)) = get(&doc, "env") else {
+            panic!("env table");
+        };
+        assert_eq!(get(env, "QUOTED.KEY"), Some(&Value::Integer(1)));
+        let Some(Value::Array(repos)) = get(&doc, "repos") else {
+            panic!("repos array");
+        };
+        assert_eq!(repos.len(), 2);
+        let Value::Table(second) = &repos[1] else {
+            panic!("repo table");
+        };
+        let Some(Value::Table(extra)) = get(second, "extra") else {
+            panic!("inline table");
+        };
+        assert_eq!(get(extra, "shallow"), Some(&Value::Boolean(true)));
+    }
+
+    #[test]
+    fn reports_line_of_syntax_errors() {
+        let err = parse("a = 1\nb = \"open\n").expect_err("unterminated");
+        assert_eq!(err, "line 2: unterminated string");
+        let err = parse("a = 1\na = 2\n").expect_err("duplicate");
+        assert_eq!(err, "line 2: duplicate key: a");
+    }
+}